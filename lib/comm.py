@@ -2,88 +2,1452 @@
 
 from __future__ import annotations
 
+import csv
+import hashlib
+import hmac
+import itertools
+import json
+import os
+import queue
+import random
+import re
+import secrets
+import socket
 import threading
 import time
-from typing import Optional
+from collections import deque
+from contextlib import contextmanager
+from dataclasses import dataclass, field
+from typing import Any, Callable, Dict, Iterator, List, Optional, Protocol, Tuple
+from urllib.parse import parse_qsl, urlsplit
 
 import zmq
 from PyQt6.QtCore import QObject, pyqtSignal
 
+from protocol import BUTTON_NAMES, apply_tcp_keepalive, clean_strings, clean_text, tcp_endpoint
+
 # Configuration
-ROBOT_ADDRESSES = [
+DEFAULT_ROBOT_ADDRESSES = [
     "10.42.0.85",
     "10.42.0.3",
     "10.42.0.2",
     "127.0.0.1",
     "10.222.255.253"
 ]
+# Comma-separated links tried before the defaults above: bare IPv4/IPv6 hosts, host:port,
+# or link URLs such as tcp://host:5555 (see parse_link_url).
+ROBOT_ADDRESSES = [
+    a.strip() for a in os.environ.get("KSU_ROBOT_ADDRESSES", "").split(",") if a.strip()
+] + DEFAULT_ROBOT_ADDRESSES
+# Extra robots driven alongside the main one in formation mode, as comma-separated
+# name=link entries; see parse_formation.
+FORMATION_SPEC = os.environ.get("KSU_FORMATION", "")
 COMMAND_PORT = 5555
 TELEMETRY_PORT = 5556
 PING_INTERVAL_S = 1
 HEARTBEAT_TIMEOUT_S = 2.0
 COMMAND_TIMEOUT_MS = 2000
+# The robot names itself in its ping reply; firmware from before that replies without it.
+ROBOT_SERVICE = "ksu-storm"
+# Authentication and handshake together must finish within this, or the attempt is dropped
+# rather than left half-connected.
+HANDSHAKE_TIMEOUT_S = 6.0
+# How long a silent command port gets to show a ZMQ greeting when diagnosing it.
+PORT_PROBE_TIMEOUT_S = 1.0
+# An address where something other than a robot answered isn't retried for this long.
+FOREIGN_SERVICE_BACKOFF_S = 10.0
+# Pause between connection attempts while no robot is linked, and between health checks
+# (and replacement attempts) while one is.
+RECONNECT_IDLE_S = 1.0
+RECONNECT_LINKED_S = 0.5
+# Each of those waits is randomly stretched or shrunk by up to this fraction, so several
+# driver stations (or one on a flapping link) don't retry against the robot in lockstep.
+RECONNECT_JITTER = float(os.environ.get("KSU_RECONNECT_JITTER", "0.25"))
+RECONNECT_MAX_JITTER = 0.9
+# Consecutive command timeouts tolerated before the link is treated as lost.
+MAX_COMMAND_TIMEOUTS = 2
 TELEMETRY_TIMEOUT_MS = 100
+# Queued drive frames beyond this are dropped rather than sent late.
+DRIVE_SEND_HWM = 2
+# Shared secret for the robot's optional challenge/response auth.
+AUTH_SECRET = os.environ.get("KSU_AUTH_SECRET", "")
+# Commands re-sent after a reconnect if they never got a reply. A resume is replayed after
+# any pause still queued, and a later pause discards it, so the robot ends up as last asked.
+RELIABLE_COMMANDS = {"mode", "reset", "pause", "resume"}
+# Of those, the ones that stop the robot: replayed first and never dropped.
+SAFETY_COMMANDS = {"reset", "pause"}
+OUTBOX_MAX_ENTRIES = 16
+# What this driver station asks the robot to let it do: "driver" for everything, or
+# "spotter" for auxiliary controls (the camera gimbal) alongside someone else driving.
+ROLE_DRIVER = "driver"
+ROLE_SPOTTER = "spotter"
+ROLES = (ROLE_DRIVER, ROLE_SPOTTER)
+CONTROL_ROLE = os.environ.get("KSU_CONTROL_ROLE", ROLE_DRIVER).strip().lower()
+OUTBOX_MAX_AGE_S = 5.0
+# Send the whole button state as a bitmask in each joystick frame (bit i = button i) when the
+# robot accepts one, instead of a 'button' command per press and release. The robot diffs
+# masks for edges, so a lost frame can't leave a button stuck down.
+BUTTON_MASK = os.environ.get("KSU_BUTTON_MASK", "1").strip().lower() not in ("0", "false", "no")
+
+# Upper bounds (ms) of the round-trip latency histogram buckets; the last bucket is open-ended.
+LATENCY_BUCKETS_MS = (10.0, 50.0, 200.0)
+# The robot numbers telemetry frames in "telemetry_seq", wrapping at this; see TelemetryLossMeter.
+TELEMETRY_SEQ_MODULUS = 1 << 16
+# Frames up to this far behind the newest are late arrivals; a jump further back, or more than
+# MAX_GAP ahead, is the robot restarting its count rather than frames lost.
+TELEMETRY_SEQ_MAX_LATE = 16
+TELEMETRY_SEQ_MAX_GAP = 1024
+# Recent loss is judged over the last this-many expected frames, once at least MIN are in.
+TELEMETRY_LOSS_WINDOW = 200
+TELEMETRY_LOSS_MIN_FRAMES = 20
+# Link bandwidth is averaged over this trailing window; see BandwidthMeter.
+BANDWIDTH_WINDOW_S = 5.0
+# How telemetry values are shown: dotted path -> label, unit, scale applied to the raw
+# value and decimals. Drivers extend or override these per field from their settings
+# file, so a new field (or one sent in different units) needs no code change.
+DEFAULT_TELEMETRY_FORMATS: Dict[str, Dict[str, Any]] = {
+    "battery": {"label": "Battery", "unit": "V", "decimals": 2},
+    "derate": {"label": "Output limit", "unit": "%", "scale": 100.0, "decimals": 0},
+    "pose.x": {"label": "X", "unit": "m", "decimals": 2},
+    "pose.y": {"label": "Y", "unit": "m", "decimals": 2},
+    "pose.theta_deg": {"label": "Heading", "unit": "°", "decimals": 1},
+    "odometry_resets": {"label": "Odometry resets", "decimals": 0},
+    "timing.command.mean_ms": {"label": "Command time", "unit": "ms", "decimals": 2},
+    "loop_rate.achieved_hz": {"label": "Control loop", "unit": "Hz", "decimals": 1},
+}
+# How a dashboard tile draws its field; see DashboardBinding.
+DASHBOARD_WIDGET_KINDS = ("number", "gauge", "bar", "indicator")
+# Outbound commands captured by SessionRecorder; configuration and mode changes are
+# left out so a replay only ever drives, never reconfigures the robot.
+RECORDED_COMMANDS = {"joystick", "button"}
+# Every outbound command is kept in memory this long for the "what did I just send" view,
+# capped at MAX entries so a fast joystick stream can't grow it without bound.
+COMMAND_HISTORY_S = float(os.environ.get("KSU_COMMAND_HISTORY_S", "30"))
+COMMAND_HISTORY_MAX = 2000
+# Bookkeeping fields every command carries, left out of the history's per-command details.
+COMMAND_ENVELOPE_FIELDS = ("type", "timestamp", "client_id", "seq", "session")
+# Upper bound on how often the UI is told data arrived; telemetry alone can run far faster.
+LINK_ACTIVITY_INTERVAL_S = 0.1
+# Telemetry CSV rows are buffered and flushed to disk at least this often.
+TELEMETRY_CSV_FLUSH_S = 1.0
+# Rows waiting for the disk; past this (a stalled disk) new rows are dropped and counted.
+TELEMETRY_CSV_QUEUE_MAX = 2000
+WIRE_LOG_BYTES_PER_LINE = 16
+# Channels multiplexed on the telemetry socket as [channel, payload...]; see ChannelDemux.
+# Robots send telemetry as a single frame, which is routed as CHANNEL_TELEMETRY. Alerts
+# only come on their channel once the 'hello' has said this station reads channels.
+CHANNEL_TELEMETRY = b"telemetry"
+CHANNEL_ALERT = b"alert"
+# Link benchmark frames are [BENCH_TOPIC, header, payload] on the telemetry socket.
+# A burst that goes quiet this long without its closing frame is scored as it stands.
+BENCH_TOPIC = b"bench"
+BENCH_IDLE_TIMEOUT_S = 2.0
+
+# Identifies this driver process so the robot can de-duplicate re-sent commands.
+CLIENT_ID = secrets.token_hex(8)
+_sequence = itertools.count(1)
+_sequence_lock = threading.Lock()
+
+
+def next_sequence() -> int:
+    with _sequence_lock:
+        return next(_sequence)
+
+
+def build_command(command_type: str, **kwargs) -> dict:
+    """A command as sent on the wire, with this driver's envelope fields."""
+    return {
+        "type": command_type,
+        "timestamp": time.time(),
+        "client_id": CLIENT_ID,
+        "seq": next_sequence(),
+        **kwargs,
+    }
+
+
+def resolve_host(host: str, port: int = COMMAND_PORT) -> bool:
+    """True if ``host`` resolves; literal IPs do without a DNS lookup."""
+    try:
+        socket.getaddrinfo(host.strip("[]"), port, type=socket.SOCK_STREAM)
+    except (socket.gaierror, UnicodeError):
+        return False
+    return True
+
+
+def probe_command_port(host: str, port: int = COMMAND_PORT) -> LinkError:
+    """Explain a command port that never replied: nothing there, a foreign service, or a silent robot."""
+    try:
+        sock = socket.create_connection((host.strip("[]"), port), timeout=PORT_PROBE_TIMEOUT_S)
+    except OSError as e:
+        return LinkTimeout(f"no reply; nothing accepting connections on port {port} ({e})")
+    with sock:
+        try:
+            # Any ZMQ peer opens with a greeting that starts with 0xFF.
+            greeting = sock.recv(1)
+        except OSError:
+            greeting = b""
+    if greeting == b"\xff":
+        return LinkTimeout(f"no reply within {COMMAND_TIMEOUT_MS} ms; a ZMQ service is listening but not answering")
+    return LinkForeign(f"connected to something that isn't a KSU-Storm robot (no ZMQ greeting on port {port})")
+
+
+class LinkError(Exception):
+    """Base class for command link failures; catch a subclass to react to one kind."""
+
+
+class LinkDisconnected(LinkError):
+    """The socket or context is gone and the client has to be rebuilt."""
+
+
+class LinkWouldBlock(LinkError):
+    """A non-blocking send couldn't be queued right now."""
+
+
+class LinkTimeout(LinkError):
+    """The robot didn't reply within COMMAND_TIMEOUT_MS."""
+
+
+class LinkCorrupt(LinkError):
+    """A reply arrived but wasn't a JSON object."""
+
+
+class LinkIOError(LinkError):
+    """Any other transport failure."""
+
+
+class LinkForeign(LinkError):
+    """Something answered on the robot's port, but it isn't a KSU-Storm robot."""
+
+
+class LinkUnsupported(LinkError):
+    """The link URL is valid but no transport is registered for its scheme."""
+
+
+class LinkUrlError(ValueError):
+    """A link URL that can't be parsed; the message says which part is wrong."""
+
+
+# Schemes parse_link_url understands. Only those with an entry in LINK_FACTORIES can be opened.
+NETWORK_LINK_SCHEMES = ("tcp", "udp", "tls", "ws")
+LINK_SCHEMES = NETWORK_LINK_SCHEMES + ("serial",)
+DEFAULT_SERIAL_BAUD = 115200
+
+
+@dataclass
+class LinkSpec:
+    """A parsed link URL: which transport, and where it points."""
+
+    scheme: str
+    host: str = ""
+    port: int = 0
+    path: str = ""
+    params: Dict[str, str] = field(default_factory=dict)
+
+    def display(self) -> str:
+        """Short form for the status bar and logs."""
+        if self.scheme == "serial":
+            return f"{self.path} @ {self.params.get('baud', DEFAULT_SERIAL_BAUD)} baud"
+        host = f"[{self.host}]" if ":" in self.host else self.host
+        prefix = "" if self.scheme == "tcp" else f"{self.scheme}://"
+        return f"{prefix}{host}:{self.port}"
+
+
+def _parse_port(text: str, what: str) -> int:
+    try:
+        port = int(text)
+    except ValueError:
+        raise LinkUrlError(f"{what} must be a number, got {text!r}") from None
+    if not 1 <= port <= 65535:
+        raise LinkUrlError(f"{what} must be between 1 and 65535, got {port}")
+    return port
+
+
+def parse_link_url(url: str) -> LinkSpec:
+    """Parse a robot link such as ``tcp://host:5555`` or ``serial:///dev/ttyUSB0?baud=115200``.
+
+    A bare host (IPv4, IPv6 or name), optionally with ``:port``, is a TCP link, so plain
+    KSU_ROBOT_ADDRESSES entries keep working. Network links default to COMMAND_PORT; TCP
+    takes ``?telemetry=PORT`` and otherwise uses the port after the command port.
+    """
+    url = url.strip()
+    if not url:
+        raise LinkUrlError("empty link address")
+    if "://" not in url:
+        # Bare host: more than one colon means an unbracketed IPv6 literal, not host:port.
+        url = f"tcp://[{url}]" if url.count(":") > 1 and not url.startswith("[") else f"tcp://{url}"
+
+    parts = urlsplit(url)
+    scheme = parts.scheme.lower()
+    if scheme not in LINK_SCHEMES:
+        raise LinkUrlError(f"unknown link scheme {parts.scheme!r}; expected one of {', '.join(LINK_SCHEMES)}")
+    params = dict(parse_qsl(parts.query, keep_blank_values=True))
+
+    if scheme == "serial":
+        if parts.netloc:
+            raise LinkUrlError(f"serial links take a device path (serial:///dev/...), not a host: {url!r}")
+        if not parts.path or parts.path == "/":
+            raise LinkUrlError(f"serial link has no device path: {url!r}")
+        baud = params.get("baud", str(DEFAULT_SERIAL_BAUD))
+        if not baud.isdigit() or int(baud) <= 0:
+            raise LinkUrlError(f"serial baud must be a positive whole number, got {baud!r}")
+        params["baud"] = baud
+        return LinkSpec(scheme, path=parts.path, params=params)
+
+    try:
+        host, port = parts.hostname, parts.port
+    except ValueError:
+        # urlsplit rejects non-numeric and out-of-range ports; say which with our own check.
+        _parse_port(parts.netloc.rpartition(":")[2], "port")
+        raise LinkUrlError(f"bad host or port in {url!r}") from None
+    if not host:
+        raise LinkUrlError(f"{scheme} link has no host: {url!r}")
+    if parts.path not in ("", "/"):
+        raise LinkUrlError(f"{scheme} link has an unexpected path {parts.path!r}")
+    if port is None:
+        port = COMMAND_PORT
+    elif port == 0:
+        raise LinkUrlError("port must be between 1 and 65535, got 0")
+    if scheme == "tcp":
+        telemetry = params.get("telemetry")
+        if telemetry is None and port == 65535:
+            raise LinkUrlError("port 65535 leaves no room for the telemetry port; set ?telemetry=")
+        params["telemetry"] = str(_parse_port(telemetry, "telemetry port") if telemetry is not None else port + 1)
+    return LinkSpec(scheme, host=host, port=port, params=params)
+
+
+def link_error_from_zmq(error: zmq.ZMQError) -> LinkError:
+    if isinstance(error, zmq.Again):
+        return LinkWouldBlock(str(error))
+    if getattr(error, "errno", None) in (zmq.ETERM, zmq.ENOTSOCK):
+        return LinkDisconnected(str(error))
+    return LinkIOError(str(error))
+
+
+class TelemetryFormat:
+    """Display metadata for one telemetry value."""
+
+    def __init__(self, label: str, unit: str = "", scale: float = 1.0, decimals: int = 2):
+        self.label = label
+        self.unit = unit
+        self.scale = scale
+        self.decimals = decimals
+
+    @classmethod
+    def from_config(cls, path: str, config: Dict[str, Any]) -> TelemetryFormat:
+        return cls(
+            label=str(config.get("label", path)),
+            unit=str(config.get("unit", "")),
+            scale=float(config.get("scale", 1.0)),
+            decimals=int(config.get("decimals", 2)),
+        )
+
+    def format(self, value: Any) -> str:
+        if isinstance(value, bool) or not isinstance(value, (int, float)):
+            return str(value)
+        text = f"{value * self.scale:.{self.decimals}f}"
+        return f"{text} {self.unit}" if self.unit else text
+
+
+class DashboardBinding:
+    """One dashboard tile: which telemetry field it shows and as what kind of widget.
+
+    Gauges and bars fill between ``minimum`` and ``maximum``; indicators light while the
+    value is truthy; numbers just show the formatted value.
+    """
+
+    def __init__(self, path: str, kind: str = "number", minimum: float = 0.0, maximum: float = 1.0):
+        path = str(path).strip()
+        if not path:
+            raise ValueError("dashboard field is empty")
+        if kind not in DASHBOARD_WIDGET_KINDS:
+            raise ValueError(f"unknown dashboard widget {kind!r}; expected one of {', '.join(DASHBOARD_WIDGET_KINDS)}")
+        minimum, maximum = float(minimum), float(maximum)
+        if kind in ("gauge", "bar") and not maximum > minimum:
+            raise ValueError(f"{kind} range for {path} is empty: {minimum:g} to {maximum:g}")
+        self.path = path
+        self.kind = kind
+        self.minimum = minimum
+        self.maximum = maximum
+
+    @classmethod
+    def from_config(cls, config: Dict[str, Any]) -> DashboardBinding:
+        return cls(config.get("path", ""), str(config.get("kind", "number")),
+                   config.get("min", 0.0), config.get("max", 1.0))
+
+    def to_config(self) -> Dict[str, Any]:
+        config: Dict[str, Any] = {"path": self.path, "kind": self.kind}
+        if self.kind in ("gauge", "bar"):
+            config.update({"min": self.minimum, "max": self.maximum})
+        return config
+
+    def fraction(self, value: Any) -> Optional[float]:
+        """How full a gauge or bar is, 0.0 to 1.0, or None for a missing or non-numeric value.
+
+        An indicator reads 1.0 when lit and 0.0 when not; a number has no fill.
+        """
+        if self.kind == "number":
+            return None
+        if self.kind == "indicator":
+            return None if value is None else float(bool(value))
+        if isinstance(value, bool) or not isinstance(value, (int, float)):
+            return None
+        return min(1.0, max(0.0, (value - self.minimum) / (self.maximum - self.minimum)))
+
+
+def parse_dashboard(config: Any) -> List[DashboardBinding]:
+    """Dashboard tiles from saved settings, skipping entries that no longer make sense."""
+    bindings = []
+    for entry in config if isinstance(config, list) else []:
+        try:
+            bindings.append(DashboardBinding.from_config(entry))
+        except (AttributeError, TypeError, ValueError) as e:
+            print(f"[DashboardBinding] Ignoring dashboard entry {entry!r}: {e}")
+    return bindings
+
+
+def build_telemetry_formats(overrides: Optional[Dict[str, Any]] = None) -> Dict[str, TelemetryFormat]:
+    """Merge per-field overrides over the defaults; an override of None hides the field."""
+    merged: Dict[str, Any] = {path: dict(config) for path, config in DEFAULT_TELEMETRY_FORMATS.items()}
+    for path, config in (overrides or {}).items():
+        if config is None:
+            merged.pop(path, None)
+        elif isinstance(config, dict):
+            merged[path] = {**merged.get(path, {}), **config}
+    formats = {}
+    for path, config in merged.items():
+        try:
+            formats[path] = TelemetryFormat.from_config(path, config)
+        except (TypeError, ValueError) as e:
+            print(f"[TelemetryFormat] Ignoring bad format for {path}: {e}")
+    return formats
+
+
+class TelemetryStore:
+    """Latest raw telemetry values with a per-field last-updated timestamp and display formats."""
+
+    def __init__(self, formats: Optional[Dict[str, TelemetryFormat]] = None):
+        self.values: Dict[str, Any] = {}
+        self.updated_at: Dict[str, float] = {}
+        self.formats = build_telemetry_formats() if formats is None else formats
+
+    def update(self, data: dict) -> None:
+        now = time.time()
+        for key, value in data.items():
+            self.values[key] = value
+            self.updated_at[key] = now
+
+    def get(self, key: str, default: Any = None) -> Any:
+        return self.values.get(key, default)
+
+    def age(self, key: str) -> Optional[float]:
+        """Seconds since ``key`` was last received, or None if never seen."""
+        updated = self.updated_at.get(key)
+        return None if updated is None else time.time() - updated
+
+    def is_stale(self, key: str, threshold_s: float) -> bool:
+        age = self.age(key)
+        return age is None or age > threshold_s
+
+    def updated_since(self, key: str, since: float) -> bool:
+        """True if ``key`` arrived at or after ``since`` (a time.time() timestamp)."""
+        return self.updated_at.get(key, float("-inf")) >= since
+
+    def last_updated(self) -> Optional[float]:
+        """time.time() of the newest telemetry received, or None if none ever has."""
+        return max(self.updated_at.values(), default=None)
+
+    def lookup(self, path: str, default: Any = None) -> Any:
+        """Raw value at a dotted path such as ``pose.x``."""
+        value: Any = self.values
+        for part in path.split("."):
+            if isinstance(value, list) and part.isdigit() and int(part) < len(value):
+                value = value[int(part)]
+            elif isinstance(value, dict) and part in value:
+                value = value[part]
+            else:
+                return default
+        return value
+
+    def paths(self) -> List[str]:
+        """Every dotted field received so far, as ``lookup`` and the CSV log name them."""
+        return sorted(flatten_telemetry(self.values))
+
+    def display(self, path: str) -> str:
+        """Value at ``path`` formatted for the UI, or ``--`` if it hasn't arrived."""
+        value = self.lookup(path)
+        if value is None:
+            return "--"
+        fmt = self.formats.get(path)
+        return fmt.format(value) if fmt else str(value)
+
+
+def flatten_telemetry(data: Any, prefix: str = "") -> Dict[str, Any]:
+    """Flatten telemetry into dotted columns: ``pose.x``, ``motor_speeds.0``.
+
+    Lists of plain values get a column per index; lists holding objects (alerts,
+    self-test results) stay in one column as JSON.
+    """
+    if isinstance(data, dict):
+        flat: Dict[str, Any] = {}
+        for key, value in data.items():
+            flat.update(flatten_telemetry(value, f"{prefix}{key}."))
+        return flat
+    path = prefix[:-1]
+    if isinstance(data, list):
+        if all(not isinstance(item, (dict, list)) for item in data):
+            return {f"{path}.{i}": item for i, item in enumerate(data)}
+        return {path: json.dumps(data)}
+    return {path: data}
+
+
+class TelemetryCsvWriter(threading.Thread):
+    """Mirrors telemetry to CSV from its own thread so disk latency never stalls receiving.
+
+    One row per update with a leading ``time`` column. The columns are every field seen
+    so far, left blank while a field is missing. A field that first shows up mid-run
+    starts the next part file (``run.csv``, ``run.2.csv``, ...) with the wider header,
+    so each file stays a plain CSV that spreadsheets open as-is.
+    """
+
+    def __init__(self, path: str, max_queued: int = TELEMETRY_CSV_QUEUE_MAX):
+        super().__init__(daemon=True)
+        self.path = path
+        self.queue: queue.Queue = queue.Queue(maxsize=max_queued)
+        self.columns: List[str] = []
+        self.parts = 0
+        self.rows = 0
+        # Rows lost because the queue was full; the receiving thread never waits on the disk.
+        self.dropped = 0
+        self.file = None
+        self.writer = None
+
+    def write(self, data: dict) -> None:
+        try:
+            self.queue.put_nowait((time.time(), data))
+        except queue.Full:
+            self.dropped += 1
+
+    def stop(self) -> None:
+        """Write out whatever is queued, then close the file."""
+        try:
+            self.queue.put(None, timeout=2)
+        except queue.Full:
+            print(f"[TelemetryCsvWriter] {self.path} is not draining; giving up on the queued rows")
+        self.join(timeout=2)
+
+    def part_path(self, part: int) -> str:
+        if part <= 1:
+            return self.path
+        base, ext = os.path.splitext(self.path)
+        return f"{base}.{part}{ext}"
+
+    def _start_part(self) -> None:
+        if self.file is not None:
+            self.file.close()
+        self.parts += 1
+        self.file = open(self.part_path(self.parts), "w", newline="", encoding="utf-8")
+        self.writer = csv.writer(self.file)
+        self.writer.writerow(["time"] + self.columns)
+
+    def _write_row(self, stamp: float, data: dict) -> None:
+        flat = flatten_telemetry(data)
+        new = [column for column in flat if column not in self.columns]
+        if new or self.file is None:
+            self.columns += new
+            self._start_part()
+        self.writer.writerow([f"{stamp:.3f}"] + ["" if flat.get(c) is None else flat[c] for c in self.columns])
+        self.rows += 1
+
+    def run(self) -> None:
+        last_flush = time.monotonic()
+        try:
+            while True:
+                try:
+                    item = self.queue.get(timeout=TELEMETRY_CSV_FLUSH_S)
+                except queue.Empty:
+                    item = ()
+                if item is None:
+                    break
+                if item:
+                    self._write_row(*item)
+                if self.file is not None and time.monotonic() - last_flush >= TELEMETRY_CSV_FLUSH_S:
+                    self.file.flush()
+                    last_flush = time.monotonic()
+        except OSError as e:
+            print(f"[TelemetryCsvWriter] Stopped writing {self.path}: {e}")
+        finally:
+            if self.file is not None:
+                self.file.close()
+
+
+def hexdump(data: bytes) -> List[str]:
+    """Classic offset / hex / ASCII lines for ``data``."""
+    lines = []
+    for offset in range(0, len(data), WIRE_LOG_BYTES_PER_LINE):
+        chunk = data[offset:offset + WIRE_LOG_BYTES_PER_LINE]
+        hex_part = " ".join(f"{b:02x}" for b in chunk)
+        text = "".join(chr(b) if 32 <= b < 127 else "." for b in chunk)
+        lines.append(f"  {offset:06x}  {hex_part:<{WIRE_LOG_BYTES_PER_LINE * 3 - 1}}  |{text}|")
+    return lines
+
+
+class WireLogWriter(threading.Thread):
+    """Hex-dumps every frame a link sends or receives, exactly as it crossed the socket.
+
+    Links call record() only while a writer is attached, so logging costs nothing when off.
+    Frames are copied onto a queue and formatted here, so the link's timing barely changes.
+    """
+
+    def __init__(self, path: str):
+        super().__init__(daemon=True)
+        self.path = path
+        self.queue: queue.Queue = queue.Queue()
+        self.frames = 0
+        self.bytes = 0
+
+    def record(self, direction: str, stream: str, frames: List[bytes]) -> None:
+        """Log one message: ``direction`` is "OUT" or "IN", ``stream`` names the socket."""
+        self.queue.put((time.time(), direction, stream, [bytes(frame) for frame in frames]))
+
+    def stop(self) -> None:
+        """Write out whatever is queued, then close the file."""
+        self.queue.put(None)
+        self.join(timeout=2)
+
+    def _write_message(self, file, stamp: float, direction: str, stream: str, frames: List[bytes]) -> None:
+        clock = time.strftime("%H:%M:%S", time.localtime(stamp)) + f".{int(stamp * 1e6) % 1000000:06d}"
+        for index, frame in enumerate(frames):
+            part = f" frame {index + 1}/{len(frames)}" if len(frames) > 1 else ""
+            file.write(f"{clock} {direction:<3} {stream}{part} {len(frame)} bytes\n")
+            file.write("\n".join(hexdump(frame)) + ("\n" if frame else ""))
+            self.frames += 1
+            self.bytes += len(frame)
+
+    def run(self) -> None:
+        last_flush = time.monotonic()
+        try:
+            with open(self.path, "w", encoding="ascii") as file:
+                while True:
+                    try:
+                        item = self.queue.get(timeout=TELEMETRY_CSV_FLUSH_S)
+                    except queue.Empty:
+                        item = ()
+                    if item is None:
+                        break
+                    if item:
+                        self._write_message(file, *item)
+                    if time.monotonic() - last_flush >= TELEMETRY_CSV_FLUSH_S:
+                        file.flush()
+                        last_flush = time.monotonic()
+        except OSError as e:
+            print(f"[WireLogWriter] Stopped writing {self.path}: {e}")
+
+
+# Connection lifecycle events passed to ConnectionManager hooks.
+CONNECTING = "connecting"
+RECONNECTING = "reconnecting"
+HANDSHAKE_COMPLETE = "handshake_complete"
+CONNECTED = "connected"
+DISCONNECTED = "disconnected"
+
+ConnectionHook = Callable[[str, str], None]
+
+# Stages of bringing up a link, in order, reported through WorkerSignals.connection_progress
+# so an address that is slow or hangs shows where it is stuck.
+STAGE_RESOLVING = "resolving address"
+STAGE_CONNECTING = "connecting"
+STAGE_AUTHENTICATING = "authenticating"
+STAGE_HANDSHAKING = "handshaking"
+STAGE_SUBSCRIBING = "subscribing to telemetry"
+CONNECT_STAGES = (STAGE_RESOLVING, STAGE_CONNECTING, STAGE_AUTHENTICATING, STAGE_HANDSHAKING, STAGE_SUBSCRIBING)
+
+
+class OutageLog:
+    """Connection outages for one driver session, fed by ConnectionManager's lifecycle hooks.
+
+    An outage runs from a DISCONNECTED event to the next CONNECTED one and records when it
+    started, how long it lasted, the address that failed, the address that came back, why
+    the link dropped and how many connection attempts it took. Each finished outage is
+    appended to ``path`` as a JSON line as soon as it ends, and close() adds a closing
+    ``session`` line with the uptime summary, so a crash loses at most the open outage.
+
+    Uptime is counted from the first connection, so searching for the robot at launch
+    doesn't count against the link.
+    """
+
+    def __init__(self, path: Optional[str] = None):
+        self.path = path
+        self.lock = threading.Lock()
+        self.outages: List[Dict[str, Any]] = []
+        self.current: Optional[Dict[str, Any]] = None
+        self.first_connected: Optional[float] = None
+        self.down_s = 0.0
+        self.closed = False
+
+    def record(self, event: str, address: str, reason: str = "") -> None:
+        """Hook body: ``reason`` is why the link dropped, for DISCONNECTED."""
+        now = time.monotonic()
+        finished = None
+        with self.lock:
+            if self.closed:
+                return
+            if event == DISCONNECTED and self.current is None and self.first_connected is not None:
+                self.current = {
+                    "start": time.time(),
+                    "started": now,
+                    "failed_address": address,
+                    "reconnected_address": None,
+                    "reason": reason or "unknown",
+                    "attempts": 0,
+                }
+            elif event in (CONNECTING, RECONNECTING) and self.current is not None:
+                self.current["attempts"] += 1
+            elif event == CONNECTED:
+                if self.first_connected is None:
+                    self.first_connected = now
+                if self.current is not None:
+                    finished = self._finish(now, address)
+        if finished is not None:
+            self._write({"event": "outage", **finished})
+
+    def _finish(self, now: float, address: Optional[str]) -> Dict[str, Any]:
+        outage = self.current
+        self.current = None
+        started = outage.pop("started")
+        outage["duration_s"] = now - started
+        outage["reconnected_address"] = address
+        self.down_s += outage["duration_s"]
+        self.outages.append(outage)
+        return outage
+
+    def _summary(self, now: float) -> Dict[str, Any]:
+        session_s = now - self.first_connected if self.first_connected is not None else 0.0
+        down_s = self.down_s + (now - self.current["started"] if self.current is not None else 0.0)
+        return {
+            "outages": len(self.outages) + (self.current is not None),
+            "session_s": session_s,
+            "down_s": down_s,
+            "uptime_pct": 100.0 * (1.0 - down_s / session_s) if session_s > 0 else None,
+        }
+
+    def summary(self) -> Dict[str, Any]:
+        """Session totals so far; ``uptime_pct`` is None until the first connection."""
+        with self.lock:
+            return self._summary(time.monotonic())
+
+    def snapshot(self) -> List[Dict[str, Any]]:
+        """Finished outages, oldest first, then the open one (no ``duration_s`` yet) if any."""
+        with self.lock:
+            outages = [dict(outage) for outage in self.outages]
+            if self.current is not None:
+                outages.append({k: v for k, v in self.current.items() if k != "started"})
+            return outages
+
+    def close(self) -> None:
+        """End the session: an outage still open is logged with no reconnect address."""
+        now = time.monotonic()
+        with self.lock:
+            if self.closed:
+                return
+            self.closed = True
+            finished = self._finish(now, None) if self.current is not None else None
+            summary = self._summary(now) if self.first_connected is not None else None
+        if finished is not None:
+            self._write({"event": "outage", **finished})
+        if summary is not None:
+            self._write({"event": "session", "end": time.time(), **summary})
+
+    def _write(self, entry: Dict[str, Any]) -> None:
+        if not self.path:
+            return
+        try:
+            with open(self.path, "a", encoding="utf-8") as file:
+                file.write(json.dumps(entry) + "\n")
+        except OSError as e:
+            print(f"[OutageLog] Couldn't write {self.path}: {e}")
+
 
 class WorkerSignals(QObject):
     """Signals for communication with Qt GUI thread."""
     connection_status = pyqtSignal(bool, str)
+    # (address, stage, error) while a connection attempt works through CONNECT_STAGES;
+    # error is empty unless the attempt failed at that stage.
+    connection_progress = pyqtSignal(str, str, str)
     ping_response = pyqtSignal(float)
     telemetry_update = pyqtSignal(dict)
+    # A robot alert from the alert channel, ahead of the next telemetry frame that lists it.
+    robot_alert = pyqtSignal(dict)
+    # Throttled "data arrived" pulse (telemetry or pong) for the link heartbeat indicator.
+    link_activity = pyqtSignal()
+
+    def __init__(self):
+        super().__init__()
+        # Set once nothing on the GUI side can receive these any more; background
+        # threads watch it and stop instead of posting into a dead event loop.
+        self.closed = threading.Event()
+
+    def close(self) -> None:
+        """Mark the GUI gone, e.g. as its window closes or its event loop ends."""
+        self.closed.set()
+
+    def post(self, name: str, *args) -> bool:
+        """Emit signal ``name`` to the GUI thread. False once the GUI is gone.
+
+        PyQt raises RuntimeError from emit once the QObject has been deleted, which is
+        what tearing down the application does. That can't recover, so it closes the
+        signals rather than failing again on every frame.
+        """
+        if self.closed.is_set():
+            return False
+        try:
+            getattr(self, name).emit(*args)
+        except RuntimeError as e:
+            print(f"[WorkerSignals] GUI is gone ({e}); stopping background work")
+            self.close()
+            return False
+        return True
+
+
+def is_safety_command(command: dict) -> bool:
+    """True for commands that stop the robot: SAFETY_COMMANDS and a switch to STOPPED."""
+    command_type = command.get("type")
+    if command_type == "mode":
+        return str(command.get("mode", "")).upper() == "STOPPED"
+    return command_type in SAFETY_COMMANDS
+
+
+class ReliableOutbox:
+    """Two-tier store of un-acked reliable commands, replayed after reconnect.
+
+    Safety commands are replayed ahead of everything else and never expire or get
+    evicted; a repeat only replaces the earlier copy. Other commands are bounded
+    (oldest dropped first) and expire after ``max_age_s``. A safety command also
+    discards whatever was queued before it, since the stop supersedes those.
+    """
+
+    def __init__(self, max_entries: int = OUTBOX_MAX_ENTRIES, max_age_s: float = OUTBOX_MAX_AGE_S):
+        self.max_age_s = max_age_s
+        self.safety: Dict[Tuple[Any, Any], dict] = {}
+        self.entries: deque = deque(maxlen=max_entries)
+        self.lock = threading.Lock()
+
+    def add(self, command: dict) -> None:
+        with self.lock:
+            if is_safety_command(command):
+                key = (command.get("type"), command.get("mode"))
+                self.safety.pop(key, None)
+                self.safety[key] = command
+                self.entries.clear()
+            else:
+                self.entries.append((time.time(), command))
+
+    def drain(self) -> List[dict]:
+        """Remove and return pending commands: safety ones first, then the rest oldest first."""
+        now = time.time()
+        with self.lock:
+            pending = list(self.safety.values())
+            pending += [cmd for queued_at, cmd in self.entries if now - queued_at <= self.max_age_s]
+            self.safety.clear()
+            self.entries.clear()
+        return pending
+
+
+class LatencyHistogram:
+    """Thread-safe counts of command round-trip times by bucket."""
+
+    def __init__(self, bounds_ms=LATENCY_BUCKETS_MS):
+        self.bounds_ms = tuple(bounds_ms)
+        self.lock = threading.Lock()
+        self.reset()
+
+    def labels(self) -> List[str]:
+        labels = []
+        lower = 0.0
+        for upper in self.bounds_ms:
+            labels.append(f"<{upper:.0f}" if lower == 0.0 else f"{lower:.0f}-{upper:.0f}")
+            lower = upper
+        labels.append(f">{lower:.0f}")
+        return labels
+
+    def record(self, rtt_ms: float) -> None:
+        index = next((i for i, upper in enumerate(self.bounds_ms) if rtt_ms < upper), len(self.bounds_ms))
+        with self.lock:
+            self.counts[index] += 1
+
+    def snapshot(self) -> List[int]:
+        with self.lock:
+            return list(self.counts)
+
+    def reset(self) -> None:
+        with self.lock:
+            self.counts = [0] * (len(self.bounds_ms) + 1)
+
+
+class BandwidthMeter:
+    """Thread-safe bytes per second each way on the link, over the last BANDWIDTH_WINDOW_S.
+
+    Counts the frames handed to and taken from ZMQ (commands and replies, drive frames and
+    telemetry socket messages), not the TCP and ZMQ framing around them.
+    """
+
+    def __init__(self, window_s: float = BANDWIDTH_WINDOW_S):
+        self.window_s = window_s
+        self.lock = threading.Lock()
+        self.reset()
+
+    def reset(self) -> None:
+        with self.lock:
+            self.started = time.monotonic()
+            # (monotonic time, bytes sent, bytes received) within the window.
+            self.samples: deque = deque()
+            self.sent_bytes = 0
+            self.received_bytes = 0
+
+    def record(self, sent: int = 0, received: int = 0) -> None:
+        now = time.monotonic()
+        with self.lock:
+            self.samples.append((now, sent, received))
+            self.sent_bytes += sent
+            self.received_bytes += received
+            self._expire(now)
+
+    def _expire(self, now: float) -> None:
+        while self.samples and now - self.samples[0][0] > self.window_s:
+            self.samples.popleft()
+
+    def snapshot(self) -> Dict[str, float]:
+        """Rates over the window (or the time since the last reset, if shorter) and totals."""
+        now = time.monotonic()
+        with self.lock:
+            self._expire(now)
+            span = max(min(self.window_s, now - self.started), 1e-3)
+            return {
+                "sent_bytes_per_s": sum(sent for _, sent, _ in self.samples) / span,
+                "received_bytes_per_s": sum(received for _, _, received in self.samples) / span,
+                "sent_bytes": self.sent_bytes,
+                "received_bytes": self.received_bytes,
+            }
+
+
+class TelemetryLossMeter:
+    """Estimates telemetry loss from gaps in the robot's frame numbers.
+
+    Only frames that reach the receive loop are seen, so this measures the robot-to-driver
+    direction on its own, unlike command timeouts. Numbers wrap at TELEMETRY_SEQ_MODULUS.
+    A repeated or slightly older number is counted as late rather than filling a gap, and
+    any bigger jump restarts counting from that frame.
+    """
+
+    def __init__(self):
+        self.lock = threading.Lock()
+        self.reset()
+
+    def reset(self) -> None:
+        with self.lock:
+            self.last_seq: Optional[int] = None
+            self.received = 0
+            self.lost = 0
+            self.late = 0
+            # One entry per expected frame, True where it arrived.
+            self.recent: deque = deque(maxlen=TELEMETRY_LOSS_WINDOW)
+
+    def resync(self) -> None:
+        """Forget the last number, e.g. on a new link, so the switch-over isn't counted as loss."""
+        with self.lock:
+            self.last_seq = None
+
+    def observe(self, seq: Any) -> None:
+        if not isinstance(seq, int) or isinstance(seq, bool):
+            return
+        seq %= TELEMETRY_SEQ_MODULUS
+        with self.lock:
+            if self.last_seq is not None:
+                ahead = (seq - self.last_seq) % TELEMETRY_SEQ_MODULUS
+                if ahead == 0 or TELEMETRY_SEQ_MODULUS - ahead <= TELEMETRY_SEQ_MAX_LATE:
+                    self.late += 1
+                    return
+                gap = ahead - 1
+                if gap <= TELEMETRY_SEQ_MAX_GAP:
+                    self.lost += gap
+                    self.recent.extend([False] * min(gap, TELEMETRY_LOSS_WINDOW))
+            self.last_seq = seq
+            self.received += 1
+            self.recent.append(True)
+
+    def snapshot(self) -> Dict[str, Any]:
+        """Totals since the last reset, and ``recent_pct`` once the window has enough frames."""
+        with self.lock:
+            expected = self.received + self.lost
+            window = len(self.recent)
+            return {
+                "received": self.received,
+                "lost": self.lost,
+                "late": self.late,
+                "loss_pct": 100.0 * self.lost / expected if expected else 0.0,
+                "recent_pct": 100.0 * self.recent.count(False) / window
+                if window >= TELEMETRY_LOSS_MIN_FRAMES else None,
+            }
+
+
+class BenchMeter:
+    """Scores one link benchmark burst from the frames that reach the telemetry socket.
+
+    Created just before the 'bench' command is sent, so elapsed time includes the request
+    and nothing that arrives before the reply is missed.
+    """
+
+    def __init__(self):
+        self.started = time.monotonic()
+        self.last_frame = self.started
+        self.bench_id: Optional[int] = None
+        self.expected_bytes: Optional[int] = None
+        self.received_bytes = 0
+        self.received_chunks = 0
+        # The robot's closing header, with what it actually sent.
+        self.done: Optional[dict] = None
+
+    def add(self, header: dict, payload_bytes: int) -> None:
+        if self.bench_id is None:
+            self.bench_id = header.get("id")
+        elif header.get("id") != self.bench_id:
+            return
+        self.last_frame = time.monotonic()
+        if header.get("done"):
+            self.done = header
+            return
+        self.expected_bytes = header.get("bytes")
+        self.received_bytes += payload_bytes
+        self.received_chunks += 1
+
+    def finished(self) -> bool:
+        return self.done is not None or time.monotonic() - self.last_frame > BENCH_IDLE_TIMEOUT_S
+
+    def result(self) -> dict:
+        elapsed_s = self.last_frame - self.started
+        sent = self.done.get("sent_bytes") if self.done else self.expected_bytes
+        lost = max(0, sent - self.received_bytes) if sent else 0
+        if self.done:
+            aborted = self.done.get("aborted")
+        else:
+            aborted = "no closing frame" if self.bench_id is not None else "nothing received"
+        return {
+            "received_bytes": self.received_bytes,
+            "sent_bytes": sent,
+            "lost_bytes": lost,
+            "loss_pct": 100.0 * lost / sent if sent else 0.0,
+            "elapsed_s": elapsed_s,
+            "bytes_per_s": self.received_bytes / elapsed_s if elapsed_s > 0 else 0.0,
+            "aborted": aborted,
+        }
+
+
+class SessionRecorder:
+    """Thread-safe capture of outbound drive commands with their timing, for replay."""
+
+    def __init__(self):
+        self.lock = threading.Lock()
+        self.events: List[Dict[str, Any]] = []
+        self.started_at: Optional[float] = None
+        # Held while a replay sends, so a replay never lands in the session being recorded.
+        self.suspended = 0
+
+    @contextmanager
+    def suspend(self) -> Iterator[None]:
+        with self.lock:
+            self.suspended += 1
+        try:
+            yield
+        finally:
+            with self.lock:
+                self.suspended -= 1
+
+    @property
+    def recording(self) -> bool:
+        return self.started_at is not None
+
+    def start(self) -> None:
+        with self.lock:
+            self.events = []
+            self.started_at = time.time()
+
+    def stop(self) -> List[Dict[str, Any]]:
+        """Stop recording and return the captured events."""
+        with self.lock:
+            self.started_at = None
+            events, self.events = self.events, []
+        return events
+
+    def record(self, command_type: str, fields: Dict[str, Any]) -> None:
+        if command_type not in RECORDED_COMMANDS:
+            return
+        with self.lock:
+            if self.started_at is None or self.suspended:
+                return
+            self.events.append({"t": time.time() - self.started_at, "type": command_type, "fields": dict(fields)})
+
+
+class CommandHistory:
+    """Lock-protected ring of recently sent commands and how each went. Always on, memory only."""
+
+    def __init__(self, window_s: float = COMMAND_HISTORY_S, max_entries: int = COMMAND_HISTORY_MAX):
+        self.lock = threading.Lock()
+        self.window_s = window_s
+        self.entries: deque = deque(maxlen=max_entries)
+
+    def record(self, command: dict, outcome: str, replayed: bool = False) -> None:
+        # A replay goes out long after the command was built, so it is placed when re-sent.
+        sent_at = time.time() if replayed else command.get("timestamp", time.time())
+        entry = {
+            "t": sent_at,
+            "type": command.get("type"),
+            "seq": command.get("seq"),
+            "fields": {k: v for k, v in command.items() if k not in COMMAND_ENVELOPE_FIELDS},
+            "outcome": outcome,
+            "safety": is_safety_command(command),
+            "replayed": replayed,
+        }
+        with self.lock:
+            self.entries.append(entry)
+            self._expire(time.time())
+
+    def _expire(self, now: float) -> None:
+        while self.entries and now - self.entries[0]["t"] > self.window_s:
+            self.entries.popleft()
+
+    def snapshot(self) -> List[Dict[str, Any]]:
+        """Entries from the last ``window_s`` seconds, oldest first."""
+        with self.lock:
+            self._expire(time.time())
+            entries = list(self.entries)
+        # Threads record as their replies arrive; show them in the order they were sent.
+        return sorted(entries, key=lambda entry: entry["t"])
+
+
+def describe_outcome(response: Optional[dict]) -> str:
+    if response is None:
+        return "no reply"
+    if response.get("status") == "success":
+        return "ok"
+    return f"error: {response.get('message', '')}".rstrip(": ")
+
+
+class ChannelDemux:
+    """Route [channel, payload...] messages from the robot's telemetry socket to per-channel handlers.
+
+    Each handler gets the payload frames and may return a value, which dispatch() passes back.
+    A single-frame message is telemetry, which robots send that way so that driver
+    stations from before channels can still read it.
+    """
+
+    def __init__(self):
+        self.handlers: Dict[bytes, Callable[[List[bytes]], Any]] = {}
+        # Messages on channels nobody registered for, e.g. from newer firmware.
+        self.unrouted = 0
+
+    def register(self, channel: bytes, handler: Callable[[List[bytes]], Any]) -> None:
+        self.handlers[channel] = handler
+
+    def dispatch(self, frames: List[bytes]) -> Tuple[Optional[bytes], Any]:
+        """Hand ``frames`` to its channel's handler: (channel, handler result), or (None, None) if unrouted."""
+        if len(frames) == 1:
+            channel, payload = CHANNEL_TELEMETRY, frames
+        else:
+            channel, payload = frames[0], frames[1:]
+        handler = self.handlers.get(channel)
+        if handler is None:
+            self.unrouted += 1
+            return None, None
+        return channel, handler(payload)
+
+
+def decode_json_frame(frame: bytes) -> dict:
+    """A JSON object from the robot, with control characters cleaned out of its strings."""
+    try:
+        data = clean_strings(json.loads(frame))
+    except RecursionError as e:
+        raise ValueError("JSON nested too deeply") from e
+    if not isinstance(data, dict):
+        raise ValueError(f"expected a JSON object, got {type(data).__name__}")
+    return data
 
 
 class RobotClient:
     """Client that manages command (REQ/REP) and telemetry (SUB) sockets."""
 
-    def __init__(self, robot_ip: str):
+    def __init__(self, robot_ip: str, command_port: int = COMMAND_PORT, telemetry_port: int = TELEMETRY_PORT):
         self.robot_ip = robot_ip
+        self.command_port = command_port
+        self.telemetry_port = telemetry_port
         self.context = zmq.Context()
         self.signals = WorkerSignals()
+        # Called with the new connected state; set by ConnectionManager once this client is live.
+        self.on_status: Optional[Callable[[bool], None]] = None
+        # Why the link last went down, for OutageLog.
+        self.disconnect_reason = ""
+        self.outbox: Optional[ReliableOutbox] = None
+        self.latency: Optional[LatencyHistogram] = None
+        self.telemetry_loss: Optional[TelemetryLossMeter] = None
+        self.bandwidth: Optional[BandwidthMeter] = None
+        self.recorder: Optional[SessionRecorder] = None
+        self.history: Optional[CommandHistory] = None
 
-        self.command_socket = self.context.socket(zmq.REQ)
-        self.command_socket.connect(f"tcp://{robot_ip}:{COMMAND_PORT}")
-        self.command_socket.setsockopt(zmq.RCVTIMEO, COMMAND_TIMEOUT_MS)
-        self.command_socket.setsockopt(zmq.LINGER, 0)
+        self.command_socket = self._open_command_socket()
+        # The UI thread and the ping thread share the REQ socket; hold this for a whole send/recv.
+        self.command_lock = threading.Lock()
+        self.consecutive_timeouts = 0
 
-        self.telemetry_socket = self.context.socket(zmq.SUB)
-        self.telemetry_socket.connect(f"tcp://{robot_ip}:{TELEMETRY_PORT}")
-        self.telemetry_socket.subscribe("")
-        self.telemetry_socket.setsockopt(zmq.RCVTIMEO, TELEMETRY_TIMEOUT_MS)
-        self.telemetry_socket.setsockopt(zmq.LINGER, 0)
+        # SUB socket, opened by subscribe_telemetry() once commands get through.
+        self.telemetry_socket = None
+
+        # Optional PUSH socket for unacked drive frames, set up by handshake().
+        self.drive_socket = None
+        self.unacked_commands = set()
+        # Width of the button mask the robot takes in joystick frames, from 'hello'; 0 for none.
+        self.button_mask_bits = 0
+        # Commands the robot reported via 'capabilities'; None until known.
+        self.capabilities: Optional[set] = None
+        # Role the robot granted in handshake(); robots without roles treat everyone as a driver.
+        self.role = ROLE_DRIVER
+        # Set while a link benchmark runs; bench frames are tallied here instead of emitted.
+        self.bench: Optional[BenchMeter] = None
+        # Raw frame log, attached by ConnectionManager.set_wire_log(); None when off.
+        self.wire_log: Optional[WireLogWriter] = None
+        self.channels = ChannelDemux()
+        self.channels.register(CHANNEL_TELEMETRY, self._on_telemetry_frame)
+        self.channels.register(CHANNEL_ALERT, self._on_alert_frame)
+        self.channels.register(BENCH_TOPIC, self._on_bench_frame)
 
         self.connected = False
         self.running = True
         self.last_ping_time = 0
         self.ping_sent_time = None
+        self.session: Optional[str] = None
 
         print(f"[RobotClient] Initialized connection to {robot_ip}")
 
-    def _set_connected(self, connected: bool) -> None:
+    def _open_command_socket(self) -> zmq.Socket:
+        socket = self.context.socket(zmq.REQ)
+        socket.setsockopt(zmq.IPV6, 1)
+        apply_tcp_keepalive(socket)
+        socket.connect(tcp_endpoint(self.robot_ip, self.command_port))
+        socket.setsockopt(zmq.RCVTIMEO, COMMAND_TIMEOUT_MS)
+        socket.setsockopt(zmq.LINGER, 0)
+        return socket
+
+    def subscribe_telemetry(self) -> None:
+        self.telemetry_socket = self.context.socket(zmq.SUB)
+        self.telemetry_socket.setsockopt(zmq.IPV6, 1)
+        apply_tcp_keepalive(self.telemetry_socket)
+        self.telemetry_socket.connect(tcp_endpoint(self.robot_ip, self.telemetry_port))
+        self.telemetry_socket.subscribe("")
+        self.telemetry_socket.setsockopt(zmq.RCVTIMEO, TELEMETRY_TIMEOUT_MS)
+        self.telemetry_socket.setsockopt(zmq.LINGER, 0)
+
+    def _reset_command_socket(self) -> None:
+        """A REQ socket that missed its reply can't send again, so replace it."""
+        with self.command_lock:
+            self.command_socket.close(0)
+            self.command_socket = self._open_command_socket()
+
+    def _set_connected(self, connected: bool, reason: str = "") -> None:
         if self.connected == connected:
             return
         self.connected = connected
-        self.signals.connection_status.emit(connected, self.robot_ip if connected else "")
+        if not connected:
+            self.disconnect_reason = reason
+        if self.on_status is not None:
+            self.on_status(connected)
+
+    def _build_command(self, command_type: str, **kwargs) -> dict:
+        return build_command(command_type, **kwargs)
 
     def send_command(self, command_type: str, **kwargs) -> Optional[dict]:
         """Send a command to the robot and wait for a response."""
+        command = self._build_command(command_type, **kwargs)
+        if self.recorder is not None:
+            self.recorder.record(command_type, kwargs)
+        if command_type in self.unacked_commands:
+            sent = self.send_unacked(command)
+            if self.history is not None:
+                self.history.record(command, "sent" if sent else "dropped")
+            return None
+
+        response = self.send_raw(command)
+        if self.history is not None:
+            self.history.record(command, describe_outcome(response))
+        if response is None and command_type in RELIABLE_COMMANDS and self.outbox is not None:
+            self.outbox.add(command)
+        return response
+
+    def request(self, command: dict) -> dict:
+        """Send a fully-formed command dict and return the reply, raising LinkError on failure."""
+        if self.session:
+            command["session"] = self.session
+        sent_at = time.time()
+        payload = json.dumps(command).encode()
+        wire_log = self.wire_log
+        with self.command_lock:
+            try:
+                self.command_socket.send(payload)
+            except zmq.ZMQError as e:
+                raise link_error_from_zmq(e) from e
+            if wire_log is not None:
+                wire_log.record("OUT", "cmd", [payload])
+
+            try:
+                reply = self.command_socket.recv()
+            except zmq.Again as e:
+                raise LinkTimeout(f"No reply to {command.get('type')} within {COMMAND_TIMEOUT_MS} ms") from e
+            except zmq.ZMQError as e:
+                raise link_error_from_zmq(e) from e
+        if wire_log is not None:
+            wire_log.record("IN", "cmd", [reply])
         try:
-            command = {"type": command_type, "timestamp": time.time(), **kwargs}
-            self.command_socket.send_json(command)
-            response = self.command_socket.recv_json()
+            response = decode_json_frame(reply)
+        except ValueError as e:
+            raise LinkCorrupt(f"Malformed reply to {command.get('type')}: {e}") from e
 
-            self._set_connected(True)
-            return response
-        except zmq.Again:
-            self._set_connected(False)
+        if self.latency is not None:
+            self.latency.record((time.time() - sent_at) * 1000.0)
+        if self.bandwidth is not None:
+            self.bandwidth.record(sent=len(payload), received=len(reply))
+        return response
+
+    def send_raw(self, command: dict) -> Optional[dict]:
+        """Send a fully-formed command dict, keeping its sequence number. Returns None on failure."""
+        try:
+            response = self.request(command)
+        except LinkTimeout as e:
+            # Lazy-pirate style: a lone timeout gets a fresh socket, repeated ones drop the link.
+            self.consecutive_timeouts += 1
+            self._reset_command_socket()
+            if self.consecutive_timeouts >= MAX_COMMAND_TIMEOUTS:
+                print(f"[RobotClient] {e}; link lost")
+                self._set_connected(False, f"{MAX_COMMAND_TIMEOUTS} command timeouts in a row ({e})")
             return None
-        except Exception as e:
-            print(f"[RobotClient] Command error: {e}")
-            self._set_connected(False)
+        except LinkCorrupt as e:
+            # The robot is still answering, so keep the connection.
+            print(f"[RobotClient] {e}")
             return None
+        except LinkError as e:
+            print(f"[RobotClient] Command error: {e!r}")
+            self._set_connected(False, f"{type(e).__name__}: {e}")
+            return None
+
+        recovered = self.consecutive_timeouts > 0
+        self.consecutive_timeouts = 0
+        self._set_connected(True)
+        if recovered:
+            self.flush_outbox()
+        return response
+
+    def flush_outbox(self) -> None:
+        """Re-send reliable commands that were lost while the robot wasn't answering."""
+        if self.outbox is None:
+            return
+        pending = self.outbox.drain()
+        for index, command in enumerate(pending):
+            response = self.send_raw(command)
+            if self.history is not None:
+                self.history.record(command, describe_outcome(response), replayed=True)
+            if response is None:
+                for remaining in pending[index:]:
+                    self.outbox.add(remaining)
+                return
+            print(f"[RobotClient] Re-sent {command['type']} (seq {command['seq']})")
+
+    def send_unacked(self, command: dict) -> bool:
+        """Push a command without waiting for a reply. Drops the frame if the pipe is full."""
+        if self.session:
+            command["session"] = self.session
+        payload = json.dumps(command).encode()
+        try:
+            self.drive_socket.send(payload, flags=zmq.NOBLOCK)
+        except zmq.ZMQError as e:
+            error = link_error_from_zmq(e)
+        else:
+            wire_log = self.wire_log
+            if wire_log is not None:
+                wire_log.record("OUT", "drive", [payload])
+            if self.bandwidth is not None:
+                self.bandwidth.record(sent=len(payload))
+            return True
+        if isinstance(error, LinkWouldBlock):
+            return False
+        print(f"[RobotClient] Drive channel error: {error!r}")
+        if isinstance(error, LinkDisconnected):
+            self._set_connected(False, f"drive channel: {error}")
+        return False
+
+    def handshake(self) -> None:
+        """Negotiate optional protocol features. Older robots reply with an error and keep defaults."""
+        # Claimed first: the robot only lists the commands this role may use.
+        self.claim_role(CONTROL_ROLE)
+        self.query_capabilities()
+        response = self.send_command("hello", channels=True)
+        if not response or response.get("status") != "success":
+            return
+        bits = response.get("button_mask_bits")
+        if BUTTON_MASK and isinstance(bits, int) and not isinstance(bits, bool) and bits > 0:
+            self.button_mask_bits = bits
+        if "drive_port" not in response:
+            return
+        drive_port = response["drive_port"]
+        if not isinstance(drive_port, int) or isinstance(drive_port, bool) or not 0 < drive_port < 65536:
+            print(f"[RobotClient] Ignoring malformed drive_port {drive_port!r}; drive frames stay acked")
+            return
 
-    def send_joystick(self, lx: float, ly: float, rx: float, ry: float) -> Optional[dict]:
+        self.drive_socket = self.context.socket(zmq.PUSH)
+        self.drive_socket.setsockopt(zmq.SNDHWM, DRIVE_SEND_HWM)
+        self.drive_socket.setsockopt(zmq.LINGER, 0)
+        self.drive_socket.setsockopt(zmq.IPV6, 1)
+        apply_tcp_keepalive(self.drive_socket)
+        self.drive_socket.connect(tcp_endpoint(self.robot_ip, drive_port))
+        unacked = response.get("unacked_commands", [])
+        self.unacked_commands = {c for c in unacked if isinstance(c, str)} if isinstance(unacked, list) else set()
+        print(f"[RobotClient] Unacked drive channel enabled for {sorted(self.unacked_commands)}")
+
+    def claim_role(self, role: str) -> None:
+        """Ask to act as ``role``; a refused driver claim leaves this client a spotter.
+
+        A robot that predates roles enforces nothing, so the role asked for is kept locally.
+        """
+        response = self.send_command("role", role=role)
+        granted = response.get("role") if response else None
+        if granted in ROLES:
+            self.role = granted
+        elif role in ROLES:
+            self.role = role
+        if response and response.get("status") != "success" and granted is not None:
+            print(f"[RobotClient] Role {role} refused: {response.get('message')}; acting as {self.role}")
+
+    def query_capabilities(self) -> None:
+        response = self.send_command("capabilities")
+        if response and response.get("status") == "success":
+            commands = response.get("commands", [])
+            if not isinstance(commands, list) or not all(isinstance(c, str) for c in commands):
+                print(f"[RobotClient] Ignoring malformed capabilities {str(commands)[:80]}; assuming full support")
+                return
+            self.capabilities = set(commands)
+            print(f"[RobotClient] Robot supports {sorted(self.capabilities)}")
+
+    def supports(self, command_type: str) -> bool:
+        """Assume support when the robot predates capability discovery."""
+        return self.capabilities is None or command_type in self.capabilities
+
+    def send_joystick(self, lx: float, ly: float, rx: float, ry: float,
+                      buttons: Optional[int] = None) -> Optional[dict]:
+        if buttons is not None:
+            return self.send_command("joystick", lx=lx, ly=ly, rx=rx, ry=ry, buttons=buttons)
         return self.send_command("joystick", lx=lx, ly=ly, rx=rx, ry=ry)
 
-    def send_button(self, button_id: int, action: str) -> Optional[dict]:
+    def sends_button_mask(self, button_id: int) -> bool:
+        """Whether ``button_id`` travels as a bit of the joystick frame's mask."""
+        return 0 <= button_id < self.button_mask_bits
+
+    def send_button(self, button_id: int, action: str, repeat: int = 0) -> Optional[dict]:
+        if repeat:
+            return self.send_command("button", button_id=button_id, action=action, repeat=repeat)
         return self.send_command("button", button_id=button_id, action=action)
 
     def set_mode(self, mode: str) -> Optional[dict]:
@@ -92,77 +1456,548 @@ class RobotClient:
     def reset_robot(self) -> Optional[dict]:
         return self.send_command("reset")
 
+    def pause(self) -> Optional[dict]:
+        return self.send_command("pause")
+
+    def resume(self) -> Optional[dict]:
+        return self.send_command("resume")
+
+    def shutdown(self) -> Optional[dict]:
+        return self.send_command("shutdown", confirm=True)
+
+    def wake(self) -> Optional[dict]:
+        return self.send_command("wake")
+
+    def run_selftest(self) -> Optional[dict]:
+        return self.send_command("selftest")
+
+    def dump_telemetry(self, which: str = "recent") -> Optional[dict]:
+        return self.send_command("dump", which=which)
+
+    def set_pid(self, axis: str, kp: float, ki: float, kd: float) -> Optional[dict]:
+        return self.send_command("pid", axis=axis, kp=kp, ki=ki, kd=kd)
+
+    def get_pid(self) -> Optional[dict]:
+        return self.send_command("pid_query")
+
+    def set_accel(self, up: float, down: float) -> Optional[dict]:
+        return self.send_command("accel", up=up, down=down)
+
+    def get_accel(self) -> Optional[dict]:
+        return self.send_command("accel_query")
+
+    def set_input_shaping(self, deadzone: float, expo: float) -> Optional[dict]:
+        return self.send_command("shape", deadzone=deadzone, expo=expo)
+
+    def get_input_shaping(self) -> Optional[dict]:
+        return self.send_command("shape_query")
+
+    def set_loop_rate(self, hz: float) -> Optional[dict]:
+        return self.send_command("loop_rate", hz=hz)
+
+    def get_loop_rate(self) -> Optional[dict]:
+        return self.send_command("loop_rate_query")
+
+    def set_servo(self, servo_id: str, angle: float) -> Optional[dict]:
+        return self.send_command("servo", id=servo_id, angle=angle)
+
+    def center_servos(self, servo_id: Optional[str] = None) -> Optional[dict]:
+        if servo_id is None:
+            return self.send_command("servo_center")
+        return self.send_command("servo_center", id=servo_id)
+
+    def set_name(self, name: str) -> Optional[dict]:
+        return self.send_command("name", name=name)
+
+    def get_name(self) -> Optional[dict]:
+        return self.send_command("name_query")
+
+    def list_sequences(self) -> Optional[dict]:
+        return self.send_command("sequence_list")
+
+    def run_sequence(self, name: str) -> Optional[dict]:
+        return self.send_command("run_sequence", name=name)
+
+    def stop_sequence(self) -> Optional[dict]:
+        return self.send_command("sequence_stop")
+
+    def set_geofence(self, radius_m: Optional[float]) -> Optional[dict]:
+        return self.send_command("geofence", radius_m=radius_m)
+
+    def turn(self, degrees: float) -> Optional[dict]:
+        return self.send_command("turn", degrees=degrees)
+
+    def set_drive_mode(self, mode: str) -> Optional[dict]:
+        return self.send_command("drive_mode", mode=mode)
+
+    def get_stats(self) -> Optional[dict]:
+        return self.send_command("stats")
+
+    def set_motor_reversed(self, motor: str, reverse: bool) -> Optional[dict]:
+        return self.send_command("motor_reverse", motor=motor, reversed=reverse)
+
+    def get_motor_reversed(self) -> Optional[dict]:
+        return self.send_command("motor_reverse_query")
+
+    def get_config(self) -> Optional[dict]:
+        return self.send_command("config_query")
+
+    def set_config(self, key: str, value: Any) -> Optional[dict]:
+        return self.send_command("config", key=key, value=value)
+
+    def set_brake(self, enabled: bool) -> Optional[dict]:
+        return self.send_command("brake", enabled=enabled)
+
+    def get_brake(self) -> Optional[dict]:
+        return self.send_command("brake_query")
+
+    def set_mix_mode(self, mode: str) -> Optional[dict]:
+        return self.send_command("mix_mode", mode=mode)
+
+    def get_mix_mode(self) -> Optional[dict]:
+        return self.send_command("mix_mode_query")
+
+    def start_bench(self, size_bytes: int) -> Optional[dict]:
+        return self.send_command("bench", bytes=size_bytes)
+
+    def stop_bench(self) -> Optional[dict]:
+        return self.send_command("bench_stop")
+
+    def poll_telemetry(self) -> Optional[dict]:
+        return self.send_command("telemetry_query")
+
+    def get_sensors(self) -> Optional[dict]:
+        return self.send_command("sensors_query")
+
+    def subscribe_sensors(self, enabled: bool = True) -> Optional[dict]:
+        return self.send_command("sensors_subscribe", enabled=enabled)
+
+    def set_failsafe(self, ms: float) -> Optional[dict]:
+        return self.send_command("failsafe", ms=ms)
+
+    def get_failsafe(self) -> Optional[dict]:
+        return self.send_command("failsafe_query")
+
+    def set_telemetry_rate(self, hz: float) -> Optional[dict]:
+        return self.send_command("telemetry_rate", hz=hz)
+
     def send_ping(self) -> Optional[dict]:
         self.ping_sent_time = time.time()
         return self.send_command("ping")
 
-    def receive_telemetry(self) -> Optional[dict]:
-        """Try to receive telemetry (non-blocking)."""
+    def identify(self) -> None:
+        """First ping on a new link; raises LinkError saying why it isn't a usable robot."""
         try:
-            data = self.telemetry_socket.recv_json(flags=zmq.NOBLOCK)
+            response = self.request(self._build_command("ping"))
+        except LinkTimeout:
+            raise probe_command_port(self.robot_ip, self.command_port) from None
+        except LinkCorrupt as e:
+            raise LinkForeign(f"connected to something that isn't a KSU-Storm robot ({e})") from e
+        service = response.get("service")
+        if "status" not in response or service not in (None, ROBOT_SERVICE):
+            raise LinkForeign(f"connected to something that isn't a KSU-Storm robot (replied {str(response)[:80]})")
+        if response["status"] != "success":
+            raise LinkIOError(f"robot refused ping: {response.get('message', response['status'])}")
+        if service is None:
+            print(f"[RobotClient] {self.robot_ip} predates service identification; assuming a KSU-Storm robot")
+        self._set_connected(True)
 
-            self._set_connected(True)
-            self.signals.telemetry_update.emit(data)
-            return data
-        except zmq.Again:
-            return None
-        except Exception as e:
-            print(f"[RobotClient] Telemetry error: {e}")
+    def authenticate(self) -> bool:
+        """Answer the robot's auth challenge. Returns True when commands will be accepted."""
+        challenge = self.send_command("auth_challenge")
+        if not challenge or challenge.get("status") != "success":
+            return False
+        if not challenge.get("auth_required"):
+            return True
+        if not AUTH_SECRET:
+            print("[RobotClient] Robot requires auth but KSU_AUTH_SECRET is not set")
+            return False
+
+        nonce = str(challenge.get("nonce", ""))
+        digest = hmac.new(AUTH_SECRET.encode(), nonce.encode(), hashlib.sha256).hexdigest()
+        response = self.send_command("auth", nonce=nonce, hmac=digest)
+        if not response or response.get("status") != "success":
+            return False
+
+        self.session = response.get("session")
+        return True
+
+    def _on_telemetry_frame(self, payload: List[bytes]) -> dict:
+        data = decode_json_frame(payload[0])
+        self._set_connected(True)
+        if self.telemetry_loss is not None:
+            self.telemetry_loss.observe(data.get("telemetry_seq"))
+        self.signals.post("telemetry_update", data)
+        return data
+
+    def _on_alert_frame(self, payload: List[bytes]) -> None:
+        self.signals.post("robot_alert", decode_json_frame(payload[0]))
+
+    def _on_bench_frame(self, payload: List[bytes]) -> None:
+        bench = self.bench
+        if bench is not None:
+            bench.add(decode_json_frame(payload[0]), len(payload[1]))
+
+    def receive_telemetry(self) -> Optional[dict]:
+        """Try to receive telemetry (non-blocking).
+
+        Alert and benchmark messages ahead of it are handled on the way, so a benchmark
+        burst isn't paced by the receive loop. A bad message only loses itself.
+        """
+        if self.telemetry_socket is None:
             return None
+        while True:
+            try:
+                frames = self.telemetry_socket.recv_multipart(flags=zmq.NOBLOCK)
+            except zmq.Again:
+                return None
+            except zmq.ZMQError as e:
+                print(f"[RobotClient] Telemetry error: {e}")
+                return None
+            wire_log = self.wire_log
+            if wire_log is not None:
+                wire_log.record("IN", "telemetry", frames)
+            if self.bandwidth is not None:
+                self.bandwidth.record(received=sum(len(frame) for frame in frames))
+            try:
+                channel, result = self.channels.dispatch(frames)
+            except (ValueError, IndexError) as e:
+                print(f"[RobotClient] Dropped bad message on channel {frames[0][:16]!r}: {e}")
+                continue
+            if channel == CHANNEL_TELEMETRY:
+                return result
 
     def cleanup(self) -> None:
         """Clean up sockets and terminate context."""
         self.running = False
-        self.command_socket.close(0)
-        self.telemetry_socket.close(0)
+        with self.command_lock:
+            self.command_socket.close(0)
+        if self.telemetry_socket is not None:
+            self.telemetry_socket.close(0)
+        if self.drive_socket is not None:
+            self.drive_socket.close(0)
         self.context.term()
 
 
+class RobotLink(Protocol):
+    """What ConnectionManager and TelemetryReceiver need from a transport.
+
+    RobotClient is the TCP/ZMQ implementation. A new transport implements these (plus the
+    signals, on_status, outbox, latency, telemetry_loss, bandwidth, recorder and history attributes
+    RobotClient carries) and registers a factory with register_link(). It hands every frame
+    it sends or receives, as raw bytes, to ``wire_log.record()`` whenever a wire log is attached.
+    """
+
+    connected: bool
+    consecutive_timeouts: int
+    disconnect_reason: str
+    role: str
+    wire_log: Optional[WireLogWriter]
+
+    def identify(self) -> None: ...
+    def authenticate(self) -> bool: ...
+    def handshake(self) -> None: ...
+    def subscribe_telemetry(self) -> None: ...
+    def receive_telemetry(self) -> Optional[dict]: ...
+    def send_command(self, command_type: str, **kwargs) -> Optional[dict]: ...
+    def cleanup(self) -> None: ...
+
+
+LinkFactory = Callable[[LinkSpec], RobotLink]
+
+
+def _open_tcp_link(spec: LinkSpec) -> RobotClient:
+    return RobotClient(spec.host, spec.port, int(spec.params["telemetry"]))
+
+
+# Transport constructors by URL scheme; see register_link().
+LINK_FACTORIES: Dict[str, LinkFactory] = {"tcp": _open_tcp_link}
+
+
+def register_link(scheme: str, factory: LinkFactory) -> None:
+    """Make ``scheme://`` addresses open through ``factory(spec)``."""
+    if scheme not in LINK_SCHEMES:
+        raise ValueError(f"parse_link_url doesn't know scheme {scheme!r}")
+    LINK_FACTORIES[scheme] = factory
+
+
+def open_link(spec: LinkSpec) -> RobotLink:
+    """Build the transport for ``spec``; raises LinkUnsupported if none is registered."""
+    factory = LINK_FACTORIES.get(spec.scheme)
+    if factory is None:
+        raise LinkUnsupported(f"no {spec.scheme} transport is available in this driver station")
+    return factory(spec)
+
+
+class ReconnectMonitor:
+    """Paces ConnectionManager's retries, spreading each wait by +/- ``jitter``."""
+
+    def __init__(self, jitter: float = RECONNECT_JITTER, rng: Optional[random.Random] = None):
+        # Past RECONNECT_MAX_JITTER a wait could shrink to nothing and spin the loop.
+        self.jitter = max(0.0, min(jitter, RECONNECT_MAX_JITTER))
+        self.rng = rng or random.Random()
+
+    def jittered(self, interval: float) -> float:
+        return interval * (1.0 + self.rng.uniform(-self.jitter, self.jitter))
+
+    def retry_delay(self, linked: bool) -> float:
+        """Seconds to wait before checking the link (or trying again) next."""
+        return self.jittered(RECONNECT_LINKED_S if linked else RECONNECT_IDLE_S)
+
+
 class ConnectionManager(threading.Thread):
-    """Manage connection attempts across candidate robot addresses."""
+    """Manage connection attempts across candidate robot addresses.
 
-    def __init__(self):
+    ``addresses`` defaults to ROBOT_ADDRESSES; formation members each pass their own one.
+    """
+
+    def __init__(self, addresses: Optional[List[str]] = None):
         super().__init__()
+        self.addresses = addresses
         self.signals = WorkerSignals()
         self.client: Optional[RobotClient] = None
+        self.outbox = ReliableOutbox()
+        self.latency = LatencyHistogram()
+        self.telemetry_loss = TelemetryLossMeter()
+        self.bandwidth = BandwidthMeter()
+        self.recorder = SessionRecorder()
+        self.history = CommandHistory()
+        self.wire_log: Optional[WireLogWriter] = None
+        # Give it a path before start() to have outages written to disk as well.
+        self.outages = OutageLog()
         self.lock = threading.Lock()
         self.running = True
         self.current_address_idx = 0
+        # Stage the current (or last) connection attempt reached.
+        self.connect_stage = STAGE_RESOLVING
+        # Addresses where something other than a robot answered, skipped until the given time.
+        self.backoff_until: Dict[str, float] = {}
+        self.reconnect = ReconnectMonitor()
+        # ROBOT_ADDRESSES entry of the live link, as configured (not the display form).
+        self.connected_address: Optional[str] = None
         self.daemon = True
+        self.hooks: List[ConnectionHook] = []
+        # Resync runs first: stops queued while the robot was unreachable must reach it
+        # before the UI hears about the link and starts sending commands of its own.
+        self.add_hook(self._resync_link)
+        self.add_hook(self._log_event)
+        self.add_hook(self._reset_stats)
+        self.add_hook(self._emit_status)
+        self.add_hook(self._log_outage)
+
+    def set_wire_log(self, wire_log: Optional[WireLogWriter]) -> None:
+        """Attach (or with None, detach) a raw frame log for this and every later link."""
+        with self.lock:
+            self.wire_log = wire_log
+            if self.client is not None:
+                self.client.wire_log = wire_log
+
+    def add_hook(self, hook: ConnectionHook) -> None:
+        """Register ``hook(event, address)`` for connection lifecycle events.
+
+        Hooks run on whichever thread saw the transition (usually this one), so UI code
+        should react through ``signals`` instead of touching widgets from a hook.
+        """
+        self.hooks.append(hook)
+
+    def _notify(self, event: str, address: str) -> None:
+        for hook in list(self.hooks):
+            try:
+                hook(event, address)
+            except Exception as e:
+                print(f"[ConnectionManager] {event} hook {getattr(hook, '__name__', hook)} failed: {e}")
+
+    def _log_event(self, event: str, address: str) -> None:
+        if event == CONNECTED:
+            print(f"[ConnectionManager] ✅ Connected to {address}")
+        elif event in (CONNECTING, RECONNECTING):
+            print(f"[ConnectionManager] Attempting {address}...")
+        elif event == DISCONNECTED:
+            print(f"[ConnectionManager] Lost {address}")
+
+    def _reset_stats(self, event: str, address: str) -> None:
+        # A struggling link keeps its history while a replacement is tried.
+        if event == CONNECTING:
+            self.latency.reset()
+            self.telemetry_loss.reset()
+            self.bandwidth.reset()
+        elif event == CONNECTED:
+            self.telemetry_loss.resync()
+
+    def _emit_status(self, event: str, address: str) -> None:
+        if event == CONNECTED:
+            try:
+                shown = parse_link_url(address).display()
+            except LinkUrlError:
+                shown = address
+            self.signals.post("connection_status", True, shown)
+        elif event == DISCONNECTED:
+            self.signals.post("connection_status", False, "")
+
+    def _resync_link(self, event: str, address: str) -> None:
+        """Bring the robot back in line with what we sent while it wasn't reachable."""
+        client = self.client
+        if event == CONNECTED and client is not None:
+            client.flush_outbox()
+
+    def _log_outage(self, event: str, address: str) -> None:
+        client = self.client
+        reason = client.disconnect_reason if event == DISCONNECTED and client is not None else ""
+        self.outages.record(event, address, reason)
+
+    def _report_stage(self, address: str, stage: str, error: str = "") -> None:
+        self.connect_stage = stage
+        if error:
+            print(f"[ConnectionManager] {address} failed while {stage}: {error}")
+        self.signals.post("connection_progress", address, stage, error)
+
+    def _stage_failed(self, address: str, candidate: Optional[RobotClient], error: str) -> None:
+        if candidate is not None:
+            candidate.cleanup()
+        self._report_stage(address, self.connect_stage, error)
+
+    def prefer_address(self, address: Optional[str]) -> None:
+        """Start with ``address`` (e.g. the one that worked last run) if it is still configured."""
+        if address in self._addresses():
+            self.current_address_idx = self._addresses().index(address)
+
+    def _addresses(self) -> List[str]:
+        return self.addresses if self.addresses is not None else ROBOT_ADDRESSES
 
     def _advance_address(self) -> None:
-        self.current_address_idx = (self.current_address_idx + 1) % len(ROBOT_ADDRESSES)
+        self.current_address_idx = (self.current_address_idx + 1) % len(self._addresses())
+
+    def _needs_reconnect(self) -> bool:
+        client = self.client
+        return client is None or not client.connected or client.consecutive_timeouts > 0
+
+    def _connect(self, address: str) -> Optional[RobotClient]:
+        """Bring up a client off to the side; returned only once it is fully handshaken.
+
+        The candidate keeps private signals and no status hook until it is swapped in, so nothing
+        (UI, telemetry thread, drive commands) sees a half-established link.
+        """
+        self._report_stage(address, STAGE_RESOLVING)
+        try:
+            spec = parse_link_url(address)
+        except LinkUrlError as e:
+            # A config mistake won't fix itself; stop retrying this entry.
+            self.backoff_until[address] = float("inf")
+            self._stage_failed(address, None, f"bad link address: {e}")
+            return None
+        if spec.host and not resolve_host(spec.host, spec.port):
+            self._stage_failed(address, None, "address does not resolve")
+            return None
+        try:
+            candidate = open_link(spec)
+        except LinkUnsupported as e:
+            self.backoff_until[address] = float("inf")
+            self._stage_failed(address, None, str(e))
+            return None
+
+        candidate.outbox = self.outbox
+        candidate.latency = self.latency
+        candidate.telemetry_loss = self.telemetry_loss
+        candidate.bandwidth = self.bandwidth
+        candidate.recorder = self.recorder
+        candidate.history = self.history
+        candidate.wire_log = self.wire_log
+
+        self._report_stage(address, STAGE_CONNECTING)
+        try:
+            candidate.identify()
+        except LinkError as e:
+            if isinstance(e, LinkForeign):
+                self.backoff_until[address] = time.monotonic() + self.reconnect.jittered(FOREIGN_SERVICE_BACKOFF_S)
+            self._stage_failed(address, candidate, str(e))
+            return None
+        deadline = time.monotonic() + HANDSHAKE_TIMEOUT_S
+        self._report_stage(address, STAGE_AUTHENTICATING)
+        if not candidate.authenticate():
+            self._stage_failed(address, candidate, "authentication rejected")
+            return None
+        self._report_stage(address, STAGE_HANDSHAKING)
+        candidate.handshake()
+        if not candidate.connected or candidate.consecutive_timeouts > 0:
+            # Answered a ping, then went quiet: don't swap in a half-connected link.
+            self._stage_failed(address, candidate, "robot stopped answering mid-handshake")
+            return None
+        if time.monotonic() > deadline:
+            self._stage_failed(address, candidate, f"handshake took over {HANDSHAKE_TIMEOUT_S:.0f} s")
+            return None
+        self._report_stage(address, STAGE_SUBSCRIBING)
+        candidate.subscribe_telemetry()
+        self._notify(HANDSHAKE_COMPLETE, address)
+        return candidate
+
+    def _swap_client(self, candidate: RobotClient, address: str) -> None:
+        with self.lock:
+            old = self.client
+            candidate.signals = self.signals
+            candidate.on_status = lambda connected: self._notify(CONNECTED if connected else DISCONNECTED, address)
+            candidate.wire_log = self.wire_log
+            self.client = candidate
+            self.connected_address = address
+        if old is not None:
+            # Tearing down the old link must not report a disconnect for the new one.
+            old.signals = WorkerSignals()
+            old.on_status = None
+            old.cleanup()
+
+        self._notify(CONNECTED, address)
+
+    def _drop_dead_client(self) -> None:
+        with self.lock:
+            old = self.client
+            if old is None or old.connected:
+                return
+            self.client = None
+            self.connected_address = None
+        old.cleanup()
+
+    def _attempt_connection(self) -> None:
+        """One connection attempt at the current address, advancing to the next on failure."""
+        # Make-before-break: a link that is merely struggling stays in use
+        # until a replacement has completed its handshake.
+        address = self._addresses()[self.current_address_idx]
+        if time.monotonic() < self.backoff_until.get(address, 0.0):
+            if self.client is None:
+                self._advance_address()
+            return
+        struggling = self.client is not None and self.client.connected
+        self._notify(RECONNECTING if struggling else CONNECTING, address)
+
+        try:
+            candidate = self._connect(address)
+        except Exception as e:
+            print(f"[ConnectionManager] Connection failed: {e}")
+            self._report_stage(address, self.connect_stage, str(e) or type(e).__name__)
+            candidate = None
+
+        if candidate is not None and not self._needs_reconnect():
+            # The old link recovered while we were connecting; keep it.
+            candidate.cleanup()
+        elif candidate is not None:
+            self._swap_client(candidate, address)
+        else:
+            # The index still points at the address that last worked, so a lost link
+            # is retried there first before moving on to the others.
+            self._drop_dead_client()
+            if self.client is None:
+                self._advance_address()
 
     def run(self) -> None:
         print("[ConnectionManager] Starting...")
 
         while self.running:
-            with self.lock:
-                if self.client is None or not self.client.connected:
-                    address = ROBOT_ADDRESSES[self.current_address_idx]
-                    print(f"[ConnectionManager] Attempting {address}...")
+            if self.signals.closed.is_set():
+                print("[ConnectionManager] GUI closed; stopping")
+                self.stop()
+                break
+            if self._needs_reconnect():
+                self._attempt_connection()
 
-                    try:
-                        if self.client:
-                            self.client.cleanup()
-
-                        self.client = RobotClient(address)
-                        self.client.signals = self.signals
-
-                        response = self.client.send_ping()
-                        if response and response.get("status") == "success":
-                            print(f"[ConnectionManager] ✅ Connected to {address}")
-                            self.signals.connection_status.emit(True, f"{address}:{COMMAND_PORT}")
-                        else:
-                            self._advance_address()
-                            self.client = None
-                    except Exception as e:
-                        print(f"[ConnectionManager] Connection failed: {e}")
-                        self._advance_address()
-                        self.client = None
-                        self.signals.connection_status.emit(False, "")
-
-            time.sleep(1.0 if self.client is None else 0.5)
+            time.sleep(self.reconnect.retry_delay(linked=self.client is not None))
 
     def get_client(self) -> Optional[RobotClient]:
         with self.lock:
@@ -183,23 +2018,45 @@ class TelemetryReceiver(threading.Thread):
         self.conn_manager = conn_manager
         self.running = True
         self.last_ping_time = 0
+        self.last_activity_emit = 0.0
+        # Cleared to stop the pings, which the robot counts as a heartbeat, without
+        # stopping telemetry.
+        self.pinging = True
+        # Set to a started TelemetryCsvWriter to mirror every update to disk.
+        self.csv_writer: Optional[TelemetryCsvWriter] = None
         self.daemon = True
 
+    def _note_activity(self, client) -> None:
+        now = time.monotonic()
+        if now - self.last_activity_emit >= LINK_ACTIVITY_INTERVAL_S:
+            self.last_activity_emit = now
+            client.signals.post("link_activity")
+
     def run(self) -> None:
         print("[TelemetryReceiver] Starting...")
 
         while self.running:
+            if self.conn_manager.signals.closed.is_set():
+                print("[TelemetryReceiver] GUI closed; stopping")
+                self.running = False
+                break
             client = self.conn_manager.get_client()
 
             if client:
-                client.receive_telemetry()
-                if time.time() - self.last_ping_time > PING_INTERVAL_S:
+                data = client.receive_telemetry()
+                if data is not None:
+                    self._note_activity(client)
+                    csv_writer = self.csv_writer
+                    if csv_writer is not None:
+                        csv_writer.write(data)
+                if self.pinging and time.time() - self.last_ping_time > PING_INTERVAL_S:
                     ping_start = time.time()
                     response = client.send_ping()
 
                     if response and response.get("status") == "success":
                         ping_ms = (time.time() - ping_start) * 1000
-                        client.signals.ping_response.emit(ping_ms)
+                        client.signals.post("ping_response", ping_ms)
+                        self._note_activity(client)
 
                     self.last_ping_time = time.time()
             else:
@@ -209,3 +2066,162 @@ class TelemetryReceiver(threading.Thread):
 
     def stop(self) -> None:
         self.running = False
+
+
+# Drive axes a mirrored formation member reverses: strafe and rotation, so it moves as the
+# mirror image of the main robot across their shared forward axis.
+FORMATION_MIRRORED_AXES = ("lx", "rx")
+FORMATION_NAME_PATTERN = re.compile(r"^[A-Za-z0-9_.-]{1,32}$")
+
+
+@dataclass
+class FormationMember:
+    """One extra robot in formation mode and how the broadcast drive is adjusted for it."""
+
+    name: str
+    address: str
+    mirror: bool = False
+    scale: float = 1.0
+
+    def adjust(self, command_type: str, fields: Dict[str, Any]) -> Dict[str, Any]:
+        """``fields`` of a broadcast command as this robot should get them."""
+        if command_type != "joystick":
+            return dict(fields)
+        adjusted = dict(fields)
+        for axis in ("lx", "ly", "rx"):
+            if axis in adjusted:
+                value = float(adjusted[axis]) * self.scale
+                if self.mirror and axis in FORMATION_MIRRORED_AXES:
+                    value = -value
+                adjusted[axis] = max(-1.0, min(1.0, value))
+        return adjusted
+
+
+def parse_formation(spec: str) -> List[FormationMember]:
+    """Parse KSU_FORMATION, e.g. ``left=10.42.0.3,right=tcp://10.42.0.4:5555?mirror=1&scale=0.8``.
+
+    Each entry is a name and a link address (see parse_link_url). The link's ``mirror``
+    parameter reverses strafe and rotation for that robot, and ``scale`` (0-1] slows its
+    drive, e.g. for the inside of a turn. Raises LinkUrlError on a bad entry.
+    """
+    members: List[FormationMember] = []
+    for entry in (e.strip() for e in spec.split(",")):
+        if not entry:
+            continue
+        name, sep, address = entry.partition("=")
+        name = name.strip()
+        if not sep or not FORMATION_NAME_PATTERN.match(name):
+            raise LinkUrlError(f"formation entry {entry!r} should be name=link")
+        if any(member.name == name for member in members):
+            raise LinkUrlError(f"formation robot {name!r} is listed twice")
+        params = parse_link_url(address).params
+        mirror = params.get("mirror", "0").lower() in ("1", "true", "yes")
+        try:
+            scale = float(params.get("scale", "1"))
+        except ValueError:
+            raise LinkUrlError(f"formation robot {name!r} has a non-numeric scale") from None
+        if not 0.0 < scale <= 1.0:
+            raise LinkUrlError(f"formation robot {name!r} scale must be in (0, 1], got {scale}")
+        members.append(FormationMember(name, address.strip(), mirror, scale))
+    return members
+
+
+class MultiLink:
+    """Fans commands out to the robots of a formation and keeps their telemetry by robot.
+
+    Every member has its own ConnectionManager and TelemetryReceiver, so one that is down
+    reconnects on its own while the rest keep driving. Commands for a robot that is down
+    are skipped; reliable ones wait in its outbox and are replayed when it comes back, so
+    a stop reaches it even then.
+    """
+
+    def __init__(self, members: List[FormationMember]):
+        self.members: Dict[str, FormationMember] = {member.name: member for member in members}
+        self.managers: Dict[str, ConnectionManager] = {}
+        self.receivers: Dict[str, TelemetryReceiver] = {}
+        self.lock = threading.Lock()
+        # Per robot: latest telemetry frame and when (monotonic) it arrived.
+        self.telemetry: Dict[str, dict] = {}
+        self.telemetry_at: Dict[str, float] = {}
+        # Per robot: type and outcome of the last command fanned out to it.
+        self.last_command: Dict[str, Tuple[str, str]] = {}
+        for member in members:
+            manager = ConnectionManager([member.address])
+            manager.signals.telemetry_update.connect(
+                lambda data, name=member.name: self.record_telemetry(name, data))
+            self.managers[member.name] = manager
+            self.receivers[member.name] = TelemetryReceiver(manager)
+            # No heartbeat until the main robot's link is up; see set_pinging().
+            self.receivers[member.name].pinging = False
+
+    def start(self) -> None:
+        for name in self.members:
+            self.managers[name].start()
+            self.receivers[name].start()
+
+    def stop(self) -> None:
+        for name in self.members:
+            self.managers[name].signals.close()
+            self.receivers[name].stop()
+            self.managers[name].stop()
+
+    def set_pinging(self, enabled: bool) -> None:
+        """Ping the members only while the main link is up.
+
+        Their pings are a heartbeat, so a member kept pinged after the main link drops
+        never trips its failsafe and goes on driving with the last frame it was sent.
+        """
+        for receiver in self.receivers.values():
+            receiver.pinging = enabled
+
+    def record_telemetry(self, name: str, data: dict) -> None:
+        with self.lock:
+            self.telemetry[name] = data
+            self.telemetry_at[name] = time.monotonic()
+
+    def links(self) -> Dict[str, Optional[RobotLink]]:
+        """Each member's live link, or None while it is down."""
+        return {name: manager.get_client() for name, manager in self.managers.items()}
+
+    def send_command(self, command_type: str, **kwargs) -> Dict[str, Optional[dict]]:
+        """Send to every member, adjusted for each. A member that is down gets None."""
+        responses: Dict[str, Optional[dict]] = {}
+        for name, link in self.links().items():
+            fields = self.members[name].adjust(command_type, kwargs)
+            if link is None:
+                if command_type in RELIABLE_COMMANDS:
+                    self.managers[name].outbox.add(build_command(command_type, **fields))
+                    outcome = "queued"
+                else:
+                    outcome = "down"
+                response = None
+            else:
+                response = link.send_command(command_type, **fields)
+                outcome = ("sent" if command_type in getattr(link, "unacked_commands", ())
+                           else describe_outcome(response))
+            with self.lock:
+                self.last_command[name] = (command_type, outcome)
+            responses[name] = response
+        return responses
+
+    def status(self) -> List[Dict[str, Any]]:
+        """One row per member for the formation panel, in configured order."""
+        now = time.monotonic()
+        links = self.links()
+        rows = []
+        with self.lock:
+            for name, member in self.members.items():
+                data = self.telemetry.get(name, {})
+                arrived = self.telemetry_at.get(name)
+                alerts = data.get("alerts") or []
+                rows.append({
+                    "name": name,
+                    "address": member.address,
+                    "connected": links[name] is not None,
+                    "telemetry_age_s": None if arrived is None else now - arrived,
+                    "state": (data.get("state") or {}).get("summary") or data.get("mode"),
+                    "battery": data.get("battery"),
+                    "last_command": self.last_command.get(name),
+                    "last_alert": alerts[-1].get("message") if alerts else None,
+                })
+        return rows
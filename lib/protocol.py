@@ -0,0 +1,62 @@
+"""Wire-protocol helpers shared by the robot and the driver station.
+
+Plain Python only: robot.py imports this on the robot, which has no PyQt6.
+"""
+
+from __future__ import annotations
+
+import os
+import re
+from typing import Any
+
+import zmq
+
+# TCP keep-alive on every robot link, so a peer that vanishes without closing (e.g. a Wi-Fi
+# drop) is reaped. Nagle needs no option: libzmq always sets TCP_NODELAY, so small frames go
+# out immediately.
+TCP_KEEPALIVE = os.environ.get("KSU_TCP_KEEPALIVE", "1").strip().lower() not in ("0", "false", "no")
+TCP_KEEPALIVE_IDLE_S = int(os.environ.get("KSU_TCP_KEEPALIVE_IDLE_S", "5"))
+TCP_KEEPALIVE_INTVL_S = int(os.environ.get("KSU_TCP_KEEPALIVE_INTVL_S", "1"))
+TCP_KEEPALIVE_CNT = int(os.environ.get("KSU_TCP_KEEPALIVE_CNT", "3"))
+
+# Button bit positions, following pygame's SDL Xbox layout; higher bits are virtual trigger buttons.
+BUTTON_NAMES = {
+    0: "A", 1: "B", 2: "X", 3: "Y", 4: "LB", 5: "RB", 6: "Back", 7: "Start", 8: "LS", 9: "RS", 10: "Guide",
+}
+
+# Control characters (C0, DEL, C1) and Unicode line/paragraph separators. Robot names,
+# alerts and error messages are shown and logged as-is, and a newline in a command type
+# or name could forge extra log lines, so both ends keep them on one line.
+CONTROL_CHARS_RE = re.compile("[\x00-\x1f\x7f-\x9f\u2028\u2029]+")
+
+
+def clean_text(text: str) -> str:
+    """``text`` with each run of control characters or line breaks replaced by a space."""
+    return CONTROL_CHARS_RE.sub(" ", text)
+
+
+def clean_strings(value: Any) -> Any:
+    """clean_text() applied to every string, including dict keys, in a decoded JSON value."""
+    if isinstance(value, str):
+        return clean_text(value)
+    if isinstance(value, dict):
+        return {clean_strings(k): clean_strings(v) for k, v in value.items()}
+    if isinstance(value, list):
+        return [clean_strings(v) for v in value]
+    return value
+
+
+def apply_tcp_keepalive(sock: zmq.Socket) -> None:
+    """Apply the KSU_TCP_KEEPALIVE* settings; must run before bind/connect."""
+    sock.setsockopt(zmq.TCP_KEEPALIVE, 1 if TCP_KEEPALIVE else 0)
+    if TCP_KEEPALIVE:
+        sock.setsockopt(zmq.TCP_KEEPALIVE_IDLE, TCP_KEEPALIVE_IDLE_S)
+        sock.setsockopt(zmq.TCP_KEEPALIVE_INTVL, TCP_KEEPALIVE_INTVL_S)
+        sock.setsockopt(zmq.TCP_KEEPALIVE_CNT, TCP_KEEPALIVE_CNT)
+
+
+def tcp_endpoint(host: str, port: int) -> str:
+    """Build a ZMQ TCP endpoint, bracketing IPv6 literals."""
+    if ":" in host and not host.startswith("["):
+        host = f"[{host}]"
+    return f"tcp://{host}:{port}"
@@ -1,12 +1,17 @@
 import os
 import sys
+import json
+import html
 import time
 import logging
 import math
+import re
+from bisect import bisect_right
+from collections import deque
 from pathlib import Path
 import pygame
 from PyQt6.QtWidgets import QApplication, QMainWindow
-from PyQt6.QtCore import QTimer, Qt
+from PyQt6.QtCore import QEvent, QTimer, Qt
 from PyQt6.QtGui import QIcon
 from PyQt6 import uic
 
@@ -39,6 +44,136 @@ MAX_ANGULAR_SPEED_DPS = 180.0
 EXPECTED_POSE_HORIZON_S = 0.35
 SLOW_DRIVE_SCALE = 0.2
 AXIS_DEADZONE = 0.03
+AXIS_EXPO = 1.0  # 1.0 = linear; larger values soften the response near center
+TELEMETRY_STALE_S = 1.0
+# Telemetry rate requested from the robot depending on how closely we're watching.
+TELEMETRY_RATE_ACTIVE_HZ = 10.0
+TELEMETRY_RATE_CONGESTED_HZ = 4.0
+TELEMETRY_RATE_MINIMIZED_HZ = 1.0
+CONGESTED_PING_MS = 150.0
+TELEMETRY_STALE_CHECK_MS = 250
+# The link heartbeat flatlines after this long without telemetry or a pong, or after
+# LINK_FLATLINE_PERIODS telemetry periods when the robot has been asked to send slower.
+LINK_FLATLINE_S = float(os.environ.get("KSU_LINK_FLATLINE_S", "0.5"))
+LINK_FLATLINE_PERIODS = 3
+STATS_REFRESH_MS = 500
+# Telemetry in flight when the driver changes mode still shows the old one; only follow
+# the robot's reported mode once it has disagreed for this long.
+MODE_MISMATCH_GRACE_S = 0.5
+# How often to ask the robot for its uptime; a drop between polls means it restarted.
+UPTIME_REFRESH_MS = 5000
+# How often a running link benchmark is checked for completion.
+BENCH_POLL_MS = 100
+# Joystick send rate adapts to round-trip time: full rate at or below FAST, slowest at or above SLOW.
+SEND_RATE_ADJUST_MS = 1000
+SEND_RATE_FAST_RTT_MS = 40.0
+SEND_RATE_SLOW_RTT_MS = 250.0
+SEND_RATE_MAX_INTERVAL_MS = 200.0
+SEND_RATE_MAX_THRESHOLD = 0.05
+SEND_RATE_RTT_WINDOW = 5
+# Link quality: poor on command timeouts, a telemetry gap or a slow RTT; good once RTT is
+# back under RECOVERED. Degraded mode starts after DEGRADE_AFTER_S of poor and ends
+# after RECOVER_AFTER_S of good, so a single slow ping doesn't flap features on and off.
+LINK_QUALITY_CHECK_MS = 500
+LINK_POOR_RTT_MS = 300.0
+LINK_RECOVERED_RTT_MS = 120.0
+LINK_POOR_TELEMETRY_GAP_S = 1.0
+# Recent telemetry loss (from frame-number gaps) that makes the link fair or poor.
+LINK_FAIR_TELEMETRY_LOSS_PCT = 2.0
+LINK_POOR_TELEMETRY_LOSS_PCT = 10.0
+LINK_DEGRADE_AFTER_S = 2.0
+LINK_RECOVER_AFTER_S = 5.0
+# Essential-control settings while degraded.
+TELEMETRY_RATE_DEGRADED_HZ = 2.0
+DEGRADED_AXIS_DEADZONE = 0.12
+DEGRADED_SEND_INTERVAL_MS = 250.0
+DEGRADED_SEND_THRESHOLD = 0.08
+# Resting-offset learning after a controller attaches.
+# When pygame's input subsystem starts raising errors, it is torn down and rebuilt, first
+# at once and then backing off between these while the rebuild keeps failing.
+GAMEPAD_REINIT_MIN_S = 1.0
+GAMEPAD_REINIT_MAX_S = 10.0
+CENTER_LEARN_WINDOW_S = 1.0
+CENTER_MAX_OFFSET = 0.15  # Readings larger than this mean the stick is being held.
+CENTER_STABLE_TOLERANCE = 0.01
+CENTER_MIN_SAMPLES = 10
+PID_AXES = ("forward", "strafe", "rotate")
+MOTOR_NAMES = ("FL", "FR", "RL", "RR")
+DRIVE_MODES = ("OPEN", "CLOSED")
+# Robot mecanum desaturation strategies, as named by its mix_mode command.
+MIX_MODES = ("scale", "clip", "translation", "forward")
+DRIVE_AXIS_NAMES = ("lx", "ly", "rx", "ry")
+# Axes read as [lx, ly, rx, ry].
+DEFAULT_DRIVE_AXES = (0, 1, 2, 4)
+SPEED_PROFILE_BUTTON = int(os.environ.get("KSU_SPEED_PROFILE_BUTTON", "5"))  # Right bumper
+# Hold this and press SPEED_PROFILE_BUTTON to cycle controller presets instead of speed caps.
+PRESET_MODIFIER_BUTTON = int(os.environ.get("KSU_PRESET_MODIFIER_BUTTON", "6"))  # Back/View
+# Driver-side settings (controller presets) persisted across restarts.
+DRIVER_SETTINGS_FILE = os.environ.get("KSU_DRIVER_SETTINGS_FILE", "driver_settings.json").strip()
+# Recorded driving sessions, one JSON file per session.
+SESSION_DIR = os.environ.get("KSU_SESSION_DIR", "sessions").strip()
+# Where robot telemetry dumps are saved, one JSON file per dump.
+TELEMETRY_DUMP_DIR = os.environ.get("KSU_TELEMETRY_DUMP_DIR", ".").strip()
+# Telemetry CSV logs go next to the dumps; set KSU_TELEMETRY_CSV=1 to start logging at launch.
+TELEMETRY_CSV_AT_LAUNCH = os.environ.get("KSU_TELEMETRY_CSV", "0").strip().lower() in ("1", "true", "yes")
+# Raw link logs (hex dumps of every frame) go there too; KSU_WIRE_LOG=1 starts one at launch.
+WIRE_LOG_AT_LAUNCH = os.environ.get("KSU_WIRE_LOG", "0").strip().lower() in ("1", "true", "yes")
+# Each session's connection outages are always logged there, one JSON line per outage.
+OUTAGE_LOG_PATTERN = "outages_%Y%m%d_%H%M%S.jsonl"
+AUTOFIRE_MIN_INTERVAL_MS = 50.0
+# While buttons go out as a mask, the current state is re-sent at least this often so the
+# robot catches up on a lost frame even when nothing changes.
+BUTTON_MASK_REFRESH_S = 0.5
+TRIGGER_DEFAULT_PRESS = 0.6
+TRIGGER_DEFAULT_HYSTERESIS = 0.2
+ODOMETRY_RESET_BUTTON = int(os.environ.get("KSU_ODOMETRY_RESET_BUTTON", "8"))  # Left stick click
+# What the right stick does: "drive" rotates, "gimbal" aims the pan/tilt servos instead,
+# "both" rotates and pans with rx while ry tilts.
+RIGHT_STICK_MODES = ("drive", "gimbal", "both")
+RIGHT_STICK_MODE = os.environ.get("KSU_RIGHT_STICK_MODE", "drive").strip().lower()
+# D-pad drives at this fixed fraction of full speed for fine positioning.
+DPAD_NUDGE = float(os.environ.get("KSU_DPAD_NUDGE", "0.25"))
+GIMBAL_CENTER_BUTTON = int(os.environ.get("KSU_GIMBAL_CENTER_BUTTON", "9"))  # Right stick click
+# Swaps the robot's front and back, e.g. after it flips over. Forward and strafe reverse;
+# rotation and the gimbal don't, since they look the same from either end.
+FLIP_BUTTON = int(os.environ.get("KSU_FLIP_BUTTON", "7"))  # Start/Menu
+FLIPPED_AXES = ("lx", "ly")
+# Hold this and use the D-pad to trim instead of drive: left/right steering, up/down throttle.
+TRIM_MODE_BUTTON = int(os.environ.get("KSU_TRIM_MODE_BUTTON", "4"))  # Left bumper
+TRIM_STEP = 0.01
+MAX_TRIM = 0.15
+# Dashboard tiles per row, and what the dashboard shows before it's been customised.
+DASHBOARD_COLUMNS = 3
+DEFAULT_DASHBOARD = [
+    {"path": "battery", "kind": "gauge", "min": 9.0, "max": 12.6},
+    {"path": "derate", "kind": "bar", "min": 0.0, "max": 1.0},
+]
+# Robot alert kinds that stop the driver sending motion until the operator acknowledges.
+MOTION_LOCKOUT_ALERTS = {
+    kind.strip()
+    for kind in os.environ.get("KSU_MOTION_LOCKOUT_ALERTS", "current_limit,geofence,tilt").split(",")
+    if kind.strip()
+}
+# Alerts older than this (robot clock) are history, e.g. from before we connected.
+MOTION_LOCKOUT_MAX_ALERT_AGE_S = 5.0
+# The robot's control loop counts as stalled once its loop_tick hasn't moved for this long
+# while telemetry is still arriving.
+LOOP_STALL_S = float(os.environ.get("KSU_LOOP_STALL_S", "1.5"))
+# Roll or pitch past this many degrees is flagged on the orientation display. The robot's
+# own tilt limit (if set) is what actually stops it.
+TILT_WARN_DEG = float(os.environ.get("KSU_TILT_WARN_DEG", "15"))
+# Full stick deflection slews a servo this fast; the robot clamps to each servo's range.
+SERVO_RATE_DPS = 120.0
+SERVO_SEND_INTERVAL_MS = 100.0
+SERVO_SEND_THRESHOLD_DEG = 0.5
+
+# Telemetry field -> UI labels that display it, greyed out when the field goes stale.
+TELEMETRY_FIELD_LABELS = {
+    "pose": ("label_3", "label_2", "label_4"),
+    "odometry_mode": ("label_odo_mode",),
+    "battery": ("battery_label",),
+    "timing": ("timing_label",),
+}
 
 FACE_BUTTON_COLORS = {
     0: "green",   # A
@@ -48,27 +183,537 @@ FACE_BUTTON_COLORS = {
 }
 
 
+def shape_axis(value, deadzone, expo):
+    """Apply a rescaled deadzone and expo curve to a raw axis value in [-1, 1]."""
+    value = max(-1.0, min(1.0, float(value)))
+    if abs(value) <= deadzone:
+        return 0.0
+    sign = 1.0 if value >= 0.0 else -1.0
+    scaled = (abs(value) - deadzone) / (1.0 - deadzone)
+    return sign * (scaled ** expo)
+
+
+class StickCenterCalibrator:
+    """Learns each axis' resting offset while the controller is untouched, then subtracts it.
+
+    Learning starts at the first sample after reset() and lasts CENTER_LEARN_WINDOW_S.
+    An axis only accumulates samples while its reading is small and steady; any
+    movement restarts that axis so a held stick is never learned as center.
+    """
+    def __init__(self, axes):
+        self.axes = tuple(axes)
+        self.reset()
+
+    def reset(self):
+        self.offsets = {axis: 0.0 for axis in self.axes}
+        self._sums = {axis: 0.0 for axis in self.axes}
+        self._counts = {axis: 0 for axis in self.axes}
+        self._last = {}
+        self._learn_started = None
+        self.learning = True
+
+    def sample(self, raw):
+        if not self.learning:
+            return
+        now = time.time()
+        if self._learn_started is None:
+            self._learn_started = now
+
+        for axis, value in raw.items():
+            previous = self._last.get(axis)
+            self._last[axis] = value
+            steady = previous is None or abs(value - previous) <= CENTER_STABLE_TOLERANCE
+            if abs(value) <= CENTER_MAX_OFFSET and steady:
+                self._sums[axis] += value
+                self._counts[axis] += 1
+            else:
+                self._sums[axis] = 0.0
+                self._counts[axis] = 0
+
+        if now - self._learn_started >= CENTER_LEARN_WINDOW_S:
+            for axis in self.axes:
+                if self._counts[axis] >= CENTER_MIN_SAMPLES:
+                    self.offsets[axis] = self._sums[axis] / self._counts[axis]
+            self.learning = False
+            logger.info(f"Learned stick center offsets: {self.offsets}")
+
+    def apply(self, axis, value):
+        return max(-1.0, min(1.0, value - self.offsets.get(axis, 0.0)))
+
+
+class SendRateController:
+    """Derives the joystick send interval and change threshold from recent round-trip times."""
+    def __init__(self):
+        self.rtt_samples = deque(maxlen=SEND_RATE_RTT_WINDOW)
+        self.reset()
+
+    def reset(self):
+        self.rtt_samples.clear()
+        self.min_interval_s = GAMEPAD_POLL_RATE_MS / 1000.0
+        self.threshold = JOYSTICK_THRESHOLD
+
+    def record_rtt(self, rtt_ms):
+        self.rtt_samples.append(rtt_ms)
+
+    def average_rtt(self):
+        return sum(self.rtt_samples) / len(self.rtt_samples) if self.rtt_samples else None
+
+    def adjust(self):
+        """Recompute limits from the recent RTT average. Returns True if they changed."""
+        if not self.rtt_samples:
+            return False
+        rtt_ms = self.average_rtt()
+        span = SEND_RATE_SLOW_RTT_MS - SEND_RATE_FAST_RTT_MS
+        congestion = max(0.0, min(1.0, (rtt_ms - SEND_RATE_FAST_RTT_MS) / span))
+
+        fastest_ms = float(GAMEPAD_POLL_RATE_MS)
+        interval_s = (fastest_ms + congestion * (SEND_RATE_MAX_INTERVAL_MS - fastest_ms)) / 1000.0
+        threshold = JOYSTICK_THRESHOLD + congestion * (SEND_RATE_MAX_THRESHOLD - JOYSTICK_THRESHOLD)
+        changed = abs(interval_s - self.min_interval_s) > 0.005 or abs(threshold - self.threshold) > 0.002
+        self.min_interval_s = interval_s
+        self.threshold = threshold
+        return changed
+
+
+class GamepadRecovery:
+    """Paces rebuilding pygame's joystick subsystem after it fails, so a hiccup self-heals."""
+    def __init__(self):
+        self.failed_since = None
+        self.next_attempt = 0.0
+        self.delay_s = GAMEPAD_REINIT_MIN_S
+        self.recovered_at = None
+        self.last_error = ""
+        self.reinits = 0
+
+    @property
+    def failed(self):
+        return self.failed_since is not None
+
+    def fail(self, error, now):
+        """Note an input error. Returns True if it starts a new failure rather than continuing one."""
+        self.last_error = str(error) or type(error).__name__
+        if self.failed:
+            return False
+        self.failed_since = now
+        if self.recovered_at is None or now - self.recovered_at > GAMEPAD_REINIT_MAX_S:
+            self.delay_s = GAMEPAD_REINIT_MIN_S
+            self.next_attempt = now
+        else:
+            # Failed again right after a rebuild: keep backing off instead of rebuilding every poll.
+            self.next_attempt = now + self.delay_s
+            self.delay_s = min(self.delay_s * 2, GAMEPAD_REINIT_MAX_S)
+        return True
+
+    def due(self, now):
+        return self.failed and now >= self.next_attempt
+
+    def attempt_failed(self, error, now):
+        self.last_error = str(error) or type(error).__name__
+        self.next_attempt = now + self.delay_s
+        self.delay_s = min(self.delay_s * 2, GAMEPAD_REINIT_MAX_S)
+
+    def recovered(self, now):
+        """Input works again. Returns how long it was down."""
+        down_s = now - self.failed_since
+        self.failed_since = None
+        self.recovered_at = now
+        self.reinits += 1
+        return down_s
+
+
+class LinkDegradePolicy:
+    """Rates the link good/fair/poor and decides when to fall back to essential control."""
+    def __init__(self):
+        self.reset()
+
+    def reset(self):
+        self.quality = "good"
+        self.degraded = False
+        self.condition_since = None
+
+    @staticmethod
+    def classify(rtt_ms, telemetry_age_s, command_timeouts, max_telemetry_gap_s=LINK_POOR_TELEMETRY_GAP_S,
+                 telemetry_loss_pct=None):
+        if command_timeouts > 0:
+            return "poor"
+        if telemetry_age_s is not None and telemetry_age_s > max_telemetry_gap_s:
+            return "poor"
+        if rtt_ms is not None and rtt_ms > LINK_POOR_RTT_MS:
+            return "poor"
+        if telemetry_loss_pct is not None and telemetry_loss_pct >= LINK_POOR_TELEMETRY_LOSS_PCT:
+            return "poor"
+        if rtt_ms is not None and rtt_ms > LINK_RECOVERED_RTT_MS:
+            return "fair"
+        if telemetry_loss_pct is not None and telemetry_loss_pct >= LINK_FAIR_TELEMETRY_LOSS_PCT:
+            return "fair"
+        return "good"
+
+    def update(self, rtt_ms, telemetry_age_s, command_timeouts,
+               max_telemetry_gap_s=LINK_POOR_TELEMETRY_GAP_S, now=None, telemetry_loss_pct=None):
+        """Re-rate the link. Returns True when degraded mode was entered or left."""
+        now = time.time() if now is None else now
+        self.quality = self.classify(rtt_ms, telemetry_age_s, command_timeouts, max_telemetry_gap_s,
+                                     telemetry_loss_pct)
+        switching = self.quality == ("good" if self.degraded else "poor")
+        if not switching:
+            self.condition_since = None
+            return False
+        if self.condition_since is None:
+            self.condition_since = now
+        hold_s = LINK_RECOVER_AFTER_S if self.degraded else LINK_DEGRADE_AFTER_S
+        if now - self.condition_since < hold_s:
+            return False
+        self.degraded = not self.degraded
+        self.condition_since = None
+        return True
+
+
+class SessionPlayer:
+    """Steps through a recorded session on the driver's clock, with pause and seek."""
+    def __init__(self):
+        self.load("", [])
+
+    def load(self, name, events):
+        self.name = name
+        self.events = sorted(events, key=lambda e: e["t"])
+        self.times = [e["t"] for e in self.events]
+        self.playing = False
+        self.position_s = 0.0
+        self.index = 0
+        self.last_tick = None
+
+    @property
+    def duration_s(self):
+        return self.times[-1] if self.times else 0.0
+
+    @property
+    def finished(self):
+        return self.index >= len(self.events)
+
+    def play(self):
+        if self.finished:
+            self.seek(0.0)
+        self.playing = True
+        self.last_tick = time.time()
+
+    def pause(self):
+        self.playing = False
+
+    def seek(self, position_s):
+        self.position_s = max(0.0, min(self.duration_s, float(position_s)))
+        # Events stamped exactly at the seek point still play.
+        self.index = bisect_right(self.times, self.position_s - 1e-9)
+        self.last_tick = time.time()
+
+    def tick(self):
+        """Advance to now and return the events that have come due."""
+        if not self.playing:
+            return []
+        now = time.time()
+        self.position_s = min(self.duration_s, self.position_s + now - self.last_tick)
+        self.last_tick = now
+        due = []
+        while not self.finished and self.events[self.index]["t"] <= self.position_s:
+            due.append(self.events[self.index])
+            self.index += 1
+        if self.finished:
+            self.playing = False
+        return due
+
+
+def session_path(name):
+    return os.path.join(SESSION_DIR, re.sub(r"[^A-Za-z0-9_.-]+", "_", name) + ".json")
+
+
+def list_sessions():
+    if not os.path.isdir(SESSION_DIR):
+        return []
+    return sorted(f[:-len(".json")] for f in os.listdir(SESSION_DIR) if f.endswith(".json"))
+
+
+def save_session(name, events):
+    """Write a session and return the name it is listed under."""
+    os.makedirs(SESSION_DIR, exist_ok=True)
+    path = session_path(name)
+    with open(path, "w", encoding="utf-8") as f:
+        json.dump({"name": name, "recorded_at": time.time(), "events": events}, f, indent=1)
+    return os.path.basename(path)[:-len(".json")]
+
+
+def load_session(name):
+    with open(session_path(name), "r", encoding="utf-8") as f:
+        data = json.load(f)
+    return [e for e in data.get("events", []) if e.get("type") in comm.RECORDED_COMMANDS]
+
+
+def parse_console_command(text):
+    """Turn console input into (type, fields).
+
+    Accepts a JSON object, or ``type key=value ...`` where each value is read as
+    JSON when possible (numbers, true/false, lists) and as a string otherwise.
+    """
+    text = text.strip()
+    if text.startswith("{"):
+        command = json.loads(text)
+        if not isinstance(command, dict) or not isinstance(command.get("type"), str):
+            raise ValueError("JSON command needs a string 'type'")
+        fields = dict(command)
+        return fields.pop("type"), fields
+
+    parts = text.split()
+    if not parts:
+        raise ValueError("Empty command")
+    fields = {}
+    for part in parts[1:]:
+        key, sep, raw_value = part.partition("=")
+        if not sep or not key:
+            raise ValueError(f"Expected key=value, got '{part}'")
+        try:
+            fields[key] = json.loads(raw_value)
+        except ValueError:
+            fields[key] = raw_value
+    return parts[0], fields
+
+
+def load_driver_settings(path=DRIVER_SETTINGS_FILE):
+    """Load persisted driver settings, or an empty dict if none are saved."""
+    if not path or not os.path.exists(path):
+        return {}
+    try:
+        with open(path, "r", encoding="utf-8") as f:
+            data = json.load(f)
+        return data if isinstance(data, dict) else {}
+    except Exception as e:
+        logger.error(f"Failed to load driver settings {path}: {e}")
+        return {}
+
+
+def save_driver_settings(settings, path=DRIVER_SETTINGS_FILE):
+    """Atomically write driver settings so a crash mid-write can't corrupt them."""
+    if not path:
+        return
+    tmp_path = f"{path}.tmp"
+    try:
+        with open(tmp_path, "w", encoding="utf-8") as f:
+            json.dump(settings, f, indent=2)
+        os.replace(tmp_path, path)
+    except Exception as e:
+        logger.error(f"Failed to save driver settings {path}: {e}")
+
+
+def parse_autofire_buttons(raw):
+    """Parse "button:interval_ms,..." into {button: interval_s}. Malformed entries are skipped."""
+    autofire = {}
+    for part in str(raw).split(","):
+        try:
+            button, interval_ms = part.split(":")
+            autofire[int(button)] = max(AUTOFIRE_MIN_INTERVAL_MS, float(interval_ms)) / 1000.0
+        except ValueError:
+            continue
+    return autofire
+
+
+class RobotControlState:
+    """The robot's own view of whether it will move, from the 'state' telemetry field."""
+    def __init__(self, state=None):
+        state = state or {}
+        self.summary = str(state.get("summary", "UNKNOWN"))
+        self.armed = bool(state.get("armed", False))
+        self.mode = str(state.get("mode", "STOPPED")).upper()
+        self.failsafe = bool(state.get("failsafe", False))
+        self.park = str(state.get("park", "ACTIVE"))
+        self.paused = bool(state.get("paused", False))
+        self.arming_s = float(state.get("arming_s", 0.0))
+        self.awaiting_center = bool(state.get("awaiting_center", False))
+
+    def color(self):
+        if self.failsafe or self.park != "ACTIVE":
+            return "red"
+        return "green" if self.armed else "orange"
+
+
+class TriggerButton:
+    """Turns an analog axis into a virtual button: DOWN at ``press``, UP again at ``release``."""
+    def __init__(self, axis, button, press=0.6, release=0.4):
+        self.axis = axis
+        self.button = button
+        self.press = press
+        self.release = release
+        self.pressed = False
+
+    def update(self, value):
+        """Feed the latest axis reading. Returns "DOWN"/"UP" on a transition, else None."""
+        if not self.pressed and value >= self.press:
+            self.pressed = True
+            return "DOWN"
+        if self.pressed and value <= self.release:
+            self.pressed = False
+            return "UP"
+        return None
+
+
+def parse_trigger_buttons(raw):
+    """Parse "axis:button[:press[:release]],..." into TriggerButtons. Malformed entries are skipped."""
+    triggers = []
+    for part in str(raw).split(","):
+        fields = part.split(":")
+        if len(fields) < 2 or len(fields) > 4:
+            continue
+        try:
+            axis, button = int(fields[0]), int(fields[1])
+            press = float(fields[2]) if len(fields) > 2 else TRIGGER_DEFAULT_PRESS
+            release = float(fields[3]) if len(fields) > 3 else press - TRIGGER_DEFAULT_HYSTERESIS
+        except ValueError:
+            continue
+        if release < press:
+            triggers.append(TriggerButton(axis, button, press, release))
+    return triggers
+
+
+def parse_speed_profiles(raw):
+    """Parse a comma-separated list of caps in (0, 1], falling back to full speed."""
+    profiles = []
+    for part in str(raw).split(","):
+        try:
+            cap = float(part)
+        except ValueError:
+            continue
+        if cap > 0.0:
+            profiles.append(min(1.0, cap))
+    return tuple(profiles) or (1.0,)
+
+
+def parse_drive_axes(raw):
+    """Parse "lx,ly,rx,ry" axis indices; -1 leaves that input unused. Falls back to the default."""
+    try:
+        axes = tuple(int(part) for part in str(raw).split(","))
+    except ValueError:
+        return DEFAULT_DRIVE_AXES
+    return axes if len(axes) == len(DRIVE_AXIS_NAMES) else DEFAULT_DRIVE_AXES
+
+
+def dpad_nudge(hat, nudge=DPAD_NUDGE):
+    """D-pad hat (x, y) as [lx, ly, rx, ry]: left/right strafe, up/down drive, never rotate."""
+    x, y = hat
+    return (x * nudge, y * nudge, 0.0, 0.0)
+
+
+def clamp_trim(value):
+    return max(-MAX_TRIM, min(MAX_TRIM, round(float(value), 3)))
+
+
+def apply_drive_trim(values, trim):
+    """Add the throttle trim to ly and the steering trim to rx while any drive input is active.
+
+    Released controls stay at zero so the robot never creeps on trim alone.
+    """
+    if not any(values.get(name) for name in ("lx", "ly", "rx")):
+        return values
+    trimmed = dict(values)
+    trimmed["ly"] = max(-1.0, min(1.0, trimmed["ly"] + trim["throttle"]))
+    trimmed["rx"] = max(-1.0, min(1.0, trimmed["rx"] + trim["steering"]))
+    return trimmed
+
+
+def arbitrate_drive_inputs(stick, dpad):
+    """Choose which control drives this frame; sources are never mixed.
+
+    Precedence is keyboard (handled by the caller), then the analog sticks, then the
+    D-pad: if any stick axis is outside its deadzone the whole D-pad reading is
+    ignored, so brushing a stick while nudging can't add a second direction.
+    Opposing directions within one source (W+S, a stick already can't) cancel.
+    """
+    return tuple(stick) if any(stick) else tuple(dpad)
+
+
+def flip_drive_inputs(values):
+    """Drive inputs as seen with the robot's front and back swapped."""
+    return {name: -value if name in FLIPPED_AXES else value for name, value in values.items()}
+
+
+def parse_orientation(value):
+    """Telemetry "imu" as {'roll', 'pitch', 'yaw'} in degrees, or None if absent or malformed.
+
+    Takes Euler angles or a quaternion {'w', 'x', 'y', 'z'} (converted in ZYX order),
+    since IMUs report either.
+    """
+    if not isinstance(value, dict):
+        return None
+    try:
+        if all(k in value for k in "wxyz"):
+            w, x, y, z = (float(value[k]) for k in "wxyz")
+            norm = math.sqrt(w * w + x * x + y * y + z * z)
+            if norm == 0.0:
+                return None
+            w, x, y, z = w / norm, x / norm, y / norm, z / norm
+            roll = math.degrees(math.atan2(2.0 * (w * x + y * z), 1.0 - 2.0 * (x * x + y * y)))
+            pitch = math.degrees(math.asin(max(-1.0, min(1.0, 2.0 * (w * y - z * x)))))
+            yaw = math.degrees(math.atan2(2.0 * (w * z + x * y), 1.0 - 2.0 * (y * y + z * z)))
+        else:
+            roll, pitch, yaw = (float(value[k]) for k in ("roll", "pitch", "yaw"))
+    except (KeyError, TypeError, ValueError):
+        return None
+    if not all(math.isfinite(v) for v in (roll, pitch, yaw)):
+        return None
+    return {'roll': roll, 'pitch': pitch, 'yaw': yaw % 360.0}
+
+
+def missing_drive_axes(drive_axes, num_axes):
+    """Names of the drive inputs the controller can't provide; those read as zero."""
+    return [name for name, axis in zip(DRIVE_AXIS_NAMES, drive_axes) if not 0 <= axis < num_axes]
+
+
+# Controller axes read as [lx, ly, rx, ry], e.g. KSU_DRIVE_AXES="0,1,3,-1" for a flight
+# stick with twist on axis 3 and no fourth axis.
+DRIVE_AXES = parse_drive_axes(os.environ.get("KSU_DRIVE_AXES", ",".join(map(str, DEFAULT_DRIVE_AXES))))
+
+# Buttons that re-send DOWN while held, e.g. KSU_AUTOFIRE_BUTTONS="3:200" repeats Y every 200 ms.
+# Buttons not listed stay edge-triggered.
+AUTOFIRE_BUTTONS = parse_autofire_buttons(os.environ.get("KSU_AUTOFIRE_BUTTONS", ""))
+
+# Analog axes that also act as buttons, e.g. KSU_TRIGGER_BUTTONS="5:20:0.6:0.4" presses
+# virtual button 20 when axis 5 reaches 0.6 and releases it at 0.4. Thresholds are on the
+# raw axis value (triggers often rest at -1.0); pick ids above the physical button count.
+TRIGGER_BUTTONS = parse_trigger_buttons(os.environ.get("KSU_TRIGGER_BUTTONS", ""))
+
+# Speed caps applied to all axes before sending, cycled with SPEED_PROFILE_BUTTON.
+SPEED_PROFILES = parse_speed_profiles(os.environ.get("KSU_SPEED_PROFILES", "0.3,0.6,1.0"))
+
+
 class AppWindow(DriverUIHelpers, QMainWindow):
     def __init__(self):
         super().__init__()
         uic.loadUi(str(UI_FILE), self)
         self.setup_tabs()
+        if hasattr(self, "main_tabs"):
+            self.main_tabs.currentChanged.connect(self.handle_tab_changed)
 
         self.joystick = None
+        self.joystick_needs_center = False
+        self.drive_axes = DRIVE_AXES
+        self.stick_center = StickCenterCalibrator(self.drive_axes)
+        self.gamepad_recovery = GamepadRecovery()
         self.init_pygame_and_joystick()
 
-        # Connection manager (ZMQ-based)
+        # Connection manager (ZMQ-based) and telemetry receiver; started by start_link()
+        # once every handler and widget they report to exists.
         self.conn_manager = comm.ConnectionManager()
-        self.conn_manager.signals.connection_status.connect(self.update_connection_status)
-        self.conn_manager.start()
-
-        # Telemetry receiver
         self.telemetry_receiver = comm.TelemetryReceiver(self.conn_manager)
-        self.telemetry_receiver.start()
+        # Formation mode: extra robots that get the same drive and mode commands (KSU_FORMATION).
+        try:
+            formation = comm.parse_formation(comm.FORMATION_SPEC)
+        except comm.LinkUrlError as e:
+            logger.error(f"Ignoring KSU_FORMATION: {e}")
+            formation = []
+        self.formation = comm.MultiLink(formation) if formation else None
         
         # Connect signals
+        self.conn_manager.signals.connection_status.connect(self.update_connection_status)
+        self.conn_manager.signals.connection_progress.connect(self.handle_connection_progress)
         self.conn_manager.signals.ping_response.connect(self.handle_ping_response)
         self.conn_manager.signals.telemetry_update.connect(self.handle_telemetry)
+        self.conn_manager.signals.robot_alert.connect(self.handle_robot_alert)
+        self.conn_manager.signals.link_activity.connect(self.handle_link_activity)
         
         # Gamepad polling timer
         self.gamepad_timer = QTimer()
@@ -84,17 +729,171 @@ class AppWindow(DriverUIHelpers, QMainWindow):
         self.teleop_duration = 210  # 3 minutes 30 seconds (210 seconds) for teleop
         self.match_start_time = 0
 
+        # Telemetry staleness check
+        self.staleness_timer = QTimer()
+        self.staleness_timer.timeout.connect(self.update_telemetry_staleness)
+        self.staleness_timer.start(TELEMETRY_STALE_CHECK_MS)
+
+        # Link statistics refresh
+        self.stats_timer = QTimer()
+        self.stats_timer.timeout.connect(self.update_link_stats)
+        self.stats_timer.start(STATS_REFRESH_MS)
+        # Last uptime seen and the link it came from; a lower uptime on the same link means a restart.
+        self.last_robot_uptime_s = None
+        self.uptime_address = None
+        self.uptime_timer = QTimer()
+        self.uptime_timer.timeout.connect(self.refresh_robot_uptime)
+        self.uptime_timer.start(UPTIME_REFRESH_MS)
+
+        # Joystick send rate follows link latency
+        self.send_rate = SendRateController()
+        self.last_joystick_send = 0.0
+        self.send_rate_timer = QTimer()
+        self.send_rate_timer.timeout.connect(self.adjust_send_rate)
+        self.send_rate_timer.start(SEND_RATE_ADJUST_MS)
+
+        # Session replay steps at the gamepad rate so timing matches how it was recorded
+        self.session_player = SessionPlayer()
+        self.session_buttons_held = set()
+        self.session_timer = QTimer()
+        self.session_timer.timeout.connect(self.step_session_replay)
+
+        # Automatic fallback to essential control on a poor link
+        self.link_policy = LinkDegradePolicy()
+        self.link_quality_timer = QTimer()
+        self.link_quality_timer.timeout.connect(self.update_link_quality)
+        self.link_quality_timer.start(LINK_QUALITY_CHECK_MS)
+
         # State tracking
         self.joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
         self.last_sent_joystick_values = self.joystick_values.copy()
+        # Held buttons as a mask (bit i = button i), for robots that take one; see comm.BUTTON_MASK.
+        self.button_mask = 0
+        self.last_sent_button_mask = 0
         self.current_mode = "STOPPED"
+        self.robot_paused = False
+        self.robot_park_state = "ACTIVE"
+        self.pending_odometry_reset = None
+        self.last_selftest = None
+        self.last_alert_id = 0
+        self.last_sequence_state = None
+        self.last_turn_state = None
+        # Gimbal angles being aimed at, and the last angles the robot confirmed.
+        self.gimbal_target = {}
+        self.gimbal_angles = {}
+        self.gimbal_aiming = False
+        self.last_gimbal_step = time.time()
+        self.last_servo_send = 0.0
+        # Autofire buttons currently held: button -> [next fire time, repeats sent]
+        self.held_autofire_buttons = {}
         self.current_pose = {"x": 0.0, "y": 0.0, "theta_deg": 0.0}
         self.expected_pose = self.current_pose.copy()
+        self.speed_profile_index = len(SPEED_PROFILES) - 1
+        self.last_ping_ms = None
+        self.requested_telemetry_hz = None
+        self.axis_deadzone = AXIS_DEADZONE
+        self.axis_expo = AXIS_EXPO
+        self.driver_settings = load_driver_settings()
+        # Raw telemetry is kept as received; "telemetry_formats" only changes how it's shown.
+        self.telemetry_store = comm.TelemetryStore(
+            comm.build_telemetry_formats(self.driver_settings.get("telemetry_formats"))
+        )
+        self.dashboard = comm.parse_dashboard(self.driver_settings.get("dashboard", DEFAULT_DASHBOARD))
+        # Telemetry fields offered in the dashboard editor; grows as the robot sends new ones.
+        self.dashboard_fields = []
+        # Telemetry received before the current link came up is from the old link and stays
+        # marked until the robot sends that field again; None while disconnected.
+        self.telemetry_link_since = None
+        self.presets = self.driver_settings.get("presets") or {"default": self.current_preset()}
+        self.active_preset = self.driver_settings.get("active_preset", "default")
+        self.right_stick_mode = self.driver_settings.get("right_stick_mode", RIGHT_STICK_MODE)
+        if self.right_stick_mode not in RIGHT_STICK_MODES:
+            self.right_stick_mode = "drive"
+        self.drive_flipped = False
+        saved_trim = self.driver_settings.get("trim")
+        saved_trim = saved_trim if isinstance(saved_trim, dict) else {}
+        self.drive_trim = {key: clamp_trim(saved_trim.get(key, 0.0)) for key in ("steering", "throttle")}
+        # Last D-pad position seen in trim mode, so a held direction nudges once.
+        self.trim_hat = (0, 0)
 
         # Add field view to odometry panel
         self.setup_field_view()
         self.setup_main_camera_view()
         self.setup_camera_stream()
+        self.setup_speed_profile_controls(SPEED_PROFILES, self.speed_profile_index)
+        self.setup_gimbal_controls(RIGHT_STICK_MODES, self.right_stick_mode)
+        self.setup_drive_mode_control(DRIVE_MODES)
+        self.setup_flip_control()
+        self.setup_trim_controls(MAX_TRIM)
+        self.show_trim(self.drive_trim)
+        self.setup_robot_name_controls()
+        self.setup_link_pulse(LINK_FLATLINE_S)
+        self.setup_link_quality_label()
+        self.setup_telemetry_freshness_label()
+        self.setup_sensor_lights()
+        # Latest parsed IMU reading, or None while the robot reports none.
+        self.orientation = None
+        self.setup_orientation_display(TILT_WARN_DEG)
+        self.setup_battery_label()
+        self.setup_alert_label()
+        self.setup_arming_label()
+        # Reason driving is latched off after an unsafe robot alert, or None.
+        self.motion_lockout = None
+        self.setup_motion_lockout_banner()
+        # Robot loop_tick last seen, and when (monotonic) it last advanced.
+        self.loop_tick = None
+        self.loop_tick_advanced_at = 0.0
+        self.loop_stalled = False
+        self.setup_loop_stall_banner()
+        self.robot_state = RobotControlState()
+        self.mode_mismatch_since = None
+        self.setup_robot_state_label()
+        self.setup_axis_shaping_controls(self.axis_deadzone, self.axis_expo, self.preview_axis_output)
+        self.setup_preset_controls()
+        self.apply_preset(
+            self.active_preset if self.active_preset in self.presets else next(iter(self.presets)),
+            persist=False,
+        )
+        self.setup_pid_controls(PID_AXES)
+        self.setup_accel_controls()
+        self.motor_reversed = {}
+        self.setup_motor_reverse_controls(MOTOR_NAMES)
+        self.setup_brake_control()
+        self.setup_mix_mode_control(MIX_MODES)
+        self.setup_pause_control()
+        self.setup_park_control()
+        self.setup_timing_label()
+        self.setup_uptime_label()
+        self.setup_telemetry_readout(self.telemetry_store.formats)
+        self.setup_dashboard(comm.DASHBOARD_WIDGET_KINDS)
+        self._show_dashboard_layout()
+        self.setup_selftest_controls()
+        self.setup_dump_controls()
+        self.setup_telemetry_csv_controls()
+        self.setup_wire_log_controls()
+        self.setup_session_controls()
+        self.setup_sequence_controls()
+        self.setup_turn_controls()
+        self.setup_geofence_controls()
+        self.show_sessions(list_sessions())
+        self.setup_command_console()
+        self.setup_stick_center_controls()
+        self.setup_failsafe_controls()
+        self.setup_robot_shaping_controls()
+        self.setup_loop_rate_controls()
+        self.robot_config = {}
+        self.setup_robot_config_table()
+        self.bench_client = None
+        self.bench_timer = QTimer(self)
+        self.bench_timer.timeout.connect(self.poll_link_bench)
+        self.setup_bench_controls()
+        self.setup_latency_histogram(self.conn_manager.latency.labels())
+        self.setup_telemetry_loss_label()
+        self.conn_manager.outages.path = os.path.join(TELEMETRY_DUMP_DIR, time.strftime(OUTAGE_LOG_PATTERN))
+        self.setup_outage_log(self.conn_manager.outages.path)
+        self.setup_command_history(comm.COMMAND_HISTORY_S)
+        if self.formation is not None:
+            self.setup_formation_panel(TELEMETRY_STALE_S)
         self.current_pose = {
             "x": self.field_widget.field_width_m / 2.0,
             "y": self.field_widget.field_height_m / 2.0,
@@ -128,9 +927,51 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             self.keyboard_speed_slider.valueChanged.connect(self.update_keyboard_speed)
             self.keyboard_speed_label.setText(f"Keyboard Speed: {self.keyboard_speed:.0%}")
         
+        if TELEMETRY_CSV_AT_LAUNCH:
+            self.toggle_telemetry_csv()
+        if WIRE_LOG_AT_LAUNCH:
+            self.toggle_wire_log()
+        self.start_link()
         logger.info("Driver station initialized")
         logger.info("Keyboard controls: WASD=move, QE=rotate, Shift=speed boost, Space=stop")
 
+    def start_link(self):
+        """Start the link threads. Call only after all signal handlers are wired up.
+
+        Their signals cross threads, so Qt queues anything emitted before the event loop
+        runs and delivers it once app.exec() starts; with the handlers connected first,
+        a robot that is already up at launch can't have its first state change dropped.
+        """
+        self.conn_manager.prefer_address(self.driver_settings.get("last_robot_address"))
+        self.conn_manager.start()
+        self.telemetry_receiver.start()
+        if self.formation is not None:
+            self.formation.start()
+            logger.info(f"Formation mode with {', '.join(self.formation.members)}")
+
+    def _formation_send(self, command_type, **fields):
+        """Repeat a command to the other robots in the formation, if there are any."""
+        if self.formation is not None:
+            self.formation.send_command(command_type, **fields)
+
+    def _formation_drive(self, lx, ly, rx, ry):
+        # Buttons stay with the main robot; the formation only shares its motion.
+        self._formation_send("joystick", lx=lx, ly=ly, rx=rx, ry=ry)
+
+    def refresh_formation(self):
+        client = self.conn_manager.get_client()
+        main = {
+            'name': self.shown_robot_name or "main",
+            'address': self.conn_manager.connected_address or "",
+            'connected': client is not None,
+            'telemetry_age_s': self.telemetry_store.age('state'),
+            'state': (self.telemetry_store.get('state') or {}).get('summary'),
+            'battery': self.telemetry_store.get('battery'),
+            'last_command': None,
+            'last_alert': None,
+        }
+        self.show_formation([main] + self.formation.status())
+
     def update_odometry_labels(self, x_m, y_m, theta_deg):
         if hasattr(self, 'label_3'):
             self.label_3.setText(f"X: {x_m:.2f} m")
@@ -183,12 +1024,86 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             else:
                 logger.warning(f"Failed to set odometry mode: {mode}")
 
+    def refresh_sequences(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('sequence_list'):
+            return
+        response = client.list_sequences()
+        if response and response.get('status') == 'success':
+            self.show_sequences(response.get('sequences', {}))
+
+    def run_sequence(self, name):
+        """Ask the robot to run a stored sequence; it refuses unless it is safe to start."""
+        client = self.conn_manager.get_client()
+        if not client or not name:
+            return
+        if self.motion_lockout is not None:
+            logger.warning(f"Not running {name}: driving is disabled until the robot alert is acknowledged")
+            return
+        response = client.run_sequence(name)
+        if response and response.get('status') == 'success':
+            logger.info(f"Sequence {name} started")
+        else:
+            message = response.get('message') if response else 'no response'
+            self.show_sequence_state({'state': 'aborted', 'name': name, 'reason': message})
+            logger.warning(f"Sequence {name} not started: {message}")
+
+    def stop_sequence(self):
+        client = self.conn_manager.get_client()
+        if client:
+            client.stop_sequence()
+
+    def set_geofence(self, radius_m):
+        """Set the robot's geofence radius around the odometry origin, or None to remove it."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.set_geofence(radius_m)
+        if response and response.get('status') == 'success':
+            self.show_geofence({'radius_m': response.get('radius_m')})
+            logger.info(f"Geofence {'off' if radius_m is None else f'set to {radius_m:.2f} m'}")
+        else:
+            logger.warning(f"Failed to set geofence: {response}")
+
+    def turn_robot(self, degrees):
+        """Ask the robot to rotate in place by a relative angle, measured by odometry."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        if self.motion_lockout is not None:
+            logger.warning("Not turning: driving is disabled until the robot alert is acknowledged")
+            return
+        response = client.turn(degrees)
+        if response and response.get('status') == 'success':
+            logger.info(f"Turning {degrees:.1f} deg")
+        else:
+            message = response.get('message') if response else 'no response'
+            self.show_turn_state({'state': 'aborted', 'target_deg': degrees, 'reason': message})
+            logger.warning(f"Turn of {degrees:.1f} deg not started: {message}")
+
+    def set_drive_mode(self, mode):
+        """Switch the robot between open-loop and PID closed-loop drive."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.set_drive_mode(mode)
+        if response and response.get('status') == 'success':
+            self.show_drive_mode(response.get('drive_mode', mode))
+            logger.info(f"Drive mode set to {mode.lower()} loop")
+        else:
+            message = response.get('message') if response else 'no response'
+            self.show_drive_mode(self.telemetry_store.get('drive_mode', "OPEN"))
+            self.show_alert({'message': f"Drive mode not changed: {message}"})
+            logger.warning(f"Failed to set drive mode {mode}: {message}")
+
     def reset_odometry(self):
         """Reset odometry pose on robot and local field widget."""
         client = self.conn_manager.get_client()
         if client:
             response = client.send_command('reset_odometry')
             if response and response.get('status') == 'success':
+                # Confirmed once telemetry carries this reset count.
+                self.pending_odometry_reset = response.get('odometry_resets')
                 logger.info("Odometry reset requested")
         center_x = self.field_widget.field_width_m / 2.0
         center_y = self.field_widget.field_height_m / 2.0
@@ -198,6 +1113,357 @@ class AppWindow(DriverUIHelpers, QMainWindow):
         self.field_widget.set_expected_pose(center_x, center_y, 0.0)
         self.update_odometry_labels(center_x, center_y, 0.0)
     
+    def apply_pid_gains(self, axis):
+        """Send one axis' gains from the tuning panel to the robot."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        kp, ki, kd = self.pid_gains_from_ui(axis)
+        response = client.set_pid(axis, kp, ki, kd)
+        if response and response.get('status') == 'success':
+            logger.info(f"PID {axis} set to kp={kp} ki={ki} kd={kd}")
+        else:
+            logger.warning(f"Failed to set PID gains for {axis}: {response}")
+
+    def apply_accel_limits(self):
+        """Send the ramp limits from the tuning panel to the robot."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        accel = self.accel_limits_from_ui()
+        response = client.set_accel(accel['up'], accel['down'])
+        if response and response.get('status') == 'success':
+            logger.info(f"Acceleration limits set to {response.get('accel')}")
+        else:
+            logger.warning(f"Failed to set acceleration limits: {response}")
+
+    def apply_robot_capabilities(self):
+        """Disable panels for commands the connected robot doesn't implement."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        self.pid_group.setEnabled(client.supports('pid') and client.supports('pid_query'))
+        self.accel_group.setEnabled(client.supports('accel') and client.supports('accel_query'))
+        self.failsafe_group.setEnabled(client.supports('failsafe') and client.supports('failsafe_query'))
+        self.robot_shaping_group.setEnabled(client.supports('shape') and client.supports('shape_query'))
+        self.loop_rate_group.setEnabled(client.supports('loop_rate') and client.supports('loop_rate_query'))
+        self.btn_poll_telemetry.setEnabled(client.supports('telemetry_query'))
+        self.bench_group.setEnabled(client.supports('bench') and client.supports('bench_stop'))
+        self.btn_brake.setEnabled(client.supports('brake') and client.supports('brake_query'))
+        self.mix_mode_combo.setEnabled(client.supports('mix_mode') and client.supports('mix_mode_query'))
+        self.robot_config_group.setEnabled(client.supports('config') and client.supports('config_query'))
+        self.motor_reverse_group.setEnabled(client.supports('motor_reverse')
+                                            and client.supports('motor_reverse_query'))
+        self.btn_pause.setEnabled(client.supports('pause') and client.supports('resume'))
+        self.btn_park.setEnabled(client.supports('shutdown') and client.supports('wake'))
+        self.btn_selftest.setEnabled(client.supports('selftest'))
+        self.btn_dump_recent.setEnabled(client.supports('dump'))
+        self.btn_dump_failsafe.setEnabled(client.supports('dump'))
+        self.gimbal_group.setEnabled(client.supports('servo') and client.supports('servo_center'))
+        self.btn_rename_robot.setEnabled(client.supports('name'))
+        self.drive_mode_combo.setEnabled(client.supports('drive_mode'))
+        self.sequence_group.setEnabled(client.supports('run_sequence') and client.supports('sequence_list'))
+        self.turn_group.setEnabled(client.supports('turn'))
+        self.geofence_group.setEnabled(client.supports('geofence'))
+        self.btn_auto.setEnabled(client.supports('mode'))
+        self.btn_teleop.setEnabled(client.supports('mode'))
+        self.btn_rst.setEnabled(client.supports('reset'))
+        if hasattr(self, 'pushButton'):
+            self.pushButton.setEnabled(client.supports('reset_odometry'))
+        if hasattr(self, 'btn_odo_optical'):
+            self.btn_odo_optical.setEnabled(client.supports('odometry_mode'))
+        if hasattr(self, 'btn_odo_motor'):
+            self.btn_odo_motor.setEnabled(client.supports('odometry_mode'))
+
+    def refresh_pid_gains(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('pid_query'):
+            return
+        response = client.get_pid()
+        if response and response.get('status') == 'success':
+            self.show_pid_gains(response.get('pid', {}))
+
+    def refresh_robot_name(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('name_query'):
+            return
+        response = client.get_name()
+        if response and response.get('status') == 'success':
+            self.show_robot_name(response.get('name'))
+
+    def subscribe_sensors(self):
+        """Show the robot's digital inputs and have it push their edges as they happen."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        if client.supports('sensors_subscribe'):
+            response = client.subscribe_sensors(True)
+        elif client.supports('sensors_query'):
+            response = client.get_sensors()
+        else:
+            return
+        if response and response.get('status') == 'success':
+            self.show_sensor_inputs(response.get('inputs', {}))
+
+    def poll_telemetry(self):
+        """Pull one telemetry frame from the robot now, without waiting for the stream."""
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('telemetry_query'):
+            return
+        response = client.poll_telemetry()
+        if response and response.get('status') == 'success' and isinstance(response.get('telemetry'), dict):
+            self.handle_telemetry(response['telemetry'])
+        else:
+            logger.warning(f"Telemetry poll failed: {response}")
+
+    def handle_tab_changed(self, index):
+        # Opening the diagnostics panel shows current values straight away.
+        if self.main_tabs.tabText(index) == "Diagnostics":
+            self.poll_telemetry()
+
+    def refresh_robot_uptime(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('stats'):
+            return
+        response = client.get_stats()
+        if not response or response.get('status') != 'success' or 'uptime_s' not in response:
+            return
+        uptime_s = float(response['uptime_s'])
+        restarted = self.last_robot_uptime_s is not None and uptime_s < self.last_robot_uptime_s
+        if restarted:
+            logger.warning(f"Robot restarted: uptime dropped from {self.last_robot_uptime_s:.0f} s to {uptime_s:.0f} s")
+            self.show_alert({"kind": "robot_restart", "message": "Robot process restarted during this session"})
+        self.last_robot_uptime_s = uptime_s
+        self.show_uptime(uptime_s, response.get('connections'), restarted)
+
+    def rename_robot(self, name):
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.set_name(name)
+        if response and response.get('status') == 'success':
+            self.show_robot_name(response.get('name'))
+            logger.info(f"Robot renamed to {response.get('name')!r}")
+        else:
+            logger.warning(f"Failed to rename robot: {response}")
+
+    def refresh_accel_limits(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('accel_query'):
+            return
+        response = client.get_accel()
+        if response and response.get('status') == 'success':
+            self.show_accel_limits(response.get('accel', {}))
+
+    def refresh_failsafe_timeout(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('failsafe_query'):
+            return
+        response = client.get_failsafe()
+        if response and response.get('status') == 'success':
+            self.show_failsafe_timeout(response)
+
+    def refresh_robot_shaping(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('shape_query'):
+            return
+        response = client.get_input_shaping()
+        if response and response.get('status') == 'success':
+            self.show_robot_shaping(response)
+
+    def refresh_loop_rate(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('loop_rate_query'):
+            return
+        response = client.get_loop_rate()
+        if response and response.get('status') == 'success':
+            self.show_loop_rate(response)
+
+    def set_loop_rate(self, hz):
+        """Change how often the robot's control loop runs."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.set_loop_rate(hz)
+        if response and response.get('status') == 'success':
+            self.show_loop_rate(response)
+            logger.info(f"Robot control loop set to {response.get('hz'):.0f} Hz")
+        else:
+            logger.warning(f"Failed to set control loop rate: {response}")
+
+    def refresh_robot_settings(self):
+        """Re-read every robot setting shown in a panel, e.g. after connecting."""
+        self.refresh_pid_gains()
+        self.refresh_accel_limits()
+        self.refresh_failsafe_timeout()
+        self.refresh_robot_shaping()
+        self.refresh_loop_rate()
+        self.refresh_motor_reversed()
+        self.refresh_brake()
+        self.refresh_mix_mode()
+        self.refresh_robot_name()
+        self.refresh_robot_config()
+
+    def refresh_robot_config(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('config_query'):
+            return
+        response = client.get_config()
+        if not response or response.get('status') != 'success':
+            return
+        self.robot_config = response.get('config', {})
+        read_only = set(response.get('read_only', []))
+        volatile = set(response.get('volatile', []))
+        rows = [(key, value if isinstance(value, str) else json.dumps(value), key in read_only, key in volatile)
+                for key, value in sorted(self.robot_config.items())]
+        self.show_robot_config(rows)
+
+    def set_robot_config(self, key, text):
+        """Set one robot config key from its edited text, read as JSON unless the key holds text."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        value = text
+        if not isinstance(self.robot_config.get(key), str):
+            try:
+                value = json.loads(text)
+            except ValueError:
+                pass
+        response = client.set_config(key, value)
+        if response and response.get('status') == 'success':
+            logger.info(f"Robot config {key} set to {response.get('value')!r}"
+                        + ("" if response.get('persisted', True) else " (until the robot restarts)"))
+            # The panels for that setting show it too.
+            self.refresh_robot_settings()
+        else:
+            logger.warning(f"Failed to set robot config {key}: {response}")
+            self.refresh_robot_config()
+
+    def copy_robot_config(self):
+        """Put the last-read robot config on the clipboard as JSON, e.g. for a support request."""
+        QApplication.clipboard().setText(json.dumps(self.robot_config, indent=2, sort_keys=True))
+
+    def refresh_motor_reversed(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('motor_reverse_query'):
+            return
+        response = client.get_motor_reversed()
+        if response and response.get('status') == 'success':
+            self.motor_reversed = response.get('reversed', {})
+            self.show_motor_reversed(self.motor_reversed)
+
+    def set_motor_reversed(self, motor, reverse):
+        client = self.conn_manager.get_client()
+        response = client.set_motor_reversed(motor, reverse) if client else None
+        if response and response.get('status') == 'success':
+            self.motor_reversed = response.get('reversed', {})
+            self.show_motor_reversed(self.motor_reversed)
+            logger.info(f"Motor {motor} {'reversed' if reverse else 'normal'}")
+        else:
+            logger.warning(f"Failed to change {motor} direction: {response}")
+            # Put the toggle back to what the robot last reported.
+            self.show_motor_reversed(self.motor_reversed)
+
+    def refresh_brake(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('brake_query'):
+            return
+        response = client.get_brake()
+        if response and response.get('status') == 'success':
+            self.show_brake(response.get('enabled', True))
+
+    def set_brake(self, enabled):
+        """Have motors brake (True) or coast (False) when told to stop."""
+        client = self.conn_manager.get_client()
+        response = client.set_brake(enabled) if client else None
+        if response and response.get('status') == 'success':
+            self.show_brake(response.get('enabled', enabled))
+            logger.info(f"Motors {'brake' if enabled else 'coast'} on stop")
+        else:
+            logger.warning(f"Failed to change brake mode: {response}")
+            self.refresh_brake()
+
+    def refresh_mix_mode(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('mix_mode_query'):
+            return
+        response = client.get_mix_mode()
+        if response and response.get('status') == 'success':
+            self.show_mix_mode(response.get('mode', "scale"))
+
+    def set_mix_mode(self, mode):
+        """Choose how the robot desaturates its mecanum mix; see MIX_MODES."""
+        client = self.conn_manager.get_client()
+        response = client.set_mix_mode(mode) if client else None
+        if response and response.get('status') == 'success':
+            self.show_mix_mode(response.get('mode', mode))
+            logger.info(f"Mix mode set to {mode}")
+        else:
+            logger.warning(f"Failed to set mix mode {mode}: {response}")
+            self.refresh_mix_mode()
+
+    def run_link_bench(self, size_bytes):
+        """Ask the robot for a burst of ``size_bytes`` and score it as it arrives."""
+        client = self.conn_manager.get_client()
+        if not client or self.bench_client is not None:
+            return
+        # Listen before asking, so frames that beat the reply still count.
+        client.bench = comm.BenchMeter()
+        response = client.start_bench(size_bytes)
+        if not response or response.get('status') != 'success':
+            client.bench = None
+            logger.warning(f"Benchmark refused: {response}")
+            self.show_bench_result(None)
+            return
+        self.bench_client = client
+        self.bench_timer.start(BENCH_POLL_MS)
+        self.show_bench_result(None, running=True)
+
+    def stop_link_bench(self):
+        client = self.conn_manager.get_client()
+        if client and self.bench_client is not None:
+            client.stop_bench()
+
+    def poll_link_bench(self):
+        client = self.bench_client
+        meter = client.bench if client is not None else None
+        if meter is None:
+            self._end_link_bench(None)
+        elif meter.finished():
+            result = meter.result()
+            logger.info(f"Benchmark: {result}")
+            self._end_link_bench(result)
+
+    def _end_link_bench(self, result):
+        if self.bench_client is not None:
+            self.bench_client.bench = None
+        self.bench_client = None
+        self.bench_timer.stop()
+        self.show_bench_result(result)
+
+    def apply_failsafe_timeout(self, ms):
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.set_failsafe(ms)
+        if response and response.get('status') == 'success':
+            self.show_failsafe_timeout(response)
+            logger.info(f"Failsafe timeout set to {response.get('ms')} ms")
+        else:
+            logger.warning(f"Failed to set failsafe timeout: {response}")
+
+    def apply_robot_shaping(self, deadzone, expo):
+        """Send robot-side stick shaping; it applies after this station's own deadzone and expo."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.set_input_shaping(deadzone, expo)
+        if response and response.get('status') == 'success':
+            self.show_robot_shaping(response)
+            logger.info(f"Robot input shaping set to {response.get('shape')}")
+        else:
+            logger.warning(f"Failed to set robot input shaping: {response}")
+
     def set_auto_mode(self):
         """Switch robot to autonomous mode."""
         if self._set_robot_mode("AUTO"):
@@ -223,16 +1489,281 @@ class AppWindow(DriverUIHelpers, QMainWindow):
     def reset_robot(self):
         """Reset robot to stopped state."""
         client = self.conn_manager.get_client()
+        self._formation_send("reset")
         if client:
+            self.stop_session_replay("robot reset")
             client.reset_robot()
             self.current_mode = "STOPPED"
             self.robot_status.setText("Stopped")
             self.stop_match_timer()
             logger.info("Robot reset")
 
+    def set_paused(self, paused):
+        """Freeze or release motor output without changing mode or dropping the link."""
+        client = self.conn_manager.get_client()
+        response = None
+        if paused:
+            self.stop_session_replay("paused")
+        if client:
+            response = client.pause() if paused else client.resume()
+        self._formation_send("pause" if paused else "resume")
+        if response and response.get("status") == "success":
+            self.robot_paused = bool(response.get("paused", paused))
+            logger.info("Robot paused" if self.robot_paused else "Robot resumed; center sticks to drive")
+        else:
+            logger.warning(f"Failed to {'pause' if paused else 'resume'} robot: {response}")
+        self.show_paused(self.robot_paused)
+
+    def set_parked(self, parked):
+        """Park the robot for the end of a session, or wake it back up (it stays STOPPED)."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        if parked:
+            self.stop_session_replay("parked")
+        response = client.shutdown() if parked else client.wake()
+        if response and response.get("status") == "success":
+            self.robot_park_state = response.get("park", self.robot_park_state)
+            if not parked:
+                self.current_mode = "STOPPED"
+                self.robot_status.setText("Stopped")
+            logger.info(f"Robot {self.robot_park_state.lower()}")
+        else:
+            logger.warning(f"Failed to {'park' if parked else 'wake'} robot: {response}")
+        self.show_park(self.robot_park_state)
+
+    def send_console_command(self):
+        """Send whatever the operator typed in the Diagnostics console and show the reply."""
+        text = self.console_input.text().strip()
+        if not text:
+            return
+        self.append_console(f"> {text}")
+        try:
+            command_type, fields = parse_console_command(text)
+        except ValueError as e:
+            self.append_console(f"! {e}")
+            return
+        self.console_input.clear()
+
+        client = self.conn_manager.get_client()
+        if not client:
+            self.append_console("! Not connected")
+            return
+        if not client.supports(command_type):
+            self.append_console(f"! '{command_type}' is not in the robot's capabilities; sending anyway")
+
+        try:
+            response = client.send_command(command_type, **fields)
+        except Exception as e:
+            self.append_console(f"! {e}")
+            return
+        if command_type in client.unacked_commands:
+            self.append_console("< (sent on the unacked drive channel, no reply)")
+        elif response is None:
+            self.append_console("< (no reply)")
+        else:
+            self.append_console(f"< {json.dumps(response)}")
+
+    def run_selftest(self):
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.run_selftest()
+        if response and response.get('status') == 'success':
+            self.show_selftest({'state': 'running'})
+            logger.info("Self-test started")
+        else:
+            message = response.get('message') if response else 'no response'
+            self.selftest_label.setText(f"<b style='color: red;'>Not started</b><br>{message}")
+            logger.warning(f"Self-test not started: {message}")
+
+    def dump_telemetry(self, which):
+        """Download a robot telemetry dump, save it to disk and plot it."""
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        response = client.dump_telemetry(which)
+        if not response or response.get('status') != 'success':
+            message = response.get('message') if response else 'no response'
+            self.dump_label.setText(f"<b style='color: red;'>Dump failed</b>: {message}")
+            logger.warning(f"Telemetry dump failed: {message}")
+            return
+
+        samples = response.get('samples', [])
+        path = os.path.join(TELEMETRY_DUMP_DIR, time.strftime(f"telemetry_dump_{which}_%Y%m%d_%H%M%S.json"))
+        try:
+            with open(path, "w", encoding="utf-8") as f:
+                json.dump(response, f, indent=2)
+        except OSError as e:
+            logger.error(f"Failed to save telemetry dump {path}: {e}")
+            path = "not saved"
+        self.dump_label.setText(f"{len(samples)} samples at {response.get('rate_hz')} Hz ({path})")
+        self.telemetry_plot_widget.set_samples(samples)
+        logger.info(f"Saved {which} telemetry dump with {len(samples)} samples to {path}")
+
+    def toggle_telemetry_csv(self):
+        """Start or stop mirroring every telemetry update to a CSV file."""
+        writer = self.telemetry_receiver.csv_writer
+        if writer is not None:
+            self.telemetry_receiver.csv_writer = None
+            writer.stop()
+            self.show_telemetry_csv(None, writer)
+            logger.info(f"Telemetry CSV closed: {writer.rows} rows in {writer.parts} file(s) at {writer.path}, "
+                        f"{writer.dropped} dropped")
+            return
+
+        path = os.path.join(TELEMETRY_DUMP_DIR, time.strftime("telemetry_%Y%m%d_%H%M%S.csv"))
+        writer = comm.TelemetryCsvWriter(path)
+        writer.start()
+        self.telemetry_receiver.csv_writer = writer
+        self.show_telemetry_csv(path, None)
+        logger.info(f"Logging telemetry to {path}")
+
+    def toggle_wire_log(self):
+        """Start or stop hex-dumping every frame the robot link sends and receives."""
+        writer = self.conn_manager.wire_log
+        if writer is not None:
+            self.conn_manager.set_wire_log(None)
+            writer.stop()
+            self.show_wire_log(None, writer)
+            logger.info(f"Raw link log closed: {writer.frames} frames, {writer.bytes} bytes at {writer.path}")
+            return
+
+        path = os.path.join(TELEMETRY_DUMP_DIR, time.strftime("wire_%Y%m%d_%H%M%S.log"))
+        writer = comm.WireLogWriter(path)
+        writer.start()
+        self.conn_manager.set_wire_log(writer)
+        self.show_wire_log(path, None)
+        logger.info(f"Logging raw link frames to {path}")
+
+    def toggle_session_recording(self):
+        recorder = self.conn_manager.recorder
+        if not recorder.recording:
+            if self.session_player.playing:
+                return
+            recorder.start()
+            logger.info("Session recording started")
+            self._show_session_state("Recording")
+            return
+
+        events = recorder.stop()
+        name = self._prompt_session_name() if events else None
+        if name:
+            try:
+                saved_name = save_session(name, events)
+                logger.info(f"Saved session '{saved_name}' with {len(events)} commands")
+                self.show_sessions(list_sessions(), saved_name)
+            except OSError as e:
+                logger.error(f"Failed to save session {name}: {e}")
+        self._show_session_state("Idle" if name or not events else "Recording discarded")
+
+    def _session_safe_to_start(self):
+        if self.current_mode != "TELEOP":
+            return "Replay needs TELEOP"
+        if self.robot_paused:
+            return "Robot is paused"
+        if self.robot_park_state != "ACTIVE":
+            return "Robot is parked"
+        if self.motion_lockout is not None:
+            return "Acknowledge the robot alert first"
+        if any(self.joystick_values.values()) or self.joystick_needs_center:
+            return "Center the sticks first"
+        return None
+
+    def toggle_session_replay(self):
+        player = self.session_player
+        if player.playing:
+            player.pause()
+            self._release_session_controls()
+            self.session_timer.stop()
+            self._show_session_state("Paused")
+            return
+
+        client = self.conn_manager.get_client()
+        name = self.session_combo.currentText()
+        if not client or not name:
+            return
+        problem = self._session_safe_to_start()
+        if problem:
+            self._show_session_state(problem)
+            return
+        if name != player.name:
+            try:
+                player.load(name, load_session(name))
+            except (OSError, ValueError) as e:
+                self._show_session_state(f"Can't load {name}: {e}")
+                return
+
+        # Start from centered sticks, as the robot's safe start expects.
+        client.send_joystick(0.0, 0.0, 0.0, 0.0)
+        self._formation_drive(0.0, 0.0, 0.0, 0.0)
+        player.play()
+        self.session_timer.start(GAMEPAD_POLL_RATE_MS)
+        logger.info(f"Replaying session '{name}' from {player.position_s:.1f} s")
+        self._show_session_state("Playing")
+
+    def seek_session_replay(self, position_s):
+        self.session_player.seek(position_s)
+        self._release_session_controls()
+        self._show_session_state("Playing" if self.session_player.playing else "Paused")
+
+    def stop_session_replay(self, reason):
+        player = self.session_player
+        was_active = player.playing or player.position_s > 0
+        player.pause()
+        player.seek(0.0)
+        self.session_timer.stop()
+        if was_active:
+            self._release_session_controls()
+            logger.info(f"Session replay stopped: {reason}")
+        self._show_session_state(f"Stopped ({reason})" if was_active else "Idle")
+
+    def step_session_replay(self):
+        client = self.conn_manager.get_client()
+        if not client:
+            self.stop_session_replay("link lost")
+            return
+        for event in self.session_player.tick():
+            fields = event.get("fields", {})
+            with self.conn_manager.recorder.suspend():
+                client.send_command(event["type"], **fields)
+            if event["type"] == "joystick":
+                self._formation_drive(*(fields.get(axis, 0.0) for axis in DRIVE_AXIS_NAMES))
+            if event["type"] == "button":
+                if fields.get("action") == "DOWN":
+                    self.session_buttons_held.add(fields.get("button_id"))
+                else:
+                    self.session_buttons_held.discard(fields.get("button_id"))
+        if self.session_player.finished:
+            self.stop_session_replay("finished")
+        else:
+            self._show_session_state("Playing")
+
+    def _release_session_controls(self):
+        """Zero the sticks and let go of any buttons the replay was holding."""
+        client = self.conn_manager.get_client()
+        if client:
+            # Recorded joystick frames may carry a button mask; an empty one releases it.
+            client.send_joystick(0.0, 0.0, 0.0, 0.0, buttons=0 if client.button_mask_bits else None)
+            for button in self.session_buttons_held:
+                client.send_button(button, "UP")
+        self._formation_drive(0.0, 0.0, 0.0, 0.0)
+        self.session_buttons_held.clear()
+        self.last_sent_joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
+        self.last_sent_button_mask = 0
+
+    def _show_session_state(self, status):
+        player = self.session_player
+        self.show_session_state(
+            self.conn_manager.recorder.recording, player.playing, player.position_s, player.duration_s, status
+        )
+
     def _set_robot_mode(self, mode):
         mode = str(mode).upper()
         client = self.conn_manager.get_client()
+        if mode == "STOPPED":
+            # Stopping the others can't wait on the main robot answering.
+            self._formation_send("mode", mode=mode)
         if not client:
             return False
 
@@ -240,15 +1771,38 @@ class AppWindow(DriverUIHelpers, QMainWindow):
         if not response or response.get("status") != "success":
             logger.warning(f"Failed to set mode: {mode}")
             return False
+        if mode != "STOPPED":
+            self._formation_send("mode", mode=mode)
 
+        self._show_robot_mode(mode)
+        return True
+
+    def _show_robot_mode(self, mode):
         self.current_mode = mode
+        if mode != "TELEOP":
+            self.stop_session_replay(f"mode {mode}")
         if mode == "AUTO":
             self.robot_status.setText("Autonomous")
         elif mode == "TELEOP":
             self.robot_status.setText("Teleoperated")
         else:
             self.robot_status.setText("Stopped")
-        return True
+
+    def update_robot_state(self, state):
+        """Adopt the robot's reported control state; it wins over what the driver assumed."""
+        previous = self.robot_state
+        self.robot_state = state
+        if state.summary != previous.summary:
+            logger.info(f"Robot state: {state.summary}")
+        if state.mode == self.current_mode:
+            self.mode_mismatch_since = None
+        elif self.mode_mismatch_since is None:
+            self.mode_mismatch_since = time.time()
+        elif time.time() - self.mode_mismatch_since >= MODE_MISMATCH_GRACE_S:
+            logger.warning(f"Robot reports mode {state.mode} but the driver showed {self.current_mode}; following the robot")
+            self.mode_mismatch_since = None
+            self._show_robot_mode(state.mode)
+        self.show_robot_state(state)
 
     def _set_control_mode_label(self, mode_name, color=None):
         if not hasattr(self, "control_mode_label"):
@@ -270,15 +1824,229 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             return
         label.setStyleSheet(f"color: {FACE_BUTTON_COLORS[button_index] if active else 'lightgray'}")
 
+    def set_speed_profile(self, index):
+        """Select the active speed cap by index into SPEED_PROFILES."""
+        self.speed_profile_index = int(index) % len(SPEED_PROFILES)
+        cap = SPEED_PROFILES[self.speed_profile_index]
+        self.show_speed_profile(cap, self.speed_profile_index)
+        logger.info(f"Speed cap set to {cap:.0%}")
+
+    def set_right_stick_mode(self, mode):
+        """Route the right stick to drive rotation, the pan/tilt gimbal, or both."""
+        if mode not in RIGHT_STICK_MODES:
+            return
+        self.right_stick_mode = mode
+        self.gimbal_aiming = False
+        self.driver_settings["right_stick_mode"] = mode
+        save_driver_settings(self.driver_settings)
+        logger.info(f"Right stick mode: {mode}")
+
+    def center_gimbal(self):
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('servo_center'):
+            return
+        response = client.center_servos()
+        if response and response.get('status') == 'success':
+            self._sync_gimbal(response.get('servos', {}))
+            logger.info("Gimbal centered")
+        else:
+            logger.warning(f"Failed to center gimbal: {response}")
+
+    def _sync_gimbal(self, angles):
+        self.gimbal_angles.update({name: float(angle) for name, angle in angles.items()})
+        self.gimbal_target = dict(self.gimbal_angles)
+        self.show_gimbal(self.gimbal_angles)
+
+    def _step_gimbal(self, client, pan, tilt):
+        """Slew the gimbal target with the stick and send changed angles at a bounded rate."""
+        now = time.time()
+        # Cap dt so a stalled poll can't turn into a big jump.
+        dt = min(0.1, now - self.last_gimbal_step)
+        self.last_gimbal_step = now
+        self.gimbal_aiming = bool(pan or tilt)
+        if not self.gimbal_target or not client.supports('servo'):
+            return
+        for name, rate in (("pan", pan), ("tilt", tilt)):
+            if name in self.gimbal_target:
+                self.gimbal_target[name] += rate * SERVO_RATE_DPS * dt
+        if now - self.last_servo_send < SERVO_SEND_INTERVAL_MS / 1000.0:
+            return
+        for name, angle in self.gimbal_target.items():
+            if abs(angle - self.gimbal_angles.get(name, angle)) < SERVO_SEND_THRESHOLD_DEG:
+                continue
+            self.last_servo_send = now
+            response = client.set_servo(name, angle)
+            if response and response.get('status') == 'success':
+                # Take the robot's clamped angle so the target never winds up past a limit.
+                self._sync_gimbal({name: response.get('angle', angle)})
+
+    def set_axis_deadzone(self, deadzone):
+        self.axis_deadzone = max(0.0, min(0.5, float(deadzone)))
+        self.show_axis_shaping(self.axis_deadzone, self.axis_expo)
+
+    def set_axis_expo(self, expo):
+        self.axis_expo = max(1.0, min(3.0, float(expo)))
+        self.show_axis_shaping(self.axis_deadzone, self.axis_expo)
+
+    def current_preset(self):
+        """Snapshot of the live controller configuration."""
+        return {
+            "axes": list(self.drive_axes),
+            "deadzone": self.axis_deadzone,
+            "expo": self.axis_expo,
+            "speed_cap": SPEED_PROFILES[self.speed_profile_index],
+        }
+
+    def apply_preset(self, name, persist=True):
+        preset = self.presets.get(name)
+        if preset is None:
+            return
+        axes = tuple(int(a) for a in preset.get("axes", DRIVE_AXES))
+        if len(axes) == len(DRIVE_AXES) and axes != self.drive_axes:
+            self.drive_axes = axes
+            self.stick_center = StickCenterCalibrator(self.drive_axes)
+            if self.joystick is not None and not self.joystick_needs_center:
+                self.gamepad_label.setText(f"Gamepad: {self._describe_controller()}")
+        self.set_axis_deadzone(preset.get("deadzone", AXIS_DEADZONE))
+        self.set_axis_expo(preset.get("expo", AXIS_EXPO))
+        cap = float(preset.get("speed_cap", SPEED_PROFILES[-1]))
+        self.set_speed_profile(min(range(len(SPEED_PROFILES)), key=lambda i: abs(SPEED_PROFILES[i] - cap)))
+
+        self.active_preset = name
+        if persist:
+            self._save_presets()
+        else:
+            self.show_presets(list(self.presets), self.active_preset)
+        logger.info(f"Applied controller preset '{name}'")
+
+    def save_preset(self, name):
+        self.presets[name] = self.current_preset()
+        self.active_preset = name
+        self._save_presets()
+        logger.info(f"Saved controller preset '{name}'")
+
+    def rename_preset(self, old, new):
+        if old not in self.presets or new in self.presets:
+            return
+        self.presets = {new if key == old else key: value for key, value in self.presets.items()}
+        if self.active_preset == old:
+            self.active_preset = new
+        self._save_presets()
+
+    def delete_preset(self, name):
+        if name not in self.presets or len(self.presets) == 1:
+            return
+        del self.presets[name]
+        if self.active_preset == name:
+            self.apply_preset(next(iter(self.presets)))
+        self._save_presets()
+
+    def cycle_preset(self):
+        names = list(self.presets)
+        index = names.index(self.active_preset) if self.active_preset in names else -1
+        self.apply_preset(names[(index + 1) % len(names)])
+
+    def _save_presets(self):
+        self.driver_settings["presets"] = self.presets
+        self.driver_settings["active_preset"] = self.active_preset
+        save_driver_settings(self.driver_settings)
+        self.show_presets(list(self.presets), self.active_preset)
+
+    def _effective_deadzone(self):
+        if self.link_policy.degraded:
+            return max(self.axis_deadzone, DEGRADED_AXIS_DEADZONE)
+        return self.axis_deadzone
+
+    def _shape_axis(self, value):
+        return shape_axis(value, self._effective_deadzone(), self.axis_expo)
+
+    def preview_axis_output(self, value):
+        """Final output for a raw stick value, including the active speed cap."""
+        return self._scaled_axes(self._shape_axis(value), 0.0, 0.0, 0.0)[0]
+
+    def handle_robot_alert(self, alert, age_s=0.0):
+        """Show a robot alert once, whether it came on the alert channel or in telemetry.
+
+        ``age_s`` is how old the alert was on the robot's clock when it was sent; the alert
+        channel delivers alerts as they are raised.
+        """
+        if alert.get('id', 0) <= self.last_alert_id:
+            return
+        self.last_alert_id = alert['id']
+        logger.warning(f"Robot alert ({alert.get('kind')}): {alert.get('message')}")
+        self.show_alert(alert)
+        if alert.get('kind') in MOTION_LOCKOUT_ALERTS and age_s < MOTION_LOCKOUT_MAX_ALERT_AGE_S:
+            self.latch_motion_lockout(f"{alert.get('kind')}: {alert.get('message')}")
+
+    def latch_motion_lockout(self, reason):
+        """Stop sending motion after an unsafe robot alert, until the operator acknowledges it."""
+        if self.motion_lockout is None:
+            logger.warning(f"Driving disabled until acknowledged: {reason}")
+        self.motion_lockout = reason
+        self.show_motion_lockout(reason)
+        if self.session_player.playing:
+            self.stop_session_replay("robot alert")
+        client = self.conn_manager.get_client()
+        if client:
+            client.send_joystick(0.0, 0.0, 0.0, 0.0)
+            self.last_sent_joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
+        self._formation_drive(0.0, 0.0, 0.0, 0.0)
+
+    def acknowledge_motion_lockout(self):
+        if self.motion_lockout is None:
+            return
+        logger.info(f"Operator acknowledged: {self.motion_lockout}")
+        self.motion_lockout = None
+        self.show_motion_lockout(None)
+        if self.joystick is not None:
+            # Don't resume into whatever the sticks were doing when the alert hit.
+            self.joystick_needs_center = True
+            self.gamepad_label.setText(f"Gamepad: {self._describe_controller()} (center sticks to arm)")
+
+    def set_drive_flipped(self, flipped):
+        """Swap which end of the robot is the front for every input source."""
+        if flipped == self.drive_flipped:
+            return
+        self.drive_flipped = flipped
+        self.show_flip(flipped)
+        if self.joystick is not None:
+            # Whatever the sticks were doing now means the opposite; make the driver let go first.
+            self.joystick_needs_center = True
+            self.gamepad_label.setText(f"Gamepad: {self._describe_controller()} (center sticks to arm)")
+        logger.info(f"Drive orientation: {'flipped' if flipped else 'normal'}")
+
+    def nudge_trim(self, steering_steps, throttle_steps):
+        self.set_trim(self.drive_trim["steering"] + steering_steps * TRIM_STEP,
+                      self.drive_trim["throttle"] + throttle_steps * TRIM_STEP)
+
+    def set_trim(self, steering, throttle):
+        """Set and persist the drive trims, clamped to +/-MAX_TRIM."""
+        trim = {"steering": clamp_trim(steering), "throttle": clamp_trim(throttle)}
+        if trim == self.drive_trim:
+            return
+        self.drive_trim = trim
+        self.driver_settings["trim"] = trim
+        save_driver_settings(self.driver_settings)
+        self.show_trim(trim)
+        logger.info(f"Trim: steering {trim['steering']:+.2f}, throttle {trim['throttle']:+.2f}")
+
+    def reset_trim(self):
+        self.set_trim(0.0, 0.0)
+
+    def _step_trim(self, hat):
+        """Nudge the trims once per D-pad press while the trim button is held."""
+        if hat != self.trim_hat and hat != (0, 0):
+            self.nudge_trim(hat[0], hat[1])
+        self.trim_hat = hat
+
+    def cycle_speed_profile(self):
+        self.set_speed_profile(self.speed_profile_index + 1)
+
     def _scaled_axes(self, lx, ly, rx, ry):
+        scale = SPEED_PROFILES[self.speed_profile_index]
         if self.slow_drive.isChecked():
-            return (
-                lx * SLOW_DRIVE_SCALE,
-                ly * SLOW_DRIVE_SCALE,
-                rx * SLOW_DRIVE_SCALE,
-                ry * SLOW_DRIVE_SCALE,
-            )
-        return lx, ly, rx, ry
+            scale *= SLOW_DRIVE_SCALE
+        return lx * scale, ly * scale, rx * scale, ry * scale
     
     def start_match_timer(self):
         """Start the match timer."""
@@ -342,9 +2110,204 @@ class AppWindow(DriverUIHelpers, QMainWindow):
     def handle_ping_response(self, ping_ms):
         """Handle ping response from robot."""
         self.ping_label.setText(f"Ping: {ping_ms:.1f} ms")
+        self.last_ping_ms = ping_ms
+        self.send_rate.record_rtt(ping_ms)
+        self.update_telemetry_rate()
+
+    def handle_connection_progress(self, address, stage, error):
+        """Show how far a connection attempt has got, so a slow or hung address shows where it stalls."""
+        if error:
+            logger.warning(f"Connecting to {address} failed while {stage}: {error}")
+        if self.conn_manager.get_client() is not None:
+            # A replacement for a live link is being tried in the background; keep showing the live one.
+            return
+        step = comm.CONNECT_STAGES.index(stage) + 1
+        color, verb = ("red", "Failed") if error else ("orange", "Connecting")
+        text = f"Status: <b style='color: {color};'>{verb}: {stage}</b> ({step}/{len(comm.CONNECT_STAGES)})"
+        if error:
+            text += f"<br>{html.escape(error)}"
+        self.status_label.setText(text)
+        self.address_label.setText(f"Address: {address}")
+
+    def handle_link_activity(self):
+        self.link_pulse.pulse()
+
+    def adjust_send_rate(self):
+        if self.send_rate.adjust():
+            logger.info(
+                f"Joystick send interval {self.send_rate.min_interval_s * 1000:.0f} ms, "
+                f"threshold {self.send_rate.threshold:.3f}"
+            )
+
+    def update_link_quality(self):
+        client = self.conn_manager.get_client()
+        if not client:
+            return
+        # A slow telemetry rate we asked for ourselves isn't a gap.
+        max_gap_s = LINK_POOR_TELEMETRY_GAP_S
+        if self.requested_telemetry_hz:
+            max_gap_s = max(max_gap_s, LINK_FLATLINE_PERIODS / self.requested_telemetry_hz)
+        loss_pct = self.conn_manager.telemetry_loss.snapshot()["recent_pct"]
+        changed = self.link_policy.update(
+            self.send_rate.average_rtt(),
+            self.telemetry_store.age('timestamp'),
+            client.consecutive_timeouts,
+            max_gap_s,
+            telemetry_loss_pct=loss_pct,
+        )
+        self.show_link_quality(self.link_policy.quality, self.link_policy.degraded, loss_pct)
+        if not changed:
+            return
+        if self.link_policy.degraded:
+            logger.warning("Link quality poor: degraded mode on (low-rate telemetry, coarser sticks, no autofire)")
+        else:
+            logger.info("Link quality recovered: degraded mode off")
+        self.update_telemetry_rate()
+
+    def _joystick_send_limits(self):
+        """Minimum interval and change threshold for joystick sends right now."""
+        interval_s = self.send_rate.min_interval_s
+        threshold = self.send_rate.threshold
+        if self.link_policy.degraded:
+            interval_s = max(interval_s, DEGRADED_SEND_INTERVAL_MS / 1000.0)
+            threshold = max(threshold, DEGRADED_SEND_THRESHOLD)
+        return interval_s, threshold
+
+    def desired_telemetry_rate(self):
+        if self.isMinimized():
+            return TELEMETRY_RATE_MINIMIZED_HZ
+        if self.link_policy.degraded:
+            return TELEMETRY_RATE_DEGRADED_HZ
+        if self.last_ping_ms is not None and self.last_ping_ms > CONGESTED_PING_MS:
+            return TELEMETRY_RATE_CONGESTED_HZ
+        return TELEMETRY_RATE_ACTIVE_HZ
+
+    def update_telemetry_rate(self):
+        """Ask the robot for a new telemetry rate when our needs change."""
+        hz = self.desired_telemetry_rate()
+        if hz == self.requested_telemetry_hz:
+            return
+        client = self.conn_manager.get_client()
+        if not client or not client.supports('telemetry_rate'):
+            return
+        response = client.set_telemetry_rate(hz)
+        if response and response.get("status") == "success":
+            self.requested_telemetry_hz = hz
+            self.link_pulse.set_flatline_s(max(LINK_FLATLINE_S, LINK_FLATLINE_PERIODS / hz))
+            logger.info(f"Requested telemetry at {hz:.0f} Hz")
+
+    def changeEvent(self, event):
+        super().changeEvent(event)
+        if event.type() == QEvent.Type.WindowStateChange and hasattr(self, "conn_manager"):
+            self.update_telemetry_rate()
     
+    def refresh_command_history(self):
+        self.show_command_history(self.conn_manager.history.snapshot())
+
+    def update_link_stats(self):
+        self.latency_histogram_widget.set_counts(self.conn_manager.latency.snapshot())
+        self.show_telemetry_loss(self.conn_manager.telemetry_loss.snapshot())
+        self.show_bandwidth(self.conn_manager.bandwidth.snapshot())
+        outages = self.conn_manager.outages
+        self.show_outages(outages.snapshot(), outages.summary())
+        if self.formation is not None:
+            self.refresh_formation()
+
+    def telemetry_freshness(self, field):
+        """'live', 'stale' or 'disconnected' for a telemetry field.
+
+        'disconnected' covers both a down link and values left over from before the current
+        link came up, so a reconnect doesn't make old numbers look merely late.
+        """
+        store = self.telemetry_store
+        if self.telemetry_link_since is None or not store.updated_since(field, self.telemetry_link_since):
+            return "disconnected"
+        return "stale" if store.is_stale(field, TELEMETRY_STALE_S) else "live"
+
+    def add_dashboard_widget(self, path, kind, minimum, maximum):
+        """Show a telemetry field on the dashboard; the layout is saved with the driver settings."""
+        try:
+            binding = comm.DashboardBinding(path, kind, minimum, maximum)
+        except ValueError as e:
+            logger.warning(f"Not adding dashboard widget: {e}")
+            return
+        self.dashboard.append(binding)
+        self._save_dashboard()
+        logger.info(f"Dashboard shows {binding.path} as a {binding.kind}")
+
+    def remove_dashboard_widget(self, index):
+        if not 0 <= index < len(self.dashboard):
+            return
+        binding = self.dashboard.pop(index)
+        self._save_dashboard()
+        logger.info(f"Dashboard no longer shows {binding.path}")
+
+    def _save_dashboard(self):
+        self.driver_settings["dashboard"] = [binding.to_config() for binding in self.dashboard]
+        save_driver_settings(self.driver_settings)
+        self._show_dashboard_layout()
+
+    def _show_dashboard_layout(self):
+        formats = self.telemetry_store.formats
+        labels = [formats[b.path].label if b.path in formats else b.path for b in self.dashboard]
+        self.show_dashboard_layout(self.dashboard, labels, DASHBOARD_COLUMNS)
+        self.update_dashboard()
+
+    def update_dashboard(self):
+        """Refresh the dashboard tiles from the telemetry store."""
+        store = self.telemetry_store
+        self.show_dashboard([
+            (store.display(b.path), b.fraction(store.lookup(b.path)),
+             self.telemetry_freshness(b.path.split(".")[0]))
+            for b in self.dashboard
+        ])
+
+    def update_telemetry_staleness(self):
+        """Mark labels whose telemetry field is stale or from a link that has since dropped."""
+        store = self.telemetry_store
+        self.update_dashboard()
+        for field, label_names in TELEMETRY_FIELD_LABELS.items():
+            state, age = self.telemetry_freshness(field), store.age(field)
+            for label_name in label_names:
+                if hasattr(self, label_name):
+                    self.set_label_freshness(getattr(self, label_name), state, age)
+        for path, label in self.telemetry_readout_labels.items():
+            field = path.split(".")[0]
+            self.set_label_freshness(label, self.telemetry_freshness(field), store.age(field))
+
+        last = store.last_updated()
+        age = None if last is None else time.time() - last
+        if self.telemetry_link_since is None:
+            overall = "disconnected"
+        elif last is None or last < self.telemetry_link_since or age > TELEMETRY_STALE_S:
+            overall = "stale"
+        else:
+            overall = "live"
+        self.show_telemetry_freshness(overall, age)
+
+    def check_loop_tick(self, tick):
+        """Flag a robot whose control loop has stopped even though its telemetry hasn't."""
+        now = time.monotonic()
+        if tick != self.loop_tick:
+            # Any change counts, so a restarted robot counting from zero isn't a stall.
+            self.loop_tick = tick
+            self.loop_tick_advanced_at = now
+            stalled = False
+        else:
+            stalled = now - self.loop_tick_advanced_at > LOOP_STALL_S
+        if stalled != self.loop_stalled:
+            self.loop_stalled = stalled
+            if stalled:
+                logger.error(f"Robot control loop stalled at tick {tick}; telemetry is still arriving")
+            else:
+                logger.info("Robot control loop running again")
+        self.show_loop_stall(now - self.loop_tick_advanced_at if stalled else None)
+
     def handle_telemetry(self, data):
         """Handle telemetry data from robot."""
+        self.telemetry_store.update(data)
+        if 'loop_tick' in data:
+            self.check_loop_tick(data['loop_tick'])
         # Update UI with telemetry data
         # Example: battery, sensor readings, motor status, etc.
         try:
@@ -364,6 +2327,87 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             self.current_pose = {"x": x_m, "y": y_m, "theta_deg": theta_deg}
             self.update_expected_pose()
 
+            if self.pending_odometry_reset is not None and int(data.get('odometry_resets', 0)) >= self.pending_odometry_reset:
+                self.pending_odometry_reset = None
+                logger.info(f"Odometry reset confirmed: x={x_m:.2f} m y={y_m:.2f} m theta={theta_deg:.1f} deg")
+
+            if 'battery' in data:
+                self.show_battery(self.telemetry_store.display('battery'), float(data.get('derate', 1.0)))
+
+            if 'timing' in data:
+                self.show_timing(data['timing'])
+
+            if 'drive_mode' in data:
+                self.show_drive_mode(data['drive_mode'])
+
+            sequence = data.get('sequence')
+            if sequence and sequence != self.last_sequence_state:
+                self.last_sequence_state = sequence
+                self.show_sequence_state(sequence)
+
+            if 'geofence' in data:
+                self.show_geofence(data['geofence'])
+
+            if 'imu' in data:
+                self.orientation = parse_orientation(data['imu'])
+                self.show_orientation(self.orientation, bool((data.get('tilt') or {}).get('tripped')))
+
+            digital = (data.get('sensors') or {}).get('digital')
+            if isinstance(digital, dict):
+                self.show_sensor_inputs(digital)
+
+            turn = data.get('turn')
+            if turn and turn != self.last_turn_state:
+                self.last_turn_state = turn
+                self.show_turn_state(turn)
+
+            # Follow the robot while the stick is idle, e.g. after a reset re-centers it.
+            servos = data.get('servos')
+            if servos and not self.gimbal_aiming:
+                self._sync_gimbal(servos)
+
+            self.show_telemetry_readout(
+                {path: self.telemetry_store.display(path) for path in self.telemetry_readout_labels}
+            )
+            self.update_dashboard()
+            fields = self.telemetry_store.paths()
+            if fields != self.dashboard_fields:
+                self.dashboard_fields = fields
+                self.show_dashboard_fields(fields)
+
+            alerts = data.get('alerts') or []
+            if alerts and alerts[-1].get('id', 0) < self.last_alert_id:
+                # Robot restarted and its alert ids began again.
+                self.last_alert_id = 0
+            for alert in alerts:
+                age_s = float(data.get('timestamp', 0.0)) - float(alert.get('t', 0.0))
+                self.handle_robot_alert(alert, age_s)
+
+            selftest = data.get('selftest')
+            if selftest and selftest != self.last_selftest:
+                self.last_selftest = selftest
+                self.show_selftest(selftest)
+
+            if 'arming_s' in data:
+                self.show_arming(float(data['arming_s']))
+
+            if 'state' in data:
+                self.update_robot_state(RobotControlState(data['state']))
+
+            paused = bool(data.get('paused', False))
+            if paused != self.robot_paused:
+                self.robot_paused = paused
+                self.show_paused(paused)
+
+            park = data.get('park', "ACTIVE")
+            if park != self.robot_park_state:
+                self.robot_park_state = park
+                if park != "PARKING":
+                    # Parking ends in STOPPED and waking leaves the robot there.
+                    self.current_mode = "STOPPED"
+                    self.robot_status.setText("Stopped")
+                self.show_park(park)
+
             if odometry_mode and hasattr(self, 'label_odo_mode'):
                 self.label_odo_mode.setText(f"Odometry Mode: {str(odometry_mode).title()}")
         except Exception as e:
@@ -462,27 +2506,222 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             if pygame.joystick.get_count() > 0:
                 self.joystick = pygame.joystick.Joystick(0)
                 self.joystick.init()
-                self.gamepad_label.setText(f"Gamepad: {self.joystick.get_name()}")
+                self.gamepad_label.setText(f"Gamepad: {self._describe_controller()}")
                 logger.info(f"Found joystick: {self.joystick.get_name()}")
             else:
                 self.gamepad_label.setText("Gamepad: Not Found")
                 logger.warning("No joystick found")
         except Exception as e:
             logger.error(f"Error initializing pygame/joystick: {e}")
-            self.gamepad_label.setText("Gamepad: Error")
+            self.gamepad_label.setText("Gamepad: Error (retrying)")
+            self.gamepad_recovery.fail(e, time.monotonic())
+
+    def _describe_controller(self):
+        """Log what the controller provides and return its name, flagging drive inputs it lacks."""
+        name = self.joystick.get_name()
+        num_axes = self.joystick.get_numaxes()
+        mapping = ", ".join(
+            f"{role}={axis if 0 <= axis < num_axes else 'missing'}"
+            for role, axis in zip(DRIVE_AXIS_NAMES, self.drive_axes)
+        )
+        logger.info(
+            f"Controller {name}: {num_axes} axes, {self.joystick.get_numbuttons()} buttons, "
+            f"{self.joystick.get_numhats()} hats; drive axes {mapping}"
+        )
+        for trigger in TRIGGER_BUTTONS:
+            if trigger.axis >= num_axes:
+                logger.warning(f"Controller {name} has no axis {trigger.axis} for trigger button {trigger.button}")
+
+        missing = missing_drive_axes(self.drive_axes, num_axes)
+        if not missing:
+            self.gamepad_label.setToolTip("")
+            return name
+        logger.warning(f"Controller {name} lacks {', '.join(missing)}; those inputs stay at zero")
+        self.gamepad_label.setToolTip(f"{num_axes} axes; remap lx,ly,rx,ry with KSU_DRIVE_AXES or a preset")
+        return f"{name} <span style='color: orange;'>(no {', '.join(m.upper() for m in missing)})</span>"
+
+    def _read_drive_axes(self):
+        """Read raw [lx, ly, rx, ry] with the learned resting offsets removed. Missing axes read 0."""
+        raw = {
+            axis: self.joystick.get_axis(axis)
+            for axis in self.drive_axes
+            if 0 <= axis < self.joystick.get_numaxes()
+        }
+        self.stick_center.sample(raw)
+        return tuple(self.stick_center.apply(axis, raw.get(axis, 0.0)) for axis in self.drive_axes)
+
+    def _joystick_centered(self):
+        return all(abs(value) <= AXIS_DEADZONE for value in self._read_drive_axes())
+
+    def relearn_stick_center(self):
+        """Forget learned offsets and re-learn them over the next second. Don't touch the sticks."""
+        self.stick_center.reset()
+        logger.info("Re-learning stick center offsets")
+
+    def _handle_joystick_removed(self, event):
+        """Controller unplugged: stop the robot now instead of waiting for its failsafe."""
+        if self.joystick is None or event.instance_id != self.joystick.get_instance_id():
+            return
+        logger.warning(f"Controller lost: {self.joystick.get_name()}")
+        self.joystick = None
+        self.gamepad_label.setText("Gamepad: <b style='color: red;'>Controller lost</b>")
+        self._release_controller_inputs()
+
+    def _release_controller_inputs(self):
+        """Zero the sticks and release held buttons on the robot, as if the controller let go."""
+        self.joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
+        self.button_mask = 0
+        client = self.conn_manager.get_client()
+        if client:
+            # An empty mask releases everything the robot thinks is held.
+            client.send_joystick(0.0, 0.0, 0.0, 0.0, buttons=0 if client.button_mask_bits else None)
+            for button in self.held_autofire_buttons:
+                if not client.sends_button_mask(button):
+                    client.send_button(button, "UP")
+            for trigger in TRIGGER_BUTTONS:
+                if trigger.pressed and not client.sends_button_mask(trigger.button):
+                    client.send_button(trigger.button, "UP")
+        self._formation_drive(0.0, 0.0, 0.0, 0.0)
+        self.last_sent_button_mask = 0
+        self.held_autofire_buttons.clear()
+        for trigger in TRIGGER_BUTTONS:
+            trigger.pressed = False
+        self.last_sent_joystick_values = self.joystick_values.copy()
+
+    def _handle_joystick_added(self, event):
+        if self.joystick is not None:
+            return
+        self.joystick = pygame.joystick.Joystick(event.device_index)
+        self.joystick.init()
+        self.stick_center.reset()
+        # Don't trust a freshly attached controller until its sticks are at rest.
+        self.joystick_needs_center = True
+        self.gamepad_label.setText(f"Gamepad: {self._describe_controller()} (center sticks to arm)")
+        logger.info(f"Controller attached: {self.joystick.get_name()}")
+
+    def _gamepad_failed(self, error):
+        """pygame's input subsystem errored: stop driving from it until it has been rebuilt."""
+        if not self.gamepad_recovery.fail(error, time.monotonic()):
+            return
+        logger.error(f"Gamepad input failed ({self.gamepad_recovery.last_error}); reinitializing")
+        self.joystick = None
+        self._release_controller_inputs()
+        self.gamepad_label.setText("Gamepad: <b style='color: red;'>Input error, reinitializing...</b>")
+
+    def _reinit_gamepad(self):
+        """Tear down and rebuild pygame's joystick subsystem, then reopen the first controller."""
+        now = time.monotonic()
+        try:
+            pygame.joystick.quit()
+            # Also brings back anything else that went down with it, e.g. the event queue.
+            pygame.init()
+            pygame.joystick.init()
+            joystick = None
+            if pygame.joystick.get_count() > 0:
+                joystick = pygame.joystick.Joystick(0)
+                joystick.init()
+        except pygame.error as e:
+            self.gamepad_recovery.attempt_failed(e, now)
+            logger.warning(f"Gamepad reinit failed ({e}); retrying in {self.gamepad_recovery.next_attempt - now:.0f} s")
+            return
+        down_s = self.gamepad_recovery.recovered(now)
+        self.joystick = joystick
+        logger.info(f"Gamepad input recovered after {down_s:.1f} s (reinit #{self.gamepad_recovery.reinits})")
+        if joystick is None:
+            self.gamepad_label.setText("Gamepad: Not Found (input recovered)")
+            return
+        self.stick_center.reset()
+        self.joystick_needs_center = True
+        self.gamepad_label.setText(f"Gamepad: {self._describe_controller()} (recovered, center sticks to arm)")
+
+    def _poll_gamepad_events(self):
+        """Drain pygame events, handling controller hot-plug. Returns the remaining events."""
+        if self.gamepad_recovery.due(time.monotonic()):
+            self._reinit_gamepad()
+        if self.gamepad_recovery.failed:
+            return []
+        try:
+            events = pygame.event.get()
+        except pygame.error as e:
+            self._gamepad_failed(e)
+            return []
+
+        remaining = []
+        for event in events:
+            if event.type == pygame.JOYDEVICEREMOVED:
+                self._handle_joystick_removed(event)
+            elif event.type == pygame.JOYDEVICEADDED:
+                try:
+                    self._handle_joystick_added(event)
+                except pygame.error as e:
+                    self._gamepad_failed(e)
+            else:
+                remaining.append(event)
+        return remaining
 
     def update_connection_status(self, is_connected, address):
         """Update UI based on connection status."""
         if is_connected:
-            self.status_label.setText("Status: <b style='color: green;'>Connected</b>")
+            client = self.conn_manager.get_client()
+            if client is not None and client.role == comm.ROLE_SPOTTER:
+                self.status_label.setText("Status: <b style='color: green;'>Connected</b> as spotter (gimbal only)")
+            else:
+                self.status_label.setText("Status: <b style='color: green;'>Connected</b>")
             self.address_label.setText(f"Address: {address}")
+            self.telemetry_link_since = time.time()
             logger.info(f"Connected to {address}")
+            link = self.conn_manager.connected_address
+            if link and self.driver_settings.get("last_robot_address") != link:
+                # Try this link first on the next launch.
+                self.driver_settings["last_robot_address"] = link
+                save_driver_settings(self.driver_settings)
+            if link != self.uptime_address:
+                # Another robot's uptime says nothing about this one restarting.
+                self.last_robot_uptime_s = None
+                self.uptime_address = link
+            self.requested_telemetry_hz = None
+            self.send_rate.reset()
+            self.apply_robot_capabilities()
+            self.update_telemetry_rate()
+            self.refresh_robot_settings()
+            self.refresh_sequences()
+            self.refresh_robot_uptime()
+            self.subscribe_sensors()
+            if self.formation is not None:
+                self.formation.set_pinging(True)
         else:
+            # The formation follows the main robot's sticks, which are gone with its link.
+            self._formation_drive(0.0, 0.0, 0.0, 0.0)
+            if self.formation is not None:
+                self.formation.set_pinging(False)
+            self.show_robot_name(None)
+            # last_robot_uptime_s survives the drop, so a restart during it is caught on reconnect.
+            self.show_uptime(None, None)
+            self.show_sensor_inputs({})
+            self._end_link_bench(None)
+            # A lost link is its own failure; judge the loop afresh on the next connection.
+            self.loop_tick = None
+            self.loop_stalled = False
+            self.show_loop_stall(None)
+            self.show_arming(0.0)
+            self.robot_state = RobotControlState()
+            self.show_robot_state(self.robot_state)
+            self.gimbal_target = {}
+            self.gimbal_angles = {}
+            self.gimbal_aiming = False
+            self.show_gimbal({})
             self.status_label.setText("Status: <b style='color: red;'>Disconnected</b>")
             self.address_label.setText("Address: N/A")
+            self.telemetry_link_since = None
+            self.update_telemetry_staleness()
             self.ping_label.setText("Ping: -- ms")
             self.robot_status.setText("Stopped")
             self.current_mode = "STOPPED"
+            self.held_autofire_buttons.clear()
+            self.link_pulse.reset()
+            self.link_policy.reset()
+            self.show_link_quality(None, False)
+            self.stop_session_replay("disconnected")
             
             # Reset button colors
             self.button_a_label.setStyleSheet("color: lightgray")
@@ -492,12 +2731,77 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             
             logger.warning("Disconnected from robot")
 
+    def _button_held(self, button):
+        return (
+            self.joystick is not None
+            and button < self.joystick.get_numbuttons()
+            and bool(self.joystick.get_button(button))
+        )
+
+    def _fire_held_buttons(self, client):
+        """Re-send DOWN for held autofire buttons whose interval has elapsed."""
+        now = time.time()
+        for button, state in self.held_autofire_buttons.items():
+            next_fire, repeats = state
+            if now < next_fire:
+                continue
+            state[1] = repeats + 1
+            client.send_button(button, "DOWN", repeat=state[1])
+            # Schedule from now rather than next_fire so a stalled link never bursts catch-up repeats.
+            state[0] = now + AUTOFIRE_BUTTONS[button]
+
+    def _button_down(self, client, button):
+        if button == SPEED_PROFILE_BUTTON and self._button_held(PRESET_MODIFIER_BUTTON):
+            self.cycle_preset()
+        elif button == SPEED_PROFILE_BUTTON:
+            self.cycle_speed_profile()
+        elif button == ODOMETRY_RESET_BUTTON:
+            self.reset_odometry()
+        elif button == GIMBAL_CENTER_BUTTON and self.right_stick_mode != "drive":
+            self.center_gimbal()
+        elif button == FLIP_BUTTON:
+            self.set_drive_flipped(not self.drive_flipped)
+        self._send_button_edge(client, button, "DOWN")
+        if button in AUTOFIRE_BUTTONS:
+            self.held_autofire_buttons[button] = [time.time() + AUTOFIRE_BUTTONS[button], 0]
+        if button in FACE_BUTTON_COLORS:
+            self._set_face_button_style(button, active=True)
+
+    def _button_up(self, client, button):
+        self.held_autofire_buttons.pop(button, None)
+        self._send_button_edge(client, button, "UP")
+        if button in FACE_BUTTON_COLORS:
+            self._set_face_button_style(button, active=False)
+
+    def _send_button_edge(self, client, button, action):
+        """Send a press or release, as a bit in the next joystick frame when the robot takes a mask."""
+        if not client.sends_button_mask(button):
+            client.send_button(button, action)
+        elif action == "DOWN":
+            self.button_mask |= 1 << button
+        else:
+            self.button_mask &= ~(1 << button)
+
+    def _poll_trigger_buttons(self, client):
+        """Threshold analog triggers into virtual button presses/releases."""
+        if self.joystick is None or self.joystick_needs_center:
+            return
+        for trigger in TRIGGER_BUTTONS:
+            if trigger.axis >= self.joystick.get_numaxes():
+                continue
+            action = trigger.update(self.joystick.get_axis(trigger.axis))
+            if action == "DOWN":
+                self._button_down(client, trigger.button)
+            elif action == "UP":
+                self._button_up(client, trigger.button)
+
     def values_changed_significantly(self, old_values, new_values, threshold=JOYSTICK_THRESHOLD):
         """Check if joystick values changed beyond threshold."""
         return any(abs(old_values[k] - new_values[k]) > threshold for k in old_values)
 
     def poll_gamepad(self):
         """Poll gamepad state and send updates to robot."""
+        events = self._poll_gamepad_events()
         client = self.conn_manager.get_client()
         if not client:
             return
@@ -516,39 +2820,68 @@ class AppWindow(DriverUIHelpers, QMainWindow):
                 self._set_control_mode_label("None")
             
             # Use keyboard input if active, otherwise use joystick
+            raw_sticks = shaped_sticks = (0.0, 0.0, 0.0, 0.0)
             if has_keyboard_input:
+                shaped_sticks = tuple(keyboard_input)
                 self.joystick_values['lx'] = keyboard_input[0]
                 self.joystick_values['ly'] = keyboard_input[1]
                 self.joystick_values['rx'] = keyboard_input[2]
                 self.joystick_values['ry'] = keyboard_input[3]
+            elif self.joystick is not None and self.joystick_needs_center:
+                self.joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
+                if self._joystick_centered():
+                    self.joystick_needs_center = False
+                    self.gamepad_label.setText(f"Gamepad: {self._describe_controller()}")
+                    logger.info("Controller centered, input armed")
             elif self.joystick is not None:
                 # Poll joystick only if no keyboard input
-                pygame.event.pump()
-                # Read and apply deadzone to joystick axes
-                axis_lx = self.joystick.get_axis(0)
-                axis_ly = self.joystick.get_axis(1)
-                axis_rx = self.joystick.get_axis(2)
-                axis_ry = self.joystick.get_axis(4)
-
-                self.joystick_values['lx'] = axis_lx if abs(axis_lx) > AXIS_DEADZONE else 0.0
-                self.joystick_values['ly'] = -axis_ly if abs(axis_ly) > AXIS_DEADZONE else 0.0
-                self.joystick_values['rx'] = axis_rx if abs(axis_rx) > AXIS_DEADZONE else 0.0
-                self.joystick_values['ry'] = -axis_ry if abs(axis_ry) > AXIS_DEADZONE else 0.0
-
-                # Handle button events
-                for event in pygame.event.get():
-                    if event.type == pygame.JOYBUTTONDOWN:
-                        client.send_button(event.button, "DOWN")
-                        if event.button in FACE_BUTTON_COLORS:
-                            self._set_face_button_style(event.button, active=True)
-                            
-                    elif event.type == pygame.JOYBUTTONUP:
-                        client.send_button(event.button, "UP")
-                        if event.button in FACE_BUTTON_COLORS:
-                            self._set_face_button_style(event.button, active=False)
+                # Read and apply deadzone/expo shaping to joystick axes
+                axis_lx, axis_ly, axis_rx, axis_ry = self._read_drive_axes()
+
+                raw_sticks = (axis_lx, -axis_ly, axis_rx, -axis_ry)
+                stick = shaped_sticks = tuple(self._shape_axis(value) for value in raw_sticks)
+                hat = self.joystick.get_hat(0) if self.joystick.get_numhats() > 0 else (0, 0)
+                if self._button_held(TRIM_MODE_BUTTON):
+                    self._step_trim(hat)
+                    hat = (0, 0)
+                else:
+                    self.trim_hat = (0, 0)
+                if self.right_stick_mode == "gimbal":
+                    # The right stick only aims here, so it doesn't compete with the D-pad.
+                    drive = arbitrate_drive_inputs(stick[:2] + (0.0, 0.0), dpad_nudge(hat))
+                    values = drive[:2] + stick[2:]
+                else:
+                    values = arbitrate_drive_inputs(stick, dpad_nudge(hat))
+                self.joystick_values = dict(zip(DRIVE_AXIS_NAMES, values))
+                self.axis_curve_widget.set_input(max((axis_lx, axis_ly, axis_rx, axis_ry), key=abs))
             else:
                 # No input - zero everything
                 self.joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
+            if self.drive_flipped:
+                self.joystick_values = flip_drive_inputs(self.joystick_values)
+            self.show_stick_pads(raw_sticks, shaped_sticks, self._effective_deadzone())
+
+            if client.role == comm.ROLE_SPOTTER:
+                # Someone else drives; the right stick aims the gimbal and nothing else is sent.
+                self._step_gimbal(client, self.joystick_values['rx'], self.joystick_values['ry'])
+                return
+
+            # Handle button events
+            for event in events:
+                if event.type == pygame.JOYBUTTONDOWN:
+                    self._button_down(client, event.button)
+                elif event.type == pygame.JOYBUTTONUP:
+                    self._button_up(client, event.button)
+            self._poll_trigger_buttons(client)
+
+            if not self.link_policy.degraded:
+                self._fire_held_buttons(client)
+
+            if self.right_stick_mode != "drive":
+                self._step_gimbal(client, self.joystick_values['rx'], self.joystick_values['ry'])
+                if self.right_stick_mode == "gimbal":
+                    self.joystick_values['rx'] = 0.0
+                self.joystick_values['ry'] = 0.0
 
             lx, ly, rx, ry = self._scaled_axes(
                 self.joystick_values['lx'],
@@ -560,6 +2893,9 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             self.joystick_values['ly'] = ly
             self.joystick_values['rx'] = rx
             self.joystick_values['ry'] = ry
+            self.joystick_values = apply_drive_trim(self.joystick_values, self.drive_trim)
+            if self.motion_lockout is not None:
+                self.joystick_values = {'lx': 0.0, 'ly': 0.0, 'rx': 0.0, 'ry': 0.0}
 
             # Update UI labels
             self.lx_label.setText(f"LX: {self.joystick_values['lx']:.2f}")
@@ -568,16 +2904,40 @@ class AppWindow(DriverUIHelpers, QMainWindow):
             self.ry_label.setText(f"RY: {self.joystick_values['ry']:.2f}")
             self.update_expected_pose()
 
-            # Send joystick values if changed significantly
-            if self.values_changed_significantly(self.last_sent_joystick_values, self.joystick_values):
+            # A replay owns the drive until any live stick or button input takes over.
+            if self.session_player.playing:
+                manual = any(self.joystick_values.values()) or any(e.type == pygame.JOYBUTTONDOWN for e in events)
+                if not manual:
+                    return
+                self.stop_session_replay("manual input")
+
+            # Send joystick values if changed significantly and the link has room for them.
+            # Releasing the sticks always goes out immediately.
+            # Button presses and releases go out at once too, and a held mask is refreshed.
+            now = time.time()
+            stopping = not any(self.joystick_values.values())
+            buttons = self.button_mask if client.button_mask_bits else None
+            buttons_changed = buttons is not None and buttons != self.last_sent_button_mask
+            refresh = buttons is not None and now - self.last_joystick_send >= BUTTON_MASK_REFRESH_S
+            min_interval_s, threshold = self._joystick_send_limits()
+            due = stopping or buttons_changed or now - self.last_joystick_send >= min_interval_s
+            threshold = 0.0 if stopping else threshold
+            changed = self.values_changed_significantly(self.last_sent_joystick_values, self.joystick_values, threshold)
+            if due and (changed or buttons_changed or refresh):
+                self.last_joystick_send = now
                 client.send_joystick(
                     self.joystick_values['lx'],
                     self.joystick_values['ly'],
                     self.joystick_values['rx'],
-                    self.joystick_values['ry']
+                    self.joystick_values['ry'],
+                    buttons=buttons,
                 )
+                self._formation_drive(*(self.joystick_values[axis] for axis in DRIVE_AXIS_NAMES))
                 self.last_sent_joystick_values = self.joystick_values.copy()
+                self.last_sent_button_mask = buttons or 0
                 
+        except pygame.error as e:
+            self._gamepad_failed(e)
         except Exception as e:
             logger.error(f"Error polling gamepad: {e}")
 
@@ -586,15 +2946,24 @@ class AppWindow(DriverUIHelpers, QMainWindow):
         logger.info("Closing application...")
         
         try:
+            # Nothing may post to this window from here on, even before the threads are joined.
+            self.conn_manager.signals.close()
             self.stop_camera_stream()
 
             # Stop threads
+            if self.telemetry_receiver.csv_writer is not None:
+                self.toggle_telemetry_csv()
+            if self.conn_manager.wire_log is not None:
+                self.toggle_wire_log()
             self.telemetry_receiver.stop()
             self.conn_manager.stop()
+            if self.formation is not None:
+                self.formation.stop()
             
             # Wait for threads to finish (with timeout)
             self.telemetry_receiver.join(timeout=2)
             self.conn_manager.join(timeout=2)
+            self.conn_manager.outages.close()
             
             # Clean up joystick
             if self.joystick:
@@ -618,6 +2987,8 @@ def main():
     if os.path.exists(icon_path):
         window.setWindowIcon(QIcon(icon_path))
     window.show()
+    # Also covers the event loop ending without the window being closed.
+    app.aboutToQuit.connect(window.conn_manager.signals.close)
     sys.exit(app.exec())
 
 
@@ -1,9 +1,14 @@
 import os
 import math
+import time
 import logging
 
-from PyQt6.QtWidgets import QWidget, QVBoxLayout, QTabWidget, QLabel
-from PyQt6.QtCore import Qt, QPointF, QRectF, QThread, pyqtSignal
+from PyQt6.QtWidgets import (
+    QWidget, QVBoxLayout, QHBoxLayout, QGridLayout, QTabWidget, QLabel, QComboBox, QSlider,
+    QGroupBox, QDoubleSpinBox, QPushButton, QLineEdit, QPlainTextEdit, QInputDialog, QMessageBox,
+    QTableWidget, QTableWidgetItem, QCheckBox,
+)
+from PyQt6.QtCore import Qt, QPointF, QRectF, QThread, QTimer, pyqtSignal
 from PyQt6.QtGui import QColor, QPainter, QPen, QBrush, QPolygonF, QImage, QPixmap
 
 try:
@@ -17,6 +22,16 @@ CAMERA_STREAM_URL = os.environ.get("KSU_CAMERA_STREAM_URL", "http://10.42.0.3:80
 CAMERA_RECONNECT_MS = 1500
 
 
+def format_age(age_s):
+    """Short age such as '0.4 s', '12 s' or '3m 05s'."""
+    if age_s < 10:
+        return f"{age_s:.1f} s"
+    if age_s < 60:
+        return f"{age_s:.0f} s"
+    minutes, seconds = divmod(int(age_s), 60)
+    return f"{minutes}m {seconds:02d}s"
+
+
 class FieldWidget(QWidget):
     """Simple 2D field map showing robot position and heading."""
     def __init__(self, parent=None):
@@ -119,6 +134,332 @@ class FieldWidget(QWidget):
         painter.drawText(8, 16, "Field View (orange=current, cyan=expected)")
 
 
+class AxisCurveWidget(QWidget):
+    """Plot of the stick input -> output shaping curve with the live stick value."""
+    def __init__(self, shape_fn, parent=None):
+        super().__init__(parent)
+        self.shape_fn = shape_fn
+        self.input_value = 0.0
+        self.setMinimumSize(160, 160)
+
+    def set_input(self, value):
+        value = max(-1.0, min(1.0, float(value)))
+        if value != self.input_value:
+            self.input_value = value
+            self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+
+        margin = 10
+        size = max(10, min(self.width(), self.height()) - 2 * margin)
+        rect = QRectF(margin, margin, size, size)
+        painter.fillRect(rect, QColor(30, 45, 55))
+        painter.setPen(QPen(QColor(95, 140, 150), 1))
+        painter.drawRect(rect)
+
+        def to_screen(x, y):
+            return QPointF(rect.left() + x * rect.width(), rect.bottom() - y * rect.height())
+
+        painter.setPen(QPen(QColor(70, 95, 110), 1, Qt.PenStyle.DashLine))
+        painter.drawLine(to_screen(0.0, 0.0), to_screen(1.0, 1.0))
+
+        samples = 64
+        points = [to_screen(i / samples, abs(self.shape_fn(i / samples))) for i in range(samples + 1)]
+        painter.setPen(QPen(QColor(125, 235, 240), 2))
+        for start, end in zip(points, points[1:]):
+            painter.drawLine(start, end)
+
+        x = abs(self.input_value)
+        painter.setPen(QPen(QColor(255, 255, 255), 1))
+        painter.setBrush(QBrush(QColor(230, 120, 40)))
+        painter.drawEllipse(to_screen(x, abs(self.shape_fn(x))), 4, 4)
+
+
+class StickPadWidget(QWidget):
+    """2D pad for one stick: the deadzone circle, the raw position and the shaped output."""
+    def __init__(self, title, parent=None):
+        super().__init__(parent)
+        self.title = title
+        self.raw = (0.0, 0.0)
+        self.shaped = (0.0, 0.0)
+        self.deadzone = 0.0
+        self.setMinimumSize(140, 140)
+
+    def set_state(self, raw, shaped, deadzone):
+        state = (tuple(raw), tuple(shaped), float(deadzone))
+        if state != (self.raw, self.shaped, self.deadzone):
+            self.raw, self.shaped, self.deadzone = state
+            self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+
+        margin = 10
+        size = max(10, min(self.width(), self.height()) - 2 * margin)
+        rect = QRectF(margin, margin, size, size)
+        painter.fillRect(rect, QColor(30, 45, 55))
+        painter.setPen(QPen(QColor(95, 140, 150), 1))
+        painter.drawRect(rect)
+        center = rect.center()
+        half = size / 2.0
+
+        def to_screen(x, y):
+            x = max(-1.0, min(1.0, x))
+            y = max(-1.0, min(1.0, y))
+            return QPointF(center.x() + x * half, center.y() - y * half)
+
+        painter.setPen(QPen(QColor(70, 95, 110), 1, Qt.PenStyle.DashLine))
+        painter.drawLine(to_screen(-1.0, 0.0), to_screen(1.0, 0.0))
+        painter.drawLine(to_screen(0.0, -1.0), to_screen(0.0, 1.0))
+        painter.setPen(QPen(QColor(230, 200, 80), 1))
+        painter.setBrush(Qt.BrushStyle.NoBrush)
+        painter.drawEllipse(center, self.deadzone * half, self.deadzone * half)
+
+        painter.setPen(QPen(QColor(200, 200, 200), 1))
+        painter.drawEllipse(to_screen(*self.raw), 5, 5)
+        painter.setPen(QPen(QColor(255, 255, 255), 1))
+        painter.setBrush(QBrush(QColor(230, 120, 40)))
+        painter.drawEllipse(to_screen(*self.shaped), 4, 4)
+
+        painter.setPen(QPen(QColor(235, 235, 235), 1))
+        painter.drawText(QRectF(rect.left() + 4, rect.top() + 2, rect.width() - 8, 14),
+                         Qt.AlignmentFlag.AlignLeft, self.title)
+
+
+class OrientationWidget(QWidget):
+    """Heading dial with a bubble level: the dot sits off center by roll and pitch."""
+    def __init__(self, full_scale_deg, parent=None):
+        super().__init__(parent)
+        self.full_scale_deg = max(1.0, float(full_scale_deg))
+        self.orientation = None
+        self.warning = False
+        self.setMinimumSize(110, 110)
+
+    def set_orientation(self, orientation, warning):
+        state = (dict(orientation) if orientation else None, bool(warning))
+        if state != (self.orientation, self.warning):
+            self.orientation, self.warning = state
+            self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+
+        margin = 8
+        size = max(10, min(self.width(), self.height()) - 2 * margin)
+        rect = QRectF((self.width() - size) / 2.0, (self.height() - size) / 2.0, size, size)
+        center = rect.center()
+        radius = size / 2.0
+        painter.setPen(QPen(QColor(95, 140, 150), 2))
+        painter.setBrush(QBrush(QColor(30, 45, 55)))
+        painter.drawEllipse(rect)
+        if self.orientation is None:
+            painter.setPen(QPen(QColor(160, 160, 160), 1))
+            painter.drawText(rect, Qt.AlignmentFlag.AlignCenter, "No IMU")
+            return
+
+        # Same convention as the field view: 0 deg points right, counter-clockwise positive.
+        heading_rad = math.radians(self.orientation['yaw'])
+        tip = QPointF(center.x() + radius * 0.85 * math.cos(heading_rad),
+                      center.y() - radius * 0.85 * math.sin(heading_rad))
+        painter.setPen(QPen(QColor(255, 220, 120), 3))
+        painter.drawLine(center, tip)
+
+        painter.setPen(QPen(QColor(70, 95, 110), 1, Qt.PenStyle.DashLine))
+        painter.setBrush(Qt.BrushStyle.NoBrush)
+        painter.drawEllipse(center, radius * 0.5, radius * 0.5)
+        x = max(-1.0, min(1.0, self.orientation['roll'] / self.full_scale_deg))
+        y = max(-1.0, min(1.0, self.orientation['pitch'] / self.full_scale_deg))
+        painter.setPen(QPen(QColor(255, 255, 255), 1))
+        painter.setBrush(QBrush(QColor(230, 120, 40) if self.warning else QColor(125, 235, 240)))
+        painter.drawEllipse(QPointF(center.x() + x * radius, center.y() - y * radius), 5, 5)
+
+
+class LatencyHistogramWidget(QWidget):
+    """Small bar chart of round-trip latency bucket counts."""
+    def __init__(self, labels, parent=None):
+        super().__init__(parent)
+        self.labels = list(labels)
+        self.counts = [0] * len(self.labels)
+        self.setMinimumHeight(120)
+
+    def set_counts(self, counts):
+        counts = list(counts)
+        if counts != self.counts:
+            self.counts = counts
+            self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+        painter.fillRect(self.rect(), QColor(30, 45, 55))
+
+        total = sum(self.counts)
+        peak = max(self.counts) if self.counts else 0
+        slot = self.width() / max(1, len(self.counts))
+        chart_height = self.height() - 36
+        for i, (label, count) in enumerate(zip(self.labels, self.counts)):
+            bar_height = 0 if peak == 0 else chart_height * count / peak
+            bar = QRectF(i * slot + 8, 8 + chart_height - bar_height, slot - 16, bar_height)
+            painter.fillRect(bar, QColor(125, 235, 240) if i < len(self.counts) - 1 else QColor(230, 120, 40))
+            share = 0.0 if total == 0 else count / total
+            painter.setPen(QPen(QColor(235, 235, 235), 1))
+            painter.drawText(QRectF(i * slot, self.height() - 28, slot, 14),
+                             Qt.AlignmentFlag.AlignCenter, f"{label} ms")
+            painter.drawText(QRectF(i * slot, self.height() - 14, slot, 14),
+                             Qt.AlignmentFlag.AlignCenter, f"{share:.0%}")
+
+
+class LinkPulseWidget(QWidget):
+    """Heartbeat LED: flashes on every data arrival, fades, and flatlines once data stops."""
+    FRAME_MS = 33
+    FADE_S = 0.35
+
+    def __init__(self, flatline_s, parent=None):
+        super().__init__(parent)
+        self.flatline_s = flatline_s
+        self.last_pulse = None
+        self.setFixedSize(18, 18)
+        self.timer = QTimer(self)
+        self.timer.timeout.connect(self.update)
+        self.timer.start(self.FRAME_MS)
+
+    def pulse(self):
+        self.last_pulse = time.monotonic()
+
+    def reset(self):
+        self.last_pulse = None
+        self.update()
+
+    def set_flatline_s(self, seconds):
+        self.flatline_s = seconds
+
+    def is_flatlined(self):
+        return self.last_pulse is None or time.monotonic() - self.last_pulse > self.flatline_s
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+        if self.is_flatlined():
+            color = QColor(90, 90, 90)
+        else:
+            glow = max(0.0, 1.0 - (time.monotonic() - self.last_pulse) / self.FADE_S)
+            color = QColor(40, int(110 + 145 * glow), int(60 + 60 * glow))
+        painter.setPen(QPen(QColor(20, 20, 20), 1))
+        painter.setBrush(QBrush(color))
+        painter.drawEllipse(QRectF(2, 2, self.width() - 4, self.height() - 4))
+
+
+class TelemetryPlotWidget(QWidget):
+    """Motor outputs and derate over time from a robot telemetry dump."""
+    MOTOR_COLORS = (QColor(125, 235, 240), QColor(240, 200, 90), QColor(150, 230, 120), QColor(230, 130, 200))
+
+    def __init__(self, parent=None):
+        super().__init__(parent)
+        self.samples = []
+        self.setMinimumHeight(160)
+
+    def set_samples(self, samples):
+        self.samples = list(samples)
+        self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+        painter.fillRect(self.rect(), QColor(30, 45, 55))
+        if len(self.samples) < 2:
+            painter.setPen(QPen(QColor(200, 210, 215), 1))
+            painter.drawText(QRectF(self.rect()), Qt.AlignmentFlag.AlignCenter, "No dump loaded")
+            return
+
+        t0 = self.samples[0]['t']
+        span = max(1e-6, self.samples[-1]['t'] - t0)
+        mid = self.height() / 2.0
+        half = self.height() / 2.0 - 6
+
+        def point(sample, value):
+            return QPointF((sample['t'] - t0) / span * self.width(), mid - value * half)
+
+        painter.setPen(QPen(QColor(80, 100, 110), 1))
+        painter.drawLine(QPointF(0, mid), QPointF(self.width(), mid))
+        for motor, color in enumerate(self.MOTOR_COLORS):
+            painter.setPen(QPen(color, 1.5))
+            painter.drawPolyline(QPolygonF([point(s, s['motor_speeds'][motor]) for s in self.samples]))
+        painter.setPen(QPen(QColor(230, 120, 40), 1.5, Qt.PenStyle.DashLine))
+        painter.drawPolyline(QPolygonF([point(s, s.get('derate', 1.0)) for s in self.samples]))
+
+        # Shade samples taken while the link was lost.
+        for sample in self.samples:
+            if sample.get('connection_lost'):
+                x = point(sample, 0.0).x()
+                painter.fillRect(QRectF(x, 0, 2, self.height()), QColor(220, 50, 50, 60))
+
+        painter.setPen(QPen(QColor(235, 235, 235), 1))
+        painter.drawText(QRectF(4, 2, self.width() - 8, 14), Qt.AlignmentFlag.AlignLeft,
+                         "FL/FR/RL/RR output, derate dashed")
+        painter.drawText(QRectF(4, self.height() - 16, self.width() - 8, 14), Qt.AlignmentFlag.AlignRight,
+                         f"{span:.1f} s")
+
+
+class DashboardTileWidget(QWidget):
+    """One user-chosen telemetry field drawn as a number, gauge, bar or indicator light."""
+
+    def __init__(self, title, kind, parent=None):
+        super().__init__(parent)
+        self.title = title
+        self.kind = kind
+        self.text = "--"
+        self.fraction = None
+        self.freshness = "disconnected"
+        self.setMinimumSize(110, 70)
+        self.setToolTip(title)
+
+    def set_reading(self, text, fraction, freshness):
+        if (text, fraction, freshness) != (self.text, self.fraction, self.freshness):
+            self.text, self.fraction, self.freshness = text, fraction, freshness
+            self.update()
+
+    def paintEvent(self, event):
+        painter = QPainter(self)
+        painter.setRenderHint(QPainter.RenderHint.Antialiasing, True)
+        painter.fillRect(self.rect(), QColor(30, 45, 55))
+        live = self.freshness == "live"
+        accent = QColor(125, 235, 240) if live else QColor(110, 110, 110)
+        body = QRectF(6, 20, self.width() - 12, self.height() - 40)
+
+        if self.kind == "gauge":
+            side = min(body.width(), body.height() * 2)
+            arc = QRectF(body.center().x() - side / 2, body.top(), side, side)
+            painter.setPen(QPen(QColor(70, 85, 95), 6))
+            painter.drawArc(arc, 0, 180 * 16)
+            if self.fraction is not None:
+                painter.setPen(QPen(accent, 6))
+                painter.drawArc(arc, 180 * 16, -int(180 * 16 * self.fraction))
+        elif self.kind == "bar":
+            track = QRectF(body.left(), body.center().y() - 6, body.width(), 12)
+            painter.fillRect(track, QColor(70, 85, 95))
+            if self.fraction is not None:
+                painter.fillRect(QRectF(track.left(), track.top(), track.width() * self.fraction, track.height()),
+                                 accent)
+        elif self.kind == "indicator":
+            on = self.fraction is not None and self.fraction > 0 and live
+            side = min(body.width(), body.height())
+            painter.setPen(QPen(QColor(20, 20, 20), 1))
+            painter.setBrush(QBrush(QColor(40, 170, 60) if on else QColor(80, 80, 80)))
+            painter.drawEllipse(QRectF(body.center().x() - side / 2, body.center().y() - side / 2, side, side))
+
+        painter.setPen(QPen(QColor(200, 210, 215), 1))
+        painter.drawText(QRectF(4, 2, self.width() - 8, 16), Qt.AlignmentFlag.AlignLeft, self.title)
+        painter.setPen(QPen(QColor(235, 235, 235) if live else QColor(130, 130, 130), 1))
+        value_rect = QRectF(4, self.height() - 20, self.width() - 8, 18)
+        if self.kind == "number":
+            value_rect = QRectF(4, 18, self.width() - 8, self.height() - 20)
+        painter.drawText(value_rect, Qt.AlignmentFlag.AlignCenter, self.text)
+
+
 class CameraStreamThread(QThread):
     frame_ready = pyqtSignal(QImage)
     status_changed = pyqtSignal(str)
@@ -216,13 +557,1404 @@ class DriverUIHelpers:
         camera_layout.addWidget(self.camera_tab_view, 1)
         self.main_tabs.addTab(self.camera_tab, "Camera")
 
-        for tab_name in ["Settings", "Network", "Odometry", "Diagnostics"]:
+        self.tab_layouts = {}
+        self.tab_placeholders = {}
+        for tab_name in ["Settings", "Tuning", "Network", "Odometry", "Diagnostics"]:
             tab = QWidget()
             tab_layout = QVBoxLayout(tab)
             tab_layout.setContentsMargins(12, 12, 12, 12)
-            tab_layout.addWidget(QLabel(f"{tab_name} page - add controls here."))
+            placeholder = QLabel(f"{tab_name} page - add controls here.")
+            tab_layout.addWidget(placeholder)
             tab_layout.addStretch(1)
             self.main_tabs.addTab(tab, tab_name)
+            self.tab_layouts[tab_name] = tab_layout
+            self.tab_placeholders[tab_name] = placeholder
+
+    def add_tab_widget(self, tab_name, widget):
+        """Append a widget to one of the auxiliary tabs, above its trailing stretch."""
+        layout = getattr(self, "tab_layouts", {}).get(tab_name)
+        if layout is None:
+            return False
+        placeholder = self.tab_placeholders.pop(tab_name, None)
+        if placeholder is not None:
+            layout.removeWidget(placeholder)
+            placeholder.deleteLater()
+        layout.insertWidget(layout.count() - 1, widget)
+        return True
+
+    def _add_labeled_slider(self, tab_name, minimum, maximum, value, on_change):
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        label = QLabel()
+        label.setMinimumWidth(110)
+        slider = QSlider(Qt.Orientation.Horizontal)
+        slider.setRange(minimum, maximum)
+        slider.setValue(value)
+        slider.valueChanged.connect(on_change)
+        row_layout.addWidget(label)
+        row_layout.addWidget(slider, 1)
+        self.add_tab_widget(tab_name, row)
+        return label, slider
+
+    def setup_axis_shaping_controls(self, deadzone, expo, shape_fn):
+        self.deadzone_label, self.deadzone_slider = self._add_labeled_slider(
+            "Settings", 0, 30, round(deadzone * 100), lambda v: self.set_axis_deadzone(v / 100.0)
+        )
+        self.expo_label, self.expo_slider = self._add_labeled_slider(
+            "Settings", 100, 300, round(expo * 100), lambda v: self.set_axis_expo(v / 100.0)
+        )
+        self.axis_curve_widget = AxisCurveWidget(shape_fn)
+        self.add_tab_widget("Settings", self.axis_curve_widget)
+        self.show_axis_shaping(deadzone, expo)
+
+        # Hollow dot: raw stick after center offsets; filled dot: after deadzone/expo.
+        pads = QWidget()
+        pads.setToolTip("Hollow dot: raw stick position. Filled dot: output after deadzone and expo. "
+                        "Circle: deadzone")
+        pads_layout = QHBoxLayout(pads)
+        pads_layout.setContentsMargins(0, 0, 0, 0)
+        self.stick_pads = (StickPadWidget("Left"), StickPadWidget("Right"))
+        for pad in self.stick_pads:
+            pads_layout.addWidget(pad)
+        self.add_tab_widget("Settings", pads)
+
+    def show_stick_pads(self, raw, shaped, deadzone):
+        """raw/shaped are (lx, ly, rx, ry) with +y up."""
+        self.stick_pads[0].set_state(raw[:2], shaped[:2], deadzone)
+        self.stick_pads[1].set_state(raw[2:], shaped[2:], deadzone)
+
+    def show_axis_shaping(self, deadzone, expo):
+        self.deadzone_label.setText(f"Deadzone: {deadzone:.0%}")
+        self.expo_label.setText(f"Expo: {expo:.2f}")
+        for slider, value in ((self.deadzone_slider, deadzone), (self.expo_slider, expo)):
+            if slider.value() != round(value * 100):
+                slider.blockSignals(True)
+                slider.setValue(round(value * 100))
+                slider.blockSignals(False)
+        self.axis_curve_widget.update()
+
+    def setup_preset_controls(self):
+        box = QGroupBox("Controller Presets")
+        layout = QVBoxLayout(box)
+        self.preset_combo = QComboBox()
+        self.preset_combo.activated.connect(lambda index: self.apply_preset(self.preset_combo.itemText(index)))
+        layout.addWidget(self.preset_combo)
+
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        for text, handler in (
+            ("Save As...", self._prompt_save_preset),
+            ("Rename...", self._prompt_rename_preset),
+            ("Delete", lambda: self.delete_preset(self.preset_combo.currentText())),
+        ):
+            button = QPushButton(text)
+            button.clicked.connect(handler)
+            row_layout.addWidget(button)
+        layout.addWidget(row)
+        self.add_tab_widget("Settings", box)
+
+    def show_presets(self, names, active):
+        self.preset_combo.blockSignals(True)
+        self.preset_combo.clear()
+        self.preset_combo.addItems(names)
+        if active in names:
+            self.preset_combo.setCurrentIndex(names.index(active))
+        self.preset_combo.blockSignals(False)
+
+    def _prompt_save_preset(self):
+        name, ok = QInputDialog.getText(self, "Save Preset", "Preset name:", text=self.preset_combo.currentText())
+        if ok and name.strip():
+            self.save_preset(name.strip())
+
+    def _prompt_rename_preset(self):
+        old = self.preset_combo.currentText()
+        name, ok = QInputDialog.getText(self, "Rename Preset", f"New name for '{old}':", text=old)
+        if ok and name.strip():
+            self.rename_preset(old, name.strip())
+
+    def setup_session_controls(self):
+        box = QGroupBox("Session Record / Replay")
+        layout = QVBoxLayout(box)
+        self.btn_record_session = QPushButton("Record")
+        self.btn_record_session.setToolTip("Record outbound drive commands until pressed again")
+        self.btn_record_session.clicked.connect(self.toggle_session_recording)
+        layout.addWidget(self.btn_record_session)
+
+        self.session_combo = QComboBox()
+        layout.addWidget(self.session_combo)
+
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.btn_play_session = QPushButton("Play")
+        self.btn_play_session.setToolTip("Replay in TELEOP; any stick or button input takes over immediately")
+        self.btn_play_session.clicked.connect(self.toggle_session_replay)
+        stop_button = QPushButton("Stop")
+        stop_button.clicked.connect(lambda: self.stop_session_replay("stopped"))
+        row_layout.addWidget(self.btn_play_session)
+        row_layout.addWidget(stop_button)
+        layout.addWidget(row)
+
+        self.session_seek_slider = QSlider(Qt.Orientation.Horizontal)
+        self.session_seek_slider.setRange(0, 0)
+        self.session_seek_slider.sliderReleased.connect(
+            lambda: self.seek_session_replay(self.session_seek_slider.value() / 10.0)
+        )
+        layout.addWidget(self.session_seek_slider)
+        self.session_label = QLabel("Idle")
+        layout.addWidget(self.session_label)
+        self.add_tab_widget("Diagnostics", box)
+
+    def show_sessions(self, names, selected=None):
+        self.session_combo.clear()
+        self.session_combo.addItems(names)
+        if selected in names:
+            self.session_combo.setCurrentIndex(names.index(selected))
+
+    def show_session_state(self, recording, playing, position_s, duration_s, status):
+        self.btn_record_session.setText("Stop Recording" if recording else "Record")
+        self.btn_play_session.setText("Pause" if playing else "Play")
+        self.btn_play_session.setEnabled(not recording)
+        self.session_combo.setEnabled(not recording and not playing)
+        self.session_seek_slider.setRange(0, round(duration_s * 10))
+        if not self.session_seek_slider.isSliderDown():
+            self.session_seek_slider.setValue(round(position_s * 10))
+        self.session_label.setText(f"{status} ({position_s:.1f} / {duration_s:.1f} s)")
+
+    def _prompt_session_name(self):
+        name, ok = QInputDialog.getText(self, "Save Session", "Session name:")
+        return name.strip() if ok and name.strip() else None
+
+    def setup_failsafe_controls(self):
+        box = QGroupBox("Failsafe Timeout")
+        self.failsafe_group = box
+        layout = QHBoxLayout(box)
+        self.failsafe_spin = QDoubleSpinBox()
+        self.failsafe_spin.setDecimals(0)
+        self.failsafe_spin.setRange(1500, 10000)
+        self.failsafe_spin.setSingleStep(250)
+        self.failsafe_spin.setSuffix(" ms")
+        self.failsafe_spin.setToolTip("The robot stops its motors after this long without hearing from the driver. "
+                                      "Tighten for precise driving, loosen on a laggy link")
+        apply_button = QPushButton("Apply")
+        apply_button.clicked.connect(lambda: self.apply_failsafe_timeout(self.failsafe_spin.value()))
+        read_button = QPushButton("Read from Robot")
+        read_button.clicked.connect(self.refresh_failsafe_timeout)
+        layout.addWidget(self.failsafe_spin, 1)
+        layout.addWidget(apply_button)
+        layout.addWidget(read_button)
+        self.add_tab_widget("Network", box)
+
+    def show_failsafe_timeout(self, failsafe):
+        self.failsafe_spin.setRange(float(failsafe.get("min_ms", 1500)), float(failsafe.get("max_ms", 10000)))
+        self.failsafe_spin.setValue(float(failsafe.get("ms", 0)))
+
+    def setup_robot_shaping_controls(self):
+        box = QGroupBox("Robot-side Stick Shaping")
+        box.setToolTip("Deadzone and expo the robot applies to every controller, after this station's own "
+                       "shaping in Settings. Set 0 / 1.00 to leave shaping to the driver station")
+        self.robot_shaping_group = box
+        layout = QHBoxLayout(box)
+        self.robot_deadzone_spin = QDoubleSpinBox()
+        self.robot_deadzone_spin.setDecimals(2)
+        self.robot_deadzone_spin.setRange(0.0, 0.5)
+        self.robot_deadzone_spin.setSingleStep(0.01)
+        self.robot_deadzone_spin.setPrefix("Deadzone ")
+        self.robot_expo_spin = QDoubleSpinBox()
+        self.robot_expo_spin.setDecimals(2)
+        self.robot_expo_spin.setRange(1.0, 3.0)
+        self.robot_expo_spin.setSingleStep(0.05)
+        self.robot_expo_spin.setPrefix("Expo ")
+        apply_button = QPushButton("Apply")
+        apply_button.clicked.connect(lambda: self.apply_robot_shaping(self.robot_deadzone_spin.value(),
+                                                                      self.robot_expo_spin.value()))
+        read_button = QPushButton("Read from Robot")
+        read_button.clicked.connect(self.refresh_robot_shaping)
+        layout.addWidget(self.robot_deadzone_spin, 1)
+        layout.addWidget(self.robot_expo_spin, 1)
+        layout.addWidget(apply_button)
+        layout.addWidget(read_button)
+        self.add_tab_widget("Tuning", box)
+
+    def show_robot_shaping(self, response):
+        self.robot_deadzone_spin.setMaximum(float(response.get("max_deadzone", 0.5)))
+        low, high = response.get("expo_range", (1.0, 3.0))
+        self.robot_expo_spin.setRange(float(low), float(high))
+        shape = response.get("shape", {})
+        self.robot_deadzone_spin.setValue(float(shape.get("deadzone", 0.0)))
+        self.robot_expo_spin.setValue(float(shape.get("expo", 1.0)))
+
+    def setup_loop_rate_controls(self):
+        box = QGroupBox("Control Loop Rate")
+        box.setToolTip("How often the robot steps ramping, closed-loop PID and current limits. Faster is "
+                       "smoother but costs robot CPU; gains and limits behave the same at any rate")
+        self.loop_rate_group = box
+        layout = QHBoxLayout(box)
+        self.loop_rate_spin = QDoubleSpinBox()
+        self.loop_rate_spin.setDecimals(0)
+        self.loop_rate_spin.setRange(10.0, 200.0)
+        self.loop_rate_spin.setSingleStep(10.0)
+        self.loop_rate_spin.setValue(50.0)
+        self.loop_rate_spin.setSuffix(" Hz")
+        set_button = QPushButton("Set")
+        set_button.clicked.connect(lambda: self.set_loop_rate(self.loop_rate_spin.value()))
+        self.loop_rate_label = QLabel("Achieved: --")
+        layout.addWidget(self.loop_rate_spin, 1)
+        layout.addWidget(set_button)
+        layout.addWidget(self.loop_rate_label)
+        self.add_tab_widget("Tuning", box)
+
+    def show_loop_rate(self, response):
+        low, high = response.get("range", (10.0, 200.0))
+        self.loop_rate_spin.setRange(float(low), float(high))
+        self.loop_rate_spin.setValue(float(response.get("hz", 50.0)))
+        self.loop_rate_label.setText(f"Achieved: {float(response.get('achieved_hz', 0.0)):.1f} Hz")
+
+    def setup_robot_config_table(self):
+        box = QGroupBox("Robot Configuration")
+        box.setToolTip("Every setting on the robot. Double-click a value to change it. Grey rows are "
+                       "read-only; italic ones return to their default when the robot restarts")
+        self.robot_config_group = box
+        layout = QVBoxLayout(box)
+        self.robot_config_table = QTableWidget(0, 2)
+        self.robot_config_table.setHorizontalHeaderLabels(["Key", "Value"])
+        self.robot_config_table.horizontalHeader().setStretchLastSection(True)
+        self.robot_config_table.verticalHeader().setVisible(False)
+        self.robot_config_table.itemChanged.connect(self._robot_config_edited)
+        buttons = QHBoxLayout()
+        read_button = QPushButton("Read from Robot")
+        read_button.clicked.connect(self.refresh_robot_config)
+        copy_button = QPushButton("Copy as JSON")
+        copy_button.clicked.connect(self.copy_robot_config)
+        buttons.addWidget(read_button)
+        buttons.addWidget(copy_button)
+        layout.addWidget(self.robot_config_table, 1)
+        layout.addLayout(buttons)
+        self.add_tab_widget("Diagnostics", box)
+
+    def show_robot_config(self, rows):
+        """rows: (key, value text, read_only, volatile), in display order."""
+        table = self.robot_config_table
+        table.blockSignals(True)
+        table.setRowCount(len(rows))
+        for row, (key, text, read_only, volatile) in enumerate(rows):
+            key_item = QTableWidgetItem(key)
+            key_item.setFlags(Qt.ItemFlag.ItemIsEnabled)
+            value_item = QTableWidgetItem(text)
+            if read_only:
+                value_item.setFlags(Qt.ItemFlag.ItemIsEnabled)
+                value_item.setForeground(QColor("gray"))
+            if volatile:
+                font = value_item.font()
+                font.setItalic(True)
+                value_item.setFont(font)
+            table.setItem(row, 0, key_item)
+            table.setItem(row, 1, value_item)
+        table.resizeColumnToContents(0)
+        table.blockSignals(False)
+
+    def _robot_config_edited(self, item):
+        if item.column() != 1:
+            return
+        key, text = self.robot_config_table.item(item.row(), 0).text(), item.text().strip()
+        # Applying it rebuilds the table, so leave the edit's signal first.
+        QTimer.singleShot(0, lambda: self.set_robot_config(key, text))
+
+    def setup_bench_controls(self):
+        box = QGroupBox("Link Benchmark")
+        box.setToolTip("Has the robot send a burst of data to measure telemetry bandwidth and loss. "
+                       "Runs only while the robot is still and stops if it starts driving")
+        self.bench_group = box
+        layout = QVBoxLayout(box)
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.bench_spin = QDoubleSpinBox()
+        self.bench_spin.setDecimals(0)
+        self.bench_spin.setRange(1, 8192)
+        self.bench_spin.setValue(1024)
+        self.bench_spin.setSuffix(" KiB")
+        self.btn_bench = QPushButton("Run")
+        self.btn_bench.clicked.connect(lambda: self.run_link_bench(int(self.bench_spin.value()) * 1024))
+        stop_button = QPushButton("Stop")
+        stop_button.clicked.connect(self.stop_link_bench)
+        row_layout.addWidget(self.bench_spin, 1)
+        row_layout.addWidget(self.btn_bench)
+        row_layout.addWidget(stop_button)
+        self.bench_label = QLabel()
+        layout.addWidget(row)
+        layout.addWidget(self.bench_label)
+        self.add_tab_widget("Network", box)
+        self.show_bench_result(None)
+
+    def show_bench_result(self, result, running=False):
+        self.btn_bench.setEnabled(not running)
+        if running:
+            self.bench_label.setText("Benchmark: running...")
+            return
+        if result is None:
+            self.bench_label.setText("Benchmark: --")
+            return
+        text = (f"Benchmark: {result['received_bytes'] / 1024:.0f} KiB in {result['elapsed_s']:.2f} s = "
+                f"<b>{result['bytes_per_s'] / 1024:.0f} KiB/s</b>, loss {result['loss_pct']:.1f}%")
+        if result.get("aborted"):
+            text += f" <b style='color: orange;'>({result['aborted']})</b>"
+        self.bench_label.setText(text)
+
+    def setup_latency_histogram(self, labels):
+        box = QGroupBox("Round-trip Latency")
+        layout = QVBoxLayout(box)
+        self.latency_histogram_widget = LatencyHistogramWidget(labels)
+        layout.addWidget(self.latency_histogram_widget)
+        self.add_tab_widget("Network", box)
+
+    def setup_command_history(self, window_s):
+        box = QGroupBox("Recent Commands")
+        box.setToolTip(f"Everything this station sent in the last {window_s:.0f} s and how the robot answered, "
+                       "kept in memory all the time. Safety commands (stop, pause, reset) are in red")
+        layout = QVBoxLayout(box)
+        self.command_history_table = QTableWidget(0, 4)
+        self.command_history_table.setHorizontalHeaderLabels(["Time", "Command", "Details", "Result"])
+        self.command_history_table.horizontalHeader().setStretchLastSection(True)
+        self.command_history_table.verticalHeader().setVisible(False)
+        self.command_history_table.setEditTriggers(QTableWidget.EditTrigger.NoEditTriggers)
+        buttons = QHBoxLayout()
+        show_button = QPushButton(f"Show Last {window_s:.0f} s")
+        show_button.clicked.connect(self.refresh_command_history)
+        self.command_history_pings = QCheckBox("Include pings")
+        self.command_history_pings.toggled.connect(self.refresh_command_history)
+        buttons.addWidget(show_button)
+        buttons.addWidget(self.command_history_pings)
+        buttons.addStretch(1)
+        layout.addWidget(self.command_history_table, 1)
+        layout.addLayout(buttons)
+        self.add_tab_widget("Diagnostics", box)
+
+    def show_command_history(self, entries):
+        if not self.command_history_pings.isChecked():
+            entries = [entry for entry in entries if entry["type"] != "ping"]
+        table = self.command_history_table
+        table.setRowCount(len(entries))
+        for row, entry in enumerate(entries):
+            stamp = time.strftime("%H:%M:%S", time.localtime(entry["t"])) + f".{int(entry['t'] * 1000) % 1000:03d}"
+            details = ", ".join(f"{key}={value}" for key, value in entry["fields"].items())
+            outcome = entry["outcome"] + (" (replayed)" if entry["replayed"] else "")
+            items = [QTableWidgetItem(text) for text in (stamp, f"{entry['type']} #{entry['seq']}", details, outcome)]
+            if entry["safety"]:
+                for item in items:
+                    item.setForeground(QColor("red"))
+                    font = item.font()
+                    font.setBold(True)
+                    item.setFont(font)
+            elif entry["outcome"] not in ("ok", "sent"):
+                items[3].setForeground(QColor("orange"))
+            for column, item in enumerate(items):
+                table.setItem(row, column, item)
+        table.resizeColumnsToContents()
+        table.scrollToBottom()
+
+    def setup_formation_panel(self, stale_s):
+        box = QGroupBox("Formation")
+        box.setToolTip("Every robot driven together: the main robot first, then the KSU_FORMATION ones, which get "
+                       "the same drive and mode commands. A robot that is down is skipped until it reconnects")
+        layout = QVBoxLayout(box)
+        self.formation_stale_s = stale_s
+        self.formation_table = QTableWidget(0, 6)
+        self.formation_table.setHorizontalHeaderLabels(["Robot", "Link", "State", "Battery", "Last command", "Alert"])
+        self.formation_table.horizontalHeader().setStretchLastSection(True)
+        self.formation_table.verticalHeader().setVisible(False)
+        self.formation_table.setEditTriggers(QTableWidget.EditTrigger.NoEditTriggers)
+        layout.addWidget(self.formation_table)
+        self.add_tab_widget("Network", box)
+
+    def show_formation(self, rows):
+        table = self.formation_table
+        table.setRowCount(len(rows))
+        for row, robot in enumerate(rows):
+            age = robot["telemetry_age_s"]
+            if not robot["connected"]:
+                link, color = "DOWN", QColor("red")
+            elif age is None or age > self.formation_stale_s:
+                link, color = "no telemetry", QColor("orange")
+            else:
+                link, color = "up", None
+            battery = robot["battery"]
+            command = robot["last_command"]
+            texts = (
+                robot["name"],
+                f"{link} {robot['address']}".strip(),
+                robot["state"] or "--",
+                "--" if battery is None else f"{float(battery):.1f} V",
+                "--" if command is None else f"{command[0]}: {command[1]}",
+                robot["last_alert"] or "",
+            )
+            for column, text in enumerate(texts):
+                item = QTableWidgetItem(text)
+                if color is not None and column < 2:
+                    item.setForeground(color)
+                table.setItem(row, column, item)
+        table.resizeColumnsToContents()
+
+    def setup_outage_log(self, path):
+        box = QGroupBox("Connection Outages")
+        box.setToolTip("Every time the link dropped this session: when, for how long, which address failed "
+                       f"and which came back, and why. Also written to {path}")
+        layout = QVBoxLayout(box)
+        self.outage_summary_label = QLabel()
+        self.outage_list = QPlainTextEdit()
+        self.outage_list.setReadOnly(True)
+        self.outage_list.setMaximumHeight(120)
+        layout.addWidget(self.outage_summary_label)
+        layout.addWidget(self.outage_list)
+        self.add_tab_widget("Network", box)
+        self.shown_outages = None
+        self.show_outages([], {"uptime_pct": None})
+
+    def show_outages(self, outages, summary):
+        uptime_pct = summary.get("uptime_pct")
+        if uptime_pct is None:
+            self.outage_summary_label.setText("Uptime: -- (not connected yet)")
+        else:
+            self.outage_summary_label.setText(
+                f"Uptime: <b>{uptime_pct:.1f}%</b> of {format_age(summary['session_s'])}, "
+                f"{summary['outages']} outage(s), {format_age(summary['down_s'])} down"
+            )
+        if outages == self.shown_outages:
+            return
+        self.shown_outages = outages
+        lines = []
+        for outage in outages:
+            started = time.strftime("%H:%M:%S", time.localtime(outage["start"]))
+            if "duration_s" in outage:
+                back = outage["reconnected_address"] or "never reconnected"
+                lines.append(f"{started} {format_age(outage['duration_s'])} down, "
+                             f"{outage['failed_address']} -> {back}: {outage['reason']}")
+            else:
+                lines.append(f"{started} down now, {outage['failed_address']}: {outage['reason']}")
+        self.outage_list.setPlainText("\n".join(lines) if lines else "No outages")
+
+    def setup_telemetry_loss_label(self):
+        self.telemetry_loss_label = QLabel()
+        self.telemetry_loss_label.setToolTip("Telemetry frames missing from the robot's numbering: loss on the "
+                                             "robot-to-driver direction only, separate from command timeouts")
+        self.add_tab_widget("Network", self.telemetry_loss_label)
+        self.show_telemetry_loss(None)
+        self.bandwidth_label = QLabel()
+        self.bandwidth_label.setToolTip("Bytes per second to and from the robot over the last few seconds, "
+                                        "counting commands, replies, drive frames and telemetry")
+        self.add_tab_widget("Network", self.bandwidth_label)
+        self.show_bandwidth(None)
+
+    def show_telemetry_loss(self, loss):
+        if not loss or not loss["received"]:
+            self.telemetry_loss_label.setText("Telemetry loss: --")
+            return
+        recent = "--" if loss["recent_pct"] is None else f"{loss['recent_pct']:.1f}%"
+        text = (f"Telemetry loss: <b>{recent}</b> recent, {loss['loss_pct']:.1f}% this link "
+                f"({loss['lost']} of {loss['received'] + loss['lost']} frames)")
+        if loss["late"]:
+            text += f", {loss['late']} out of order"
+        self.telemetry_loss_label.setText(text)
+
+    def show_bandwidth(self, bandwidth):
+        if not bandwidth or not (bandwidth["sent_bytes"] or bandwidth["received_bytes"]):
+            self.bandwidth_label.setText("Bandwidth: --")
+            return
+        self.bandwidth_label.setText(f"Bandwidth: <b>{bandwidth['sent_bytes_per_s'] / 1024:.1f}</b> KiB/s up, "
+                                     f"<b>{bandwidth['received_bytes_per_s'] / 1024:.1f}</b> KiB/s down")
+
+    def setup_stick_center_controls(self):
+        button = QPushButton("Re-learn Stick Center (hands off)")
+        button.clicked.connect(self.relearn_stick_center)
+        self.add_tab_widget("Settings", button)
+
+    def setup_pause_control(self):
+        self.btn_pause = QPushButton("Pause")
+        self.btn_pause.setCheckable(True)
+        self.btn_pause.setToolTip("Hold motors at zero without disconnecting")
+        self.btn_pause.clicked.connect(self.set_paused)
+        self.btn_rst.parentWidget().layout().addWidget(self.btn_pause, 3, 4)
+
+    def show_paused(self, paused):
+        self.btn_pause.setChecked(paused)
+        self.btn_pause.setText("Resume" if paused else "Pause")
+        self.btn_pause.setStyleSheet("background: orange;" if paused else "")
+
+    def setup_park_control(self):
+        self.btn_park = QPushButton("Park Robot (End Session)")
+        self.btn_park.setToolTip("Ramp the motors to zero and ignore motion until woken; stays connected")
+        self.btn_park.clicked.connect(self._toggle_park)
+        self.add_tab_widget("Settings", self.btn_park)
+        self.show_park("ACTIVE")
+
+    def show_park(self, state):
+        self.shown_park_state = state
+        self.btn_park.setText("Wake Robot" if state == "PARKED" else "Park Robot (End Session)")
+        self.btn_park.setStyleSheet("background: orange;" if state != "ACTIVE" else "")
+        if state != "ACTIVE":
+            self.robot_status.setText("Parking..." if state == "PARKING" else "Parked")
+
+    def _toggle_park(self):
+        if self.shown_park_state == "PARKED":
+            self.set_parked(False)
+            return
+        answer = QMessageBox.question(self, "Park Robot",
+                                      "Stop the robot and ignore driving until it is woken?")
+        if answer == QMessageBox.StandardButton.Yes:
+            self.set_parked(True)
+
+    def setup_selftest_controls(self):
+        box = QGroupBox("Self-Test")
+        layout = QVBoxLayout(box)
+        self.btn_selftest = QPushButton("Run Self-Test")
+        self.btn_selftest.setToolTip("Robot must be stopped; each motor is pulsed briefly")
+        self.btn_selftest.clicked.connect(self.run_selftest)
+        self.selftest_label = QLabel("Not run")
+        self.selftest_label.setTextFormat(Qt.TextFormat.RichText)
+        layout.addWidget(self.btn_selftest)
+        layout.addWidget(self.selftest_label)
+        self.add_tab_widget("Diagnostics", box)
+
+    def setup_dump_controls(self):
+        box = QGroupBox("Telemetry Dump")
+        layout = QVBoxLayout(box)
+        buttons = QWidget()
+        buttons_layout = QHBoxLayout(buttons)
+        buttons_layout.setContentsMargins(0, 0, 0, 0)
+        self.btn_dump_recent = QPushButton("Dump Recent")
+        self.btn_dump_recent.setToolTip("Download the robot's last few seconds of high-rate state")
+        self.btn_dump_recent.clicked.connect(lambda: self.dump_telemetry("recent"))
+        self.btn_dump_failsafe = QPushButton("Dump Last Failsafe")
+        self.btn_dump_failsafe.setToolTip("Download the state captured leading up to the last connection-loss stop")
+        self.btn_dump_failsafe.clicked.connect(lambda: self.dump_telemetry("failsafe"))
+        buttons_layout.addWidget(self.btn_dump_recent)
+        buttons_layout.addWidget(self.btn_dump_failsafe)
+        self.dump_label = QLabel("No dump loaded")
+        self.telemetry_plot_widget = TelemetryPlotWidget()
+        layout.addWidget(buttons)
+        layout.addWidget(self.dump_label)
+        layout.addWidget(self.telemetry_plot_widget)
+        self.add_tab_widget("Diagnostics", box)
+
+    def setup_telemetry_csv_controls(self):
+        box = QGroupBox("Telemetry CSV Log")
+        layout = QVBoxLayout(box)
+        self.btn_telemetry_csv = QPushButton("Start CSV Log")
+        self.btn_telemetry_csv.setToolTip("Write every telemetry update to a CSV file for spreadsheets/plotting")
+        self.btn_telemetry_csv.clicked.connect(self.toggle_telemetry_csv)
+        self.telemetry_csv_label = QLabel("Not logging")
+        self.telemetry_csv_label.setWordWrap(True)
+        layout.addWidget(self.btn_telemetry_csv)
+        layout.addWidget(self.telemetry_csv_label)
+        self.add_tab_widget("Diagnostics", box)
+
+    def show_telemetry_csv(self, path, finished):
+        """``path`` while logging; ``finished`` is the writer that just stopped, if any."""
+        self.btn_telemetry_csv.setText("Stop CSV Log" if path else "Start CSV Log")
+        if path:
+            self.telemetry_csv_label.setText(f"Logging to {path}")
+        elif finished is not None:
+            self.telemetry_csv_label.setText(f"Saved {finished.rows} rows to {finished.path}"
+                                             + (f" (+{finished.parts - 1} more parts)" if finished.parts > 1 else "")
+                                             + (f", {finished.dropped} dropped" if finished.dropped else ""))
+        else:
+            self.telemetry_csv_label.setText("Not logging")
+
+    def setup_wire_log_controls(self):
+        box = QGroupBox("Raw Link Log")
+        layout = QVBoxLayout(box)
+        self.btn_wire_log = QPushButton("Start Raw Log")
+        self.btn_wire_log.setToolTip("Hex-dump every frame sent to and received from the robot, with direction "
+                                     "and time, for debugging the protocol. Files grow quickly")
+        self.btn_wire_log.clicked.connect(self.toggle_wire_log)
+        self.wire_log_label = QLabel("Not logging")
+        self.wire_log_label.setWordWrap(True)
+        layout.addWidget(self.btn_wire_log)
+        layout.addWidget(self.wire_log_label)
+        self.add_tab_widget("Diagnostics", box)
+
+    def show_wire_log(self, path, finished):
+        """``path`` while logging; ``finished`` is the writer that just stopped, if any."""
+        self.btn_wire_log.setText("Stop Raw Log" if path else "Start Raw Log")
+        if path:
+            self.wire_log_label.setText(f"Logging to {path}")
+        elif finished is not None:
+            self.wire_log_label.setText(f"Saved {finished.frames} frames ({finished.bytes} bytes) to {finished.path}")
+        else:
+            self.wire_log_label.setText("Not logging")
+
+    def show_selftest(self, selftest):
+        state = selftest.get("state", "idle")
+        colors = {"passed": "green", "failed": "red", "aborted": "orange"}
+        lines = [f"<b style='color: {colors.get(state, 'gray')};'>{state.title()}</b>"]
+        for result in selftest.get("results", []):
+            mark = "PASS" if result.get("passed") else "FAIL"
+            lines.append(f"{mark} {result.get('name')}: {result.get('detail', '')}")
+        self.selftest_label.setText("<br>".join(lines))
+        self.btn_selftest.setEnabled(state != "running")
+
+    def setup_timing_label(self):
+        self.timing_label = QLabel("Robot timing: --")
+        self.timing_label.setToolTip("Robot loop rate/jitter and command handling time; "
+                                     "slow loops with a low ping point at robot CPU, not the network")
+        self.add_tab_widget("Diagnostics", self.timing_label)
+
+    def show_timing(self, timing):
+        parts = [
+            f"{name} {loop.get('hz', 0.0):.1f} Hz ±{loop.get('jitter_ms', 0.0):.1f} ms"
+            for name, loop in timing.get("loops", {}).items()
+        ]
+        command = timing.get("command", {})
+        parts.append(f"commands {command.get('mean_ms', 0.0):.2f} ms avg, {command.get('peak_ms', 0.0):.2f} ms peak")
+        self.timing_label.setText("Robot timing: " + " | ".join(parts))
+
+    def setup_uptime_label(self):
+        self.uptime_label = QLabel("Robot uptime: --")
+        self.uptime_label.setToolTip("How long the robot process has run and how many driver connections it has "
+                                     "accepted; uptime going backwards means the robot restarted")
+        self.add_tab_widget("Diagnostics", self.uptime_label)
+
+    def show_uptime(self, uptime_s, connections, restarted=False):
+        if uptime_s is None:
+            self.uptime_label.setText("Robot uptime: --")
+            return
+        minutes, seconds = divmod(int(uptime_s), 60)
+        hours, minutes = divmod(minutes, 60)
+        text = f"Robot uptime: {hours}h {minutes:02d}m {seconds:02d}s, {connections} connection(s)"
+        if restarted:
+            text += " <b style='color: orange;'>(restarted)</b>"
+        self.uptime_label.setText(text)
+
+    def setup_command_console(self):
+        box = QGroupBox("Command Console")
+        layout = QVBoxLayout(box)
+        self.console_output = QPlainTextEdit()
+        self.console_output.setReadOnly(True)
+        self.console_output.setMaximumBlockCount(500)
+        self.console_input = QLineEdit()
+        self.console_input.setPlaceholderText('e.g.  telemetry_rate hz=5   or   {"type": "ping"}')
+        self.console_input.returnPressed.connect(self.send_console_command)
+        layout.addWidget(self.console_output, 1)
+        layout.addWidget(self.console_input)
+        self.add_tab_widget("Diagnostics", box)
+
+    def append_console(self, text):
+        self.console_output.appendPlainText(text)
+
+    def setup_pid_controls(self, axes):
+        box = QGroupBox("PID Gains")
+        self.pid_group = box
+        grid = QGridLayout(box)
+        for col, header in enumerate(("Kp", "Ki", "Kd"), start=1):
+            grid.addWidget(QLabel(header), 0, col)
+
+        self.pid_spinboxes = {}
+        for row, axis in enumerate(axes, start=1):
+            grid.addWidget(QLabel(axis.title()), row, 0)
+            spins = []
+            for col in range(3):
+                spin = QDoubleSpinBox()
+                spin.setRange(0.0, 100.0)
+                spin.setDecimals(3)
+                spin.setSingleStep(0.01)
+                grid.addWidget(spin, row, col + 1)
+                spins.append(spin)
+            apply_button = QPushButton("Apply")
+            apply_button.clicked.connect(lambda _checked=False, a=axis: self.apply_pid_gains(a))
+            grid.addWidget(apply_button, row, 4)
+            self.pid_spinboxes[axis] = spins
+
+        read_button = QPushButton("Read from Robot")
+        read_button.clicked.connect(self.refresh_pid_gains)
+        grid.addWidget(read_button, len(axes) + 1, 0, 1, 5)
+        self.add_tab_widget("Tuning", box)
+
+    def setup_motor_reverse_controls(self, motors):
+        box = QGroupBox("Motor Direction")
+        box.setToolTip("Reverse a motor that spins the wrong way because of its wiring; "
+                       "the robot must be stopped to change this")
+        self.motor_reverse_group = box
+        layout = QHBoxLayout(box)
+        self.motor_reverse_buttons = {}
+        for motor in motors:
+            button = QPushButton(motor)
+            button.setCheckable(True)
+            button.toggled.connect(lambda checked, m=motor: self.set_motor_reversed(m, checked))
+            layout.addWidget(button)
+            self.motor_reverse_buttons[motor] = button
+        self.add_tab_widget("Tuning", box)
+
+    def show_motor_reversed(self, reversed_motors):
+        for motor, button in self.motor_reverse_buttons.items():
+            reverse = bool(reversed_motors.get(motor, False))
+            button.blockSignals(True)
+            button.setChecked(reverse)
+            button.blockSignals(False)
+            button.setText(f"{motor} (reversed)" if reverse else motor)
+
+    def setup_brake_control(self):
+        self.btn_brake = QPushButton()
+        self.btn_brake.setCheckable(True)
+        self.btn_brake.setToolTip("Whether the motors brake or coast to a stop when the sticks are released. "
+                                  "Failsafe, reset and pause always brake")
+        self.btn_brake.toggled.connect(self.set_brake)
+        self.add_tab_widget("Tuning", self.btn_brake)
+        self.show_brake(True)
+
+    def show_brake(self, enabled):
+        self.btn_brake.blockSignals(True)
+        self.btn_brake.setChecked(enabled)
+        self.btn_brake.blockSignals(False)
+        self.btn_brake.setText("Stop: Brake" if enabled else "Stop: Coast")
+
+    def setup_mix_mode_control(self, modes):
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        row_layout.addWidget(QLabel("Saturation:"))
+        self.mix_mode_combo = QComboBox()
+        self.mix_mode_combo.addItems([m.title() for m in modes])
+        self.mix_mode_combo.setToolTip("What gives when the sticks ask for more than the motors can do. "
+                                       "Scale: everything slows together, keeping the direction. "
+                                       "Clip: each motor is capped, fastest but drifts off line. "
+                                       "Translation: full travel speed, turning gets what's left. "
+                                       "Forward: full forward speed, strafe and turning get what's left")
+        self.mix_mode_combo.activated.connect(lambda i: self.set_mix_mode(modes[i]))
+        row_layout.addWidget(self.mix_mode_combo, 1)
+        self.add_tab_widget("Tuning", row)
+
+    def show_mix_mode(self, mode):
+        index = self.mix_mode_combo.findText(str(mode).title())
+        if index >= 0 and index != self.mix_mode_combo.currentIndex():
+            self.mix_mode_combo.setCurrentIndex(index)
+
+    def setup_accel_controls(self):
+        box = QGroupBox("Acceleration Limits (0 = unlimited)")
+        self.accel_group = box
+        layout = QVBoxLayout(box)
+        self.accel_sliders = {}
+        self.accel_labels = {}
+        for key in ("up", "down"):
+            row = QWidget()
+            row_layout = QHBoxLayout(row)
+            row_layout.setContentsMargins(0, 0, 0, 0)
+            label = QLabel()
+            label.setMinimumWidth(110)
+            slider = QSlider(Qt.Orientation.Horizontal)
+            # Tenths of full scale per second.
+            slider.setRange(0, 100)
+            slider.valueChanged.connect(lambda _v: self.show_accel_limits(self.accel_limits_from_ui()))
+            row_layout.addWidget(label)
+            row_layout.addWidget(slider, 1)
+            layout.addWidget(row)
+            self.accel_sliders[key] = slider
+            self.accel_labels[key] = label
+
+        buttons = QWidget()
+        buttons_layout = QHBoxLayout(buttons)
+        buttons_layout.setContentsMargins(0, 0, 0, 0)
+        apply_button = QPushButton("Apply")
+        apply_button.clicked.connect(self.apply_accel_limits)
+        read_button = QPushButton("Read from Robot")
+        read_button.clicked.connect(self.refresh_accel_limits)
+        buttons_layout.addWidget(apply_button)
+        buttons_layout.addWidget(read_button)
+        layout.addWidget(buttons)
+        self.add_tab_widget("Tuning", box)
+        self.show_accel_limits({"up": 0.0, "down": 0.0})
+
+    def accel_limits_from_ui(self):
+        return {key: slider.value() / 10.0 for key, slider in self.accel_sliders.items()}
+
+    def show_accel_limits(self, accel):
+        for key, slider in self.accel_sliders.items():
+            value = float(accel.get(key, 0.0))
+            text = "unlimited" if value <= 0 else f"{value:.1f}/s"
+            self.accel_labels[key].setText(f"{'Speed up' if key == 'up' else 'Slow down'}: {text}")
+            if slider.value() != round(value * 10):
+                slider.blockSignals(True)
+                slider.setValue(round(value * 10))
+                slider.blockSignals(False)
+
+    def pid_gains_from_ui(self, axis):
+        return tuple(spin.value() for spin in self.pid_spinboxes[axis])
+
+    def show_pid_gains(self, pid):
+        for axis, gains in pid.items():
+            spins = self.pid_spinboxes.get(axis)
+            if spins is None:
+                continue
+            for spin, key in zip(spins, ("kp", "ki", "kd")):
+                spin.setValue(float(gains.get(key, 0.0)))
+
+    def set_label_freshness(self, label, state, age):
+        """Style a telemetry label as 'live', 'stale' or 'disconnected', with its age in the tooltip."""
+        shown_age = None if age is None else int(age)
+        if label.property("freshness") == (state, shown_age):
+            return
+        label.setProperty("freshness", (state, shown_age))
+        styles = {"live": "", "stale": "color: gray;", "disconnected": "color: gray; font-style: italic;"}
+        label.setStyleSheet(styles[state])
+        if state == "live":
+            label.setToolTip("")
+            return
+        since = "never received" if age is None else f"last update {format_age(age)} ago"
+        reason = "No recent telemetry" if state == "stale" else "Not from the current robot link"
+        label.setToolTip(f"{reason}; {since}")
+
+    def setup_speed_profile_controls(self, profiles, active_index):
+        self.speed_cap_label = QLabel()
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.speed_cap_label)
+
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        row_layout.addWidget(QLabel("Speed Cap:"))
+        self.speed_profile_combo = QComboBox()
+        self.speed_profile_combo.addItems([f"{cap:.0%}" for cap in profiles])
+        self.speed_profile_combo.setCurrentIndex(active_index)
+        self.speed_profile_combo.currentIndexChanged.connect(self.set_speed_profile)
+        row_layout.addWidget(self.speed_profile_combo, 1)
+        self.add_tab_widget("Settings", row)
+        self.show_speed_profile(profiles[active_index], active_index)
+
+    def setup_drive_mode_control(self, modes):
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        row_layout.addWidget(QLabel("Drive loop:"))
+        self.drive_mode_combo = QComboBox()
+        self.drive_mode_combo.addItems([m.title() for m in modes])
+        self.drive_mode_combo.setToolTip("Open: sticks drive the motors directly. "
+                                         "Closed: sticks set target velocities held by the PID gains")
+        self.drive_mode_combo.activated.connect(lambda i: self.set_drive_mode(modes[i]))
+        row_layout.addWidget(self.drive_mode_combo, 1)
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(row)
+
+    def setup_flip_control(self):
+        self.btn_flip = QPushButton()
+        self.btn_flip.setCheckable(True)
+        self.btn_flip.setToolTip("Swap the robot's front and back: forward and strafe reverse, rotation doesn't")
+        self.btn_flip.clicked.connect(self.set_drive_flipped)
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.btn_flip)
+        self.show_flip(False)
+
+    def setup_trim_controls(self, max_trim):
+        box = QGroupBox("Drive Trim")
+        box.setToolTip(f"Offsets added while driving to make the robot track straight, up to +/-{max_trim:.2f}. "
+                       "Hold the trim button (left bumper) and use the D-pad: left/right steering, up/down throttle")
+        layout = QHBoxLayout(box)
+        self.trim_label = QLabel()
+        reset_button = QPushButton("Reset")
+        reset_button.clicked.connect(self.reset_trim)
+        layout.addWidget(self.trim_label, 1)
+        layout.addWidget(reset_button)
+        self.add_tab_widget("Settings", box)
+
+    def show_trim(self, trim):
+        self.trim_label.setText(f"Steering {trim['steering']:+.2f}, throttle {trim['throttle']:+.2f}")
+        self.trim_label.setStyleSheet("" if not any(trim.values()) else "color: orange;")
+
+    def show_flip(self, flipped):
+        self.btn_flip.setChecked(flipped)
+        self.btn_flip.setText("Front: FLIPPED (back is front)" if flipped else "Front: Normal")
+        self.btn_flip.setStyleSheet("background: orange; font-weight: bold;" if flipped else "")
+
+    def show_drive_mode(self, mode):
+        index = self.drive_mode_combo.findText(str(mode).title())
+        if index >= 0 and index != self.drive_mode_combo.currentIndex():
+            self.drive_mode_combo.setCurrentIndex(index)
+
+    def setup_sequence_controls(self):
+        box = QGroupBox("Sequences")
+        self.sequence_group = box
+        layout = QVBoxLayout(box)
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.sequence_combo = QComboBox()
+        self.btn_run_sequence = QPushButton("Run")
+        self.btn_run_sequence.setToolTip("Runs in TELEOP with the sticks centered; moving a stick takes over")
+        self.btn_run_sequence.clicked.connect(lambda: self.run_sequence(self.sequence_combo.currentData()))
+        stop_button = QPushButton("Stop")
+        stop_button.clicked.connect(self.stop_sequence)
+        refresh_button = QPushButton("Refresh")
+        refresh_button.clicked.connect(self.refresh_sequences)
+        row_layout.addWidget(self.sequence_combo, 1)
+        row_layout.addWidget(self.btn_run_sequence)
+        row_layout.addWidget(stop_button)
+        row_layout.addWidget(refresh_button)
+        layout.addWidget(row)
+        self.sequence_label = QLabel("Idle")
+        layout.addWidget(self.sequence_label)
+        self.add_tab_widget("Odometry", box)
+
+    def show_sequences(self, durations):
+        self.sequence_combo.clear()
+        for name, duration_s in sorted(durations.items()):
+            self.sequence_combo.addItem(f"{name} ({float(duration_s):.1f} s)", name)
+
+    def show_sequence_state(self, sequence):
+        state = sequence.get("state", "idle")
+        if state == "running":
+            self.sequence_label.setText(
+                f"<b>Running {sequence.get('name')}</b>: step {int(sequence.get('step') or 0) + 1}/{sequence.get('steps')}"
+                f" ({float(sequence.get('elapsed_s', 0.0)):.1f} / {float(sequence.get('duration_s', 0.0)):.1f} s)"
+            )
+        elif state == "aborted":
+            self.sequence_label.setText(f"<b style='color: orange;'>{sequence.get('name')} aborted</b>: "
+                                        f"{sequence.get('reason', '')}")
+        elif state == "done":
+            self.sequence_label.setText(f"{sequence.get('name')} done")
+        else:
+            self.sequence_label.setText("Idle")
+
+    def setup_turn_controls(self):
+        box = QGroupBox("Turn")
+        self.turn_group = box
+        layout = QVBoxLayout(box)
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.turn_spin = QDoubleSpinBox()
+        self.turn_spin.setRange(-360.0, 360.0)
+        self.turn_spin.setDecimals(1)
+        self.turn_spin.setSingleStep(15.0)
+        self.turn_spin.setValue(90.0)
+        self.turn_spin.setSuffix(" deg")
+        self.btn_turn = QPushButton("Turn")
+        self.btn_turn.setToolTip("Rotates in place using odometry; positive turns the way the rotate stick does. "
+                                 "Moving a stick takes over")
+        self.btn_turn.clicked.connect(lambda: self.turn_robot(self.turn_spin.value()))
+        row_layout.addWidget(self.turn_spin, 1)
+        row_layout.addWidget(self.btn_turn)
+        layout.addWidget(row)
+        self.turn_label = QLabel("Idle")
+        layout.addWidget(self.turn_label)
+        self.add_tab_widget("Odometry", box)
+
+    def show_turn_state(self, turn):
+        state = turn.get("state", "idle")
+        target = float(turn.get("target_deg", 0.0))
+        if state == "running":
+            self.turn_label.setText(f"<b>Turning {target:.1f} deg</b>: {float(turn.get('turned_deg', 0.0)):.1f} so far")
+        elif state == "aborted":
+            self.turn_label.setText(f"<b style='color: orange;'>Turn {target:.1f} deg aborted</b>: {turn.get('reason', '')}")
+        elif state == "done":
+            self.turn_label.setText(f"Turned {float(turn.get('turned_deg', 0.0)):.1f} of {target:.1f} deg")
+        else:
+            self.turn_label.setText("Idle")
+
+    def setup_geofence_controls(self):
+        box = QGroupBox("Geofence")
+        self.geofence_group = box
+        layout = QVBoxLayout(box)
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.geofence_spin = QDoubleSpinBox()
+        self.geofence_spin.setRange(0.1, 10.0)
+        self.geofence_spin.setDecimals(2)
+        self.geofence_spin.setSingleStep(0.25)
+        self.geofence_spin.setValue(1.5)
+        self.geofence_spin.setSuffix(" m")
+        set_button = QPushButton("Set")
+        set_button.setToolTip("Stop driving further than this from the odometry origin; turning and "
+                              "driving back in still work")
+        set_button.clicked.connect(lambda: self.set_geofence(self.geofence_spin.value()))
+        off_button = QPushButton("Off")
+        off_button.clicked.connect(lambda: self.set_geofence(None))
+        row_layout.addWidget(self.geofence_spin, 1)
+        row_layout.addWidget(set_button)
+        row_layout.addWidget(off_button)
+        layout.addWidget(row)
+        self.geofence_label = QLabel("Off")
+        layout.addWidget(self.geofence_label)
+        self.add_tab_widget("Odometry", box)
+
+    def show_geofence(self, geofence):
+        radius = geofence.get("radius_m")
+        if radius is None:
+            self.geofence_label.setText("Off")
+        elif geofence.get("tripped"):
+            self.geofence_label.setText(f"<b style='color: orange;'>At the {float(radius):.2f} m boundary</b>: "
+                                        f"outward motion stopped")
+        else:
+            self.geofence_label.setText(f"{float(radius):.2f} m from origin")
+
+    def setup_gimbal_controls(self, modes, mode):
+        box = QGroupBox("Right Stick / Gimbal")
+        self.gimbal_group = box
+        layout = QVBoxLayout(box)
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        row_layout.addWidget(QLabel("Right stick:"))
+        self.right_stick_combo = QComboBox()
+        self.right_stick_combo.addItems([m.capitalize() for m in modes])
+        self.right_stick_combo.setCurrentIndex(modes.index(mode))
+        self.right_stick_combo.currentIndexChanged.connect(lambda i: self.set_right_stick_mode(modes[i]))
+        row_layout.addWidget(self.right_stick_combo, 1)
+        layout.addWidget(row)
+
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.gimbal_label = QLabel()
+        self.btn_gimbal_center = QPushButton("Center")
+        self.btn_gimbal_center.clicked.connect(self.center_gimbal)
+        row_layout.addWidget(self.gimbal_label, 1)
+        row_layout.addWidget(self.btn_gimbal_center)
+        layout.addWidget(row)
+        self.add_tab_widget("Settings", box)
+        self.show_gimbal({})
+
+    def show_gimbal(self, angles):
+        text = "  ".join(f"{name.capitalize()}: {float(angle):+.0f}\N{DEGREE SIGN}" for name, angle in angles.items())
+        self.gimbal_label.setText(text or "Gimbal: --")
+
+    def setup_link_pulse(self, flatline_s):
+        row = QWidget()
+        row_layout = QHBoxLayout(row)
+        row_layout.setContentsMargins(0, 0, 0, 0)
+        self.link_pulse = LinkPulseWidget(flatline_s)
+        self.link_pulse.setToolTip("Flashes while telemetry/pings arrive; stays grey when data stops, "
+                                   "even if the link has not timed out yet")
+        row_layout.addWidget(self.link_pulse)
+        row_layout.addWidget(QLabel("Link activity"), 1)
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(row)
+
+    def setup_telemetry_readout(self, formats):
+        box = QGroupBox("Telemetry")
+        grid = QGridLayout(box)
+        self.telemetry_readout_labels = {}
+        for row, (path, fmt) in enumerate(formats.items()):
+            name = QLabel(f"{fmt.label}:")
+            name.setToolTip(path)
+            value = QLabel("--")
+            grid.addWidget(name, row, 0)
+            grid.addWidget(value, row, 1)
+            self.telemetry_readout_labels[path] = value
+        self.btn_poll_telemetry = QPushButton("Poll Now")
+        self.btn_poll_telemetry.setToolTip("Ask the robot for one current telemetry frame instead of waiting "
+                                           "for the stream")
+        self.btn_poll_telemetry.clicked.connect(self.poll_telemetry)
+        grid.addWidget(self.btn_poll_telemetry, len(formats), 0, 1, 2)
+        self.add_tab_widget("Diagnostics", box)
+
+    def show_telemetry_readout(self, texts):
+        for path, text in texts.items():
+            label = self.telemetry_readout_labels.get(path)
+            if label is not None:
+                label.setText(text)
+
+    def setup_link_quality_label(self):
+        self.link_quality_label = QLabel()
+        self.link_quality_label.setToolTip("On a poor link, telemetry slows, stick deadzone and send "
+                                           "interval grow and autofire stops until quality recovers")
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.link_quality_label)
+        self.show_link_quality(None, False)
+
+    def setup_telemetry_freshness_label(self):
+        self.telemetry_freshness_label = QLabel()
+        self.telemetry_freshness_label.setToolTip("Whether the numbers shown are live; grey values are stale, "
+                                                  "grey italic ones are left over from a dropped link")
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.telemetry_freshness_label)
+        self.show_telemetry_freshness("disconnected", None)
+
+    def show_telemetry_freshness(self, state, age):
+        colors = {"live": "green", "stale": "orange", "disconnected": "red"}
+        text = f"Telemetry: <b style='color: {colors[state]};'>{state}</b>"
+        if state != "live" and age is not None:
+            text += f" (last update {format_age(age)} ago)"
+        self.telemetry_freshness_label.setText(text)
+
+    def show_link_quality(self, quality, degraded, loss_pct=None):
+        colors = {"good": "green", "fair": "orange", "poor": "red"}
+        if quality is None:
+            text = "Link: --"
+        else:
+            text = f"Link: <b style='color: {colors.get(quality, 'gray')};'>{quality}</b>"
+            if loss_pct:
+                text += f" ({loss_pct:.0f}% telemetry loss)"
+        if degraded:
+            text += " <b style='color: white; background-color: rgb(200, 90, 20);'>&nbsp;DEGRADED&nbsp;</b>"
+        self.link_quality_label.setText(text)
+
+    def setup_sensor_lights(self):
+        self.sensor_group = QGroupBox("Digital Inputs")
+        self.sensor_group.setToolTip("Robot bump, line and limit switches; lit while active")
+        self.sensor_layout = QHBoxLayout(self.sensor_group)
+        self.sensor_lights = {}
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.sensor_group)
+        self.show_sensor_inputs({})
+
+    def show_sensor_inputs(self, states):
+        """One light per input, in the robot's order; rebuilt when the robot's inputs change."""
+        if list(states) != list(self.sensor_lights):
+            for light in self.sensor_lights.values():
+                self.sensor_layout.removeWidget(light)
+                light.deleteLater()
+            self.sensor_lights = {}
+            for name in states:
+                light = QLabel(name)
+                light.setAlignment(Qt.AlignmentFlag.AlignCenter)
+                self.sensor_layout.addWidget(light)
+                self.sensor_lights[name] = light
+            self.sensor_group.setVisible(bool(states))
+        for name, active in states.items():
+            color = "rgb(40, 170, 60)" if active else "rgb(80, 80, 80)"
+            self.sensor_lights[name].setStyleSheet(
+                f"color: white; background-color: {color}; border-radius: 6px; padding: 2px 8px;"
+            )
+
+    def setup_orientation_display(self, warn_deg):
+        self.orientation_group = QGroupBox("Orientation")
+        self.orientation_group.setToolTip(f"Robot IMU: the needle is the heading, the dot leans with roll and pitch "
+                                          f"(dashed ring = {warn_deg:.0f} deg, past which tilt is flagged)")
+        layout = QHBoxLayout(self.orientation_group)
+        self.tilt_warn_deg = warn_deg
+        self.orientation_widget = OrientationWidget(2.0 * warn_deg)
+        self.orientation_label = QLabel()
+        layout.addWidget(self.orientation_widget)
+        layout.addWidget(self.orientation_label, 1)
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.orientation_group)
+        self.show_orientation(None, False)
+
+    def show_orientation(self, orientation, tripped):
+        """``tripped`` is the robot's own tilt lockout, which holds even if the IMU drops out."""
+        self.orientation_group.setVisible(orientation is not None or tripped)
+        tilt = 0.0 if orientation is None else max(abs(orientation['roll']), abs(orientation['pitch']))
+        self.orientation_widget.set_orientation(orientation, tripped or tilt > self.tilt_warn_deg)
+        if orientation is None:
+            text = "No IMU reading"
+        else:
+            text = (f"Heading {orientation['yaw']:.0f} deg<br>"
+                    f"Roll {orientation['roll']:+.1f} deg, pitch {orientation['pitch']:+.1f} deg")
+        if tripped:
+            text += "<br><b style='color: red;'>TILTED: robot stopped motion</b>"
+        elif tilt > self.tilt_warn_deg:
+            text += f"<br><b style='color: orange;'>Tilted {tilt:.0f} deg</b>"
+        self.orientation_label.setText(text)
+
+    def setup_dashboard(self, kinds):
+        self.dashboard_group = QGroupBox("Dashboard")
+        self.dashboard_group.setToolTip("Telemetry fields picked under Settings > Dashboard widgets")
+        self.dashboard_grid = QGridLayout(self.dashboard_group)
+        self.dashboard_tiles = []
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.dashboard_group)
+
+        box = QGroupBox("Dashboard widgets")
+        layout = QGridLayout(box)
+        self.dashboard_field_combo = QComboBox()
+        self.dashboard_field_combo.setEditable(True)
+        self.dashboard_field_combo.setToolTip("Any telemetry field, dotted for nested values such as pose.x or "
+                                              "motor_speeds.0; fields the robot has sent are listed")
+        self.dashboard_kind_combo = QComboBox()
+        self.dashboard_kind_combo.addItems(kinds)
+        self.dashboard_min_spin = QDoubleSpinBox()
+        self.dashboard_max_spin = QDoubleSpinBox()
+        for spin, value in ((self.dashboard_min_spin, 0.0), (self.dashboard_max_spin, 1.0)):
+            spin.setRange(-100000.0, 100000.0)
+            spin.setDecimals(2)
+            spin.setValue(value)
+            spin.setToolTip("Range a gauge or bar fills across")
+        add_button = QPushButton("Add")
+        add_button.clicked.connect(lambda: self.add_dashboard_widget(
+            self.dashboard_field_combo.currentText(), self.dashboard_kind_combo.currentText(),
+            self.dashboard_min_spin.value(), self.dashboard_max_spin.value(),
+        ))
+        self.dashboard_binding_combo = QComboBox()
+        remove_button = QPushButton("Remove")
+        remove_button.clicked.connect(
+            lambda: self.remove_dashboard_widget(self.dashboard_binding_combo.currentIndex())
+        )
+        layout.addWidget(QLabel("Field"), 0, 0)
+        layout.addWidget(self.dashboard_field_combo, 0, 1, 1, 3)
+        layout.addWidget(QLabel("Widget"), 1, 0)
+        layout.addWidget(self.dashboard_kind_combo, 1, 1)
+        layout.addWidget(self.dashboard_min_spin, 1, 2)
+        layout.addWidget(self.dashboard_max_spin, 1, 3)
+        layout.addWidget(add_button, 2, 0, 1, 4)
+        layout.addWidget(self.dashboard_binding_combo, 3, 0, 1, 3)
+        layout.addWidget(remove_button, 3, 3)
+        self.add_tab_widget("Settings", box)
+
+    def show_dashboard_layout(self, bindings, labels, columns):
+        """Rebuild the tiles after the dashboard configuration changes."""
+        for tile in self.dashboard_tiles:
+            self.dashboard_grid.removeWidget(tile)
+            tile.deleteLater()
+        self.dashboard_tiles = []
+        self.dashboard_binding_combo.clear()
+        for index, binding in enumerate(bindings):
+            tile = DashboardTileWidget(labels[index], binding.kind)
+            self.dashboard_grid.addWidget(tile, index // columns, index % columns)
+            self.dashboard_tiles.append(tile)
+            self.dashboard_binding_combo.addItem(f"{labels[index]} ({binding.kind})")
+        self.dashboard_group.setVisible(bool(bindings))
+
+    def show_dashboard(self, readings):
+        """readings: (text, fraction, freshness) per tile, in dashboard order."""
+        for tile, (text, fraction, freshness) in zip(self.dashboard_tiles, readings):
+            tile.set_reading(text, fraction, freshness)
+
+    def show_dashboard_fields(self, paths):
+        text = self.dashboard_field_combo.currentText()
+        self.dashboard_field_combo.clear()
+        self.dashboard_field_combo.addItems(paths)
+        self.dashboard_field_combo.setEditText(text)
+
+    def setup_robot_name_controls(self):
+        self.robot_name_label = QLabel()
+        font = self.robot_name_label.font()
+        font.setPointSize(font.pointSize() + 4)
+        font.setBold(True)
+        self.robot_name_label.setFont(font)
+        if hasattr(self, "verticalLayout"):
+            index = self.verticalLayout.indexOf(self.address_label) if hasattr(self, "address_label") else -1
+            self.verticalLayout.insertWidget(index, self.robot_name_label)
+
+        self.btn_rename_robot = QPushButton("Rename Robot...")
+        self.btn_rename_robot.clicked.connect(self._prompt_robot_name)
+        self.add_tab_widget("Settings", self.btn_rename_robot)
+        self.show_robot_name(None)
+
+    def show_robot_name(self, name):
+        self.shown_robot_name = name
+        self.robot_name_label.setText(f"Robot: {name}" if name else "Robot: --")
+        self.setWindowTitle(f"Driver Station - {name}" if name else "Driver Station")
+
+    def _prompt_robot_name(self):
+        name, ok = QInputDialog.getText(self, "Rename Robot", "Robot name:", text=self.shown_robot_name or "")
+        if ok and name.strip():
+            self.rename_robot(name.strip())
+
+    def setup_battery_label(self):
+        self.battery_label = QLabel("Battery: -- V")
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.battery_label)
+
+    def setup_arming_label(self):
+        self.arming_label = QLabel()
+        font = self.arming_label.font()
+        font.setPointSize(font.pointSize() + 8)
+        font.setBold(True)
+        self.arming_label.setFont(font)
+        self.arming_label.setAlignment(Qt.AlignmentFlag.AlignCenter)
+        self.arming_label.setStyleSheet("background: #c62828; color: white; padding: 6px;")
+        self.arming_label.hide()
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.insertWidget(0, self.arming_label)
+
+    def show_arming(self, remaining_s):
+        if remaining_s > 0:
+            self.arming_label.setText(f"ARMING IN {remaining_s:.1f} s - STAND CLEAR")
+            self.arming_label.show()
+        else:
+            self.arming_label.hide()
+
+    def setup_motion_lockout_banner(self):
+        self.motion_lockout_banner = QWidget()
+        self.motion_lockout_banner.setStyleSheet("background: #c62828; color: white;")
+        layout = QHBoxLayout(self.motion_lockout_banner)
+        self.motion_lockout_label = QLabel()
+        font = self.motion_lockout_label.font()
+        font.setBold(True)
+        self.motion_lockout_label.setFont(font)
+        self.motion_lockout_label.setWordWrap(True)
+        ack_button = QPushButton("Acknowledge")
+        ack_button.setStyleSheet("background: white; color: #c62828; padding: 4px 12px;")
+        ack_button.clicked.connect(self.acknowledge_motion_lockout)
+        layout.addWidget(self.motion_lockout_label, 1)
+        layout.addWidget(ack_button)
+        self.motion_lockout_banner.hide()
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.insertWidget(0, self.motion_lockout_banner)
+
+    def show_motion_lockout(self, reason):
+        if reason:
+            self.motion_lockout_label.setText(f"DRIVING DISABLED - {reason}")
+            self.motion_lockout_banner.show()
+        else:
+            self.motion_lockout_banner.hide()
+
+    def setup_loop_stall_banner(self):
+        self.loop_stall_label = QLabel()
+        self.loop_stall_label.setStyleSheet("background: #6a1b9a; color: white; font-weight: bold; padding: 6px;")
+        self.loop_stall_label.setToolTip("The robot still answers on the network, but its control loop has stopped "
+                                         "counting: it may not be acting on commands, including stops")
+        self.loop_stall_label.setWordWrap(True)
+        self.loop_stall_label.hide()
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.insertWidget(0, self.loop_stall_label)
+
+    def show_loop_stall(self, stalled_s):
+        if stalled_s is None:
+            self.loop_stall_label.hide()
+            return
+        self.loop_stall_label.setText(f"ROBOT CONTROL LOOP STALLED - no progress for {format_age(stalled_s)}; "
+                                      "the network is up but the robot isn't running its loop")
+        self.loop_stall_label.show()
+
+    def setup_robot_state_label(self):
+        self.robot_state_label = QLabel()
+        self.robot_state_label.setToolTip("Control state reported by the robot itself: mode, failsafe, park, "
+                                          "pause and arming")
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.robot_state_label)
+        self.show_robot_state(self.robot_state)
+
+    def show_robot_state(self, state):
+        flags = [
+            ("FAILSAFE", state.failsafe),
+            ("PAUSED", state.paused),
+            (state.park, state.park != "ACTIVE"),
+            ("ARMING", state.arming_s > 0),
+            ("CENTER STICKS", state.awaiting_center),
+        ]
+        # The summary names the first thing holding the robot; list anything else behind it.
+        also = [name for name, on in flags if on and name not in state.summary]
+        self.robot_state_label.setText(
+            f"Robot state: <b style='color: {state.color()};'>{state.summary}</b>"
+            + (f" (also {', '.join(also)})" if also else "")
+        )
+
+    def setup_alert_label(self):
+        self.alert_label = QLabel("Alerts: none")
+        self.alert_label.setWordWrap(True)
+        if hasattr(self, "verticalLayout"):
+            self.verticalLayout.addWidget(self.alert_label)
+
+    def show_alert(self, alert):
+        stamp = time.strftime("%H:%M:%S", time.localtime(alert.get("t", time.time())))
+        self.alert_label.setText(f"<b style='color: orange;'>Alert {stamp}</b>: {alert.get('message', '')}")
+
+    def show_battery(self, battery_text, derate):
+        text = f"Battery: {battery_text}"
+        if derate < 1.0:
+            text += f" <b style='color: orange;'>(derated to {derate:.0%})</b>"
+        self.battery_label.setText(text)
+
+    def show_speed_profile(self, cap, index):
+        self.speed_cap_label.setText(f"Speed Cap: <b>{cap:.0%}</b>")
+        if hasattr(self, "axis_curve_widget"):
+            self.axis_curve_widget.update()
+        if self.speed_profile_combo.currentIndex() != index:
+            self.speed_profile_combo.blockSignals(True)
+            self.speed_profile_combo.setCurrentIndex(index)
+            self.speed_profile_combo.blockSignals(False)
 
     def setup_field_view(self):
         self.field_widget = FieldWidget(self)
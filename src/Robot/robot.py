@@ -1,13 +1,32 @@
 import os
+import hmac
+import json
 import math
+import queue
+import re
+import hashlib
+import ipaddress
 import logging
+import logging.handlers
+import secrets
+import socket
+import sys
 import threading
 import time
+from bisect import bisect_right
+from collections import deque
 from dataclasses import dataclass
-from typing import Any, Dict, List
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional, Tuple
 
 import zmq
 
+LIB_DIR = Path(__file__).resolve().parents[2] / "lib"
+if str(LIB_DIR) not in sys.path:
+    sys.path.insert(0, str(LIB_DIR))
+
+from protocol import BUTTON_NAMES, apply_tcp_keepalive, clean_strings, clean_text, tcp_endpoint
+
 try:
     from hardware import PwmMotor
 except Exception:
@@ -22,12 +41,40 @@ logger = logging.getLogger(__name__)
 
 # Constants
 COMMAND_PORT = 5555
+# Sent in ping replies so a driver can tell a robot from some other service on the port.
+SERVICE_NAME = "ksu-storm"
 TELEMETRY_PORT = 5556
+# Fire-and-forget channel for high-rate drive frames that don't need a reply.
+DRIVE_PORT = 5557
+# Comma-separated local IPs and/or interface names (e.g. "eth0") to listen on.
+# "*" listens everywhere, and with IPv6 enabled accepts both IPv4 and IPv6 drivers.
+BIND_ADDRESSES = [a.strip() for a in os.environ.get("KSU_BIND_ADDRESS", "*").split(",") if a.strip()] or ["*"]
+ENABLE_IPV6 = os.environ.get("KSU_IPV6", "1").strip().lower() not in ("0", "false", "no")
+ENABLE_UNACKED_DRIVE = os.environ.get("KSU_UNACKED_DRIVE", "1").strip().lower() not in ("0", "false", "no")
+UNACKED_COMMANDS = {"joystick"}
+# A joystick frame may carry the driver's whole button state as "buttons", an int with bit i
+# set while button i is held. Edges are derived by diffing it against the previous mask, so a
+# lost frame can't leave a button stuck down. Bits follow pygame's SDL Xbox layout; virtual
+# trigger buttons (the driver's KSU_TRIGGER_BUTTONS) take ids above these.
+BUTTON_MASK_BITS = 32
 TELEMETRY_RATE_HZ = 10
+# Bounds for the driver-requested telemetry rate (shared by all subscribers).
+MIN_TELEMETRY_RATE_HZ = 1.0
+MAX_TELEMETRY_RATE_HZ = 50.0
 # Must be greater than driver ping interval (comm.py PING_INTERVAL_S=1s),
 # otherwise idle teleop will flap between lost/restored each second.
 HEARTBEAT_TIMEOUT_S = 2.5
+# Range the 'failsafe' command may set the timeout to at runtime. The same rule applies:
+# the floor stays above the ping interval. Not persisted; a restart returns to the default.
+MIN_HEARTBEAT_TIMEOUT_S = 1.5
+MAX_HEARTBEAT_TIMEOUT_S = 10.0
 WATCHDOG_CHECK_INTERVAL_S = 0.1
+# Seconds the robot counts down before accepting motion after it is armed (STOPPED ->
+# TELEOP/AUTO), a driver connects to an armed robot, or a lost link comes back. Gives
+# people time to clear the area. 0 disables it, e.g. for tests and bench work.
+ARM_COUNTDOWN_S = float(os.environ.get("KSU_ARM_COUNTDOWN_S", "0"))
+# How often the command loop wakes to notice a fatal error in another thread.
+COMMAND_POLL_TIMEOUT_MS = 500
 MAX_LINEAR_SPEED_MPS = 1.2
 MAX_ANGULAR_SPEED_DPS = 180.0
 FIELD_WIDTH_M = 3.6
@@ -59,15 +106,184 @@ MOTOR_DIRECTION_MULTIPLIER = (
     float(os.environ.get("KSU_MOTOR_RL_SIGN", "1.0")),
     float(os.environ.get("KSU_MOTOR_RR_SIGN", "1.0")),
 )
+# Robot-side stick shaping, applied on top of whatever the driver station already did.
+# The 'shape' command changes both at runtime within the bounds below; the values persist.
 JOYSTICK_DEADBAND = 0.06
 INPUT_EXPO = 1.4
+MAX_INPUT_DEADBAND = 0.5
+MIN_INPUT_EXPO = 1.0
+MAX_INPUT_EXPO = 3.0
 # Most setups already map forward to positive LY in driver.py.
 # Override with KSU_JOYSTICK_Y_SIGN=1.0 if your controller is already forward-positive.
 JOYSTICK_Y_SIGN = float(os.environ.get("KSU_JOYSTICK_Y_SIGN", "-1.0"))
+# Low-pass cutoff applied to incoming stick axes before mixing. 0 bypasses the filter.
+JOYSTICK_FILTER_HZ = float(os.environ.get("KSU_JOYSTICK_FILTER_HZ", "0"))
+# Optional shared secret for challenge/response auth. Empty disables auth.
+AUTH_SECRET = os.environ.get("KSU_AUTH_SECRET", "")
+AUTH_NONCE_TTL_S = 10.0
+MAX_AUTH_NONCES = 16
+MAX_AUTH_SESSIONS = 8
+# Recent sequence numbers remembered per driver to drop re-sent duplicates.
+SEQUENCE_WINDOW = 256
+MAX_TRACKED_CLIENTS = 8
+# Per-client roles. A spotter (e.g. a camera operator beside the driver) may only send
+# these; everything else is the driver's. See RoleMiddleware.
+ROLE_DRIVER = "driver"
+ROLE_SPOTTER = "spotter"
+ROLES = (ROLE_DRIVER, ROLE_SPOTTER)
+SPOTTER_COMMANDS = {
+    "ping", "hello", "capabilities", "stats", "servo", "servo_center", "name_query", "pid_query",
+    "accel_query", "shape_query", "loop_rate_query", "failsafe_query", "current_limit_query",
+    "motor_reverse_query", "brake_query", "sensors_query", "telemetry_query", "sequence_list",
+    "config_query", "mix_mode_query",
+}
+# A driver heard from this recently still holds the role against other claims.
+DRIVER_ROLE_HOLD_S = HEARTBEAT_TIMEOUT_S
+
+# Log of every received command for post-run analysis. Empty path disables it.
+COMMAND_LOG_PATH = os.environ.get("KSU_COMMAND_LOG_PATH", "").strip()
+COMMAND_LOG_MAX_BYTES = int(os.environ.get("KSU_COMMAND_LOG_MAX_BYTES", "1000000"))
+COMMAND_LOG_BACKUPS = int(os.environ.get("KSU_COMMAND_LOG_BACKUPS", "5"))
+# Fields kept out of the command log.
+COMMAND_LOG_REDACTED = {"session", "hmac"}
+
+# Runtime-tunable settings (PID gains, ...) persisted across restarts.
+ROBOT_SETTINGS_FILE = os.environ.get("KSU_ROBOT_SETTINGS_FILE", "robot_settings.json").strip()
+PID_AXES = ("forward", "strafe", "rotate")
+# OPEN mixes the sticks straight onto the motors; CLOSED treats them as chassis velocity
+# targets that the PID controllers regulate against measured velocity.
+DRIVE_MODES = ("OPEN", "CLOSED")
+# How the mecanum mix is brought back within [-1, 1] when the sticks ask for more than the
+# motors can give; one of MIX_MODES. The 'mix_mode' command changes it and the choice persists.
+MIX_MODE = os.environ.get("KSU_MIX_MODE", "scale").strip().lower()
+DEFAULT_PID_GAINS = {"kp": 1.0, "ki": 0.0, "kd": 0.0}
+# Motor output ramp in full-scale units per second: "up" while speeding up, "down"
+# while slowing or reversing. 0 leaves that direction unlimited. Stops are never ramped.
+DEFAULT_ACCEL_LIMITS = {
+    "up": float(os.environ.get("KSU_ACCEL_UP", "0")),
+    "down": float(os.environ.get("KSU_ACCEL_DOWN", "0")),
+}
+MAX_ACCEL_PER_S = 50.0
+# What a motor does when told to stop. Braking drives it to zero at once, which the MDD10A
+# does by shorting the motor. Its outputs can't float, so coasting is emulated by easing
+# the output down at this rate (full scale per second), about as fast as the chassis
+# rolls to a stop on its own. Safety stops (failsafe, reset, pause) always brake.
+BRAKE_ON_STOP = os.environ.get("KSU_BRAKE_ON_STOP", "1").strip().lower() not in ("0", "false", "no")
+COAST_DECAY_PER_S = float(os.environ.get("KSU_COAST_DECAY_PER_S", "1.5"))
+# Ramping and current limiting re-run at this rate even when no new drive command arrives.
+# The 'loop_rate' command changes it at runtime within the bounds below; the value persists.
+MIN_OUTPUT_RATE_HZ = 10.0
+MAX_OUTPUT_RATE_HZ = 200.0
+OUTPUT_RATE_HZ = max(MIN_OUTPUT_RATE_HZ, min(MAX_OUTPUT_RATE_HZ, float(os.environ.get("KSU_OUTPUT_RATE_HZ", "50"))))
+MOTOR_NAMES = ("FL", "FR", "RL", "RR")
+# Per-motor current limit in amps, 0 disables. The MDD10A is rated 10 A continuous per channel.
+DEFAULT_CURRENT_LIMIT_A = float(os.environ.get("KSU_MOTOR_CURRENT_LIMIT_A", "10.0"))
+MAX_CURRENT_LIMIT_A = 30.0
+# While over its limit a motor's output cap shrinks in proportion to the overshoot, never
+# below MIN_SCALE; once back under, the cap grows back at RECOVERY_PER_S.
+CURRENT_LIMIT_MIN_SCALE = 0.1
+CURRENT_LIMIT_RECOVERY_PER_S = 0.5
+# Alerts kept for telemetry so a driver that connects late still sees recent ones.
+ALERT_HISTORY = 16
+# Auxiliary servos (pan/tilt gimbal): id -> (min, max) in degrees. Angles are clamped
+# into range, and 0 (center) must lie inside it.
+SERVO_LIMITS_DEG = {"pan": (-90.0, 90.0), "tilt": (-30.0, 60.0)}
+SERVO_NAMES = tuple(SERVO_LIMITS_DEG)
+SERVO_CENTER_DEG = 0.0
+# Timed drive sequences run by 'run_sequence': name -> steps of {forward, strafe, rotate,
+# duration_s}, outputs in [-1, 1] (omitted axes are 0). Stored under "sequences" in the
+# settings file; this example is used until that exists.
+DEFAULT_SEQUENCES = {
+    "demo": [
+        {"forward": 0.3, "duration_s": 1.0},
+        {"rotate": 0.3, "duration_s": 0.5},
+        {"duration_s": 0.2},
+    ],
+}
+SEQUENCE_NAME_PATTERN = re.compile(r"^[A-Za-z0-9_.-]{1,32}$")
+MAX_SEQUENCE_STEPS = 32
+MAX_SEQUENCE_STEP_S = 10.0
+# Relative turns run by 'turn' against the odometry heading. Positive degrees turn the
+# way positive rotate does (heading increasing). The rotate output is proportional to
+# the remaining angle, clamped to [MIN, MAX] so the turn neither stalls nor spins hard.
+TURN_MAX_DEG = 360.0
+TURN_TOLERANCE_DEG = 2.0
+TURN_KP = 0.02
+TURN_MIN_ROTATE = 0.1
+TURN_MAX_ROTATE = 0.5
+TURN_TIMEOUT_S = 10.0
+# 'shutdown' parks the robot for the end of a session: outputs ramp to zero over this
+# long (accel limits don't apply), then motion is ignored until 'wake'.
+PARK_RAMP_S = 1.0
+# Human-readable robot name shown by the driver; set with the 'name' command and persisted.
+DEFAULT_ROBOT_NAME = os.environ.get("KSU_ROBOT_NAME", "").strip() or socket.gethostname()
+ROBOT_NAME_MAX_LEN = 32
+# Optional circular geofence around the odometry origin (where 'reset_odometry' puts the
+# robot), set with 'geofence' and persisted. Translation is stopped when the pose this far
+# ahead at the commanded speed would be outside it and further out than now; rotating and
+# driving back in still work.
+GEOFENCE_LOOKAHEAD_S = 0.25
+GEOFENCE_ORIGIN = (FIELD_WIDTH_M / 2.0, FIELD_HEIGHT_M / 2.0)
+
+# Battery voltage -> motor output scale, as "volts:scale" points. Linear between
+# points, clamped to the end points outside them. Each drive axis can have its own curve
+# (KSU_BATTERY_DERATE_CURVE_FORWARD, _STRAFE, _ROTATE), e.g. to give up turn rate before
+# forward speed; an axis without one uses KSU_BATTERY_DERATE_CURVE.
+BATTERY_DERATE_CURVE_SPEC = os.environ.get("KSU_BATTERY_DERATE_CURVE", "11.4:1.0,10.8:0.6,10.5:0.4")
+BATTERY_DERATE_AXIS_SPECS = {axis: os.environ.get(f"KSU_BATTERY_DERATE_CURVE_{axis.upper()}", BATTERY_DERATE_CURVE_SPEC)
+                             for axis in PID_AXES}
+
+# Internal high-rate telemetry history for post-incident dumps, independent of the
+# streamed telemetry rate. The buffer at the moment of a failsafe is kept separately.
+SNAPSHOT_RATE_HZ = 50
+SNAPSHOT_WINDOW_S = 5.0
+
+# Smoothing for loop/command timing stats; peaks decay by this factor per sample.
+TIMING_EWMA_ALPHA = 0.1
+TIMING_PEAK_DECAY = 0.99
+
+# Self-test: motor pulses are short and slow so the robot barely creeps.
+SELFTEST_MOTOR_SPEED = 0.15
+SELFTEST_PULSE_S = 0.3
+SELFTEST_MIN_BATTERY_V = 11.0
+SELFTEST_TELEMETRY_MAX_AGE_S = 2.0
+
+# Digital inputs (bump, line and limit switches) as "name:bcm_pin,...", polled at
+# SENSOR_POLL_HZ. A subscribed driver gets a telemetry frame on every edge.
+DIGITAL_INPUTS_SPEC = os.environ.get("KSU_DIGITAL_INPUTS", "bump_front:5,bump_rear:6,line_left:13,line_right:19")
+SENSOR_POLL_HZ = 50
+# IMU orientation (roll, pitch, yaw in degrees) is read alongside the digital inputs and
+# streamed as telemetry "imu". Past TILT_LIMIT_DEG of roll or pitch the drive is stopped and
+# held off until the robot is back within TILT_LIMIT_DEG - TILT_HYSTERESIS_DEG and the
+# sticks are centered. 0 leaves the tilt reaction off.
+TILT_LIMIT_DEG = float(os.environ.get("KSU_TILT_LIMIT_DEG", "0"))
+TILT_HYSTERESIS_DEG = 5.0
+
+# Alerts and benchmark data are [channel, payload...] messages on the telemetry socket, so
+# they share one connection and the driver routes each by its first frame. Telemetry stays a
+# single JSON frame, which driver stations from before channels read as well.
+# Each published telemetry frame carries "telemetry_seq", counting up and wrapping at this,
+# so drivers can tell frames lost in transit from a robot that sent nothing.
+TELEMETRY_SEQ_MODULUS = 1 << 16
+CHANNEL_ALERT = b"alert"          # [channel, alert JSON], sent the moment the alert is raised
+# A client heard from this recently is taken to be subscribed to the telemetry socket still.
+CHANNEL_CLIENT_HOLD_S = 5.0
+
+# On-demand link benchmark: a burst of [BENCH_TOPIC, header, payload] frames on the
+# telemetry socket. Telemetry still goes out between chunks, and the burst stops as
+# soon as the robot is asked to drive.
+BENCH_MAX_BYTES = 8 * 1024 * 1024
+BENCH_CHUNK_BYTES = 8 * 1024
+BENCH_TOPIC = b"bench"
+# Messages the telemetry socket queues for each subscriber before ZMQ drops what follows,
+# set rather than left at ZMQ's default of 1000: room for a whole benchmark burst and the
+# telemetry sent during it, so a chunk that was queued is a chunk that goes out.
+TELEMETRY_SEND_HWM = 2 * (BENCH_MAX_BYTES // BENCH_CHUNK_BYTES)
 
 # Global state
 last_heartbeat = time.time()
 heartbeat_lock = threading.Lock()
+heartbeat_timeout_s = HEARTBEAT_TIMEOUT_S
 connection_lost = False
 robot_mode = "STOPPED"  # STOPPED, AUTO, TELEOP
 motor_controller = None
@@ -117,6 +333,191 @@ class MotorController:
         self.set_speeds(ZERO_MOTOR_SPEEDS)
 
 
+class LoopTimer:
+    """EWMA of a periodic loop's rate and jitter; one perf_counter call per tick."""
+    def __init__(self):
+        self.last_tick = None
+        self.period_s = 0.0
+        self.jitter_s = 0.0
+
+    def tick(self) -> None:
+        now = time.perf_counter()
+        if self.last_tick is not None:
+            period = now - self.last_tick
+            if self.period_s == 0.0:
+                self.period_s = period
+            self.jitter_s += TIMING_EWMA_ALPHA * (abs(period - self.period_s) - self.jitter_s)
+            self.period_s += TIMING_EWMA_ALPHA * (period - self.period_s)
+        self.last_tick = now
+
+    def snapshot(self) -> Dict[str, float]:
+        return {
+            'hz': round(1.0 / self.period_s, 2) if self.period_s else 0.0,
+            'jitter_ms': round(self.jitter_s * 1000.0, 3),
+        }
+
+
+class DurationStats:
+    """EWMA and slowly decaying peak of how long something takes."""
+    def __init__(self):
+        self.mean_s = 0.0
+        self.peak_s = 0.0
+
+    def record(self, seconds: float) -> None:
+        self.mean_s += TIMING_EWMA_ALPHA * (seconds - self.mean_s)
+        self.peak_s = max(seconds, self.peak_s * TIMING_PEAK_DECAY)
+
+    def snapshot(self) -> Dict[str, float]:
+        return {'mean_ms': round(self.mean_s * 1000.0, 3), 'peak_ms': round(self.peak_s * 1000.0, 3)}
+
+
+class CommandLogger:
+    """Appends received commands to a size-rotated file without blocking the caller.
+
+    Records go through a queue to a QueueListener thread that owns the file handler,
+    so disk latency never stalls command handling.
+    """
+    def __init__(self, path: str, max_bytes: int, backups: int):
+        directory = os.path.dirname(path)
+        if directory:
+            os.makedirs(directory, exist_ok=True)
+
+        handler = logging.handlers.RotatingFileHandler(path, maxBytes=max_bytes, backupCount=backups)
+        handler.setFormatter(logging.Formatter('%(created).3f %(message)s'))
+
+        self.queue: queue.Queue = queue.Queue()
+        self.listener = logging.handlers.QueueListener(self.queue, handler)
+        self.logger = logging.getLogger(f"{__name__}.commands")
+        self.logger.propagate = False
+        self.logger.setLevel(logging.INFO)
+        self.logger.addHandler(logging.handlers.QueueHandler(self.queue))
+        self.listener.start()
+        logger.info(f"Logging received commands to {path}")
+
+    def log(self, command: Dict[str, Any]) -> None:
+        record = {k: v for k, v in command.items() if k not in COMMAND_LOG_REDACTED}
+        self.logger.info(json.dumps(record, default=str))
+
+    def stop(self) -> None:
+        self.listener.stop()
+
+
+def parse_derate_curve(raw: str) -> List[Tuple[float, float]]:
+    """Parse "volts:scale,..." into points sorted by voltage. Malformed points are skipped."""
+    points = []
+    for part in str(raw).split(","):
+        try:
+            volts, scale = (float(v) for v in part.split(":"))
+        except ValueError:
+            continue
+        points.append((volts, max(0.0, min(1.0, scale))))
+    return sorted(points)
+
+
+def battery_derate(volts: float, curve: List[Tuple[float, float]]) -> float:
+    """Motor output scale for a battery voltage; 1.0 when no curve is configured."""
+    if not curve:
+        return 1.0
+    if volts <= curve[0][0]:
+        return curve[0][1]
+    for (v0, s0), (v1, s1) in zip(curve, curve[1:]):
+        if volts <= v1:
+            return s0 + (s1 - s0) * (volts - v0) / (v1 - v0)
+    return curve[-1][1]
+
+
+BATTERY_DERATE_CURVES = {axis: parse_derate_curve(spec) for axis, spec in BATTERY_DERATE_AXIS_SPECS.items()}
+
+
+def parse_digital_inputs(raw: str) -> Dict[str, int]:
+    """Parse "name:pin,..." into an ordered name -> BCM pin map. Malformed entries are skipped."""
+    inputs: Dict[str, int] = {}
+    for part in str(raw).split(","):
+        name, _, pin = part.partition(":")
+        name, pin = name.strip(), pin.strip()
+        if name and pin.isdigit():
+            inputs[name] = int(pin)
+    return inputs
+
+
+def sensor_bits(states: Dict[str, bool]) -> int:
+    """Pack input states into a bitmask, bit i being the i-th configured input."""
+    return sum(1 << i for i, name in enumerate(DIGITAL_INPUTS) if states.get(name))
+
+
+DIGITAL_INPUTS = parse_digital_inputs(DIGITAL_INPUTS_SPEC)
+
+
+def sanitize_robot_name(raw: Any) -> str:
+    """Printable characters only, whitespace collapsed, at most ROBOT_NAME_MAX_LEN long."""
+    if not isinstance(raw, str):
+        raise ValueError("Name must be a string")
+    name = " ".join("".join(c if c.isprintable() else " " for c in raw).split())[:ROBOT_NAME_MAX_LEN].rstrip()
+    if not name:
+        raise ValueError("Name must not be empty")
+    return name
+
+
+def load_robot_settings(path: str = ROBOT_SETTINGS_FILE) -> Dict[str, Any]:
+    """Load persisted runtime settings, or an empty dict if none are saved."""
+    if not path or not os.path.exists(path):
+        return {}
+    try:
+        with open(path, "r", encoding="utf-8") as f:
+            data = json.load(f)
+        return data if isinstance(data, dict) else {}
+    except Exception as e:
+        logger.error(f"Failed to load settings file {path}: {e}")
+        return {}
+
+
+def save_robot_settings(settings: Dict[str, Any], path: str = ROBOT_SETTINGS_FILE) -> None:
+    """Atomically write runtime settings so a crash mid-write can't corrupt them."""
+    if not path:
+        return
+    tmp_path = f"{path}.tmp"
+    try:
+        with open(tmp_path, "w", encoding="utf-8") as f:
+            json.dump(settings, f, indent=2)
+        os.replace(tmp_path, path)
+    except Exception as e:
+        logger.error(f"Failed to save settings file {path}: {e}")
+
+
+def valid_pid_gains(gains: Any) -> bool:
+    """Stored gains for one axis: any of kp, ki, kd, each finite and non-negative, as the pid command accepts."""
+    return (isinstance(gains, dict) and set(gains) <= set(DEFAULT_PID_GAINS)
+            and all(config_value_ok("number", g) and g >= 0.0 for g in gains.values()))
+
+
+class PidController:
+    """Textbook PID on a single axis. Gains are tunable at runtime."""
+    def __init__(self, kp: float = 0.0, ki: float = 0.0, kd: float = 0.0):
+        self.kp = kp
+        self.ki = ki
+        self.kd = kd
+        self.reset()
+
+    def set_gains(self, kp: float, ki: float, kd: float) -> None:
+        self.kp, self.ki, self.kd = kp, ki, kd
+        self.reset()
+
+    def gains(self) -> Dict[str, float]:
+        return {"kp": self.kp, "ki": self.ki, "kd": self.kd}
+
+    def reset(self) -> None:
+        self.integral = 0.0
+        self.prev_error = None
+
+    def update(self, error: float, dt: float) -> float:
+        if dt <= 0:
+            return self.kp * error
+        self.integral += error * dt
+        derivative = 0.0 if self.prev_error is None else (error - self.prev_error) / dt
+        self.prev_error = error
+        return (self.kp * error) + (self.ki * self.integral) + (self.kd * derivative)
+
+
 def ensure_motor_controller() -> MotorController:
     global motor_controller
     if motor_controller is None:
@@ -125,7 +526,11 @@ def ensure_motor_controller() -> MotorController:
 
 
 def _clamp_unit(value: float) -> float:
-    return max(-1.0, min(1.0, float(value)))
+    value = float(value)
+    # NaN/inf would otherwise clamp to full output.
+    if not math.isfinite(value):
+        return 0.0
+    return max(-1.0, min(1.0, value))
 
 
 @dataclass
@@ -144,6 +549,225 @@ class JoystickData:
         self.ry = _clamp_unit(self.ry)
 
 
+class OutputRamp:
+    """Slew-rate limit on the four motor outputs, stepped from a fixed-rate loop.
+
+    ``coast`` is the rate a motor told to stop falls off at when not braking; 0 brakes.
+    """
+    def __init__(self, up: float = 0.0, down: float = 0.0, coast: float = 0.0):
+        self.up = up
+        self.down = down
+        self.coast = coast
+        self.target = ZERO_MOTOR_SPEEDS.copy()
+        self.output = ZERO_MOTOR_SPEEDS.copy()
+
+    def enabled(self) -> bool:
+        return self.up > 0 or self.down > 0 or self.coast > 0
+
+    def limits(self) -> Dict[str, float]:
+        return {"up": self.up, "down": self.down}
+
+    def set_limits(self, up: float, down: float) -> None:
+        self.up = up
+        self.down = down
+
+    def reset(self) -> None:
+        self.target = ZERO_MOTOR_SPEEDS.copy()
+        self.output = ZERO_MOTOR_SPEEDS.copy()
+
+    def step(self, dt: float) -> List[float]:
+        for i, (current, target) in enumerate(zip(self.output, self.target)):
+            slowing = abs(target) < abs(current) or current * target < 0
+            rate = self.down if slowing else self.up
+            if target == 0.0 and self.coast > 0:
+                # Coasting is never quicker than the configured deceleration limit.
+                rate = min(rate, self.coast) if rate > 0 else self.coast
+            delta = target - current
+            if rate > 0:
+                delta = max(-rate * dt, min(rate * dt, delta))
+            self.output[i] = current + delta
+        return self.output.copy()
+
+
+def parse_sequence(steps: Any) -> List[Dict[str, float]]:
+    """Validate sequence steps into {forward, strafe, rotate, duration_s}; raises ValueError."""
+    if not isinstance(steps, list) or not 0 < len(steps) <= MAX_SEQUENCE_STEPS:
+        raise ValueError(f"A sequence needs 1 to {MAX_SEQUENCE_STEPS} steps")
+    parsed = []
+    for index, step in enumerate(steps):
+        if not isinstance(step, dict):
+            raise ValueError(f"Step {index} must be an object")
+        duration = float(step.get("duration_s", 0.0))
+        if not 0.0 < duration <= MAX_SEQUENCE_STEP_S:
+            raise ValueError(f"Step {index} duration must be in (0, {MAX_SEQUENCE_STEP_S}] s")
+        outputs = {axis: float(step.get(axis, 0.0)) for axis in ("forward", "strafe", "rotate")}
+        if not all(-1.0 <= v <= 1.0 for v in outputs.values()):
+            raise ValueError(f"Step {index} outputs must be in [-1, 1]")
+        parsed.append({**outputs, "duration_s": duration})
+    return parsed
+
+
+class SequenceRun:
+    """A stored sequence being played back against a monotonic start time."""
+    def __init__(self, name: str, steps: List[Dict[str, float]], started: float):
+        self.name = name
+        self.steps = steps
+        self.started = started
+        self.ends: List[float] = []
+        total = 0.0
+        for step in steps:
+            total += step["duration_s"]
+            self.ends.append(total)
+
+    def step_at(self, elapsed: float) -> Optional[int]:
+        """Index of the step active ``elapsed`` seconds in, or None once the sequence is over."""
+        index = bisect_right(self.ends, elapsed)
+        return index if index < len(self.steps) else None
+
+    def status(self, state: str, elapsed: float, step: Optional[int] = None, reason: str = "") -> Dict[str, Any]:
+        return {
+            'state': state,
+            'name': self.name,
+            'step': step,
+            'steps': len(self.steps),
+            'elapsed_s': round(elapsed, 2),
+            'duration_s': round(self.ends[-1], 2),
+            'reason': reason,
+        }
+
+
+class TurnRun:
+    """A relative turn in progress, tracking how far the odometry heading has moved."""
+    def __init__(self, target_deg: float, heading_deg: float, started: float):
+        self.target_deg = target_deg
+        self.last_heading_deg = heading_deg
+        self.turned_deg = 0.0
+        self.started = started
+
+    def track(self, heading_deg: float) -> float:
+        """Accumulate the heading change since the last call and return the angle still to turn."""
+        # Headings wrap at 360, so take the short way round between consecutive samples.
+        self.turned_deg += (heading_deg - self.last_heading_deg + 180.0) % 360.0 - 180.0
+        self.last_heading_deg = heading_deg
+        return self.target_deg - self.turned_deg
+
+    def status(self, state: str, reason: str = "") -> Dict[str, Any]:
+        return {
+            'state': state,
+            'target_deg': round(self.target_deg, 1),
+            'turned_deg': round(self.turned_deg, 1),
+            'reason': reason,
+        }
+
+
+def valid_current_limits(limits: Any) -> bool:
+    """One limit per motor, each a finite number of amps within what the current_limit command accepts."""
+    return (isinstance(limits, list) and len(limits) == len(MOTOR_NAMES)
+            and all(config_value_ok("number", a) and 0.0 <= a <= MAX_CURRENT_LIMIT_A for a in limits))
+
+
+class CurrentLimiter:
+    """Per-motor output cap that backs off while a motor draws more than its limit."""
+    def __init__(self, limits_a: List[float]):
+        self.limits_a = list(limits_a)
+        self.caps = [1.0] * len(self.limits_a)
+        self.last_update = None
+
+    def limited(self) -> List[bool]:
+        return [cap < 1.0 for cap in self.caps]
+
+    def reset(self) -> None:
+        self.caps = [1.0] * len(self.limits_a)
+        self.last_update = None
+
+    def apply(self, speeds: List[float], currents: List[Optional[float]]) -> Tuple[List[float], List[int]]:
+        """Clamp ``speeds`` to each motor's cap. Returns (clamped speeds, motors that just started limiting)."""
+        now = time.monotonic()
+        dt = 0.0 if self.last_update is None else min(0.2, now - self.last_update)
+        self.last_update = now
+
+        started = []
+        for i, (speed, current) in enumerate(zip(speeds, currents)):
+            limit = self.limits_a[i]
+            if limit > 0 and current is not None and current > limit:
+                if self.caps[i] >= 1.0:
+                    started.append(i)
+                cap = min(self.caps[i], abs(speed)) * limit / current
+                self.caps[i] = max(CURRENT_LIMIT_MIN_SCALE, cap)
+            else:
+                self.caps[i] = min(1.0, self.caps[i] + CURRENT_LIMIT_RECOVERY_PER_S * dt)
+        clamped = [max(-cap, min(cap, speed)) for speed, cap in zip(speeds, self.caps)]
+        return clamped, started
+
+
+class JoystickFilter:
+    """First-order low-pass on the stick axes of one driver connection."""
+    def __init__(self, cutoff_hz: float):
+        self.cutoff_hz = cutoff_hz
+        self.state = JoystickData()
+        self.last_update = None
+
+    def reset(self) -> None:
+        self.state = JoystickData()
+        self.last_update = None
+
+    def apply(self, data: JoystickData) -> JoystickData:
+        if self.cutoff_hz <= 0:
+            return data
+
+        now = time.time()
+        # A gap longer than the heartbeat timeout means the watchdog already zeroed
+        # the motors; start again from rest instead of from the stale state.
+        if self.last_update is None or now - self.last_update > heartbeat_timeout_s:
+            self.reset()
+            dt = 0.0
+        else:
+            dt = now - self.last_update
+        self.last_update = now
+
+        rc = 1.0 / (2.0 * math.pi * self.cutoff_hz)
+        alpha = dt / (rc + dt) if dt > 0 else 0.0
+        prev = self.state
+        self.state = JoystickData(
+            lx=prev.lx + alpha * (data.lx - prev.lx),
+            ly=prev.ly + alpha * (data.ly - prev.ly),
+            rx=prev.rx + alpha * (data.rx - prev.rx),
+            ry=prev.ry + alpha * (data.ry - prev.ry),
+        )
+        return self.state
+
+
+class BindAddressError(ValueError):
+    """A configured bind address isn't usable on this machine."""
+
+
+def validate_bind_addresses(addresses: List[str]) -> None:
+    """Fail fast, with the offending entry named, before any socket is bound."""
+    if "*" in addresses and len(addresses) > 1:
+        raise BindAddressError(f"'*' already listens everywhere; don't combine it with {addresses}")
+    for address in addresses:
+        if address == "*":
+            continue
+        try:
+            ip = ipaddress.ip_address(address.strip("[]").split("%")[0])
+        except ValueError:
+            try:
+                socket.if_nametoindex(address)
+            except OSError:
+                raise BindAddressError(f"'{address}' is neither an IP address nor a network interface here") from None
+            continue
+        family = socket.AF_INET6 if ip.version == 6 else socket.AF_INET
+        with socket.socket(family, socket.SOCK_STREAM) as probe:
+            try:
+                probe.bind((address.strip("[]"), 0))
+            except OSError as e:
+                raise BindAddressError(f"'{address}' is not assigned to any interface on this robot: {e.strerror}") from None
+
+
+def _sticks_centered(data: JoystickData) -> bool:
+    return all(abs(v) < JOYSTICK_DEADBAND for v in (data.lx, data.ly, data.rx, data.ry))
+
+
 def all_stop() -> None:
     """Emergency stop - called when connection is lost."""
     global connection_lost
@@ -162,7 +786,7 @@ def watchdog_thread() -> None:
         with heartbeat_lock:
             time_since_heartbeat = time.time() - last_heartbeat
             
-            if time_since_heartbeat > HEARTBEAT_TIMEOUT_S:
+            if time_since_heartbeat > heartbeat_timeout_s:
                 if not connection_lost:
                     all_stop()
             elif connection_lost:
@@ -172,11 +796,18 @@ def watchdog_thread() -> None:
         time.sleep(WATCHDOG_CHECK_INTERVAL_S)
 
 
-def calculate_motor_speeds(data: JoystickData) -> List[float]:
-    """
-    Calculate mecanum drive motor speeds from joystick input.
-    Returns: List of 4 motor speeds [FL, FR, RL, RR]
-    """
+def valid_output_rate(hz: Any) -> bool:
+    return isinstance(hz, (int, float)) and not isinstance(hz, bool) and MIN_OUTPUT_RATE_HZ <= hz <= MAX_OUTPUT_RATE_HZ
+
+
+def valid_input_shaping(deadband: Any, expo: Any) -> bool:
+    return (isinstance(deadband, (int, float)) and isinstance(expo, (int, float))
+            and 0.0 <= deadband <= MAX_INPUT_DEADBAND and MIN_INPUT_EXPO <= expo <= MAX_INPUT_EXPO)
+
+
+def shape_drive_inputs(data: JoystickData, deadband: float = JOYSTICK_DEADBAND,
+                       expo: float = INPUT_EXPO) -> Tuple[float, float, float]:
+    """Deadband and expo the sticks into (forward, strafe, rotate), each in [-1.0, 1.0]."""
     def apply_deadband(value, deadband):
         value = float(value)
         if abs(value) < deadband:
@@ -192,25 +823,172 @@ def calculate_motor_speeds(data: JoystickData) -> List[float]:
         sign = 1.0 if value >= 0.0 else -1.0
         return sign * (abs(value) ** expo)
 
-    x = shape_input(apply_deadband(data.lx, JOYSTICK_DEADBAND), INPUT_EXPO)  # strafe
-    y = shape_input(apply_deadband(data.ly, JOYSTICK_DEADBAND), INPUT_EXPO)  # forward
-    z = shape_input(apply_deadband(data.rx, JOYSTICK_DEADBAND), INPUT_EXPO)  # rotate
+    x = shape_input(apply_deadband(data.lx, deadband), expo)  # strafe
+    y = shape_input(apply_deadband(data.ly, deadband), expo)  # forward
+    z = shape_input(apply_deadband(data.rx, deadband), expo)  # rotate
+    return y, x, z
 
+
+def geofence_blocks(x: float, y: float, theta_deg: float, forward: float, strafe: float, radius_m: float,
+                    origin: Tuple[float, float] = GEOFENCE_ORIGIN) -> bool:
+    """True if driving (forward, strafe) from pose (x, y, theta) heads out past ``radius_m``."""
+    theta_rad = math.radians(theta_deg)
+    v_forward = forward * MAX_LINEAR_SPEED_MPS
+    v_strafe = strafe * MAX_LINEAR_SPEED_MPS
+    ahead_x = x + (v_forward * math.cos(theta_rad) - v_strafe * math.sin(theta_rad)) * GEOFENCE_LOOKAHEAD_S
+    ahead_y = y + (v_forward * math.sin(theta_rad) + v_strafe * math.cos(theta_rad)) * GEOFENCE_LOOKAHEAD_S
+    distance = math.hypot(x - origin[0], y - origin[1])
+    ahead = math.hypot(ahead_x - origin[0], ahead_y - origin[1])
+    return ahead > radius_m and ahead > distance
+
+
+def _mecanum(forward: float, strafe: float, rotate: float) -> List[float]:
+    """Raw mecanum mixing into [FL, FR, RL, RR]; outputs may exceed 1.0."""
+    y, x, z = forward, strafe, rotate
     motor1_speed = y + x + z  # Front Left
     motor2_speed = y - x - z  # Front Right
     motor3_speed = y - x + z  # Rear Left
     motor4_speed = y + x - z  # Rear Right
+    return [motor1_speed, motor2_speed, motor3_speed, motor4_speed]
 
-    speeds = [motor1_speed, motor2_speed, motor3_speed, motor4_speed]
-    
-    # Normalize speeds
+
+def _desaturate(base: List[float], extra: List[float]) -> List[float]:
+    """``base`` (scaled down together if it can't fit) plus as much of ``extra`` as still fits in [-1, 1]."""
+    peak = max(abs(s) for s in base)
+    if peak > 1.0:
+        return [s / peak for s in base]
+    share = 1.0
+    for b, e in zip(base, extra):
+        if b + e > 1.0:
+            share = min(share, (1.0 - b) / e)
+        elif b + e < -1.0:
+            share = min(share, (-1.0 - b) / e)
+    return [b + share * e for b, e in zip(base, extra)]
+
+
+def mix_scale(forward: float, strafe: float, rotate: float) -> List[float]:
+    """Scale all four down together if any exceeds 1.0.
+
+    Keeps the direction of travel and the ratio of turning to driving exactly, giving up
+    top speed whenever the sticks ask for more. Preferable for precise driving, where
+    going where the sticks point matters more than going fast. The default.
+    """
+    speeds = _mecanum(forward, strafe, rotate)
     max_speed = max(abs(s) for s in speeds)
     if max_speed > 1.0:
         speeds = [s / max_speed for s in speeds]
-
     return speeds
 
 
+def mix_clip(forward: float, strafe: float, rotate: float) -> List[float]:
+    """Clamp each motor to [-1, 1] on its own.
+
+    Every motor that isn't saturated keeps full power, so this is the fastest, but the
+    clipped motors no longer match the rest and the robot strays from the commanded
+    direction (full forward plus a turn curves less than asked). Preferable for drivers
+    who want raw punch and correct by eye.
+    """
+    return [max(-1.0, min(1.0, s)) for s in _mecanum(forward, strafe, rotate)]
+
+
+def mix_translation(forward: float, strafe: float, rotate: float) -> List[float]:
+    """Keep the full translation and give rotation only the headroom left over.
+
+    Driving flat out while turning keeps its speed and direction of travel; the turn rate
+    is what gives. Preferable when the path matters more than the heading, e.g. strafing
+    along a wall or lining up while moving.
+    """
+    return _desaturate(_mecanum(forward, strafe, 0.0), _mecanum(0.0, 0.0, rotate))
+
+
+def mix_forward(forward: float, strafe: float, rotate: float) -> List[float]:
+    """Keep the full forward/back command; strafe and rotation share what is left.
+
+    Flat-out forward stays flat out however the other stick is pushed, while sideways
+    motion and turning soften near top speed. Preferable for sprinting down the field
+    or pushing, where losing drive power to a correction costs more than a wide turn.
+    """
+    return _desaturate(_mecanum(forward, 0.0, 0.0), _mecanum(0.0, strafe, rotate))
+
+
+# Desaturation strategies by the name the 'mix_mode' command takes.
+MIX_MODES: Dict[str, Callable[[float, float, float], List[float]]] = {
+    "scale": mix_scale,
+    "clip": mix_clip,
+    "translation": mix_translation,
+    "forward": mix_forward,
+}
+
+
+def mix_motor_speeds(forward: float, strafe: float, rotate: float, mode: str = "scale") -> List[float]:
+    """Mecanum mixing into [FL, FR, RL, RR], kept within [-1, 1] by the MIX_MODES strategy ``mode``."""
+    return MIX_MODES[mode](forward, strafe, rotate)
+
+
+def derate_motor_speeds(speeds: List[float], factors: Tuple[float, float, float]) -> List[float]:
+    """Scale mixed ``speeds`` by a (forward, strafe, rotate) derate factor per axis.
+
+    Works on the mix's output, so the desaturation already applied is kept: the speeds are
+    split back into their axis components, each is scaled by its own factor, and whatever
+    the axes don't account for (clip mode can leave some) is scaled by the smallest factor.
+    Equal factors scale every motor alike.
+    """
+    if min(factors) == max(factors):
+        return [speed * factors[0] for speed in speeds]
+    axes = unmix_motor_speeds(speeds)
+    residual = [s - m for s, m in zip(speeds, _mecanum(*axes))]
+    scaled = _mecanum(*(component * factor for component, factor in zip(axes, factors)))
+    return [max(-1.0, min(1.0, s + r * min(factors))) for s, r in zip(scaled, residual)]
+
+
+def unmix_motor_speeds(speeds: List[float]) -> Tuple[float, float, float]:
+    """Inverse of mix_motor_speeds (before normalization): (forward, strafe, rotate)."""
+    fl, fr, rl, rr = speeds
+    return (fl + fr + rl + rr) / 4.0, (fl - fr - rl + rr) / 4.0, (fl - fr + rl - rr) / 4.0
+
+
+def read_chassis_velocity() -> Optional[Tuple[float, float, float]]:
+    """Measured (forward, strafe, rotate) as fractions of full speed, or None without feedback.
+
+    The drive motors have no encoders wired yet; this is the hook for reading them.
+    """
+    return None
+
+
+def read_motor_currents() -> List[Optional[float]]:
+    """Current draw per motor [FL, FR, RL, RR] in amps, or None where there's no sensor.
+
+    The MDD10A has no current sense output; this is the hook for a shunt/ADC reading.
+    """
+    return [None] * len(MOTOR_NAMES)
+
+
+def read_sensors() -> Dict[str, bool]:
+    """State of each DIGITAL_INPUTS line, True while the switch is active.
+
+    Nothing is wired yet; this is the hook for GPIO.input(pin) on each configured pin
+    (with pull-ups, an active-low switch reads 0 when pressed).
+    """
+    return {name: False for name in DIGITAL_INPUTS}
+
+
+def read_imu() -> Optional[Tuple[float, float, float]]:
+    """(roll, pitch, yaw) in degrees, or None without an IMU.
+
+    Nothing is wired yet; this is the hook for an I2C IMU such as a BNO055 or MPU-6050.
+    Yaw should turn the way the odometry heading does (counter-clockwise positive).
+    """
+    return None
+
+
+def set_servo_angle(servo: str, angle_deg: float) -> None:
+    """Drive an auxiliary servo to ``angle_deg`` (already clamped to SERVO_LIMITS_DEG).
+
+    Nothing is wired yet; this is the hook for hardware.ServoMotor or a PCA9685 channel.
+    """
+    logger.debug(f"Servo {servo} -> {angle_deg:.1f} deg")
+
+
 def set_motor_speeds(speeds: List[float]) -> None:
     """Set motor speeds in order [FL, FR, RL, RR], each in [-1.0, 1.0]."""
     controller = ensure_motor_controller()
@@ -230,31 +1008,590 @@ def update_heartbeat() -> None:
             connection_lost = False
 
 
+CommandHandler = Callable[[Dict[str, Any]], Dict[str, Any]]
+
+# Command type -> RobotServer method name. Populated by @command_handler, and
+# reported to drivers by the 'capabilities' command.
+COMMAND_REGISTRY: Dict[str, str] = {}
+
+
+def command_handler(cmd_type: str):
+    """Register a RobotServer method as the terminal handler for ``cmd_type``."""
+    def decorator(func):
+        COMMAND_REGISTRY[cmd_type] = func.__name__
+        return func
+    return decorator
+
+
+class CommandMiddleware:
+    """One stage of the command pipeline.
+
+    Return ``next_handler(command)`` to pass the command on, or return a response
+    directly to short-circuit the rest of the pipeline. Commands a middleware answers
+    itself are listed in ``provides`` so they show up in capabilities.
+    """
+    provides: Tuple[str, ...] = ()
+
+    def handle(self, command: Dict[str, Any], next_handler: CommandHandler) -> Dict[str, Any]:
+        return next_handler(command)
+
+
+class CommandPipeline:
+    """Runs commands through middleware in order, ending at a terminal handler."""
+    def __init__(self, middlewares: List[CommandMiddleware], terminal: CommandHandler):
+        self.middlewares = list(middlewares)
+        self.terminal = terminal
+
+    def __call__(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return self._run(0, command)
+
+    def command_names(self) -> List[str]:
+        names = set(COMMAND_REGISTRY)
+        for middleware in self.middlewares:
+            names.update(middleware.provides)
+        return sorted(names)
+
+    def _run(self, index: int, command: Dict[str, Any]) -> Dict[str, Any]:
+        if index >= len(self.middlewares):
+            return self.terminal(command)
+        return self.middlewares[index].handle(command, lambda cmd: self._run(index + 1, cmd))
+
+
+class AuthMiddleware(CommandMiddleware):
+    """Shared-secret challenge/response. Answers auth commands and gates everything else."""
+    provides = ('auth_challenge', 'auth')
+
+    def __init__(self, secret: str):
+        self.secret = secret
+        self.nonces: Dict[str, float] = {}
+        self.sessions: List[str] = []
+
+    def handle(self, command: Dict[str, Any], next_handler: CommandHandler) -> Dict[str, Any]:
+        cmd_type = command.get('type')
+        if cmd_type == 'auth_challenge':
+            if not self.secret:
+                return {'status': 'success', 'auth_required': False}
+            return {'status': 'success', 'auth_required': True, 'nonce': self._issue_nonce()}
+        if cmd_type == 'auth':
+            return self._verify(command) if self.secret else {'status': 'success'}
+
+        if not self.secret or command.get('session') in self.sessions:
+            return next_handler(command)
+        if cmd_type == 'ping':
+            # Let unauthenticated clients find the robot, but don't count it as a heartbeat.
+            return {'status': 'success', 'timestamp': time.time(), 'service': SERVICE_NAME}
+        logger.warning(f"Rejected unauthenticated command: {cmd_type}")
+        return {'status': 'error', 'message': 'Not authenticated'}
+
+    def _issue_nonce(self) -> str:
+        now = time.time()
+        self.nonces = {
+            nonce: issued for nonce, issued in self.nonces.items()
+            if now - issued < AUTH_NONCE_TTL_S
+        }
+        while len(self.nonces) >= MAX_AUTH_NONCES:
+            self.nonces.pop(next(iter(self.nonces)))
+        nonce = secrets.token_hex(16)
+        self.nonces[nonce] = now
+        return nonce
+
+    def _verify(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        nonce = str(command.get('nonce', ''))
+        issued = self.nonces.pop(nonce, None)
+        if issued is None or time.time() - issued >= AUTH_NONCE_TTL_S:
+            return {'status': 'error', 'message': 'Unknown or expired nonce'}
+
+        expected = hmac.new(self.secret.encode(), nonce.encode(), hashlib.sha256).hexdigest()
+        if not hmac.compare_digest(expected, str(command.get('hmac', ''))):
+            logger.warning("Authentication failed: HMAC mismatch")
+            return {'status': 'error', 'message': 'Authentication failed'}
+
+        session = secrets.token_hex(16)
+        self.sessions.append(session)
+        del self.sessions[:-MAX_AUTH_SESSIONS]
+        logger.info("Client authenticated")
+        return {'status': 'success', 'session': session}
+
+
+class RoleMiddleware(CommandMiddleware):
+    """Tracks each client's role and rejects commands outside it.
+
+    Clients are drivers until they claim a role with the 'role' command, so driver stations
+    that predate roles keep working. One client drives at a time: while a driver was heard
+    from within DRIVER_ROLE_HOLD_S, any other driver is refused everything a spotter couldn't
+    send. A driver claim is refused then too, and the client that asked is held to spotter
+    rather than left driving by default.
+    """
+    provides = ('role',)
+
+    def __init__(self):
+        self.roles: Dict[str, str] = {}
+        # Drivers' last command time, for refusing a second driver.
+        self.driver_seen: Dict[str, float] = {}
+
+    @staticmethod
+    def _client(command: Dict[str, Any]) -> str:
+        return str(command.get('client_id'))
+
+    def role_of(self, command: Dict[str, Any]) -> str:
+        return self.roles.get(self._client(command), ROLE_DRIVER)
+
+    def is_driving(self, command: Dict[str, Any]) -> bool:
+        """True if the command's client is a driver and no other driver holds the role."""
+        return (self.role_of(command) == ROLE_DRIVER
+                and self._active_driver(time.monotonic(), other_than=self._client(command)) is None)
+
+    def stats(self) -> Dict[str, Any]:
+        now = time.monotonic()
+        return {
+            'driver': self._active_driver(now),
+            'spotters': sorted(c for c, role in self.roles.items() if role == ROLE_SPOTTER),
+        }
+
+    def _active_driver(self, now: float, other_than: Optional[str] = None) -> Optional[str]:
+        for client, seen in self.driver_seen.items():
+            if client != other_than and now - seen < DRIVER_ROLE_HOLD_S \
+                    and self.roles.get(client, ROLE_DRIVER) == ROLE_DRIVER:
+                return client
+        return None
+
+    def _assign(self, client: str, role: str) -> None:
+        self.roles.pop(client, None)
+        while len(self.roles) >= MAX_TRACKED_CLIENTS:
+            self.roles.pop(next(iter(self.roles)))
+        self.roles[client] = role
+
+    def _claim(self, client: str, role: Any) -> Dict[str, Any]:
+        if role not in ROLES:
+            return {'status': 'error', 'message': f"Unknown role {role!r}; expected one of {', '.join(ROLES)}"}
+        now = time.monotonic()
+        holder = self._active_driver(now, other_than=client) if role == ROLE_DRIVER else None
+        if holder is not None:
+            self._assign(client, ROLE_SPOTTER)
+            logger.warning(f"Refused driver role to {client}: {holder} is driving")
+            return {'status': 'error', 'message': 'Another driver is connected', 'role': ROLE_SPOTTER}
+        self._assign(client, role)
+        if role == ROLE_DRIVER:
+            self.driver_seen[client] = now
+        logger.info(f"Client {client} is now {role}")
+        return {'status': 'success', 'role': role}
+
+    def handle(self, command: Dict[str, Any], next_handler: CommandHandler) -> Dict[str, Any]:
+        client = self._client(command)
+        cmd_type = command.get('type')
+        if cmd_type == 'role':
+            return self._claim(client, command.get('role'))
+        role = self.roles.get(client, ROLE_DRIVER)
+        if role == ROLE_SPOTTER and cmd_type not in SPOTTER_COMMANDS:
+            logger.warning(f"Rejected {cmd_type} from spotter {client}")
+            return {'status': 'error', 'message': f"'{cmd_type}' is not allowed for a spotter", 'role': role}
+        if role == ROLE_DRIVER:
+            now = time.monotonic()
+            self.driver_seen = {c: t for c, t in self.driver_seen.items() if now - t < DRIVER_ROLE_HOLD_S}
+            holder = self._active_driver(now, other_than=client)
+            if holder is None:
+                self.driver_seen[client] = now
+            elif cmd_type not in SPOTTER_COMMANDS:
+                logger.warning(f"Rejected {cmd_type} from {client}: {holder} is driving")
+                return {'status': 'error', 'message': 'Another driver is connected'}
+        return next_handler(command)
+
+
+class ChannelMiddleware(CommandMiddleware):
+    """Tracks which clients read [channel, payload...] messages on the telemetry socket.
+
+    A driver station says it does with "channels": true in its 'hello'. Any other client
+    heard from within CHANNEL_CLIENT_HOLD_S may be an older one that takes every message for
+    telemetry JSON, so messages that only exist on a channel are held back while one is.
+    """
+    def __init__(self):
+        # client_id -> (reads channels, when last heard from on the monotonic clock)
+        self.clients: Dict[str, Tuple[bool, float]] = {}
+
+    def all_read_channels(self) -> bool:
+        now = time.monotonic()
+        return all(channels for channels, seen in list(self.clients.values()) if now - seen < CHANNEL_CLIENT_HOLD_S)
+
+    def handle(self, command: Dict[str, Any], next_handler: CommandHandler) -> Dict[str, Any]:
+        client = str(command.get('client_id'))
+        if command.get('type') == 'hello':
+            channels = command.get('channels') is True
+        else:
+            channels = self.clients.get(client, (False, 0.0))[0]
+        self.clients.pop(client, None)
+        while len(self.clients) >= MAX_TRACKED_CLIENTS:
+            self.clients.pop(next(iter(self.clients)))
+        self.clients[client] = (channels, time.monotonic())
+        return next_handler(command)
+
+
+class HeartbeatMiddleware(CommandMiddleware):
+    """Any driver command that reaches this stage proves the driver is alive.
+
+    A spotter's pings don't count, or they would keep the robot driving after the driver left,
+    and nor do those of a second driver refused while another holds the role.
+    """
+    def __init__(self, roles: RoleMiddleware):
+        self.roles = roles
+
+    def handle(self, command: Dict[str, Any], next_handler: CommandHandler) -> Dict[str, Any]:
+        if self.roles.is_driving(command):
+            update_heartbeat()
+        return next_handler(command)
+
+
+class DuplicateFilterMiddleware(CommandMiddleware):
+    """Drops commands whose (client_id, seq) was already processed, e.g. re-sent after reconnect."""
+    def __init__(self):
+        self.recent_sequences: Dict[str, deque] = {}
+        # Diagnostics for redundant links/retries: how many re-sent commands were caught.
+        self.duplicate_count = 0
+        self.duplicates_by_type: Dict[str, int] = {}
+
+    def stats(self) -> Dict[str, Any]:
+        return {'total': self.duplicate_count, 'by_type': dict(self.duplicates_by_type)}
+
+    def is_duplicate(self, command: Dict[str, Any]) -> bool:
+        client_id = command.get('client_id')
+        seq = command.get('seq')
+        if client_id is None or seq is None:
+            return False
+
+        client_id = str(client_id)
+        seen = self.recent_sequences.get(client_id)
+        if seen is None:
+            while len(self.recent_sequences) >= MAX_TRACKED_CLIENTS:
+                self.recent_sequences.pop(next(iter(self.recent_sequences)))
+            seen = self.recent_sequences[client_id] = deque(maxlen=SEQUENCE_WINDOW)
+        if seq in seen:
+            return True
+        seen.append(seq)
+        return False
+
+    def handle(self, command: Dict[str, Any], next_handler: CommandHandler) -> Dict[str, Any]:
+        if self.is_duplicate(command):
+            cmd_type = command.get('type')
+            self.duplicate_count += 1
+            self.duplicates_by_type[cmd_type] = self.duplicates_by_type.get(cmd_type, 0) + 1
+            logger.info(f"Ignoring duplicate {command.get('type')} (seq {command.get('seq')})")
+            return {'status': 'success', 'duplicate': True}
+        return next_handler(command)
+
+
+class MalformedCommand(ValueError):
+    """A frame that isn't a command object with a string 'type'."""
+
+
+def validate_command(command: Any) -> None:
+    if not isinstance(command, dict):
+        raise MalformedCommand("Command must be a JSON object")
+    if not isinstance(command.get('type'), str):
+        raise MalformedCommand("Command 'type' must be a string")
+
+
+def decode_command(frame: bytes) -> Dict[str, Any]:
+    """Parse one wire frame into a command dict, raising MalformedCommand for anything else."""
+    try:
+        # Free-form fields are stored, logged and echoed back, so nothing past here sees control characters.
+        command = clean_strings(json.loads(frame))
+    except (ValueError, RecursionError) as e:
+        raise MalformedCommand(f"Invalid JSON: {e}") from e
+    validate_command(command)
+    return command
+
+
+@dataclass
+class SelfTestResult:
+    name: str
+    passed: bool
+    detail: str = ""
+
+
+class SelfTestAborted(Exception):
+    """Raised when conditions stop being safe partway through a self-test."""
+
+
+def _selftest_still_safe(server: "RobotServer") -> None:
+    if robot_mode != "STOPPED" or connection_lost or not server.running:
+        raise SelfTestAborted("robot left STOPPED or lost its driver")
+    if server.park_state != "ACTIVE":
+        raise SelfTestAborted("robot parked")
+
+
+def check_battery(server: "RobotServer") -> SelfTestResult:
+    volts = float(server.telemetry_data.get('battery', 0.0))
+    return SelfTestResult("battery", volts >= SELFTEST_MIN_BATTERY_V, f"{volts:.2f} V")
+
+
+def check_telemetry(server: "RobotServer") -> SelfTestResult:
+    age = time.time() - float(server.telemetry_data.get('timestamp', 0.0))
+    return SelfTestResult("telemetry", age <= SELFTEST_TELEMETRY_MAX_AGE_S, f"last frame {age:.1f} s ago")
+
+
+def check_camera(server: "RobotServer") -> SelfTestResult:
+    if not ENABLE_CAMERA_BROADCAST:
+        return SelfTestResult("camera", True, "disabled")
+    alive = server.camera_thread is not None and server.camera_thread.is_alive()
+    return SelfTestResult("camera", alive, "broadcasting" if alive else "broadcast thread not running")
+
+
+def check_motors(server: "RobotServer") -> SelfTestResult:
+    if not ensure_motor_controller().available:
+        return SelfTestResult("motors", True, "no motor hardware (simulated)")
+    try:
+        for index, name in enumerate(MOTOR_NAMES):
+            _selftest_still_safe(server)
+            speeds = ZERO_MOTOR_SPEEDS.copy()
+            speeds[index] = SELFTEST_MOTOR_SPEED
+            ensure_motor_controller().set_speeds(server._reverse_motors(speeds))
+            time.sleep(SELFTEST_PULSE_S)
+            ensure_motor_controller().stop()
+    except SelfTestAborted:
+        raise
+    except Exception as e:
+        return SelfTestResult("motors", False, f"{name}: {e}")
+    finally:
+        ensure_motor_controller().stop()
+    return SelfTestResult("motors", True, "pulsed FL, FR, RL, RR")
+
+
+# Run in order; append a function returning SelfTestResult to add a check.
+SELFTEST_CHECKS: List[Callable[["RobotServer"], SelfTestResult]] = [
+    check_battery,
+    check_telemetry,
+    check_camera,
+    check_motors,
+]
+
+
+@dataclass
+class ConfigKey:
+    """One entry of the 'config' interface."""
+    # Value type 'config' accepts: "number", "bool", "text", or "number?" for numbers that
+    # may also be null. Read-only keys may hold a "list".
+    kind: str
+    read: Callable[[], Any]
+    # Builds the individual command that sets the value, so each key keeps that command's
+    # range checks and persistence. None for read-only keys.
+    command: Optional[Callable[[Any], Dict[str, Any]]] = None
+    # False for keys that go back to their default on restart.
+    persisted: bool = True
+
+
+def config_value_ok(kind: str, value: Any) -> bool:
+    if kind == "number?" and value is None:
+        return True
+    if kind in ("number", "number?"):
+        return isinstance(value, (int, float)) and not isinstance(value, bool) and math.isfinite(value)
+    if kind == "bool":
+        return isinstance(value, bool)
+    return kind == "text" and isinstance(value, str)
+
+
 class RobotServer:
     """ZMQ-based robot server"""
-    def __init__(self):
+    def __init__(self, bind: bool = True):
+        """``bind=False`` skips the sockets so handle_command can be driven directly (e.g. by test/fuzz_commands.py)."""
         self.context = zmq.Context()
-        
-        # REP socket for commands
-        self.command_socket = self.context.socket(zmq.REP)
-        self.command_socket.bind(f"tcp://*:{COMMAND_PORT}")
-        
-        # PUB socket for telemetry
-        self.telemetry_socket = self.context.socket(zmq.PUB)
-        self.telemetry_socket.bind(f"tcp://*:{TELEMETRY_PORT}")
-        
+        self.command_socket = None
+        self.telemetry_socket = None
+        self.drive_socket = None
+
+        if bind:
+            validate_bind_addresses(BIND_ADDRESSES)
+
+            # REP socket for commands
+            self.command_socket = self.context.socket(zmq.REP)
+            self.command_socket.setsockopt(zmq.RCVTIMEO, COMMAND_POLL_TIMEOUT_MS)
+            self._bind(self.command_socket, COMMAND_PORT)
+
+            # PUB socket for telemetry
+            self.telemetry_socket = self.context.socket(zmq.PUB)
+            self.telemetry_socket.setsockopt(zmq.SNDHWM, TELEMETRY_SEND_HWM)
+            self._bind(self.telemetry_socket, TELEMETRY_PORT)
+
+            # PULL socket for unacknowledged drive frames (negotiated via 'hello')
+            if ENABLE_UNACKED_DRIVE:
+                self.drive_socket = self.context.socket(zmq.PULL)
+                self.drive_socket.setsockopt(zmq.RCVTIMEO, COMMAND_POLL_TIMEOUT_MS)
+                self._bind(self.drive_socket, DRIVE_PORT)
+
+        self.command_logger = None
+        if COMMAND_LOG_PATH:
+            self.command_logger = CommandLogger(COMMAND_LOG_PATH, COMMAND_LOG_MAX_BYTES, COMMAND_LOG_BACKUPS)
+
+        # Commands arrive on both the REP and PULL sockets.
+        self.command_lock = threading.Lock()
         self.running = True
+        self.telemetry_rate_hz = float(TELEMETRY_RATE_HZ)
+        self.fatal_error = None
         self.camera_thread = None
         self.pose_x_m = FIELD_WIDTH_M / 2.0
         self.pose_y_m = FIELD_HEIGHT_M / 2.0
         self.pose_theta_deg = 0.0
         self.last_pose_update = time.time()
         self.odometry_mode = "PRE_START"
+        # Bumped on every pose reset so drivers can see it land in telemetry.
+        self.odometry_resets = 0
+        self.selftest_thread = None
+        self.bench_thread: Optional[threading.Thread] = None
+        self.bench_cancel = threading.Event()
+        self.bench_count = 0
+        # The telemetry PUB socket is shared with the benchmark thread.
+        self.telemetry_send_lock = threading.Lock()
+        # Held while telemetry_data is refreshed and serialized, and by the sensor thread's writes.
+        # When both are needed it is taken after command_lock, never before it.
+        self.telemetry_lock = threading.Lock()
+        self.telemetry_seq = 0
+        self.loop_timers: Dict[str, LoopTimer] = {
+            'telemetry': LoopTimer(),
+            'output': LoopTimer(),
+            'snapshot': LoopTimer(),
+            'sensors': LoopTimer(),
+        }
+        self.command_timing = DurationStats()
+        self.joystick_filters: Dict[str, JoystickFilter] = {}
+        self.snapshots: deque = deque(maxlen=int(SNAPSHOT_RATE_HZ * SNAPSHOT_WINDOW_S))
+        self.failsafe_snapshot: Optional[Dict[str, Any]] = None
+        # Paused holds the motors at zero without touching mode or connection state.
+        self.paused = False
+        self.resume_needs_center = False
+        self.park_state = "ACTIVE"
+        # Monotonic time the arming countdown ends; motion is ignored until then.
+        self.armed_at = 0.0
+        # (start time, outputs when parking began) while PARKING.
+        self.park_from: Optional[Tuple[float, List[float]]] = None
+        self.settings = load_robot_settings()
+        self.pid_controllers: Dict[str, PidController] = {}
+        saved_pid = self.settings.get("pid", {})
+        if not isinstance(saved_pid, dict):
+            logger.warning(f"Ignoring stored PID gains {saved_pid!r}; using {DEFAULT_PID_GAINS}")
+            saved_pid = {}
+        for axis in PID_AXES:
+            saved = saved_pid.get(axis, {})
+            if not valid_pid_gains(saved):
+                logger.warning(f"Ignoring stored {axis} PID gains {saved!r}; using {DEFAULT_PID_GAINS}")
+                saved = {}
+            gains = {**DEFAULT_PID_GAINS, **saved}
+            self.pid_controllers[axis] = PidController(gains["kp"], gains["ki"], gains["kd"])
+        accel = {**DEFAULT_ACCEL_LIMITS, **self.settings.get("accel", {})}
+        self.output_ramp = OutputRamp(accel["up"], accel["down"])
+        brake = self.settings.get("brake")
+        self._set_brake(brake if isinstance(brake, bool) else BRAKE_ON_STOP)
+        limits = self.settings.get("current_limits")
+        if not valid_current_limits(limits):
+            if limits is not None:
+                logger.warning(f"Ignoring stored current limits {limits!r}; using {DEFAULT_CURRENT_LIMIT_A} A")
+            limits = [DEFAULT_CURRENT_LIMIT_A] * len(MOTOR_NAMES)
+        self.current_limiter = CurrentLimiter([float(a) for a in limits])
+        reverse = self.settings.get("motor_reverse")
+        if not isinstance(reverse, list) or len(reverse) != len(MOTOR_NAMES):
+            reverse = [False] * len(MOTOR_NAMES)
+        # Software fix for motors wired backwards, applied right after mixing.
+        self.motor_reversed: List[bool] = [bool(r) for r in reverse]
+        shaping = self.settings.get("input_shaping")
+        if not isinstance(shaping, dict) or not valid_input_shaping(shaping.get("deadzone"), shaping.get("expo")):
+            shaping = {"deadzone": JOYSTICK_DEADBAND, "expo": INPUT_EXPO}
+        # Same feel for every controller that connects. Shaping composes: the driver station
+        # applies its own deadzone and expo first, and this curve is applied to the result.
+        self.input_shaping: Dict[str, float] = {"deadzone": float(shaping["deadzone"]),
+                                                "expo": float(shaping["expo"])}
+        output_rate_hz = self.settings.get("output_rate_hz")
+        # Ramping, PID and current limits step by the measured interval, so the rate trades
+        # CPU for smoothness without retuning anything.
+        self.output_rate_hz = float(output_rate_hz if valid_output_rate(output_rate_hz) else OUTPUT_RATE_HZ)
+        # Swappable so tests and the simulator can inject readings.
+        self.read_currents: Callable[[], List[Optional[float]]] = read_motor_currents
+        self.read_velocity: Callable[[], Optional[Tuple[float, float, float]]] = read_chassis_velocity
+        self.read_inputs: Callable[[], Dict[str, bool]] = read_sensors
+        self.read_orientation: Callable[[], Optional[Tuple[float, float, float]]] = read_imu
+        # Latest IMU reading as {'roll', 'pitch', 'yaw'} in degrees, None while there is none.
+        self.imu: Optional[Dict[str, float]] = None
+        # Set while the robot leans past TILT_LIMIT_DEG; holds motion off like a pause.
+        self.tilted = False
+        self.sensor_states: Dict[str, bool] = {name: False for name in DIGITAL_INPUTS}
+        # Set by 'sensors_subscribe': input edges wake the telemetry loop instead of
+        # waiting for its next period.
+        self.sensor_push = False
+        self.telemetry_wake = threading.Event()
+        self.drive_mode = "OPEN"
+        mix_mode = self.settings.get("mix_mode")
+        if not isinstance(mix_mode, str):
+            mix_mode = MIX_MODE
+        if mix_mode not in MIX_MODES:
+            logger.warning(f"Unknown mix mode {mix_mode!r}; scaling instead")
+            mix_mode = "scale"
+        self.mix_mode: str = mix_mode
+        self.sequences: Dict[str, List[Dict[str, float]]] = {}
+        stored = self.settings.get("sequences", DEFAULT_SEQUENCES)
+        for name, steps in (stored if isinstance(stored, dict) else {}).items():
+            try:
+                self.sequences[name] = parse_sequence(steps)
+            except (TypeError, ValueError) as e:
+                logger.warning(f"Ignoring stored sequence {name!r}: {e}")
+        self.sequence_run: Optional[SequenceRun] = None
+        self.turn_run: Optional[TurnRun] = None
+        radius = self.settings.get("geofence_m")
+        self.geofence_m: Optional[float] = float(radius) if isinstance(radius, (int, float)) and radius > 0 else None
+        self.geofence_tripped = False
+        # Latest shaped (forward, strafe, rotate) stick demand, before battery derating.
+        self.velocity_target: Tuple[float, float, float] = (0.0, 0.0, 0.0)
+        self.alerts: deque = deque(maxlen=ALERT_HISTORY)
+        self.alert_count = 0
+        self.servo_angles: Dict[str, float] = {name: SERVO_CENTER_DEG for name in SERVO_NAMES}
+        # Buttons the driver holds, one bit per button id; see BUTTON_MASK_BITS.
+        self.button_mask = 0
+        try:
+            self.robot_name = sanitize_robot_name(self.settings.get("name", DEFAULT_ROBOT_NAME))
+        except ValueError:
+            self.robot_name = sanitize_robot_name(DEFAULT_ROBOT_NAME)
+
+        # Uptime resets only when the process restarts; each driver connection says 'hello' once.
+        self.started_at = time.monotonic()
+        self.connections_accepted = 0
+
+        self.duplicate_filter = DuplicateFilterMiddleware()
+        self.roles = RoleMiddleware()
+        self.channel_clients = ChannelMiddleware()
+        self.pipeline = CommandPipeline(
+            [
+                AuthMiddleware(AUTH_SECRET),
+                self.channel_clients,
+                self.roles,
+                HeartbeatMiddleware(self.roles),
+                self.duplicate_filter,
+            ],
+            self._dispatch_command,
+        )
         self.telemetry_data: Dict[str, Any] = {
             'battery': 12.5,
             'mode': robot_mode,
             'odometry_mode': self.odometry_mode,
+            'paused': self.paused,
+            'park': self.park_state,
+            'arming_s': 0.0,
+            'drive_mode': self.drive_mode,
+            'mix_mode': self.mix_mode,
+            'sequence': {'state': 'idle'},
+            'turn': {'state': 'idle'},
+            'geofence': {'radius_m': self.geofence_m, 'tripped': False},
+            'imu': None,
+            'tilt': {'limit_deg': TILT_LIMIT_DEG or None, 'tripped': False},
+            'derate': 1.0,
+            'derate_axes': {axis: 1.0 for axis in PID_AXES},
+            # Counts output_loop cycles. Telemetry runs on its own thread, so a hung control
+            # loop shows up as a tick that stops advancing while telemetry keeps arriving.
+            'loop_tick': 0,
+            'odometry_resets': self.odometry_resets,
+            'selftest': {'state': 'idle', 'results': []},
             'motor_speeds': ZERO_MOTOR_SPEEDS.copy(),
+            'motor_currents': [None] * len(MOTOR_NAMES),
+            'current_limited': [False] * len(MOTOR_NAMES),
+            'motor_reversed': list(self.motor_reversed),
+            'brake': self.brake,
+            'buttons': self.button_mask,
+            'alerts': [],
+            'servos': dict(self.servo_angles),
             'field': {
                 'width_m': FIELD_WIDTH_M,
                 'height_m': FIELD_HEIGHT_M
@@ -267,15 +1604,332 @@ class RobotServer:
             'sensors': {
                 'ultrasonic': 0,
                 'ir': 0,
-                'gyro': 0.0
+                'gyro': 0.0,
+                'digital': dict(self.sensor_states),
+                'digital_bits': 0,
             }
         }
         
-        logger.info(f"Robot server initialized on ports {COMMAND_PORT}/{TELEMETRY_PORT}")
+        logger.info(
+            f"Robot server initialized on {', '.join(BIND_ADDRESSES)} ports {COMMAND_PORT}/{TELEMETRY_PORT}"
+            f" ({'IPv4+IPv6' if ENABLE_IPV6 else 'IPv4 only'})"
+        )
+
+    @staticmethod
+    def _bind(sock: zmq.Socket, port: int) -> None:
+        # With IPV6 set, ZMQ listens dual-stack and sees IPv4 peers as IPv4-mapped addresses.
+        if ENABLE_IPV6:
+            sock.setsockopt(zmq.IPV6, 1)
+        apply_tcp_keepalive(sock)
+        for address in BIND_ADDRESSES:
+            sock.bind(tcp_endpoint(address, port))
 
     def _stop_drive(self) -> None:
         set_motor_speeds(ZERO_MOTOR_SPEEDS)
         self.telemetry_data["motor_speeds"] = ZERO_MOTOR_SPEEDS.copy()
+        self.output_ramp.reset()
+        self.current_limiter.reset()
+        self.velocity_target = (0.0, 0.0, 0.0)
+        self._end_sequence('aborted', 'drive stopped')
+        self._end_turn('aborted', 'drive stopped')
+        if self.park_from is not None:
+            # Already stopped, so there's nothing left to ramp down.
+            self.park_from = (self.park_from[0], ZERO_MOTOR_SPEEDS.copy())
+        for pid in self.pid_controllers.values():
+            pid.reset()
+        for joystick_filter in self.joystick_filters.values():
+            joystick_filter.reset()
+
+    def _update_derate(self) -> Tuple[float, float, float]:
+        """The (forward, strafe, rotate) derate factors for the battery voltage now."""
+        volts = float(self.telemetry_data['battery'])
+        factors = tuple(battery_derate(volts, BATTERY_DERATE_CURVES[axis]) for axis in PID_AXES)
+        # 'derate' stays the overall output limit the dashboards show: the tightest axis.
+        self.telemetry_data['derate'] = min(factors)
+        self.telemetry_data['derate_axes'] = dict(zip(PID_AXES, factors))
+        return factors
+
+    def _derate_factors(self) -> Tuple[float, float, float]:
+        """The per-axis factors from the last _update_derate."""
+        return tuple(float(self.telemetry_data['derate_axes'][axis]) for axis in PID_AXES)
+
+    def _drive_active(self) -> bool:
+        return (robot_mode == "TELEOP" and not connection_lost and not self.paused and not self.tilted
+                and self.park_state == "ACTIVE" and self._arming_remaining() == 0.0)
+
+    def _arming_remaining(self) -> float:
+        return max(0.0, self.armed_at - time.monotonic())
+
+    def control_state(self) -> Dict[str, Any]:
+        """Everything that decides whether the robot will move, with a one-line summary."""
+        arming_s = round(self._arming_remaining(), 1)
+        # The first thing holding the robot still, if anything is.
+        if connection_lost:
+            summary = "FAILSAFE"
+        elif self.park_state != "ACTIVE":
+            summary = self.park_state
+        elif robot_mode == "STOPPED":
+            summary = "STOPPED"
+        elif self.paused:
+            summary = f"{robot_mode} PAUSED"
+        elif self.tilted:
+            summary = f"{robot_mode} TILTED"
+        elif arming_s > 0:
+            summary = f"{robot_mode} ARMING {arming_s:.1f}s"
+        elif self.resume_needs_center:
+            summary = f"{robot_mode} CENTER STICKS"
+        else:
+            summary = f"{robot_mode} ARMED"
+        return {
+            'summary': summary,
+            'armed': summary == f"{robot_mode} ARMED",
+            'mode': robot_mode,
+            'failsafe': connection_lost,
+            'park': self.park_state,
+            'paused': self.paused,
+            'tilted': self.tilted,
+            'arming_s': arming_s,
+            'awaiting_center': self.resume_needs_center,
+        }
+
+    def _start_arming(self, reason: str) -> None:
+        if ARM_COUNTDOWN_S <= 0:
+            return
+        self._stop_drive()
+        self.armed_at = time.monotonic() + ARM_COUNTDOWN_S
+        self.telemetry_data['arming_s'] = ARM_COUNTDOWN_S
+        # Safe start once the countdown ends, too.
+        self.resume_needs_center = True
+        logger.info(f"Arming ({reason}): motion accepted in {ARM_COUNTDOWN_S:.1f} s")
+
+    def _drive_motors(self, motor_speeds: List[float]) -> None:
+        # output_loop walks ramped outputs toward this target and keeps limits applied.
+        self.output_ramp.target = motor_speeds
+        if not self.output_ramp.enabled():
+            self._write_motors(motor_speeds)
+
+    def _set_brake(self, brake: bool) -> None:
+        self.brake = brake
+        self.output_ramp.coast = 0.0 if brake else COAST_DECAY_PER_S
+
+    def _reverse_motors(self, speeds: List[float]) -> List[float]:
+        """Negate the outputs of motors marked reversed. Applying it twice undoes it."""
+        return [-speed if reverse and speed else speed for speed, reverse in zip(speeds, self.motor_reversed)]
+
+    def _write_motors(self, motor_speeds: List[float], only_if_changed: bool = False) -> None:
+        """Final stage of the drive path: direction fixes, per-motor current limits, then the hardware."""
+        if self.park_state == "PARKED":
+            # Last line of defence: nothing drives a parked robot until it is woken.
+            motor_speeds = ZERO_MOTOR_SPEEDS.copy()
+        outputs = self._reverse_motors(motor_speeds)
+        currents = self.read_currents()
+        outputs, started = self.current_limiter.apply(outputs, currents)
+        # Telemetry (and ramps that start from it) keep the drivetrain's view of the speeds.
+        motor_speeds = self._reverse_motors(outputs)
+        self.telemetry_data['motor_currents'] = currents
+        self.telemetry_data['current_limited'] = self.current_limiter.limited()
+        for i in started:
+            self._raise_alert(
+                'current_limit',
+                f"{MOTOR_NAMES[i]} drew {currents[i]:.1f} A over its {self.current_limiter.limits_a[i]:.1f} A limit; "
+                f"output clamped",
+            )
+        if only_if_changed and motor_speeds == self.telemetry_data['motor_speeds']:
+            return
+        set_motor_speeds(outputs)
+        self.telemetry_data['motor_speeds'] = motor_speeds
+        logger.debug(f"Motors: {outputs}")
+
+    def _set_servo(self, servo: str, angle_deg: float) -> float:
+        low, high = SERVO_LIMITS_DEG[servo]
+        angle_deg = max(low, min(high, angle_deg))
+        set_servo_angle(servo, angle_deg)
+        self.servo_angles[servo] = angle_deg
+        self.telemetry_data['servos'] = dict(self.servo_angles)
+        return angle_deg
+
+    def _center_servos(self, servos) -> None:
+        for servo in servos:
+            self._set_servo(servo, SERVO_CENTER_DEG)
+
+    def _raise_alert(self, kind: str, message: str) -> None:
+        self.alert_count += 1
+        alert = {'id': self.alert_count, 't': time.time(), 'kind': clean_text(kind), 'message': clean_text(message)}
+        self.alerts.append(alert)
+        # Telemetry keeps the recent history; the alert channel delivers this one now, unless
+        # a driver station that can't read channels would take it for a telemetry frame.
+        self.telemetry_data['alerts'] = list(self.alerts)
+        if self.channel_clients.all_read_channels():
+            self._publish([CHANNEL_ALERT, json.dumps(alert).encode()])
+        logger.warning(f"ALERT {alert['kind']}: {alert['message']}")
+
+    def _open_loop_speeds(self) -> List[float]:
+        return derate_motor_speeds(mix_motor_speeds(*self.velocity_target, self.mix_mode), self._derate_factors())
+
+    def _set_drive_mode(self, mode: str) -> None:
+        if mode == self.drive_mode:
+            return
+        self.drive_mode = mode
+        self.telemetry_data['drive_mode'] = mode
+        for pid in self.pid_controllers.values():
+            pid.reset()
+        if mode == "OPEN" and self._drive_active():
+            # Don't leave the motors on the last closed-loop output until the next stick frame.
+            self._drive_motors(self._open_loop_speeds())
+        logger.info(f"Drive mode set to {mode} loop")
+
+    def _closed_loop_step(self, dt: float) -> None:
+        """Feed forward the velocity target plus a PID correction per axis, then mix."""
+        measured = self.read_velocity()
+        if measured is None:
+            self._raise_alert('velocity_feedback', "Lost velocity feedback; drive fell back to open loop")
+            self._set_drive_mode("OPEN")
+            return
+        outputs = []
+        for axis, demand, actual, derate in zip(PID_AXES, self.velocity_target, measured, self._derate_factors()):
+            # Closed loop regulates chassis velocity per axis, so each target is derated directly.
+            target = demand * derate
+            outputs.append(target + self.pid_controllers[axis].update(target - actual, dt))
+        self.output_ramp.target = mix_motor_speeds(*outputs, self.mix_mode)
+
+    def _end_sequence(self, state: str, reason: str = "") -> None:
+        """Drop the running sequence, if any. Callers decide what the motors do next."""
+        run = self.sequence_run
+        if run is None:
+            return
+        self.sequence_run = None
+        self.telemetry_data['sequence'] = run.status(state, time.monotonic() - run.started, reason=reason)
+        logger.info(f"Sequence {run.name} {state}{f': {reason}' if reason else ''}")
+
+    def _hold_still(self) -> None:
+        self.velocity_target = (0.0, 0.0, 0.0)
+        # Odometry (and the simulator) latch the last input, so tell them we've stopped too.
+        self._integrate_pose(0.0, 0.0, 0.0)
+        self._drive_motors(ZERO_MOTOR_SPEEDS.copy())
+
+    def _step_sequence(self, now: float) -> None:
+        run = self.sequence_run
+        elapsed = now - run.started
+        index = run.step_at(elapsed)
+        if index is None:
+            self._end_sequence('done')
+            self._hold_still()
+            return
+        step = run.steps[index]
+        forward_derate, strafe_derate, rotate_derate = self._update_derate()
+        self.velocity_target = (step["forward"], step["strafe"], step["rotate"])
+        if self._enforce_geofence():
+            self._end_sequence('aborted', 'geofence')
+            self._hold_still()
+            return
+        self._integrate_pose(step["strafe"] * strafe_derate, step["forward"] * forward_derate,
+                             step["rotate"] * rotate_derate)
+        if self.drive_mode == "OPEN":
+            self._drive_motors(self._open_loop_speeds())
+        self.telemetry_data['sequence'] = run.status('running', elapsed, index)
+
+    def _end_turn(self, state: str, reason: str = "") -> None:
+        """Drop the running turn, if any. Callers decide what the motors do next."""
+        run = self.turn_run
+        if run is None:
+            return
+        self.turn_run = None
+        self.telemetry_data['turn'] = run.status(state, reason)
+        logger.info(f"Turn of {run.target_deg:.1f} deg {state} after {run.turned_deg:.1f} deg"
+                    f"{f': {reason}' if reason else ''}")
+
+    def _step_turn(self, now: float) -> None:
+        run = self.turn_run
+        remaining = run.track(self.pose_theta_deg)
+        if abs(remaining) <= TURN_TOLERANCE_DEG:
+            self._end_turn('done')
+            self._hold_still()
+            return
+        if now - run.started > TURN_TIMEOUT_S:
+            self._end_turn('aborted', 'timed out')
+            self._hold_still()
+            return
+        rotate = math.copysign(min(TURN_MAX_ROTATE, max(TURN_MIN_ROTATE, abs(remaining) * TURN_KP)), remaining)
+        rotate_derate = self._update_derate()[2]
+        self.velocity_target = (0.0, 0.0, rotate)
+        self._integrate_pose(0.0, 0.0, rotate * rotate_derate)
+        if self.drive_mode == "OPEN":
+            self._drive_motors(self._open_loop_speeds())
+        self.telemetry_data['turn'] = run.status('running')
+
+    def _enforce_geofence(self, may_clear: bool = True) -> bool:
+        """Drop the translation from velocity_target if it heads out of the fence. True if it did.
+
+        Only a fresh command (``may_clear``) can clear the trip; re-checks of an already
+        fenced target would otherwise clear and re-raise the alert on every frame.
+        """
+        forward, strafe, rotate = self.velocity_target
+        blocked = self.geofence_m is not None and geofence_blocks(
+            self.pose_x_m, self.pose_y_m, self.pose_theta_deg, forward, strafe, self.geofence_m
+        )
+        if blocked:
+            self.velocity_target = (0.0, 0.0, rotate)
+            if not self.geofence_tripped:
+                distance = math.hypot(self.pose_x_m - GEOFENCE_ORIGIN[0], self.pose_y_m - GEOFENCE_ORIGIN[1])
+                self._raise_alert('geofence', f"Stopped at {distance:.2f} m from origin; "
+                                              f"geofence is {self.geofence_m:.2f} m")
+        if blocked != self.geofence_tripped and (blocked or may_clear):
+            self.geofence_tripped = blocked
+            self.telemetry_data['geofence'] = {'radius_m': self.geofence_m, 'tripped': blocked}
+        return blocked
+
+    def _check_tilt(self) -> None:
+        """Stop the drive when the robot leans past TILT_LIMIT_DEG; allow it again once level."""
+        if TILT_LIMIT_DEG <= 0 or self.imu is None:
+            # Losing the IMU while tilted keeps the lockout; there's no reading to clear it.
+            return
+        tilt = max(abs(self.imu['roll']), abs(self.imu['pitch']))
+        if not self.tilted and tilt > TILT_LIMIT_DEG:
+            self.tilted = True
+            self._stop_drive()
+            self._raise_alert('tilt', f"Tilted {tilt:.0f} deg, over the {TILT_LIMIT_DEG:.0f} deg limit; motion stopped")
+        elif self.tilted and tilt < TILT_LIMIT_DEG - TILT_HYSTERESIS_DEG:
+            self.tilted = False
+            # Don't lurch off with whatever the sticks were doing when it tipped.
+            self.resume_needs_center = True
+            logger.info(f"Level again at {tilt:.0f} deg; motion allowed once the sticks are centered")
+        else:
+            return
+        self.telemetry_data['tilt'] = {'limit_deg': TILT_LIMIT_DEG, 'tripped': self.tilted}
+
+    def _set_park_state(self, state: str) -> None:
+        self.park_state = state
+        self.telemetry_data['park'] = state
+        logger.info(f"Robot {state.lower()}")
+
+    def _step_park(self, now: float) -> None:
+        """Scale the outputs from when parking began linearly down to zero, then park."""
+        started, speeds = self.park_from
+        remaining = 1.0 - (now - started) / PARK_RAMP_S if PARK_RAMP_S > 0 else 0.0
+        if remaining > 0.0 and not connection_lost:
+            self._write_motors([speed * remaining for speed in speeds], only_if_changed=True)
+            return
+        global robot_mode
+        robot_mode = "STOPPED"
+        self.telemetry_data['mode'] = robot_mode
+        self.park_from = None
+        self._stop_drive()
+        self._set_park_state("PARKED")
+
+    def _set_paused(self, paused: bool) -> None:
+        self.paused = paused
+        self.telemetry_data['paused'] = paused
+
+    def _filter_drive_inputs(self, command: Dict[str, Any], data: JoystickData) -> JoystickData:
+        if JOYSTICK_FILTER_HZ <= 0:
+            return data
+        client_id = str(command.get('client_id'))
+        joystick_filter = self.joystick_filters.get(client_id)
+        if joystick_filter is None:
+            while len(self.joystick_filters) >= MAX_TRACKED_CLIENTS:
+                self.joystick_filters.pop(next(iter(self.joystick_filters)))
+            joystick_filter = self.joystick_filters[client_id] = JoystickFilter(JOYSTICK_FILTER_HZ)
+        return joystick_filter.apply(data)
 
     def start_camera_broadcast(self):
         """Start MJPEG camera broadcast in a background thread."""
@@ -328,6 +1982,9 @@ class RobotServer:
         self.pose_y_m = FIELD_HEIGHT_M / 2.0
         self.pose_theta_deg = 0.0
         self.last_pose_update = time.time()
+        self.odometry_resets += 1
+        self.telemetry_data['odometry_resets'] = self.odometry_resets
+        self._update_telemetry_pose()
     
     def _read_drive_inputs(self, command: Dict[str, Any]) -> JoystickData:
         return JoystickData(
@@ -337,6 +1994,17 @@ class RobotServer:
             ry=float(command.get("ry", 0.0)),
         )
 
+    def _refresh_telemetry(self) -> None:
+        """Bring the derived telemetry fields up to date, as each streamed frame does."""
+        self.telemetry_data['timestamp'] = time.time()
+        self.telemetry_data['mode'] = robot_mode
+        self.telemetry_data['odometry_mode'] = self.odometry_mode
+        self._update_derate()
+        self._update_telemetry_pose()
+        self.telemetry_data['timing'] = self.timing_stats()
+        self.telemetry_data['state'] = self.control_state()
+        self.telemetry_data['loop_rate'] = self._loop_rate_report()
+
     def _update_telemetry_pose(self) -> None:
         self.telemetry_data["pose"] = {
             "x": self.pose_x_m,
@@ -345,132 +2013,1123 @@ class RobotServer:
         }
 
     def handle_command(self, command: Dict[str, Any]) -> Dict[str, Any]:
-        """Process incoming command"""
-        global robot_mode
-        
+        """Process incoming command through the middleware pipeline"""
+        try:
+            validate_command(command)
+        except MalformedCommand as e:
+            return {'status': 'error', 'message': str(e)}
+        started = time.perf_counter()
+        response = self.pipeline(command)
+        self.command_timing.record(time.perf_counter() - started)
+        return response
+
+    def timing_stats(self) -> Dict[str, Any]:
+        return {
+            'loops': {name: timer.snapshot() for name, timer in self.loop_timers.items()},
+            'command': self.command_timing.snapshot(),
+        }
+
+    def _save_settings(self) -> None:
+        save_robot_settings(self.settings)
+
+    @command_handler('pid')
+    def _cmd_pid(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        axis = str(command.get('axis', '')).lower()
+        controller = self.pid_controllers.get(axis)
+        if controller is None:
+            return {'status': 'error', 'message': f'Invalid PID axis: {axis}'}
+
+        kp = float(command.get('kp', controller.kp))
+        ki = float(command.get('ki', controller.ki))
+        kd = float(command.get('kd', controller.kd))
+        if min(kp, ki, kd) < 0.0 or not all(math.isfinite(g) for g in (kp, ki, kd)):
+            return {'status': 'error', 'message': 'PID gains must be finite and non-negative'}
+
+        controller.set_gains(kp, ki, kd)
+        self.settings.setdefault("pid", {})[axis] = controller.gains()
+        self._save_settings()
+        logger.info(f"PID {axis} gains set to kp={kp} ki={ki} kd={kd}")
+        return {'status': 'success', 'axis': axis, 'gains': controller.gains()}
+
+    @command_handler('accel')
+    def _cmd_accel(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        up = float(command.get('up', self.output_ramp.up))
+        down = float(command.get('down', self.output_ramp.down))
+        if not all(math.isfinite(v) and 0.0 <= v <= MAX_ACCEL_PER_S for v in (up, down)):
+            return {'status': 'error', 'message': f'Acceleration limits must be between 0 and {MAX_ACCEL_PER_S}'}
+
+        self.output_ramp.set_limits(up, down)
+        self.settings["accel"] = self.output_ramp.limits()
+        self._save_settings()
+        logger.info(f"Acceleration limits set to up={up}/s down={down}/s")
+        return {'status': 'success', 'accel': self.output_ramp.limits()}
+
+    def _dispatch_command(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Terminal pipeline stage: route the command to its registered handler"""
         cmd_type = command.get('type')
-        update_heartbeat()
-        
+        handler_name = COMMAND_REGISTRY.get(cmd_type)
+        if handler_name is None:
+            logger.warning(f"Unknown command: {cmd_type}")
+            return {'status': 'error', 'message': f'Unknown command: {cmd_type}'}
+
         try:
-            if cmd_type == 'ping':
-                return {'status': 'success', 'timestamp': time.time()}
-            
-            elif cmd_type == 'joystick':
-                joystick_data = self._read_drive_inputs(command)
-                motor_speeds = calculate_motor_speeds(joystick_data)
-                
-                if robot_mode == "TELEOP":
-                    self._integrate_pose(joystick_data.lx, joystick_data.ly, joystick_data.rx)
-                    set_motor_speeds(motor_speeds)
-                    self.telemetry_data['motor_speeds'] = motor_speeds
-                    logger.debug(f"Motors: {motor_speeds}")
-                
-                return {'status': 'success'}
-            
-            elif cmd_type == 'button':
-                button_id = command.get('button_id')
-                action = command.get('action')
-                logger.info(f"Button {button_id} {action}")
-                
-                # TODO: Handle button actions
-                
-                return {'status': 'success'}
-            
-            elif cmd_type == 'mode':
-                new_mode = command.get('mode', 'STOPPED').upper()
-                
-                if new_mode in VALID_ROBOT_MODES:
-                    robot_mode = new_mode
-                    self.telemetry_data['mode'] = robot_mode
-                    logger.info(f"Mode changed to: {robot_mode}")
-                    
-                    if robot_mode == "STOPPED":
-                        self._stop_drive()
-                    
-                    return {'status': 'success', 'mode': robot_mode}
-                else:
-                    return {'status': 'error', 'message': f'Invalid mode: {new_mode}'}
+            return getattr(self, handler_name)(command)
+        except Exception as e:
+            logger.error(f"Error handling command: {e}")
+            return {'status': 'error', 'message': str(e)}
+
+    @command_handler('ping')
+    def _cmd_ping(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {'status': 'success', 'timestamp': time.time(), 'service': SERVICE_NAME}
+
+    @command_handler('hello')
+    def _cmd_hello(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        self.connections_accepted += 1
+        role = self.roles.role_of(command)
+        logger.info(f"Client connected: {command.get('client_id', 'unknown')} as {role} (#{self.connections_accepted})")
+        if robot_mode != "STOPPED" and role == ROLE_DRIVER:
+            self._start_arming("driver connected")
+        response = {'status': 'success'}
+        if self.drive_socket is not None:
+            response['drive_port'] = DRIVE_PORT
+            response['unacked_commands'] = sorted(UNACKED_COMMANDS)
+        response['button_mask_bits'] = BUTTON_MASK_BITS
+        return response
+
+    @command_handler('capabilities')
+    def _cmd_capabilities(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        commands = self.pipeline.command_names()
+        if self.roles.role_of(command) == ROLE_SPOTTER:
+            # A spotter's driver station disables whatever it isn't allowed to use.
+            commands = [c for c in commands if c in SPOTTER_COMMANDS or c == 'role']
+        return {
+            'status': 'success',
+            'commands': commands,
+            'telemetry': sorted(self.telemetry_data.keys()),
+        }
+
+    @command_handler('joystick')
+    def _cmd_joystick(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if 'buttons' in command:
+            mask = command['buttons']
+            if not isinstance(mask, int) or isinstance(mask, bool) or not 0 <= mask < 1 << BUTTON_MASK_BITS:
+                return {'status': 'error', 'message': f'buttons must be a {BUTTON_MASK_BITS}-bit mask'}
+            # Button state is tracked whatever the drive is doing, so no release is ever missed.
+            self._set_button_mask(mask)
+        if self.park_state != "ACTIVE":
+            return {'status': 'success', 'park': self.park_state}
+        arming_s = self._arming_remaining()
+        if arming_s > 0:
+            return {'status': 'success', 'arming_s': round(arming_s, 1)}
+        if self.paused:
+            return {'status': 'success', 'paused': True}
+        if self.tilted:
+            return {'status': 'success', 'tilted': True}
+
+        raw_data = self._read_drive_inputs(command)
+        if self.resume_needs_center:
+            # Safe start: don't lurch off on resume while a stick is still deflected.
+            if not _sticks_centered(raw_data):
+                return {'status': 'success', 'awaiting_center': True}
+            self.resume_needs_center = False
+            logger.info("Sticks centered; drive resumed")
+
+        if self.sequence_run is not None:
+            # A running sequence owns the drive until a stick is actually deflected.
+            if _sticks_centered(raw_data):
+                return {'status': 'success', 'sequence': self.sequence_run.name}
+            self._end_sequence('aborted', 'manual takeover')
+
+        if self.turn_run is not None:
+            # Likewise for a turn: centered frames don't interrupt it, a deflected stick does.
+            if _sticks_centered(raw_data):
+                return {'status': 'success', 'turn': self.turn_run.target_deg}
+            self._end_turn('aborted', 'manual takeover')
+
+        joystick_data = self._filter_drive_inputs(command, raw_data)
+        forward_derate, strafe_derate, rotate_derate = self._update_derate()
+        # The second shaping stage, on sticks the driver station may already have shaped.
+        self.velocity_target = shape_drive_inputs(joystick_data, self.input_shaping["deadzone"],
+                                                  self.input_shaping["expo"])
+        if self._enforce_geofence():
+            # Odometry follows the commands, so it mustn't see the blocked translation either.
+            joystick_data = JoystickData(rx=joystick_data.rx, ry=joystick_data.ry)
+        
+        if robot_mode == "TELEOP":
+            self._integrate_pose(joystick_data.lx * strafe_derate, joystick_data.ly * forward_derate,
+                                 joystick_data.rx * rotate_derate)
+            # In closed loop, output_loop regulates toward velocity_target every tick.
+            if self.drive_mode == "OPEN":
+                self._drive_motors(self._open_loop_speeds())
+        
+        return {'status': 'success'}
+
+    @command_handler('button')
+    def _cmd_button(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        button_id = command.get('button_id')
+        action = command.get('action')
+        if isinstance(button_id, int) and not isinstance(button_id, bool) and 0 <= button_id < BUTTON_MASK_BITS:
+            # Keep the held state in step with drivers that send edges instead of a mask.
+            if action == "DOWN":
+                self.button_mask |= 1 << button_id
+            elif action == "UP":
+                self.button_mask &= ~(1 << button_id)
+            self.telemetry_data['buttons'] = self.button_mask
+        self._button_event(button_id, action, command.get('repeat', 0))
+        return {'status': 'success'}
+
+    def _set_button_mask(self, mask: int) -> None:
+        """Adopt the driver's full button state, acting on each press and release it implies."""
+        changed = mask ^ self.button_mask
+        self.button_mask = mask
+        self.telemetry_data['buttons'] = mask
+        for button_id in range(BUTTON_MASK_BITS):
+            if changed >> button_id & 1:
+                self._button_event(button_id, "DOWN" if mask >> button_id & 1 else "UP")
+
+    def _button_event(self, button_id: Any, action: Any, repeat: Any = 0) -> None:
+        name = BUTTON_NAMES.get(button_id, button_id) if isinstance(button_id, int) else button_id
+        logger.info(f"Button {name} {action}" + (f" (repeat {repeat})" if repeat else ""))
+
+        # TODO: Handle button actions
+
+    @command_handler('mode')
+    def _cmd_mode(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        global robot_mode
+        new_mode = command.get('mode', 'STOPPED').upper()
+        
+        if new_mode in VALID_ROBOT_MODES:
+            if new_mode != "STOPPED" and self.park_state != "ACTIVE":
+                return {'status': 'error', 'message': "Robot is parked; send 'wake' first"}
+            arming = robot_mode == "STOPPED" and new_mode != "STOPPED"
+            robot_mode = new_mode
+            self.telemetry_data['mode'] = robot_mode
+            logger.info(f"Mode changed to: {robot_mode}")
             
-            elif cmd_type == 'reset':
-                robot_mode = "STOPPED"
+            if robot_mode == "STOPPED":
+                self.armed_at = 0.0
                 self._stop_drive()
-                self._reset_pose()
-                self.telemetry_data['mode'] = robot_mode
-                logger.info("Robot reset")
-                return {'status': 'success'}
-
-            elif cmd_type == 'reset_odometry':
-                self._reset_pose()
-                logger.info("Odometry reset")
-                return {'status': 'success'}
-
-            elif cmd_type == 'odometry_mode':
-                mode = str(command.get('mode', 'PRE_START')).upper()
-                if mode in VALID_ODOMETRY_MODES:
-                    self.odometry_mode = mode
-                    self.telemetry_data['odometry_mode'] = self.odometry_mode
-                    return {'status': 'success', 'odometry_mode': self.odometry_mode}
-                return {'status': 'error', 'message': f'Invalid odometry mode: {mode}'}
+            elif arming:
+                self._start_arming(f"mode {robot_mode}")
             
-            else:
-                logger.warning(f"Unknown command: {cmd_type}")
-                return {'status': 'error', 'message': f'Unknown command: {cmd_type}'}
-                
-        except Exception as e:
-            logger.error(f"Error handling command: {e}")
+            return {'status': 'success', 'mode': robot_mode, 'arming_s': round(self._arming_remaining(), 1)}
+        else:
+            return {'status': 'error', 'message': f'Invalid mode: {new_mode}'}
+
+    @command_handler('pause')
+    def _cmd_pause(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if not self.paused:
+            logger.info("Motor output paused")
+        self._set_paused(True)
+        self._stop_drive()
+        return {'status': 'success', 'paused': True}
+
+    @command_handler('resume')
+    def _cmd_resume(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if self.paused:
+            logger.info("Motor output resumed (waiting for centered sticks)")
+            self.resume_needs_center = True
+        self._set_paused(False)
+        return {'status': 'success', 'paused': False}
+
+    @command_handler('shutdown')
+    def _cmd_shutdown(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Park for the end of the session: ramp the motors down and ignore motion until 'wake'."""
+        if command.get('confirm') is not True:
+            return {'status': 'error', 'message': 'Shutdown needs confirm: true'}
+        if self.park_state != "ACTIVE":
+            return {'status': 'success', 'park': self.park_state}
+        if self.selftest_thread is not None and self.selftest_thread.is_alive():
+            return {'status': 'error', 'message': 'Self-test is running'}
+        self._end_sequence('aborted', 'robot parking')
+        self._end_turn('aborted', 'robot parking')
+        self.park_from = (time.monotonic(), list(self.telemetry_data['motor_speeds']))
+        self._set_park_state("PARKING")
+        return {'status': 'success', 'park': self.park_state}
+
+    @command_handler('wake')
+    def _cmd_wake(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if self.park_state == "PARKING":
+            return {'status': 'error', 'message': 'Robot is still parking'}
+        if self.park_state == "PARKED":
+            # Stays STOPPED; the driver re-arms by choosing a mode, then centering the sticks.
+            self.resume_needs_center = True
+            self._set_park_state("ACTIVE")
+        return {'status': 'success', 'park': self.park_state, 'mode': robot_mode}
+
+    @command_handler('selftest')
+    def _cmd_selftest(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if self.selftest_thread is not None and self.selftest_thread.is_alive():
+            return {'status': 'error', 'message': 'Self-test already running'}
+        if robot_mode != "STOPPED":
+            return {'status': 'error', 'message': 'Self-test requires STOPPED mode'}
+        if self.park_state != "ACTIVE":
+            return {'status': 'error', 'message': "Robot is parked; send 'wake' first"}
+        if any(self.telemetry_data['motor_speeds']):
+            return {'status': 'error', 'message': 'Self-test requires a stationary robot'}
+
+        self.telemetry_data['selftest'] = {'state': 'running', 'results': []}
+        self.selftest_thread = threading.Thread(target=self._run_selftest, daemon=True, name="selftest")
+        self.selftest_thread.start()
+        return {'status': 'success', 'state': 'running'}
+
+    def _run_selftest(self) -> None:
+        logger.info("Self-test started")
+        results: List[Dict[str, Any]] = []
+        state = 'passed'
+        for check in SELFTEST_CHECKS:
+            name = check.__name__.removeprefix('check_')
+            try:
+                _selftest_still_safe(self)
+                result = check(self)
+            except SelfTestAborted as e:
+                state = 'aborted'
+                results.append({'name': name, 'passed': False, 'detail': str(e)})
+                break
+            except Exception as e:
+                result = SelfTestResult(name, False, str(e))
+            results.append({'name': result.name, 'passed': result.passed, 'detail': result.detail})
+            self.telemetry_data['selftest'] = {'state': 'running', 'results': list(results)}
+            if not result.passed:
+                state = 'failed'
+
+        self.telemetry_data['selftest'] = {'state': state, 'results': results, 'finished_at': time.time()}
+        logger.info(f"Self-test {state}: {results}")
+
+    @command_handler('reset')
+    def _cmd_reset(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        global robot_mode
+        # Re-centering is motion too, so only a robot that was allowed to move does it.
+        center_servos = self._drive_active()
+        robot_mode = "STOPPED"
+        self._set_paused(False)
+        self.resume_needs_center = False
+        self._stop_drive()
+        self._reset_pose()
+        if center_servos:
+            self._center_servos(SERVO_NAMES)
+        self._set_drive_mode("OPEN")
+        self.telemetry_data['mode'] = robot_mode
+        logger.info("Robot reset")
+        return {'status': 'success'}
+
+    @command_handler('reset_odometry')
+    def _cmd_reset_odometry(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if self.turn_run is not None:
+            # The turn is measured against the old heading; don't let the jump count as rotation.
+            self._end_turn('aborted', 'odometry reset')
+            self._hold_still()
+        self._reset_pose()
+        logger.info("Odometry reset")
+        return {
+            'status': 'success',
+            'odometry_resets': self.odometry_resets,
+            'pose': self.telemetry_data['pose'],
+        }
+
+    @command_handler('stats')
+    def _cmd_stats(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'timing': self.timing_stats(),
+            'duplicates': self.duplicate_filter.stats(),
+            'roles': self.roles.stats(),
+            'uptime_s': round(time.monotonic() - self.started_at, 1),
+            'connections': self.connections_accepted,
+        }
+
+    @command_handler('bench')
+    def _cmd_bench(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Send ``bytes`` of known data on the telemetry socket so the driver can measure throughput."""
+        try:
+            total = int(command.get('bytes'))
+        except (TypeError, ValueError):
+            return {'status': 'error', 'message': 'Missing or invalid bytes'}
+        if not 1 <= total <= BENCH_MAX_BYTES:
+            return {'status': 'error', 'message': f'bytes must be 1-{BENCH_MAX_BYTES}'}
+        if self.bench_thread is not None and self.bench_thread.is_alive():
+            return {'status': 'error', 'message': 'Benchmark already running'}
+        blocked = self._bench_blocked()
+        if blocked:
+            return {'status': 'error', 'message': f'Benchmark refused: {blocked}'}
+
+        self.bench_count += 1
+        self.bench_cancel.clear()
+        self.bench_thread = threading.Thread(target=self._run_bench, args=(self.bench_count, total),
+                                             daemon=True, name="bench")
+        self.bench_thread.start()
+        return {
+            'status': 'success',
+            'id': self.bench_count,
+            'bytes': total,
+            'chunks': math.ceil(total / BENCH_CHUNK_BYTES),
+            'chunk_bytes': BENCH_CHUNK_BYTES,
+        }
+
+    @command_handler('bench_stop')
+    def _cmd_bench_stop(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        running = self.bench_thread is not None and self.bench_thread.is_alive()
+        self.bench_cancel.set()
+        return {'status': 'success', 'stopped': running}
+
+    def _bench_blocked(self) -> Optional[str]:
+        """Why a benchmark can't run, or must stop, right now; None if it may go on."""
+        if connection_lost:
+            return 'link lost'
+        if any(self.velocity_target) or any(self.telemetry_data['motor_speeds']):
+            return 'robot driving'
+        return None
+
+    def _publish(self, frames: List[bytes]) -> bool:
+        """Queue one message on the telemetry socket without blocking; False if it wasn't."""
+        with self.telemetry_send_lock:
+            if self.telemetry_socket is None:
+                return False
+            try:
+                self.telemetry_socket.send_multipart(frames, zmq.NOBLOCK)
+            except zmq.Again:
+                return False
+        return True
+
+    def _run_bench(self, bench_id: int, total: int) -> None:
+        chunks = math.ceil(total / BENCH_CHUNK_BYTES)
+        pattern = bytes(range(256)) * (BENCH_CHUNK_BYTES // 256)
+        sent = 0
+        aborted = None
+        for seq in range(chunks):
+            aborted = 'stopped' if self.bench_cancel.is_set() or not self.running else self._bench_blocked()
+            if aborted:
+                break
+            size = min(BENCH_CHUNK_BYTES, total - sent)
+            header = {'id': bench_id, 'seq': seq, 'chunks': chunks, 'bytes': total}
+            if not self._publish([BENCH_TOPIC, json.dumps(header).encode(), pattern[:size]]):
+                aborted = 'send queue full'
+                break
+            sent += size
+            # Let telemetry (and everything else) in between chunks.
+            time.sleep(0)
+        # The closing frame says how much really went out, so the driver can tell loss from an abort.
+        self._publish([BENCH_TOPIC, json.dumps({'id': bench_id, 'done': True, 'sent_bytes': sent,
+                                                'aborted': aborted}).encode(), b""])
+        logger.info(f"Benchmark {bench_id}: sent {sent}/{total} bytes" + (f" (aborted: {aborted})" if aborted else ""))
+
+    @command_handler('dump')
+    def _cmd_dump(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        which = str(command.get('which', 'recent')).lower()
+        if which == 'failsafe':
+            if self.failsafe_snapshot is None:
+                return {'status': 'error', 'message': 'No failsafe has been recorded'}
+            return {'status': 'success', **self.failsafe_snapshot}
+        if which != 'recent':
+            return {'status': 'error', 'message': f'Invalid dump: {which}'}
+        return {'status': 'success', **self._snapshot_dump('recent')}
+
+    @command_handler('current_limit')
+    def _cmd_current_limit(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        motor = str(command.get('motor', 'all')).upper()
+        if motor == 'ALL':
+            motors = range(len(MOTOR_NAMES))
+        elif motor in MOTOR_NAMES:
+            motors = [MOTOR_NAMES.index(motor)]
+        else:
+            return {'status': 'error', 'message': f'Invalid motor: {motor}'}
+        amps = float(command.get('amps', DEFAULT_CURRENT_LIMIT_A))
+        if not math.isfinite(amps) or not 0.0 <= amps <= MAX_CURRENT_LIMIT_A:
+            return {'status': 'error', 'message': f'Current limit must be between 0 and {MAX_CURRENT_LIMIT_A} A'}
+
+        for i in motors:
+            self.current_limiter.limits_a[i] = amps
+        self.settings["current_limits"] = list(self.current_limiter.limits_a)
+        self._save_settings()
+        logger.info(f"Current limit for {motor} set to {amps} A")
+        return {'status': 'success', 'limits': dict(zip(MOTOR_NAMES, self.current_limiter.limits_a))}
+
+    @command_handler('motor_reverse')
+    def _cmd_motor_reverse(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Reverse one motor in software, e.g. to fix one wired backwards without rewiring."""
+        motor = command.get('motor')
+        if isinstance(motor, int) and not isinstance(motor, bool) and 0 <= motor < len(MOTOR_NAMES):
+            index = motor
+        elif isinstance(motor, str) and motor.upper() in MOTOR_NAMES:
+            index = MOTOR_NAMES.index(motor.upper())
+        else:
+            return {'status': 'error', 'message': f'Invalid motor: {motor}'}
+        reverse = command.get('reversed')
+        if not isinstance(reverse, bool):
+            return {'status': 'error', 'message': 'reversed must be true or false'}
+        if any(self.telemetry_data['motor_speeds']):
+            return {'status': 'error', 'message': 'Stop the robot before changing motor direction'}
+
+        self.motor_reversed[index] = reverse
+        self.telemetry_data['motor_reversed'] = list(self.motor_reversed)
+        self.settings["motor_reverse"] = list(self.motor_reversed)
+        self._save_settings()
+        logger.info(f"Motor {MOTOR_NAMES[index]} {'reversed' if reverse else 'normal'}")
+        return self._cmd_motor_reverse_query(command)
+
+    @command_handler('motor_reverse_query')
+    def _cmd_motor_reverse_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {'status': 'success', 'reversed': dict(zip(MOTOR_NAMES, self.motor_reversed))}
+
+    @command_handler('brake')
+    def _cmd_brake(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Choose whether motors told to stop brake or coast. Safety stops always brake."""
+        brake = command.get('enabled')
+        if not isinstance(brake, bool):
+            return {'status': 'error', 'message': 'enabled must be true or false'}
+        self._set_brake(brake)
+        self.telemetry_data['brake'] = brake
+        self.settings["brake"] = brake
+        self._save_settings()
+        logger.info(f"Motors {'brake' if brake else 'coast'} on stop")
+        return self._cmd_brake_query(command)
+
+    @command_handler('brake_query')
+    def _cmd_brake_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {'status': 'success', 'enabled': self.brake, 'coast_decay_per_s': COAST_DECAY_PER_S}
+
+    @staticmethod
+    def _servo_id(command: Dict[str, Any]) -> Optional[str]:
+        # Servos can be addressed by name or by index into SERVO_NAMES.
+        servo_id = command.get('id')
+        if isinstance(servo_id, int) and not isinstance(servo_id, bool) and 0 <= servo_id < len(SERVO_NAMES):
+            return SERVO_NAMES[servo_id]
+        if isinstance(servo_id, str) and servo_id.lower() in SERVO_LIMITS_DEG:
+            return servo_id.lower()
+        return None
+
+    @command_handler('servo')
+    def _cmd_servo(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        servo = self._servo_id(command)
+        if servo is None:
+            return {'status': 'error', 'message': f"Invalid servo: {command.get('id')}"}
+        angle = float(command.get('angle', SERVO_CENTER_DEG))
+        if not math.isfinite(angle):
+            return {'status': 'error', 'message': 'Servo angle must be finite'}
+        # Same gating as drive: only TELEOP moves anything, and pause holds it.
+        if self._drive_active():
+            self._set_servo(servo, angle)
+        return {'status': 'success', 'id': servo, 'angle': self.servo_angles[servo]}
+
+    @command_handler('servo_center')
+    def _cmd_servo_center(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if command.get('id') is None:
+            servos = SERVO_NAMES
+        else:
+            servo = self._servo_id(command)
+            if servo is None:
+                return {'status': 'error', 'message': f"Invalid servo: {command.get('id')}"}
+            servos = (servo,)
+        # Gated like 'servo': a stopped or paused robot keeps its gimbal where it is.
+        if self._drive_active():
+            self._center_servos(servos)
+            logger.info(f"Centered servos: {', '.join(servos)}")
+        return {'status': 'success', 'servos': dict(self.servo_angles)}
+
+    @command_handler('name')
+    def _cmd_name(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        try:
+            name = sanitize_robot_name(command.get('name'))
+        except ValueError as e:
             return {'status': 'error', 'message': str(e)}
+        self.robot_name = name
+        self.settings["name"] = name
+        self._save_settings()
+        logger.info(f"Robot name set to {name!r}")
+        return {'status': 'success', 'name': name}
+
+    @command_handler('name_query')
+    def _cmd_name_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {'status': 'success', 'name': self.robot_name}
+
+    @command_handler('current_limit_query')
+    def _cmd_current_limit_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'limits': dict(zip(MOTOR_NAMES, self.current_limiter.limits_a)),
+            'limited': dict(zip(MOTOR_NAMES, self.current_limiter.limited())),
+            'currents': dict(zip(MOTOR_NAMES, self.telemetry_data['motor_currents'])),
+        }
+
+    @command_handler('sensors_query')
+    def _cmd_sensors_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'inputs': dict(self.sensor_states),
+            'bits': sensor_bits(self.sensor_states),
+            'pins': dict(DIGITAL_INPUTS),
+            'subscribed': self.sensor_push,
+        }
+
+    @command_handler('sensors_subscribe')
+    def _cmd_sensors_subscribe(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Push telemetry as soon as a digital input changes, not just at the telemetry rate."""
+        enabled = command.get('enabled', True)
+        if not isinstance(enabled, bool):
+            return {'status': 'error', 'message': 'enabled must be true or false'}
+        self.sensor_push = enabled
+        logger.info(f"Sensor edge push {'enabled' if enabled else 'disabled'}")
+        return self._cmd_sensors_query(command)
+
+    @command_handler('pid_query')
+    def _cmd_pid_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'pid': {axis: pid.gains() for axis, pid in self.pid_controllers.items()},
+        }
+
+    @command_handler('shape')
+    def _cmd_shape(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Set the robot-side stick deadzone and/or expo; omitted fields keep their value."""
+        try:
+            deadzone = float(command.get('deadzone', self.input_shaping['deadzone']))
+            expo = float(command.get('expo', self.input_shaping['expo']))
+        except (TypeError, ValueError):
+            return {'status': 'error', 'message': 'deadzone and expo must be numbers'}
+        if not valid_input_shaping(deadzone, expo):
+            return {'status': 'error', 'message': f'deadzone must be 0-{MAX_INPUT_DEADBAND} and expo '
+                                                  f'{MIN_INPUT_EXPO}-{MAX_INPUT_EXPO}'}
+        self.input_shaping = {'deadzone': deadzone, 'expo': expo}
+        self.settings["input_shaping"] = dict(self.input_shaping)
+        self._save_settings()
+        logger.info(f"Input shaping set to deadzone={deadzone} expo={expo}")
+        return self._cmd_shape_query(command)
+
+    @command_handler('shape_query')
+    def _cmd_shape_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'shape': dict(self.input_shaping),
+            'max_deadzone': MAX_INPUT_DEADBAND,
+            'expo_range': [MIN_INPUT_EXPO, MAX_INPUT_EXPO],
+        }
+
+    def _loop_rate_report(self) -> Dict[str, float]:
+        return {'hz': self.output_rate_hz, 'achieved_hz': self.loop_timers['output'].snapshot()['hz']}
+
+    @command_handler('loop_rate')
+    def _cmd_loop_rate(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Set how often the control loop steps ramping, closed-loop PID and current limits."""
+        try:
+            hz = float(command.get('hz'))
+        except (TypeError, ValueError):
+            return {'status': 'error', 'message': 'Missing or invalid hz'}
+        if not valid_output_rate(hz):
+            return {'status': 'error', 'message': f'Loop rate must be {MIN_OUTPUT_RATE_HZ:.0f}-'
+                                                  f'{MAX_OUTPUT_RATE_HZ:.0f} Hz'}
+        self.output_rate_hz = hz
+        self.settings["output_rate_hz"] = hz
+        self._save_settings()
+        logger.info(f"Control loop rate set to {hz:.1f} Hz")
+        return self._cmd_loop_rate_query(command)
+
+    @command_handler('loop_rate_query')
+    def _cmd_loop_rate_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            **self._loop_rate_report(),
+            'range': [MIN_OUTPUT_RATE_HZ, MAX_OUTPUT_RATE_HZ],
+        }
+
+    def _config_keys(self) -> Dict[str, ConfigKey]:
+        """Every runtime setting by dotted key, e.g. accel.up, pid.forward.kp, motor_reverse.FL."""
+        keys = {
+            'name': ConfigKey("text", lambda: self.robot_name, lambda v: {'type': 'name', 'name': v}),
+            'brake': ConfigKey("bool", lambda: self.brake, lambda v: {'type': 'brake', 'enabled': v}),
+            'mix_mode': ConfigKey("text", lambda: self.mix_mode, lambda v: {'type': 'mix_mode', 'mode': v}),
+            'accel.up': ConfigKey("number", lambda: self.output_ramp.up, lambda v: {'type': 'accel', 'up': v}),
+            'accel.down': ConfigKey("number", lambda: self.output_ramp.down,
+                                    lambda v: {'type': 'accel', 'down': v}),
+            'shape.deadzone': ConfigKey("number", lambda: self.input_shaping['deadzone'],
+                                        lambda v: {'type': 'shape', 'deadzone': v}),
+            'shape.expo': ConfigKey("number", lambda: self.input_shaping['expo'],
+                                    lambda v: {'type': 'shape', 'expo': v}),
+            'loop_rate_hz': ConfigKey("number", lambda: self.output_rate_hz,
+                                      lambda v: {'type': 'loop_rate', 'hz': v}),
+            'geofence_m': ConfigKey("number?", lambda: self.geofence_m,
+                                    lambda v: {'type': 'geofence', 'radius_m': v}),
+            'failsafe_ms': ConfigKey("number", lambda: round(heartbeat_timeout_s * 1000.0),
+                                     lambda v: {'type': 'failsafe', 'ms': v}, persisted=False),
+            'telemetry_rate_hz': ConfigKey("number", lambda: self.telemetry_rate_hz,
+                                           lambda v: {'type': 'telemetry_rate', 'hz': v}, persisted=False),
+        }
+        for axis, pid in self.pid_controllers.items():
+            for gain in ('kp', 'ki', 'kd'):
+                keys[f'pid.{axis}.{gain}'] = ConfigKey("number", lambda p=pid, g=gain: p.gains()[g],
+                                                       lambda v, a=axis, g=gain: {'type': 'pid', 'axis': a, g: v})
+        unit_axes = ((1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0))
+        mix = list(zip(*(mix_motor_speeds(*axis) for axis in unit_axes)))
+        for index, motor in enumerate(MOTOR_NAMES):
+            keys[f'motor_reverse.{motor}'] = ConfigKey(
+                "bool", lambda i=index: self.motor_reversed[i],
+                lambda v, m=motor: {'type': 'motor_reverse', 'motor': m, 'reversed': v})
+            keys[f'current_limit.{motor}'] = ConfigKey(
+                "number", lambda i=index: self.current_limiter.limits_a[i],
+                lambda v, m=motor: {'type': 'current_limit', 'motor': m, 'amps': v})
+            # (forward, strafe, rotate) coefficients; the mecanum mix is fixed in code.
+            keys[f'mix.{motor}'] = ConfigKey("list", lambda row=mix[index]: list(row))
+        return keys
+
+    @command_handler('config_query')
+    def _cmd_config_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """The whole runtime configuration, for support and for reproducing a setup."""
+        keys = self._config_keys()
+        return {
+            'status': 'success',
+            'config': {key: entry.read() for key, entry in sorted(keys.items())},
+            'read_only': sorted(key for key, entry in keys.items() if entry.command is None),
+            'volatile': sorted(key for key, entry in keys.items() if not entry.persisted),
+        }
+
+    @command_handler('config')
+    def _cmd_config(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Set any one configuration key through the command that owns it."""
+        key = command.get('key')
+        entry = self._config_keys().get(key) if isinstance(key, str) else None
+        if entry is None:
+            return {'status': 'error', 'message': f'Unknown config key: {key}'}
+        if entry.command is None:
+            return {'status': 'error', 'message': f'{key} is read-only'}
+        value = command.get('value')
+        if not config_value_ok(entry.kind, value):
+            expected = {'number?': 'a number or null', 'bool': 'true or false', 'text': 'a string'}
+            return {'status': 'error', 'message': f"{key} must be {expected.get(entry.kind, 'a number')}"}
+        response = self._dispatch_command(entry.command(value))
+        if response.get('status') != 'success':
+            return {'status': 'error', 'key': key, 'message': response.get('message', 'rejected')}
+        return {'status': 'success', 'key': key, 'value': entry.read(), 'persisted': entry.persisted}
+
+    @command_handler('telemetry_query')
+    def _cmd_telemetry_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """One current telemetry frame in the reply, for drivers that pull instead of streaming.
+
+        Independent of the telemetry stream and its rate; works with or without subscribers.
+        """
+        # Copied under telemetry_lock so the sensor and telemetry threads can't change it
+        # mid-copy, and round-tripped so later writes don't reach the reply.
+        with self.telemetry_lock:
+            self._refresh_telemetry()
+            telemetry = json.loads(json.dumps(self.telemetry_data))
+        return {'status': 'success', 'telemetry': telemetry}
+
+    @command_handler('accel_query')
+    def _cmd_accel_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {'status': 'success', 'accel': self.output_ramp.limits()}
+
+    @command_handler('failsafe')
+    def _cmd_failsafe(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Set how long the watchdog waits without a command before stopping the motors."""
+        global heartbeat_timeout_s
+        try:
+            timeout_s = float(command.get('ms')) / 1000.0
+        except (TypeError, ValueError):
+            return {'status': 'error', 'message': 'Missing or invalid ms'}
+        if not MIN_HEARTBEAT_TIMEOUT_S <= timeout_s <= MAX_HEARTBEAT_TIMEOUT_S:
+            return {'status': 'error', 'message': f'Failsafe timeout must be {MIN_HEARTBEAT_TIMEOUT_S * 1000:.0f}-'
+                                                  f'{MAX_HEARTBEAT_TIMEOUT_S * 1000:.0f} ms'}
+        with heartbeat_lock:
+            heartbeat_timeout_s = timeout_s
+        logger.info(f"Failsafe timeout set to {timeout_s * 1000:.0f} ms")
+        return self._cmd_failsafe_query(command)
+
+    @command_handler('failsafe_query')
+    def _cmd_failsafe_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'ms': round(heartbeat_timeout_s * 1000.0),
+            'min_ms': round(MIN_HEARTBEAT_TIMEOUT_S * 1000.0),
+            'max_ms': round(MAX_HEARTBEAT_TIMEOUT_S * 1000.0),
+        }
+
+    @command_handler('telemetry_rate')
+    def _cmd_telemetry_rate(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Set the rate of the telemetry stream, which every subscriber shares.
+
+        There is one PUB socket, so the rate is global rather than per connection and the last
+        request wins. Spotters are refused (it isn't in SPOTTER_COMMANDS), so only the driver sets it.
+        """
+        hz = float(command.get('hz', TELEMETRY_RATE_HZ))
+        self.telemetry_rate_hz = max(MIN_TELEMETRY_RATE_HZ, min(MAX_TELEMETRY_RATE_HZ, hz))
+        logger.info(f"Telemetry rate set to {self.telemetry_rate_hz:.1f} Hz by {command.get('client_id', 'unknown')}")
+        return {'status': 'success', 'hz': self.telemetry_rate_hz}
+
+    @command_handler('sequence_list')
+    def _cmd_sequence_list(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            'status': 'success',
+            'sequences': {name: round(sum(s["duration_s"] for s in steps), 2) for name, steps in self.sequences.items()},
+        }
+
+    @command_handler('sequence_define')
+    def _cmd_sequence_define(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Store or replace a sequence; ``steps`` of null deletes it."""
+        name = command.get('name')
+        if not isinstance(name, str) or not SEQUENCE_NAME_PATTERN.match(name):
+            return {'status': 'error', 'message': 'Sequence name must be 1-32 letters, digits, _ . or -'}
+        if self.sequence_run is not None and self.sequence_run.name == name:
+            return {'status': 'error', 'message': f'Sequence {name} is running'}
+        if 'steps' not in command:
+            return {'status': 'error', 'message': 'Missing steps (null deletes the sequence)'}
+        if command['steps'] is None:
+            self.sequences.pop(name, None)
+        else:
+            try:
+                self.sequences[name] = parse_sequence(command.get('steps'))
+            except (TypeError, ValueError) as e:
+                return {'status': 'error', 'message': str(e)}
+        self.settings["sequences"] = self.sequences
+        self._save_settings()
+        return {'status': 'success', 'sequences': sorted(self.sequences)}
+
+    @command_handler('run_sequence')
+    def _cmd_run_sequence(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        name = command.get('name')
+        steps = self.sequences.get(name) if isinstance(name, str) else None
+        if steps is None:
+            return {'status': 'error', 'message': f'Unknown sequence: {name}'}
+        if not self._drive_active():
+            return {'status': 'error', 'message': 'Sequences only run in TELEOP while connected and not paused'}
+        # Safe start: never override a driver who is still holding a stick.
+        if self.resume_needs_center or any(self.velocity_target):
+            return {'status': 'error', 'message': 'Center the sticks before starting a sequence'}
+        self._end_sequence('aborted', f'replaced by {name}')
+        self._end_turn('aborted', f'replaced by sequence {name}')
+        self.sequence_run = SequenceRun(name, steps, time.monotonic())
+        self.telemetry_data['sequence'] = self.sequence_run.status('running', 0.0, 0)
+        logger.info(f"Running sequence {name} ({len(steps)} steps)")
+        return {'status': 'success', 'sequence': name}
+
+    @command_handler('sequence_stop')
+    def _cmd_sequence_stop(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        if self.sequence_run is not None:
+            self._end_sequence('aborted', 'stopped by operator')
+            self._hold_still()
+        return {'status': 'success'}
+
+    @command_handler('turn')
+    def _cmd_turn(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Rotate in place by ``degrees`` relative to the current odometry heading."""
+        try:
+            degrees = float(command.get('degrees'))
+        except (TypeError, ValueError):
+            return {'status': 'error', 'message': 'Missing or invalid degrees'}
+        if not math.isfinite(degrees) or abs(degrees) > TURN_MAX_DEG:
+            return {'status': 'error', 'message': f'Turn must be within ±{TURN_MAX_DEG:g} degrees'}
+        if not self._drive_active():
+            return {'status': 'error', 'message': 'Turns only run in TELEOP while connected and not paused'}
+        # Safe start, as for sequences. A running turn or sequence may be replaced.
+        driver_holding = any(self.velocity_target) and self.turn_run is None and self.sequence_run is None
+        if self.resume_needs_center or driver_holding:
+            return {'status': 'error', 'message': 'Center the sticks before starting a turn'}
+        self._end_turn('aborted', 'replaced by a new turn')
+        self._end_sequence('aborted', 'replaced by a turn')
+        self.turn_run = TurnRun(degrees, self.pose_theta_deg, time.monotonic())
+        self.telemetry_data['turn'] = self.turn_run.status('running')
+        logger.info(f"Turning {degrees:.1f} deg")
+        return {'status': 'success', 'turn': degrees}
+
+    @command_handler('geofence')
+    def _cmd_geofence(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Set the geofence radius in meters; a radius_m of null or "off" removes it."""
+        radius = command.get('radius_m')
+        if radius is None or str(radius).lower() == 'off':
+            self.geofence_m = None
+        else:
+            try:
+                radius = float(radius)
+            except (TypeError, ValueError):
+                return {'status': 'error', 'message': 'radius_m must be a number, null or "off"'}
+            if not math.isfinite(radius) or radius <= 0:
+                return {'status': 'error', 'message': 'Geofence radius must be positive'}
+            self.geofence_m = radius
+        self.geofence_tripped = False
+        self.telemetry_data['geofence'] = {'radius_m': self.geofence_m, 'tripped': False}
+        self.settings["geofence_m"] = self.geofence_m
+        self._save_settings()
+        logger.info(f"Geofence {'off' if self.geofence_m is None else f'set to {self.geofence_m:.2f} m'}")
+        return {'status': 'success', 'radius_m': self.geofence_m}
+
+    @command_handler('drive_mode')
+    def _cmd_drive_mode(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        mode = str(command.get('mode', '')).upper()
+        if mode not in DRIVE_MODES:
+            return {'status': 'error', 'message': f'Invalid drive mode: {mode}'}
+        if mode == "CLOSED" and self.read_velocity() is None:
+            return {'status': 'error', 'message': 'Closed loop needs velocity feedback and none is available'}
+        self._set_drive_mode(mode)
+        return {'status': 'success', 'drive_mode': self.drive_mode}
+
+    @command_handler('mix_mode')
+    def _cmd_mix_mode(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        """Choose how saturated mecanum outputs are brought back within range; see MIX_MODES."""
+        mode = str(command.get('mode', '')).lower()
+        if mode not in MIX_MODES:
+            return {'status': 'error', 'message': f"Invalid mix mode: {mode} (one of {', '.join(MIX_MODES)})"}
+        self.mix_mode = mode
+        self.telemetry_data['mix_mode'] = mode
+        self.settings["mix_mode"] = mode
+        self._save_settings()
+        if self.drive_mode == "OPEN" and self._drive_active():
+            self._drive_motors(self._open_loop_speeds())
+        logger.info(f"Mix mode set to {mode}")
+        return self._cmd_mix_mode_query(command)
+
+    @command_handler('mix_mode_query')
+    def _cmd_mix_mode_query(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        return {'status': 'success', 'mode': self.mix_mode, 'modes': list(MIX_MODES)}
+
+    @command_handler('odometry_mode')
+    def _cmd_odometry_mode(self, command: Dict[str, Any]) -> Dict[str, Any]:
+        mode = str(command.get('mode', 'PRE_START')).upper()
+        if mode in VALID_ODOMETRY_MODES:
+            self.odometry_mode = mode
+            self.telemetry_data['odometry_mode'] = self.odometry_mode
+            return {'status': 'success', 'odometry_mode': self.odometry_mode}
+        return {'status': 'error', 'message': f'Invalid odometry mode: {mode}'}
     
+    def _log_command(self, command: Dict[str, Any]) -> None:
+        if self.command_logger is not None:
+            self.command_logger.log(command)
+
     def command_loop(self) -> None:
         """Handle incoming commands"""
         logger.info("Command handler ready")
         
         while self.running:
             try:
-                command = self.command_socket.recv_json()
-                response = self.handle_command(command)
-                self.command_socket.send_json(response)
+                frame = self.command_socket.recv()
+            except zmq.Again:
+                continue
+            except zmq.ZMQError as e:
+                logger.error(f"Command loop error: {e}")
+                continue
+
+            try:
+                command = decode_command(frame)
+                self._log_command(command)
+                with self.command_lock:
+                    response = self.handle_command(command)
+            except MalformedCommand as e:
+                logger.warning(f"Rejected malformed command: {e}")
+                response = {'status': 'error', 'message': str(e)}
             except Exception as e:
                 logger.error(f"Command loop error: {e}")
-                try:
-                    self.command_socket.send_json({
-                        'status': 'error',
-                        'message': str(e)
-                    })
-                except Exception:
-                    pass
+                response = {'status': 'error', 'message': str(e)}
+
+            # REP must answer every request or the socket wedges.
+            try:
+                self.command_socket.send_json(response)
+            except Exception as e:
+                logger.error(f"Failed to send reply: {e}")
     
+    def drive_loop(self) -> None:
+        """Apply fire-and-forget drive frames; liveness comes from pings on the REP socket."""
+        logger.info(f"Unacked drive channel ready on port {DRIVE_PORT}")
+
+        while self.running:
+            try:
+                command = decode_command(self.drive_socket.recv())
+                self._log_command(command)
+                if command.get('type') not in UNACKED_COMMANDS:
+                    logger.warning(f"Ignoring {command.get('type')} on unacked drive channel")
+                    continue
+                with self.command_lock:
+                    response = self.handle_command(command)
+                if response.get('status') != 'success':
+                    logger.warning(f"Drive frame rejected: {response.get('message')}")
+            except zmq.Again:
+                continue
+            except Exception as e:
+                logger.error(f"Drive loop error: {e}")
+
     def telemetry_loop(self) -> None:
         """Broadcast telemetry"""
         logger.info("Telemetry broadcaster ready")
         
         while self.running:
+            self.loop_timers['telemetry'].tick()
             try:
                 # TODO: Update with real sensor data
                 # self.telemetry_data['battery'] = read_battery()
                 # self.telemetry_data['sensors']['ultrasonic'] = read_ultrasonic()
                 
-                self.telemetry_data['timestamp'] = time.time()
-                self.telemetry_data['mode'] = robot_mode
-                self.telemetry_data['odometry_mode'] = self.odometry_mode
-                self._update_telemetry_pose()
-                
-                self.telemetry_socket.send_json(self.telemetry_data)
-                time.sleep(1.0 / TELEMETRY_RATE_HZ)
+                with self.telemetry_lock:
+                    self._refresh_telemetry()
+                    self.telemetry_wake.clear()
+                    self.telemetry_data['telemetry_seq'] = self.telemetry_seq
+                    self.telemetry_seq = (self.telemetry_seq + 1) % TELEMETRY_SEQ_MODULUS
+                    frame = json.dumps(self.telemetry_data).encode()
+                self._publish([frame])
+                # Sleeps out the period unless a subscribed sensor edge needs sending now.
+                self.telemetry_wake.wait(1.0 / self.telemetry_rate_hz)
                 
             except Exception as e:
                 logger.error(f"Telemetry error: {e}")
     
-    def start(self) -> None:
-        """Start server threads"""
+    def _poll_sensors(self) -> bool:
+        """Read the digital inputs into telemetry. True if any changed since the last read."""
+        try:
+            raw = self.read_inputs()
+        except Exception as e:
+            logger.error(f"Failed to read sensors: {e}")
+            return False
+        states = {name: bool(raw.get(name, False)) for name in DIGITAL_INPUTS}
+        if states == self.sensor_states:
+            return False
+        for name, state in states.items():
+            if state != self.sensor_states[name]:
+                logger.debug(f"Sensor {name} -> {'on' if state else 'off'}")
+        self.sensor_states = states
+        with self.telemetry_lock:
+            self.telemetry_data['sensors']['digital'] = dict(states)
+            self.telemetry_data['sensors']['digital_bits'] = sensor_bits(states)
+        return True
+
+    def _poll_imu(self) -> None:
+        """Read the IMU into telemetry and apply the tilt reaction."""
+        try:
+            reading = self.read_orientation()
+        except Exception as e:
+            logger.error(f"Failed to read IMU: {e}")
+            reading = None
+        imu = None
+        if reading is not None:
+            try:
+                roll, pitch, yaw = (float(v) for v in reading)
+            except (TypeError, ValueError):
+                logger.error(f"Ignoring malformed IMU reading {reading!r}")
+            else:
+                if all(math.isfinite(v) for v in (roll, pitch, yaw)):
+                    imu = {'roll': roll, 'pitch': pitch, 'yaw': yaw % 360.0}
+        with self.command_lock, self.telemetry_lock:
+            self.imu = imu
+            self.telemetry_data['imu'] = imu
+            self._check_tilt()
+
+    def sensor_loop(self) -> None:
+        """Poll the digital inputs and IMU at SENSOR_POLL_HZ, pushing edges to a subscribed driver."""
+        while self.running:
+            self.loop_timers['sensors'].tick()
+            if self._poll_sensors() and self.sensor_push:
+                self.telemetry_wake.set()
+            self._poll_imu()
+            time.sleep(1.0 / SENSOR_POLL_HZ)
+
+    def _snapshot_sample(self) -> Dict[str, Any]:
+        return {
+            't': time.time(),
+            'mode': robot_mode,
+            'paused': self.paused,
+            'park': self.park_state,
+            'connection_lost': connection_lost,
+            'motor_speeds': list(self.telemetry_data['motor_speeds']),
+            'battery': self.telemetry_data['battery'],
+            'derate': self.telemetry_data['derate'],
+            'derate_axes': dict(self.telemetry_data['derate_axes']),
+            'pose': {'x': self.pose_x_m, 'y': self.pose_y_m, 'theta_deg': self.pose_theta_deg},
+        }
+
+    def _snapshot_dump(self, reason: str) -> Dict[str, Any]:
+        return {'reason': reason, 'rate_hz': SNAPSHOT_RATE_HZ, 'samples': list(self.snapshots)}
+
+    def snapshot_loop(self) -> None:
+        """Record recent internal state at SNAPSHOT_RATE_HZ for the 'dump' command."""
+        was_lost = connection_lost
+        while self.running:
+            self.loop_timers['snapshot'].tick()
+            self.snapshots.append(self._snapshot_sample())
+            if connection_lost and not was_lost:
+                # Freeze the lead-up to the failsafe before it scrolls out of the window.
+                self.failsafe_snapshot = self._snapshot_dump('failsafe')
+                logger.info(f"Captured {len(self.snapshots)} samples before failsafe")
+            was_lost = connection_lost
+            time.sleep(1.0 / SNAPSHOT_RATE_HZ)
+
+    def output_loop(self) -> None:
+        """Step ramped outputs toward the drive target and keep current limits applied."""
+        last_step = next_tick = time.monotonic()
+        was_lost = connection_lost
+        while self.running:
+            self.loop_timers['output'].tick()
+            now = time.monotonic()
+            with self.command_lock:
+                if was_lost and not connection_lost and robot_mode != "STOPPED":
+                    self._start_arming("link restored")
+                was_lost = connection_lost
+                self.telemetry_data['arming_s'] = round(self._arming_remaining(), 1)
+                if self.park_state == "PARKING":
+                    self._step_park(now)
+                elif self._drive_active():
+                    if self.sequence_run is not None:
+                        self._step_sequence(now)
+                    if self.turn_run is not None:
+                        self._step_turn(now)
+                    # A held stick sends no new frames, so re-check the fence as the pose moves.
+                    if self._enforce_geofence(may_clear=False):
+                        # Odometry (and the simulator) latch the last input; stop the translation there too.
+                        self._integrate_pose(0.0, 0.0, self.velocity_target[2] * self._derate_factors()[2])
+                        if self.drive_mode == "OPEN":
+                            self._drive_motors(self._open_loop_speeds())
+                    if self.drive_mode == "CLOSED":
+                        self._closed_loop_step(now - last_step)
+                    if self.output_ramp.enabled():
+                        motor_speeds = self.output_ramp.step(now - last_step)
+                    else:
+                        motor_speeds = list(self.output_ramp.target)
+                    self._write_motors(motor_speeds, only_if_changed=True)
+                else:
+                    # The watchdog's all_stop() doesn't take command_lock, so a write from this
+                    # loop can land after its zero; hold the motors at zero here as well.
+                    self._write_motors(ZERO_MOTOR_SPEEDS.copy(), only_if_changed=True)
+                    self.output_ramp.reset()
+                    if connection_lost and self.button_mask:
+                        # The driver can't release what it was holding, so do it for them.
+                        self._set_button_mask(0)
+                    self._end_sequence('aborted', 'drive stopped')
+                    self._end_turn('aborted', 'drive stopped')
+                self.telemetry_data['loop_tick'] += 1
+            last_step = now
+            # Sleep to the next tick rather than a full period so the loop's own work doesn't
+            # drag the rate down; after an overrun, start again from now instead of bursting.
+            next_tick = max(next_tick + 1.0 / self.output_rate_hz, time.monotonic())
+            time.sleep(max(0.0, next_tick - time.monotonic()))
+
+    def _start_critical_thread(self, target, name: str) -> threading.Thread:
+        """Run a thread the server can't operate without; if it dies, shut the server down."""
+        def run():
+            try:
+                target()
+                if self.running:
+                    self.fatal_error = f"{name} thread exited unexpectedly"
+            except Exception as e:
+                logger.critical(f"{name} thread crashed: {e}", exc_info=True)
+                self.fatal_error = f"{name} thread crashed: {e}"
+            if self.fatal_error:
+                self._stop_drive()
+                self.running = False
+
+        thread = threading.Thread(target=run, daemon=True, name=name)
+        thread.start()
+        return thread
+
+    def start(self) -> int:
+        """Start server threads. Returns a process exit code."""
         self.start_camera_broadcast()
 
         # Start watchdog
-        watchdog = threading.Thread(target=watchdog_thread, daemon=True)
-        watchdog.start()
+        self._start_critical_thread(watchdog_thread, "watchdog")
         
         # Start telemetry
-        telemetry_thread = threading.Thread(target=self.telemetry_loop, daemon=True)
-        telemetry_thread.start()
+        self._start_critical_thread(self.telemetry_loop, "telemetry")
+        self._start_critical_thread(self.output_loop, "output")
+        self._start_critical_thread(self.snapshot_loop, "snapshot")
+
+        # Losing the switches shouldn't take down driving, so this one isn't critical.
+        threading.Thread(target=self.sensor_loop, daemon=True, name="sensors").start()
+
+        if self.drive_socket is not None:
+            drive_thread = threading.Thread(target=self.drive_loop, daemon=True)
+            drive_thread.start()
         
         # Run command handler in main thread
         try:
@@ -478,6 +3137,11 @@ class RobotServer:
         except KeyboardInterrupt:
             logger.info("Server shutdown requested")
             self.running = False
+
+        if self.fatal_error:
+            logger.critical(f"Robot server stopping: {self.fatal_error}")
+            return 1
+        return 0
     
     def cleanup(self) -> None:
         """Clean up resources"""
@@ -486,8 +3150,11 @@ class RobotServer:
             ensure_motor_controller().stop()
         except Exception as e:
             logger.error(f"Failed to stop motors during cleanup: {e}")
-        self.command_socket.close()
-        self.telemetry_socket.close()
+        for socket in (self.command_socket, self.telemetry_socket, self.drive_socket):
+            if socket is not None:
+                socket.close()
+        if self.command_logger is not None:
+            self.command_logger.stop()
         self.context.term()
 
 
@@ -496,13 +3163,22 @@ def main():
     """Start the robot server"""
     logger.info("Starting robot server...")
     
-    server = RobotServer()
+    try:
+        server = RobotServer()
+    except BindAddressError as e:
+        logger.critical(f"Invalid KSU_BIND_ADDRESS: {e}")
+        sys.exit(1)
+    except zmq.ZMQError as e:
+        logger.critical(f"Failed to bind robot sockets (is another server running?): {e}")
+        sys.exit(1)
     
+    exit_code = 1
     try:
-        server.start()
+        exit_code = server.start()
     finally:
         server.cleanup()
         logger.info("Robot server stopped")
+    sys.exit(exit_code)
 
 
 if __name__ == "__main__":
@@ -0,0 +1,108 @@
+"""Headless robot simulator that speaks the same ZMQ protocol as robot.py.
+
+Runs the real RobotServer command handling without hardware or camera, but
+integrates the last drive command continuously and drains a simulated battery
+so the driver station has live odometry/battery telemetry to display.
+"""
+
+import logging
+import os
+import sys
+import threading
+import time
+from typing import List
+
+import zmq
+
+# Must be set before importing robot so its module-level config picks it up.
+os.environ.setdefault("KSU_ENABLE_CAMERA_BROADCAST", "0")
+
+import robot
+
+SIM_RATE_HZ = 50
+SIM_BATTERY_FULL_V = 12.6
+SIM_BATTERY_EMPTY_V = 10.5
+# Volts drained per second at full output on all four motors.
+SIM_BATTERY_DRAIN_V_PER_S = 0.01
+# Measured chassis velocity lags the motor outputs with this time constant.
+SIM_DRIVE_TIME_CONSTANT_S = 0.15
+
+logger = logging.getLogger("sim")
+
+
+class SimRobotServer(robot.RobotServer):
+    """RobotServer whose pose and battery evolve in a background physics loop."""
+
+    def __init__(self):
+        super().__init__()
+        self.sim_lock = threading.Lock()
+        self.sim_inputs = (0.0, 0.0, 0.0)
+        self.telemetry_data['battery'] = SIM_BATTERY_FULL_V
+        self.loop_timers['physics'] = robot.LoopTimer()
+        # Simulated encoders, so closed-loop drive can be exercised without hardware.
+        self.sim_velocity = (0.0, 0.0, 0.0)
+        self.read_velocity = lambda: self.sim_velocity
+        # A level robot whose IMU heading tracks the simulated pose.
+        self.read_orientation = lambda: (0.0, 0.0, self.pose_theta_deg)
+
+    def _integrate_pose(self, lx: float, ly: float, rx: float) -> None:
+        # Command handling only latches the input; the physics loop integrates it.
+        with self.sim_lock:
+            self.sim_inputs = (lx, ly, rx)
+
+    def _step(self) -> None:
+        with self.sim_lock:
+            if not self._drive_active():
+                self.sim_inputs = (0.0, 0.0, 0.0)
+                self.telemetry_data['motor_speeds'] = robot.ZERO_MOTOR_SPEEDS.copy()
+            lx, ly, rx = self.sim_inputs
+            super()._integrate_pose(lx, ly, rx)
+
+        speeds: List[float] = self.telemetry_data['motor_speeds']
+        alpha = min(1.0, 1.0 / (SIM_RATE_HZ * SIM_DRIVE_TIME_CONSTANT_S))
+        self.sim_velocity = tuple(
+            v + alpha * (target - v) for v, target in zip(self.sim_velocity, robot.unmix_motor_speeds(speeds))
+        )
+        load = sum(abs(s) for s in speeds) / len(speeds)
+        battery = float(self.telemetry_data['battery']) - (load * SIM_BATTERY_DRAIN_V_PER_S / SIM_RATE_HZ)
+        self.telemetry_data['battery'] = max(SIM_BATTERY_EMPTY_V, battery)
+
+    def _reset_pose(self) -> None:
+        super()._reset_pose()
+        with self.sim_lock:
+            self.sim_inputs = (0.0, 0.0, 0.0)
+
+    def physics_loop(self) -> None:
+        logger.info(f"Simulator physics running at {SIM_RATE_HZ} Hz")
+        while self.running:
+            self.loop_timers['physics'].tick()
+            self._step()
+            time.sleep(1.0 / SIM_RATE_HZ)
+
+    def start(self) -> int:
+        self._start_critical_thread(self.physics_loop, "sim-physics")
+        return super().start()
+
+
+def main():
+    logger.info("Starting headless robot simulator...")
+    try:
+        server = SimRobotServer()
+    except robot.BindAddressError as e:
+        logger.critical(f"Invalid KSU_BIND_ADDRESS: {e}")
+        sys.exit(1)
+    except zmq.ZMQError as e:
+        logger.critical(f"Failed to bind simulator sockets (is a robot server running?): {e}")
+        sys.exit(1)
+
+    exit_code = 1
+    try:
+        exit_code = server.start()
+    finally:
+        server.cleanup()
+        logger.info("Robot simulator stopped")
+    sys.exit(exit_code)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,58 @@
+"""Shared setup for the scripts in test/.
+
+Importing this puts lib/, src/Robot and src/DriverStation on sys.path and keeps
+an in-process RobotServer away from the camera, the settings file and the
+command log. Scripts then collect failures with Checks and drive the robot
+with send():
+
+    from _harness import Checks, send
+    import robot
+
+    check = Checks()
+    check(reply.get("status") == "success", f"mode: {reply}")
+    return check.report("mode changes accepted")
+"""
+
+import itertools
+import os
+import sys
+from pathlib import Path
+
+os.environ["KSU_ENABLE_CAMERA_BROADCAST"] = "0"
+os.environ["KSU_ROBOT_SETTINGS_FILE"] = ""
+os.environ["KSU_COMMAND_LOG_PATH"] = ""
+
+ROOT = Path(__file__).resolve().parent.parent
+for _path in (ROOT / "src" / "DriverStation", ROOT / "src" / "Robot", ROOT / "lib"):
+    if str(_path) not in sys.path:
+        sys.path.insert(0, str(_path))
+
+_seq = itertools.count()
+
+
+def send(server, command_type, client_id="harness", **fields):
+    """Hand one command to server.handle_command with a fresh sequence number."""
+    return server.handle_command({"type": command_type, "client_id": client_id, "seq": next(_seq), **fields})
+
+
+class Checks:
+    """Collects failed checks so a script reports every one, not just the first."""
+
+    def __init__(self):
+        self.failures = []
+
+    def __call__(self, condition, message):
+        if not condition:
+            self.failures.append(message)
+
+    def fail(self, message):
+        self.failures.append(message)
+
+    def report(self, ok_message):
+        """Print the failures, or ok_message if there were none; returns the exit status."""
+        for message in self.failures:
+            print(f"FAIL: {message}")
+        if self.failures:
+            return 1
+        print(f"OK: {ok_message}")
+        return 0
@@ -0,0 +1,77 @@
+"""Check the arming countdown that holds the robot still after it is armed.
+
+Drives RobotServer without sockets, with KSU_ARM_COUNTDOWN_S patched in:
+
+    python test/arming_countdown.py
+
+Asserts that going from STOPPED to TELEOP starts the countdown and reports it
+in the reply, telemetry and control state, that joystick frames are ignored
+until it ends, that the sticks must then be centered before the robot drives,
+that a driver saying hello to an armed robot restarts it, and that stopping
+cancels it. Then checks a countdown of 0 (the default) skips arming entirely.
+Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+import time
+
+from _harness import Checks, send
+import robot
+
+COUNTDOWN_S = 3.0
+
+
+def push(server):
+    """Send a forward stick frame and return the reply."""
+    return send(server, "joystick", ly=0.5 * robot.JOYSTICK_Y_SIGN)
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    check = Checks()
+
+    robot.ARM_COUNTDOWN_S = COUNTDOWN_S
+    server = robot.RobotServer(bind=False)
+    try:
+        reply = send(server, "mode", mode="TELEOP")
+        check(reply.get("status") == "success" and 0 < reply.get("arming_s", 0) <= COUNTDOWN_S,
+              f"arming TELEOP: {reply}")
+        check(server.telemetry_data["arming_s"] == COUNTDOWN_S,
+              f"telemetry arming_s {server.telemetry_data['arming_s']}")
+        check("ARMING" in server.control_state()["summary"], f"control state {server.control_state()}")
+        reply = push(server)
+        check("arming_s" in reply and server.velocity_target == (0.0, 0.0, 0.0),
+              f"joystick during the countdown: {reply}, target {server.velocity_target}")
+
+        # Countdown over: a stick already pushed must come back to center first.
+        server.armed_at = time.monotonic()
+        push(server)
+        check(server.velocity_target == (0.0, 0.0, 0.0), f"drove off a held stick: {server.velocity_target}")
+        send(server, "joystick")
+        push(server)
+        check(server.velocity_target[0] > 0, f"centered then pushed, target {server.velocity_target}")
+
+        send(server, "hello", client_id="second")
+        check(server._arming_remaining() > 0 and server.velocity_target == (0.0, 0.0, 0.0),
+              "a driver connecting to an armed robot did not restart the countdown")
+        send(server, "mode", mode="STOPPED")
+        check(server._arming_remaining() == 0.0, "stopping left the countdown running")
+    finally:
+        server.cleanup()
+
+    robot.ARM_COUNTDOWN_S = 0.0
+    server = robot.RobotServer(bind=False)
+    try:
+        reply = send(server, "mode", mode="TELEOP")
+        check(reply.get("arming_s") == 0.0, f"countdown 0 still armed: {reply}")
+        push(server)
+        check(server.velocity_target[0] > 0, f"countdown 0 held the robot: {server.velocity_target}")
+    finally:
+        server.cleanup()
+
+    return check.report("motion held through the countdown, then safe start; countdown 0 skips it")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,84 @@
+"""Check the low-battery derate, with its own curve per drive axis.
+
+Calls robot.derate_motor_speeds directly and drives RobotServer without sockets:
+
+    python test/battery_derate.py
+
+Asserts that equal factors scale every motor alike, that unequal factors scale
+each axis of the mixed output by its own factor without undoing the mix's
+desaturation, and that a sagging battery derates joystick driving per axis and
+reports the factors in telemetry ('derate' being the tightest). Then checks
+that a stop and the connection-loss failsafe still zero the motors while
+derated. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+
+def close(a, b):
+    return all(abs(x - y) < 1e-9 for x, y in zip(a, b))
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    check = Checks()
+
+    mixed = robot.mix_motor_speeds(0.5, 0.2, 0.3)
+    speeds = robot.derate_motor_speeds(mixed, (0.6, 0.6, 0.6))
+    check(close(speeds, [s * 0.6 for s in mixed]), f"equal factors gave {speeds}")
+    speeds = robot.derate_motor_speeds(mixed, (1.0, 1.0, 0.5))
+    check(close(robot.unmix_motor_speeds(speeds), (0.5, 0.2, 0.15)), f"rotate-only derate gave {speeds}")
+    # Half of each axis saturates the mix; the derate works on the desaturated output.
+    for mode in robot.MIX_MODES:
+        mixed = robot.mix_motor_speeds(0.5, 0.5, 0.5, mode)
+        forward, strafe, rotate = robot.unmix_motor_speeds(mixed)
+        speeds = robot.derate_motor_speeds(mixed, (1.0, 0.5, 0.25))
+        check(close(robot.unmix_motor_speeds(speeds), (forward, strafe * 0.5, rotate * 0.25)),
+              f"{mode}: derated {mixed} to {speeds}")
+        check(max(abs(s) for s in speeds) <= 1.0, f"{mode}: derate left [-1, 1]: {speeds}")
+
+    written = []
+    robot.set_motor_speeds = lambda speeds: written.append(list(speeds))
+    curves = robot.BATTERY_DERATE_CURVES
+    robot.BATTERY_DERATE_CURVES = {axis: robot.parse_derate_curve(spec) for axis, spec in
+                                   (("forward", "11.4:1.0,10.8:0.8"), ("strafe", "11.4:1.0,10.8:0.6"),
+                                    ("rotate", "11.4:1.0,10.8:0.5"))}
+    server = robot.RobotServer(bind=False)
+    try:
+        send(server, "mode", mode="TELEOP")
+        send(server, "shape", deadzone=0.0, expo=1.0)
+        server.telemetry_data["battery"] = 12.0
+        send(server, "joystick", ly=0.5 * robot.JOYSTICK_Y_SIGN, rx=0.4)
+        check(server.telemetry_data["derate"] == 1.0, f"full battery derated to {server.telemetry_data['derate']}")
+        check(close(robot.unmix_motor_speeds(server.telemetry_data["motor_speeds"]), (0.5, 0.0, 0.4)),
+              f"full battery drove {server.telemetry_data['motor_speeds']}")
+
+        server.telemetry_data["battery"] = 10.8
+        send(server, "joystick", ly=0.4 * robot.JOYSTICK_Y_SIGN, lx=0.3, rx=0.2)
+        check(server.telemetry_data["derate_axes"] == {"forward": 0.8, "strafe": 0.6, "rotate": 0.5},
+              f"derate_axes {server.telemetry_data['derate_axes']}")
+        check(server.telemetry_data["derate"] == 0.5,
+              f"derate {server.telemetry_data['derate']}, expected the tightest")
+        axes = robot.unmix_motor_speeds(server.telemetry_data["motor_speeds"])
+        check(close(axes, (0.32, 0.18, 0.1)), f"low battery drove axes {axes}, expected (0.32, 0.18, 0.1)")
+
+        send(server, "mode", mode="STOPPED")
+        check(written[-1] == robot.ZERO_MOTOR_SPEEDS, f"stop while derated left {written[-1]}")
+        send(server, "mode", mode="TELEOP")
+        send(server, "joystick", ly=0.5 * robot.JOYSTICK_Y_SIGN)
+        robot.all_stop()
+        check(written[-1] == robot.ZERO_MOTOR_SPEEDS, f"failsafe while derated left {written[-1]}")
+    finally:
+        robot.connection_lost = False
+        robot.BATTERY_DERATE_CURVES = curves
+        server.cleanup()
+
+    return check.report("each axis derated on its own curve after the mix; stop and failsafe still zero the motors")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
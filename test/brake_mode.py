@@ -0,0 +1,76 @@
+"""Check that stopped motors brake or coast as configured.
+
+    python test/brake_mode.py
+
+With braking on, asserts a released stick zeroes the outputs at once. With
+coasting on, steps the output ramp and asserts the outputs fall off at the
+coast rate (or the slower deceleration limit) while a new stick input still
+takes effect immediately, and that pause still stops the motors dead. Also
+checks the setting is validated, persisted and reported. Exits non-zero if
+any check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+DT = 0.1
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    written = []
+    robot.set_motor_speeds = lambda speeds: written.append(list(speeds))
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    try:
+        check(send(server, "brake_query")["enabled"] is robot.BRAKE_ON_STOP, "default doesn't follow KSU_BRAKE_ON_STOP")
+        send(server, "accel", up=0.0, down=0.0)
+        send(server, "brake", enabled=True)
+        send(server, "mode", mode="TELEOP")
+        send(server, "joystick", ly=-1.0)
+        check(any(written[-1]), f"expected the motors driven: {written[-1]}")
+        send(server, "joystick")
+        check(written[-1] == robot.ZERO_MOTOR_SPEEDS, f"braking left outputs at {written[-1]}")
+
+        check(send(server, "brake", enabled="off")["status"] == "error", "non-bool accepted")
+        reply = send(server, "brake", enabled=False)
+        check(reply["status"] == "success" and reply["enabled"] is False, f"coast reply: {reply}")
+        check(server.settings.get("brake") is False and server.telemetry_data["brake"] is False, "coast not persisted")
+
+        ramp = server.output_ramp
+        send(server, "joystick", ly=-1.0)
+        start = ramp.step(DT)
+        check(all(start), f"coasting held back a new input: {start}")
+        send(server, "joystick")
+        coast = ramp.step(DT)
+        expected = [speed - robot.COAST_DECAY_PER_S * DT * (1 if speed > 0 else -1) for speed in start]
+        check(all(abs(a - b) < 1e-9 for a, b in zip(coast, expected)), f"coasted to {coast}, expected {expected}")
+        for _ in range(int(2 / (robot.COAST_DECAY_PER_S * DT)) + 1):
+            coast = ramp.step(DT)
+        check(coast == robot.ZERO_MOTOR_SPEEDS, f"coast never reached zero: {coast}")
+
+        # A slower deceleration limit wins over the coast rate.
+        down = robot.COAST_DECAY_PER_S / 2
+        send(server, "accel", up=0.0, down=down)
+        send(server, "joystick", ly=-1.0)
+        start = ramp.step(DT)
+        send(server, "joystick")
+        slowed = ramp.step(DT)
+        check(all(abs(abs(a) - (abs(b) - down * DT)) < 1e-9 for a, b in zip(slowed, start)),
+              f"coast ignored the deceleration limit: {start} -> {slowed}")
+
+        send(server, "pause")
+        check(written[-1] == robot.ZERO_MOTOR_SPEEDS and ramp.output == robot.ZERO_MOTOR_SPEEDS,
+              f"pause coasted: wrote {written[-1]}, ramp at {ramp.output}")
+    finally:
+        server.cleanup()
+
+    return check.report("stopped motors brake or coast as configured, safety stops brake")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
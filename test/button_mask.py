@@ -0,0 +1,83 @@
+"""Check that a button bitmask in joystick frames is turned into presses and releases.
+
+    python test/button_mask.py
+
+Sends joystick frames carrying the driver's button state and asserts the robot
+derives exactly the edges between successive masks, keeps tracking them while
+paused, agrees with the older per-edge 'button' command, rejects malformed
+masks, and releases everything held when the driver's link fails. Also checks
+both sides use the same bit layout. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+import threading
+import time
+
+from _harness import Checks, send
+import comm
+import robot
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    server = robot.RobotServer(bind=False)
+    events = []
+    server._button_event = lambda button_id, action, repeat=0: events.append((button_id, action))
+    check = Checks()
+
+    def frame(buttons):
+        events.clear()
+        return send(server, "joystick", lx=0.0, ly=0.0, rx=0.0, ry=0.0, buttons=buttons)
+
+    check(robot.BUTTON_NAMES is comm.BUTTON_NAMES, "robot and driver station keep separate button tables")
+    try:
+        check(send(server, "hello").get("button_mask_bits") == robot.BUTTON_MASK_BITS, "hello doesn't offer masks")
+        send(server, "mode", mode="TELEOP")
+
+        frame(0b101)
+        check(sorted(events) == [(0, "DOWN"), (2, "DOWN")], f"A+X pressed gave {events}")
+        frame(0b101)
+        check(events == [], f"unchanged mask gave {events}")
+        frame(0b1100)
+        check(sorted(events) == [(0, "UP"), (3, "DOWN")], f"A released, Y pressed gave {events}")
+        check(server.telemetry_data["buttons"] == 0b1100, f"telemetry buttons {server.telemetry_data['buttons']}")
+
+        send(server, "pause")
+        frame(0b1000)
+        check(events == [(2, "UP")], f"release while paused gave {events}")
+        send(server, "resume")
+
+        # The edge command and the mask describe the same state.
+        events.clear()
+        send(server, "button", button_id=5, action="DOWN")
+        check(server.button_mask == 0b101000, f"button DOWN left mask {bin(server.button_mask)}")
+        frame(0b1000)
+        check(events == [(5, "UP")], f"mask without RB gave {events}")
+
+        for bad in (-1, 1 << robot.BUTTON_MASK_BITS, True, "5", 2.0):
+            reply = frame(bad)
+            check(reply.get("status") == "error" and events == [], f"buttons={bad!r} accepted: {reply}")
+        check(server.button_mask == 0b1000, "a rejected mask changed the held buttons")
+        send(server, "joystick", lx=0.0, ly=0.0, rx=0.0, ry=0.0)
+        check(server.button_mask == 0b1000, "a frame without a mask released buttons")
+
+        events.clear()
+        loop = threading.Thread(target=server.output_loop, daemon=True)
+        robot.connection_lost = True
+        loop.start()
+        deadline = time.monotonic() + 2.0
+        while server.button_mask and time.monotonic() < deadline:
+            time.sleep(0.01)
+        server.running = False
+        loop.join(2.0)
+        robot.connection_lost = False
+        check(server.button_mask == 0 and events == [(3, "UP")], f"failsafe left {bin(server.button_mask)}, {events}")
+    finally:
+        server.cleanup()
+
+    return check.report("button masks turned into presses and releases, nothing left held")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
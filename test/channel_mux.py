@@ -0,0 +1,182 @@
+"""Check that telemetry, alerts and benchmark data share the telemetry socket cleanly.
+
+Runs the robot's publishing side and the driver station's receiving side
+against in-memory sockets:
+
+    python test/channel_mux.py
+
+Captures what RobotServer publishes while it raises an alert, runs a small link
+benchmark and sends telemetry, and checks alerts and benchmark data carry their
+channel while telemetry stays one JSON frame, and that a benchmark chunk the
+socket refuses isn't counted as sent. With an older driver station that never
+said it reads channels still sending commands, the check is that every message
+it gets parses as telemetry and alerts only reach it through telemetry.
+The captured messages are then interleaved, together with a pre-channel
+telemetry frame, a message on an unknown channel and a corrupt alert, and fed
+to RobotClient.receive_telemetry. The check is that each one reaches its own
+handler in order and a bad message doesn't hold up the ones behind it. Exits
+non-zero if any check fails.
+"""
+
+import json
+import logging
+import sys
+import threading
+import time
+
+import zmq
+
+from _harness import Checks, send
+import comm
+import robot
+
+
+class CapturingPub:
+    """Stands in for the robot's PUB socket."""
+
+    def __init__(self, full_after=None):
+        self.sent = []
+        self.telemetry_sent = threading.Event()
+        # Refuse the send after this many, as a full queue does, then accept again.
+        self.full_after = full_after
+
+    def send_multipart(self, frames, flags=0):
+        if len(self.sent) == self.full_after:
+            self.full_after = None
+            raise zmq.Again()
+        self.sent.append(list(frames))
+        if len(frames) == 1:
+            self.telemetry_sent.set()
+
+
+class ScriptedSub:
+    """Stands in for the driver station's SUB socket, replaying queued messages."""
+
+    def __init__(self, messages):
+        self.messages = list(messages)
+
+    def recv_multipart(self, flags=0):
+        if not self.messages:
+            raise zmq.Again()
+        return self.messages.pop(0)
+
+    def close(self, linger=None):
+        pass
+
+
+def channel_of(frames):
+    # Telemetry is the one single-frame message, as ChannelDemux reads it.
+    return comm.CHANNEL_TELEMETRY if len(frames) == 1 else frames[0]
+
+
+def publish_telemetry(server, pub):
+    """Run the robot's telemetry loop until it has published a frame."""
+    loop = threading.Thread(target=server.telemetry_loop, daemon=True)
+    loop.start()
+    published = pub.telemetry_sent.wait(2.0)
+    server.running = False
+    server.telemetry_wake.set()
+    loop.join(2.0)
+    return published
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    check = Checks()
+
+    check((robot.CHANNEL_ALERT, robot.BENCH_TOPIC) == (comm.CHANNEL_ALERT, comm.BENCH_TOPIC),
+          "robot and driver station disagree on channel names")
+
+    # Robot side: alerts and bench data go out tagged with their channel, telemetry as one frame.
+    server = robot.RobotServer(bind=False)
+    pub = server.telemetry_socket = CapturingPub()
+    send(server, "hello", client_id="driver", channels=True)
+    server._raise_alert("current_limit", "FL over limit")
+    server._run_bench(7, 2 * robot.BENCH_CHUNK_BYTES)
+    check(publish_telemetry(server, pub), "telemetry loop published nothing")
+
+    channels = [channel_of(frames) for frames in pub.sent]
+    check(channels[0] == robot.CHANNEL_ALERT, f"alert wasn't published when raised: {channels[:1]}")
+    check(channels.count(robot.BENCH_TOPIC) == 3, f"expected 2 bench chunks and a done frame: {channels}")
+    check(set(channels) <= {comm.CHANNEL_TELEMETRY, robot.CHANNEL_ALERT, robot.BENCH_TOPIC},
+          f"message on an unknown channel: {channels}")
+    telemetry = [frames for frames in pub.sent if channel_of(frames) == comm.CHANNEL_TELEMETRY]
+    alerts = [frames for frames in pub.sent if frames[0] == robot.CHANNEL_ALERT]
+    bench = [frames for frames in pub.sent if frames[0] == robot.BENCH_TOPIC]
+    if not (telemetry and alerts and len(bench) == 3):
+        return report(check)
+    check(json.loads(telemetry[0][0]).get("alerts", [{}])[-1].get("id") == 1,
+          "telemetry lost the alert history")
+
+    # New robot, old driver station: one that reads every message as telemetry JSON, as
+    # recv_json does, gets only telemetry while it is around. Alerts wait for it to leave.
+    server = robot.RobotServer(bind=False)
+    pub = server.telemetry_socket = CapturingPub()
+    send(server, "hello", client_id="old-driver")
+    send(server, "hello", client_id="driver", channels=True)
+    server._raise_alert("current_limit", "FL over limit")
+    check(publish_telemetry(server, pub), "telemetry loop published nothing for the old driver station")
+    for frames in pub.sent:
+        try:
+            data = json.loads(frames[0])
+        except ValueError:
+            data = None
+        check(isinstance(data, dict) and data.get("mode") == "STOPPED",
+              f"old driver station can't read {frames[0][:40]!r} as telemetry")
+    check(json.loads(pub.sent[-1][0]).get("alerts", [{}])[-1].get("kind") == "current_limit",
+          "old driver station lost the alert from telemetry")
+    server.channel_clients.clients["old-driver"] = (False, time.monotonic() - robot.CHANNEL_CLIENT_HOLD_S)
+    server._raise_alert("current_limit", "FR over limit")
+    check(pub.sent[-1][0] == robot.CHANNEL_ALERT, "alert held back after the old driver station left")
+    server.telemetry_socket = None
+    server.cleanup()
+
+    # A chunk the socket refused isn't counted as sent, and the burst stops there.
+    server = robot.RobotServer(bind=False)
+    full = server.telemetry_socket = CapturingPub(full_after=2)
+    server._run_bench(8, 4 * robot.BENCH_CHUNK_BYTES)
+    server.telemetry_socket = None
+    server.cleanup()
+    done = json.loads(full.sent[-1][1])
+    check(len(full.sent) == 3 and done.get("sent_bytes") == 2 * robot.BENCH_CHUNK_BYTES
+          and done.get("aborted") == "send queue full", f"refused chunk counted as sent: {done}")
+
+    # Driver side: interleave everything, plus traffic a real link can carry.
+    legacy = [json.dumps({"mode": "STOPPED", "legacy": True}).encode()]
+    unknown = [b"future-channel", b"{}"]
+    corrupt_alert = [comm.CHANNEL_ALERT, b"not json"]
+    script = [bench[0], alerts[0], corrupt_alert, telemetry[0], unknown, bench[1], legacy, bench[2]]
+
+    client = comm.RobotClient("127.0.0.1")
+    client.telemetry_socket = ScriptedSub(script)
+    client.bench = comm.BenchMeter()
+    seen = []
+    client.signals.telemetry_update.connect(lambda data: seen.append(("telemetry", data)))
+    client.signals.robot_alert.connect(lambda alert: seen.append(("alert", alert)))
+
+    first = client.receive_telemetry()
+    check(first is not None and "legacy" not in first, f"first receive returned {first}")
+    check([kind for kind, _ in seen] == ["alert", "telemetry"], f"out of order or missing: {seen}")
+    check(seen and seen[0][1].get("kind") == "current_limit", f"alert payload changed: {seen[:1]}")
+    check(client.bench.received_chunks == 1,
+          f"bench chunk ahead of telemetry not counted: {client.bench.received_chunks}")
+
+    second = client.receive_telemetry()
+    check(second is not None and second.get("legacy"), f"pre-channel telemetry frame not read as telemetry: {second}")
+    check(client.channels.unrouted == 1, f"unknown channel not skipped: {client.channels.unrouted}")
+    check(client.bench.received_chunks == 2,
+          f"bench chunk between telemetry frames not counted: {client.bench.received_chunks}")
+
+    check(client.receive_telemetry() is None, "a message was left over")
+    check(client.bench.done is not None and client.bench.result()["lost_bytes"] == 0,
+          f"bench done frame not seen: {client.bench.result()}")
+    client.cleanup()
+    return report(check)
+
+
+def report(check):
+    return check.report("telemetry, alert and bench channels interleave and demultiplex in order")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
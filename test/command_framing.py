@@ -0,0 +1,59 @@
+"""Check how the robot frames commands: one ZMQ message is exactly one command.
+
+Feeds edge-case frames straight to robot.decode_command, as command_loop and
+drive_loop do with each received message:
+
+    python test/command_framing.py
+
+There is no byte-stream reader to resynchronize: ZMQ keeps message boundaries,
+so a frame either holds one whole command or is rejected whole. Asserts that
+a trailing newline or surrounding whitespace (as line-oriented tools send) is
+accepted, that two commands glued into one frame, with or without a newline
+between them, are rejected rather than half applied, that each half of a
+command split across two frames is rejected, and that empty and blank frames
+are rejected. Exits non-zero if any check fails.
+"""
+
+import json
+import sys
+
+from _harness import Checks
+import robot
+
+
+def rejected(frame):
+    try:
+        robot.decode_command(frame)
+    except robot.MalformedCommand:
+        return True
+    return False
+
+
+def main():
+    check = Checks()
+
+    stop = json.dumps({"type": "mode", "mode": "STOPPED", "seq": 1}).encode()
+    teleop = json.dumps({"type": "mode", "mode": "TELEOP", "seq": 2}).encode()
+
+    for frame in (stop, stop + b"\n", stop + b"\r\n", b"\n" + stop, b"  " + stop + b"\t\n"):
+        try:
+            command = robot.decode_command(frame)
+        except robot.MalformedCommand as e:
+            check.fail(f"{frame!r} rejected: {e}")
+            continue
+        check(command.get("mode") == "STOPPED", f"{frame!r} decoded as {command}")
+
+    for frame in (stop + teleop, stop + b"\n" + teleop, stop + b"\n" + teleop + b"\n", stop + b"\n\n" + teleop):
+        check(rejected(frame), f"glued frame {frame!r} accepted")
+
+    for cut in (1, len(stop) // 2, len(stop) - 1):
+        check(rejected(stop[:cut]) and rejected(stop[cut:]), f"command split at byte {cut} accepted")
+
+    for frame in (b"", b"\n", b"\r\n", b"   \n\n"):
+        check(rejected(frame), f"blank frame {frame!r} accepted")
+
+    return check.report("one frame is one command; glued, split and blank frames rejected whole")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
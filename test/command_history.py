@@ -0,0 +1,86 @@
+"""Check the driver's always-on history of recently sent commands.
+
+Exercises comm.CommandHistory through RobotClient.send_command without sockets:
+
+    python test/command_history.py
+
+Sends acked, unacked and failing commands through a client whose transport is
+stubbed out, replays one from the outbox, and asserts each lands in the
+history with its fields, result and safety flag, in the order sent, and that
+entries older than the window or beyond the cap are dropped. Exits non-zero if
+any check fails.
+"""
+
+import sys
+import time
+
+from _harness import Checks
+import comm
+
+
+class StubbedClient(comm.RobotClient):
+    """A RobotClient whose replies are scripted: 'pid' errors, 'mode' gets no reply."""
+
+    def __init__(self):
+        super().__init__("127.0.0.1")
+        self.unacked_commands = {"joystick"}
+        self.drive_full = False
+
+    def send_raw(self, command):
+        if command["type"] == "mode":
+            return None
+        if command["type"] == "pid":
+            return {"status": "error", "message": "Invalid PID axis: sideways"}
+        return {"status": "success"}
+
+    def send_unacked(self, command):
+        return not self.drive_full
+
+
+def main():
+    check = Checks()
+
+    client = StubbedClient()
+    client.history = comm.CommandHistory()
+    client.outbox = comm.ReliableOutbox()
+    try:
+        client.send_command("joystick", lx=0.5, ly=0.0, rx=0.0, ry=0.0)
+        client.drive_full = True
+        client.send_command("joystick", lx=0.0, ly=0.0, rx=0.0, ry=0.0)
+        client.send_command("pid", axis="sideways", kp=1.0)
+        client.send_command("mode", mode="STOPPED")
+        client.send_command("pause")
+        client.send_raw = lambda command: {"status": "success"}
+        client.flush_outbox()
+    finally:
+        client.cleanup()
+
+    entries = client.history.snapshot()
+    summary = [(e["type"], e["outcome"], e["safety"], e["replayed"]) for e in entries]
+    expected = [
+        ("joystick", "sent", False, False),
+        ("joystick", "dropped", False, False),
+        ("pid", "error: Invalid PID axis: sideways", False, False),
+        ("mode", "no reply", True, False),
+        ("pause", "ok", True, False),
+        ("mode", "ok", True, True),
+    ]
+    check(summary == expected, f"history {summary}")
+    if entries:
+        check(entries[0]["fields"] == {"lx": 0.5, "ly": 0.0, "rx": 0.0, "ry": 0.0},
+              f"fields kept the envelope or lost values: {entries[0]['fields']}")
+        check(entries[-1]["seq"] == entries[3]["seq"], "the replay isn't shown as the same command")
+        check(all(a["t"] <= b["t"] for a, b in zip(entries, entries[1:])), "history out of order")
+
+    history = comm.CommandHistory(window_s=0.2, max_entries=3)
+    for seq in range(5):
+        history.record({"type": "joystick", "seq": seq, "timestamp": time.time()}, "sent")
+    check([e["seq"] for e in history.snapshot()] == [2, 3, 4], "cap didn't keep the newest entries")
+    time.sleep(0.3)
+    check(history.snapshot() == [], "entries outlived the window")
+
+    return check.report(f"{len(entries)} commands kept with their results, safety commands flagged")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,70 @@
+"""Check that the generic config commands cover the robot's settings.
+
+    python test/config_interface.py
+
+Asserts config_query reports every setting the individual commands manage,
+with their current values, and that config sets each through its own command:
+the change shows up in the individual query and in the saved settings, values
+of the wrong type or out of range are refused with the owning command's
+reason, unknown and read-only keys are refused, and keys that don't survive a
+restart are flagged. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    try:
+        reply = send(server, "config_query")
+        config = reply.get("config", {})
+        for key in ("name", "brake", "accel.up", "shape.expo", "loop_rate_hz", "failsafe_ms", "geofence_m",
+                    "telemetry_rate_hz", "pid.forward.kp", "motor_reverse.FL", "current_limit.RR", "mix.FL"):
+            check(key in config, f"config_query is missing {key}")
+        check(config.get("mix.FR") == [1.0, -1.0, -1.0], f"mix.FR {config.get('mix.FR')}")
+        check(reply.get("volatile") == ["failsafe_ms", "telemetry_rate_hz"], f"volatile {reply.get('volatile')}")
+
+        for key, value, query, read in (
+                ("accel.up", 2.5, "accel_query", lambda r: r["accel"]["up"]),
+                ("brake", False, "brake_query", lambda r: r["enabled"]),
+                ("pid.strafe.ki", 0.25, "pid_query", lambda r: r["pid"]["strafe"]["ki"]),
+                ("motor_reverse.RL", True, "motor_reverse_query", lambda r: r["reversed"]["RL"]),
+                ("current_limit.FR", 3.0, "current_limit_query", lambda r: r["limits"]["FR"]),
+                ("name", "Storm 2", "name_query", lambda r: r["name"]),
+                ("failsafe_ms", 3000, "failsafe_query", lambda r: r["ms"]),
+                ("geofence_m", None, None, None)):
+            reply = send(server, "config", key=key, value=value)
+            check(reply.get("status") == "success" and reply.get("value") == value, f"setting {key}: {reply}")
+            if query is not None:
+                shown = read(send(server, query))
+                check(shown == value, f"{query} shows {shown} after config {key}={value}")
+            check(send(server, "config_query")["config"].get(key) == value, f"config_query disagrees on {key}")
+        check(server.settings.get("accel", {}).get("up") == 2.5, "accel.up not persisted")
+        check(server.settings.get("motor_reverse") == [False, False, True, False], "motor_reverse not persisted")
+        check(send(server, "config", key="failsafe_ms", value=2000)["persisted"] is False, "failsafe said persisted")
+
+        for key, value in (("accel.up", "fast"), ("accel.up", True), ("brake", 1), ("name", 7),
+                           ("loop_rate_hz", None), ("pid.forward.kp", float("nan"))):
+            reply = send(server, "config", key=key, value=value)
+            check(reply.get("status") == "error", f"{key}={value!r} accepted: {reply}")
+        reply = send(server, "config", key="loop_rate_hz", value=5000)
+        check(reply.get("status") == "error" and "Loop rate" in reply.get("message", ""), f"range check: {reply}")
+        check(send(server, "config_query")["config"]["loop_rate_hz"] == server.output_rate_hz != 5000,
+              "refused loop rate still applied")
+        check(send(server, "config", key="mix.FL", value=[1, 1, 1]).get("status") == "error", "mix was writable")
+        check(send(server, "config", key="warp_drive", value=1).get("status") == "error", "unknown key accepted")
+    finally:
+        server.cleanup()
+
+    return check.report(f"{len(config)} config keys read and set through their own commands")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,87 @@
+"""Check that a spotter is held to auxiliary commands while the driver drives.
+
+    python test/control_roles.py
+
+A driver and a spotter claim their roles. Asserts the spotter can aim the
+gimbal but is refused drive and configuration commands with a reply naming
+its role, that its capabilities list only what it may send, that its pings
+don't count as the driver's heartbeat, that a second driver claim while the
+first is active is refused and leaves that client a spotter, and that a
+client that never claimed a role only drives once the driver has gone quiet.
+Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    try:
+        reply = send(server, "role", client_id="driver", role="driver")
+        check(reply.get("status") == "success" and reply.get("role") == "driver", f"driver claim: {reply}")
+        reply = send(server, "role", client_id="camera", role="spotter")
+        check(reply.get("status") == "success" and reply.get("role") == "spotter", f"spotter claim: {reply}")
+
+        reply = send(server, "servo", client_id="camera", id="pan", angle=20.0)
+        check(reply.get("status") == "success", f"spotter couldn't aim the gimbal: {reply}")
+        for command_type, fields in (("joystick", {"lx": 0.5, "ly": 0.5, "rx": 0.0, "ry": 0.0}),
+                                     ("mode", {"mode": "TELEOP"}), ("reset", {}), ("pid", {"axis": "forward"}),
+                                     ("telemetry_rate", {"hz": 1.0})):
+            reply = send(server, command_type, client_id="camera", **fields)
+            check(reply.get("status") == "error" and reply.get("role") == "spotter",
+                  f"spotter's {command_type} was not refused: {reply}")
+        check(robot.robot_mode == "STOPPED", f"spotter changed the mode to {robot.robot_mode}")
+        check(send(server, "mode", client_id="driver", mode="TELEOP").get("status") == "success",
+              "driver refused a mode change")
+        send(server, "mode", client_id="driver", mode="STOPPED")
+
+        commands = set(send(server, "capabilities", client_id="camera")["commands"])
+        check("servo" in commands and "joystick" not in commands and "mode" not in commands,
+              f"spotter capabilities {sorted(commands)}")
+        check("joystick" in send(server, "capabilities", client_id="driver")["commands"],
+              "driver lost joystick capability")
+
+        robot.last_heartbeat = 0.0
+        send(server, "ping", client_id="camera")
+        check(robot.last_heartbeat == 0.0, "a spotter ping counted as the driver's heartbeat")
+        send(server, "ping", client_id="driver")
+        check(robot.last_heartbeat > 0.0, "the driver's ping didn't count as a heartbeat")
+
+        reply = send(server, "role", client_id="intruder", role="driver")
+        check(reply.get("status") == "error" and reply.get("role") == "spotter", f"second driver claim: {reply}")
+        reply = send(server, "joystick", client_id="intruder", lx=1.0, ly=0.0, rx=0.0, ry=0.0)
+        check(reply.get("status") == "error", f"refused driver could still drive: {reply}")
+
+        roles = send(server, "stats", client_id="driver")["roles"]
+        check(roles["driver"] == "driver" and roles["spotters"] == ["camera", "intruder"], f"stats roles {roles}")
+
+        # Clients that never claim a role are drivers, so older driver stations keep working,
+        # but only one drives: while the driver is active another is held to spotter commands.
+        reply = send(server, "reset_odometry", client_id="legacy")
+        check(reply.get("status") == "error", f"unclaimed client drove alongside the driver: {reply}")
+        check(send(server, "telemetry_query", client_id="legacy").get("status") == "success",
+              "unclaimed client refused a query")
+        robot.last_heartbeat = 0.0
+        send(server, "ping", client_id="legacy")
+        check(robot.last_heartbeat == 0.0, "a second driver's ping counted as the driver's heartbeat")
+        server.roles.driver_seen["driver"] -= robot.DRIVER_ROLE_HOLD_S
+        check(send(server, "reset_odometry", client_id="legacy").get("status") == "success",
+              "unclaimed client was refused after the driver left")
+
+        reply = send(server, "role", client_id="camera", role="pilot")
+        check(reply.get("status") == "error" and "role" not in reply, f"unknown role accepted: {reply}")
+    finally:
+        server.cleanup()
+
+    return check.report("spotter held to its commands while the driver drives")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,83 @@
+"""Check per-motor current limiting with injected current readings.
+
+    python test/current_limit.py
+
+Feeds fake current readings through the drive path and asserts that only the
+motor over its limit is clamped, that an alert is raised once when limiting
+starts, that the output recovers once the current drops, and that a malformed
+stored limit falls back to the default. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+import time
+
+from _harness import Checks, send
+import robot
+
+LIMIT_A = 5.0
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    currents = [1.0, 1.0, 1.0, 1.0]
+    server.read_currents = lambda: list(currents)
+    try:
+        check(send(server, "current_limit", motor="all", amps=LIMIT_A)["status"] == "success", "set limit failed")
+        check(send(server, "current_limit", motor="XX", amps=1.0)["status"] == "error", "bad motor accepted")
+        check(send(server, "current_limit", amps=-1.0)["status"] == "error", "negative limit accepted")
+        send(server, "mode", mode="TELEOP")
+
+        # Full forward with every motor under its limit passes straight through.
+        send(server, "joystick", ly=-1.0)
+        speeds = server.telemetry_data["motor_speeds"]
+        check(all(abs(s) == 1.0 for s in speeds), f"unlimited speeds were clamped: {speeds}")
+
+        # FL stalls at twice its limit: only FL is cut, by the overshoot ratio.
+        currents[0] = 2 * LIMIT_A
+        send(server, "joystick", ly=-1.0)
+        speeds = server.telemetry_data["motor_speeds"]
+        check(abs(speeds[0]) <= 0.5 + 1e-9, f"FL not clamped: {speeds}")
+        check(all(abs(s) == 1.0 for s in speeds[1:]), f"other motors clamped: {speeds}")
+        check(server.telemetry_data["current_limited"] == [True, False, False, False],
+              f"current_limited {server.telemetry_data['current_limited']}")
+        alerts = server.telemetry_data["alerts"]
+        check(len(alerts) == 1 and alerts[0]["kind"] == "current_limit", f"alerts {alerts}")
+
+        # Staying over the limit tightens the clamp but doesn't repeat the alert.
+        send(server, "joystick", ly=-1.0)
+        check(abs(server.telemetry_data["motor_speeds"][0]) < 0.5, "clamp didn't tighten while over limit")
+        check(len(server.telemetry_data["alerts"]) == 1, "alert repeated while still limiting")
+
+        # Current back to normal: the cap grows back.
+        currents[0] = 1.0
+        clamped = abs(server.telemetry_data["motor_speeds"][0])
+        time.sleep(0.2)
+        send(server, "joystick", ly=-1.0)
+        check(abs(server.telemetry_data["motor_speeds"][0]) > clamped, "output didn't recover after current dropped")
+
+        query = send(server, "current_limit_query")
+        check(query["limits"]["FL"] == LIMIT_A, f"query {query}")
+    finally:
+        server.cleanup()
+
+    # A broken settings file falls back to the default limits instead of stopping the robot from starting.
+    load_settings = robot.load_robot_settings
+    try:
+        for stored in (["x", 1, 1, 1], [5.0, 5.0], [float("nan")] * 4, [-1.0] * 4, "5"):
+            robot.load_robot_settings = lambda stored=stored: {"current_limits": stored}
+            server = robot.RobotServer(bind=False)
+            limits = server.current_limiter.limits_a
+            server.cleanup()
+            check(limits == [robot.DEFAULT_CURRENT_LIMIT_A] * 4, f"stored {stored!r} gave limits {limits}")
+    finally:
+        robot.load_robot_settings = load_settings
+
+    return check.report("current limits clamp, alert and recover")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
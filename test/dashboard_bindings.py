@@ -0,0 +1,80 @@
+"""Check the telemetry-to-dashboard binding layer.
+
+Exercises comm.DashboardBinding, comm.parse_dashboard and TelemetryStore without a UI:
+
+    python test/dashboard_bindings.py
+
+Round-trips a saved dashboard, checks broken entries are skipped rather than
+breaking the rest, and feeds the store telemetry with a field no default knows
+about, nested values and a list, checking each can be looked up by the dotted
+path the editor lists and scaled for its widget. Exits non-zero if any check
+fails.
+"""
+
+import sys
+
+from _harness import Checks
+import comm
+
+SAVED = [
+    {"path": "battery", "kind": "gauge", "min": 9.0, "max": 12.6},
+    {"path": "pose.theta_deg", "kind": "number"},
+    {"path": "motor_speeds.2", "kind": "bar", "min": -1.0, "max": 1.0},
+    {"path": "sensors.digital.bump", "kind": "indicator"},
+    {"path": "arm_temp_c", "kind": "bar", "min": 20, "max": 80},
+]
+BROKEN = [
+    {"path": "", "kind": "number"},
+    {"path": "battery", "kind": "dial"},
+    {"path": "battery", "kind": "gauge", "min": 5, "max": 5},
+    {"path": "battery", "kind": "bar", "min": "low"},
+    "battery",
+]
+
+
+def main():
+    check = Checks()
+
+    bindings = comm.parse_dashboard(SAVED + BROKEN)
+    check([b.path for b in bindings] == [entry["path"] for entry in SAVED],
+          f"expected only the good entries, got {[b.path for b in bindings]}")
+    again = comm.parse_dashboard([b.to_config() for b in bindings])
+    check([b.to_config() for b in again] == [b.to_config() for b in bindings], "saved dashboard didn't round-trip")
+    check(comm.parse_dashboard({"battery": "gauge"}) == [], "non-list dashboard setting not ignored")
+
+    store = comm.TelemetryStore()
+    store.update({
+        "battery": 10.8,
+        "pose": {"x": 1.0, "y": 2.0, "theta_deg": 90.0},
+        "motor_speeds": [0.1, 0.2, -0.5, 0.4],
+        "sensors": {"digital": {"bump": True, "line": False}},
+        "arm_temp_c": 95.0,
+    })
+    paths = store.paths()
+    for binding in bindings:
+        check(binding.path in paths, f"{binding.path} not offered to the editor: {paths}")
+
+    expected = {
+        "battery": (0.5, "10.80 V"),
+        "pose.theta_deg": (None, "90.0 °"),
+        "motor_speeds.2": (0.25, "-0.5"),
+        "sensors.digital.bump": (1.0, "True"),
+        "arm_temp_c": (1.0, "95.0"),
+    }
+    for binding in bindings:
+        fraction, text = expected[binding.path]
+        got = binding.fraction(store.lookup(binding.path))
+        check(got is None if fraction is None else got is not None and abs(got - fraction) < 1e-9,
+              f"{binding.path} fraction {got}, expected {fraction}")
+        check(store.display(binding.path) == text, f"{binding.path} shows {store.display(binding.path)!r}")
+
+    check(store.lookup("motor_speeds.9") is None, "out-of-range list index found a value")
+    check(bindings[0].fraction(None) is None and bindings[3].fraction(None) is None, "missing value drew a reading")
+    check(bindings[0].fraction(True) is None, "a boolean filled a gauge")
+
+    return check.report(f"{len(SAVED)} dashboard bindings loaded, {len(BROKEN)} broken entries skipped, "
+                        "values bound by path")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
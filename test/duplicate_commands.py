@@ -0,0 +1,57 @@
+"""Check that re-sent commands are applied once and counted as duplicates.
+
+    python test/duplicate_commands.py
+
+Sends the same (client_id, seq) several times, as a redundant link or a
+reliable-send retry would, and asserts the command took effect exactly once
+while the duplicate counter reported by the 'stats' command went up for every
+repeat. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+REPEATS = 3
+
+
+def duplicate_stats(server):
+    # Queries need their own seqs or they'd be caught as duplicates too.
+    return send(server, "stats")["duplicates"]
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    try:
+        before = server.odometry_resets
+        command = {"type": "reset_odometry", "client_id": "driver-a", "seq": 41}
+        replies = [server.handle_command(dict(command)) for _ in range(REPEATS)]
+
+        check(server.odometry_resets == before + 1,
+              f"reset applied {server.odometry_resets - before} times, expected once")
+        check("duplicate" not in replies[0], f"first send flagged as duplicate: {replies[0]}")
+        check(all(r.get("duplicate") for r in replies[1:]), f"repeats not flagged: {replies[1:]}")
+
+        stats = duplicate_stats(server)
+        check(stats["total"] == REPEATS - 1, f"duplicate total {stats['total']}, expected {REPEATS - 1}")
+        check(stats["by_type"].get("reset_odometry") == REPEATS - 1, f"by_type {stats['by_type']}")
+
+        # The same seq from another driver is a different command. Only one client drives at
+        # a time, so driver-a is first taken to have left.
+        server.roles.driver_seen.clear()
+        server.handle_command({"type": "reset_odometry", "client_id": "driver-b", "seq": 41})
+        check(server.odometry_resets == before + 2, "same seq from a second client was dropped")
+        check(duplicate_stats(server)["total"] == REPEATS - 1, "second client's command counted as duplicate")
+    finally:
+        server.cleanup()
+
+    return check.report("duplicates applied once and counted")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,129 @@
+"""Check that formation mode fans commands out to several robots and tracks each one.
+
+Drives comm.MultiLink against fake links, without sockets or threads:
+
+    python test/formation.py
+
+Parses a KSU_FORMATION spec (and rejects broken ones), then asserts that
+drive frames reach every robot adjusted for it (mirrored, scaled, clamped),
+that a robot that is down is skipped while the others keep driving, that
+reliable commands for it wait in its outbox for the reconnect, that
+telemetry is kept per robot, and that the members are only pinged (their
+heartbeat) while the main robot's link is up. Exits non-zero if any check fails.
+"""
+
+import sys
+import threading
+import time
+from types import SimpleNamespace
+
+from _harness import Checks
+import comm
+
+SPEC = "left=10.0.0.2, right=tcp://10.0.0.3:5555?mirror=1&scale=0.5, rear=10.0.0.4"
+
+
+class FakeLink:
+    """A connected robot that accepts every command."""
+
+    def __init__(self):
+        self.connected = True
+        self.unacked_commands = {"joystick"}
+        self.sent = []
+        self.signals = SimpleNamespace(post=lambda *args: None)
+
+    def send_command(self, command_type, **fields):
+        self.sent.append((command_type, fields))
+        return None if command_type in self.unacked_commands else {"status": "success"}
+
+    def receive_telemetry(self):
+        return None
+
+    def send_ping(self):
+        self.sent.append(("ping", {}))
+        return {"status": "success"}
+
+
+def pings_sent(formation, name, link, seconds=0.2):
+    """Run the member's TelemetryReceiver briefly and count the pings it sends."""
+    receiver = formation.receivers[name]
+    receiver.last_ping_time = 0
+    before = len([c for c in link.sent if c[0] == "ping"])
+    receiver.running = True
+    thread = threading.Thread(target=receiver.run, daemon=True)
+    thread.start()
+    time.sleep(seconds)
+    receiver.stop()
+    thread.join(1.0)
+    return len([c for c in link.sent if c[0] == "ping"]) - before
+
+
+def main():
+    check = Checks()
+
+    members = comm.parse_formation(SPEC)
+    check([m.name for m in members] == ["left", "right", "rear"], f"members {members}")
+    check(members[1].mirror and members[1].scale == 0.5 and not members[0].mirror, f"right robot {members[1]}")
+    for bad in ("left", "left=10.0.0.2,left=10.0.0.3", "x=10.0.0.2?scale=2", "x=10.0.0.2?scale=fast",
+                "x=ftp://10.0.0.2", "bad name=10.0.0.2"):
+        try:
+            comm.parse_formation(bad)
+            check.fail(f"accepted {bad!r}")
+        except comm.LinkUrlError:
+            pass
+    check(comm.parse_formation("") == [], "empty spec made a formation")
+
+    formation = comm.MultiLink(members)
+    check(formation.managers["right"]._addresses() == [members[1].address], "member manager has the wrong address")
+    links = {name: FakeLink() for name in ("left", "right")}
+    for name, link in links.items():
+        formation.managers[name].client = link
+
+    responses = formation.send_command("joystick", lx=0.4, ly=0.8, rx=-0.2, ry=0.1)
+    check(links["left"].sent[-1] == ("joystick", {"lx": 0.4, "ly": 0.8, "rx": -0.2, "ry": 0.1}),
+          f"left got {links['left'].sent[-1]}")
+    check(links["right"].sent[-1] == ("joystick", {"lx": -0.2, "ly": 0.4, "rx": 0.1, "ry": 0.1}),
+          f"mirrored right got {links['right'].sent[-1]}")
+    check(responses["rear"] is None, "down robot answered")
+    rows = {row["name"]: row for row in formation.status()}
+    check(rows["rear"]["connected"] is False and rows["rear"]["last_command"] == ("joystick", "down"),
+          f"down robot row {rows['rear']}")
+    check(rows["left"]["last_command"] == ("joystick", "sent"), f"left row {rows['left']}")
+
+    formation.members["left"].scale = 1.0
+    formation.members["left"].mirror = True
+    formation.send_command("joystick", lx=-1.0, ly=1.0, rx=1.0, ry=0.0)
+    check(links["left"].sent[-1][1] == {"lx": 1.0, "ly": 1.0, "rx": -1.0, "ry": 0.0}, f"{links['left'].sent[-1]}")
+
+    # The rear robot is down: stops wait for its reconnect, plain drive frames don't.
+    formation.send_command("mode", mode="STOPPED")
+    formation.send_command("pause")
+    check(links["right"].sent[-2:] == [("mode", {"mode": "STOPPED"}), ("pause", {})], "up robots missed the stop")
+    queued = formation.managers["rear"].outbox.drain()
+    check([(c["type"], c.get("mode")) for c in queued] == [("mode", "STOPPED"), ("pause", None)],
+          f"rear outbox {queued}")
+    check(all(c["client_id"] == comm.CLIENT_ID and "seq" in c for c in queued), "queued commands lack envelopes")
+    check(formation.managers["left"].outbox.drain() == [], "a robot that was up had commands queued")
+    check(formation.status()[2]["last_command"] == ("pause", "queued"), "queued command not shown")
+
+    # Telemetry is kept per robot.
+    formation.managers["left"].signals.telemetry_update.emit({"battery": 12.1, "state": {"summary": "TELEOP ARMED"}})
+    formation.managers["right"].signals.telemetry_update.emit({"battery": 11.2, "mode": "STOPPED"})
+    rows = {row["name"]: row for row in formation.status()}
+    check(rows["left"]["battery"] == 12.1 and rows["left"]["state"] == "TELEOP ARMED", f"left {rows['left']}")
+    check(rows["right"]["battery"] == 11.2 and rows["right"]["state"] == "STOPPED", f"right {rows['right']}")
+    check(rows["rear"]["telemetry_age_s"] is None, "down robot has telemetry")
+
+    # Pings are the members' heartbeat, so they only flow while the main link is up.
+    check(not any(r.pinging for r in formation.receivers.values()), "members pinged before the main link was up")
+    check(pings_sent(formation, "left", links["left"]) == 0, "left pinged while the main link was down")
+    formation.set_pinging(True)
+    check(pings_sent(formation, "left", links["left"]) == 1, "left not pinged with the main link up")
+    formation.set_pinging(False)
+    check(pings_sent(formation, "left", links["left"]) == 0, "left still pinged after the main link dropped")
+
+    return check.report(f"commands fanned out to {len(members)} robots, one down, telemetry kept per robot")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
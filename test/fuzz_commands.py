@@ -0,0 +1,143 @@
+"""Throw random frames and malformed commands at the robot's command handling.
+
+Drives RobotServer without sockets, so no hardware or network is needed:
+
+    python test/fuzz_commands.py [iterations] [seed]
+
+Every frame must be either rejected by decode_command or answered by
+handle_command with a JSON-serializable success/error reply, quickly, and
+without leaving the motors at a non-finite or out-of-range speed.
+Exits non-zero on the first frame that breaks one of those rules.
+"""
+
+import json
+import logging
+import math
+import random
+import sys
+import time
+
+import _harness  # noqa: F401
+import robot
+
+DEFAULT_ITERATIONS = 20000
+MAX_CALL_S = 0.25
+
+FIELD_NAMES = [
+    "lx", "ly", "rx", "ry", "mode", "axis", "kp", "ki", "kd", "hz",
+    "button_id", "action", "client_id", "seq", "session", "nonce", "hmac", "timestamp",
+]
+SPECIAL_FLOATS = [0.0, -0.0, 1.0, -1.0, 1e308, -1e308, 5e-324, math.nan, math.inf, -math.inf]
+
+
+def random_text(rng):
+    alphabet = "abcXYZ_-: é中\U0001f916\x00\n\"\\"
+    return "".join(rng.choice(alphabet) for _ in range(rng.randint(0, 12)))
+
+
+def random_value(rng, depth=0):
+    kind = rng.randint(0, 7 if depth < 3 else 4)
+    if kind == 0:
+        return None
+    if kind == 1:
+        return rng.choice([True, False])
+    if kind == 2:
+        return rng.choice([0, -1, 1, 2**31, -(2**63), 10**40, rng.randint(-1000, 1000)])
+    if kind == 3:
+        return rng.choice(SPECIAL_FLOATS + [rng.uniform(-2.0, 2.0)])
+    if kind == 4:
+        return random_text(rng)
+    if kind == 5:
+        return [random_value(rng, depth + 1) for _ in range(rng.randint(0, 4))]
+    return {random_text(rng): random_value(rng, depth + 1) for _ in range(rng.randint(0, 4))}
+
+
+def random_command(rng, command_types):
+    command = {"type": rng.choice(command_types) if rng.random() < 0.9 else random_value(rng)}
+    for name in rng.sample(FIELD_NAMES, rng.randint(0, 6)):
+        if name in ("mode", "axis", "action") and rng.random() < 0.5:
+            command[name] = rng.choice(["TELEOP", "teleop", "STOPPED", "AUTO", "forward", "rotate", "DOWN"])
+        else:
+            command[name] = random_value(rng)
+    return command
+
+
+def random_frame(rng, command_types):
+    roll = rng.random()
+    if roll < 0.1:
+        return bytes(rng.randint(0, 255) for _ in range(rng.randint(0, 64)))
+    if roll < 0.2:
+        return json.dumps(random_value(rng)).encode()
+    if roll < 0.3:
+        # Valid drive frames keep the TELEOP/joystick path exercised.
+        return json.dumps({"type": "mode", "mode": "TELEOP"}).encode()
+    frame = json.dumps(random_command(rng, command_types)).encode()
+    if roll < 0.4 and frame:
+        cut = rng.randint(0, len(frame) - 1)
+        frame = frame[:cut] + bytes([rng.randint(0, 255)]) + frame[cut + 1:]
+    return frame
+
+
+def check_frame(server, frame):
+    """Return None if the frame was handled correctly, otherwise a description of the failure."""
+    started = time.monotonic()
+    try:
+        command = robot.decode_command(frame)
+    except robot.MalformedCommand:
+        return None
+    except Exception as e:
+        return f"decode_command raised {type(e).__name__}: {e}"
+
+    try:
+        response = server.handle_command(command)
+    except Exception as e:
+        return f"handle_command raised {type(e).__name__}: {e}"
+    elapsed = time.monotonic() - started
+
+    if elapsed > MAX_CALL_S:
+        return f"handle_command took {elapsed:.2f} s"
+    if not isinstance(response, dict) or response.get("status") not in ("success", "error"):
+        return f"bad reply {response!r}"
+    try:
+        json.dumps(response)
+    except Exception as e:
+        return f"reply not serializable: {e}"
+
+    speeds = server.telemetry_data["motor_speeds"]
+    if not all(math.isfinite(s) and -1.0 <= s <= 1.0 for s in speeds):
+        return f"motor speeds out of range: {speeds}"
+    return None
+
+
+def main():
+    iterations = int(sys.argv[1]) if len(sys.argv) > 1 else DEFAULT_ITERATIONS
+    seed = int(sys.argv[2]) if len(sys.argv) > 2 else random.randrange(2**32)
+    rng = random.Random(seed)
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+
+    server = robot.RobotServer(bind=False)
+    command_types = server.pipeline.command_names()
+    print(f"Fuzzing {iterations} frames with seed {seed} over {command_types}")
+
+    try:
+        for value in (None, 1, "ping", [], {}, {"type": None}, {"type": ["ping"]}):
+            if server.handle_command(value).get("status") != "error":
+                print(f"FAIL: non-command {value!r} was not rejected")
+                return 1
+
+        for i in range(iterations):
+            frame = random_frame(rng, command_types)
+            failure = check_frame(server, frame)
+            if failure:
+                print(f"FAIL at frame {i} (seed {seed}): {failure}")
+                print(f"  frame: {frame!r}")
+                return 1
+    finally:
+        server.cleanup()
+
+    print(f"OK: {iterations} frames handled")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,63 @@
+"""Check how the driver paces rebuilding a failed gamepad input subsystem.
+
+Exercises driver.GamepadRecovery with a fake clock, without a controller:
+
+    python test/gamepad_recovery.py
+
+Asserts that the first error schedules a rebuild at once and repeated errors
+don't reschedule it, that failed rebuilds back off up to the cap, that a
+recovery reports how long input was down, and that a failure right after a
+recovery keeps backing off instead of rebuilding on every poll, while one after
+a long healthy spell starts over. Exits non-zero if any check fails.
+"""
+
+import sys
+
+from _harness import Checks
+import driver
+
+MIN_S = driver.GAMEPAD_REINIT_MIN_S
+MAX_S = driver.GAMEPAD_REINIT_MAX_S
+
+
+def main():
+    check = Checks()
+
+    recovery = driver.GamepadRecovery()
+    check(not recovery.failed and not recovery.due(0.0), "healthy input wants a rebuild")
+
+    check(recovery.fail(RuntimeError("Joystick not initialized"), 100.0), "first error not reported as new")
+    check(not recovery.fail(RuntimeError("again"), 100.5), "repeated error reported as a new failure")
+    check(recovery.due(100.5), "first rebuild not due at once")
+    check(recovery.last_error == "again", f"last error {recovery.last_error!r}")
+
+    now = 100.5
+    waits = []
+    for _ in range(6):
+        recovery.attempt_failed(RuntimeError("no input devices"), now)
+        waits.append(recovery.next_attempt - now)
+        check(not recovery.due(recovery.next_attempt - 0.01), "rebuild due before its backoff ended")
+        now = recovery.next_attempt
+        check(recovery.due(now), "rebuild not due after its backoff")
+    expected = [min(MIN_S * 2 ** i, MAX_S) for i in range(6)]
+    check(waits == expected, f"backoff {waits}, expected {expected}")
+
+    down_s = recovery.recovered(now)
+    check(abs(down_s - (now - 100.0)) < 1e-9 and not recovery.failed, f"recovered after {down_s} s")
+    check(recovery.reinits == 1, f"{recovery.reinits} reinits counted")
+
+    # Flapping: failing again straight after a rebuild waits out the backoff.
+    check(recovery.fail(RuntimeError("flap"), now + 0.1), "error after recovery not reported as new")
+    check(not recovery.due(now + 0.1), "rebuilt immediately after a flap")
+    recovery.recovered(recovery.next_attempt)
+
+    # A failure after a long healthy spell starts over at once.
+    later = recovery.recovered_at + MAX_S + 1.0
+    recovery.fail(RuntimeError("hiccup"), later)
+    check(recovery.due(later) and recovery.delay_s == MIN_S, "long-healthy failure didn't start over")
+
+    return check.report(f"gamepad rebuilds back off {MIN_S:g}-{MAX_S:g} s and recover")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,89 @@
+"""Check that the geofence stops outward motion at the boundary, using injected poses.
+
+    python test/geofence.py
+
+Places the robot at chosen odometry poses and sends joystick frames, asserting
+that translation heading out past the fence is cut (with one 'geofence' alert),
+while rotating, driving back in and everything with the fence off still drive
+the motors. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+import time
+
+from _harness import Checks, send
+import robot
+
+RADIUS_M = 1.0
+
+
+def place(server, dx, dy, theta_deg=0.0):
+    """Put the robot (dx, dy) meters from the geofence origin."""
+    server.pose_x_m = robot.GEOFENCE_ORIGIN[0] + dx
+    server.pose_y_m = robot.GEOFENCE_ORIGIN[1] + dy
+    server.pose_theta_deg = theta_deg
+    server.last_pose_update = time.time()
+
+
+def geofence_alerts(server):
+    return sum(1 for alert in server.alerts if alert["kind"] == "geofence")
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    # The robot multiplies ly by KSU_JOYSTICK_Y_SIGN, so this is forward on the wire.
+    forward = robot.JOYSTICK_Y_SIGN
+    try:
+        check(send(server, "geofence", radius_m=-1.0)["status"] == "error", "negative radius accepted")
+        check(send(server, "geofence", radius_m="far")["status"] == "error", "non-numeric radius accepted")
+        check(send(server, "geofence", radius_m=RADIUS_M)["status"] == "success", "set geofence failed")
+        send(server, "mode", mode="TELEOP")
+
+        place(server, 0.0, 0.0)
+        send(server, "joystick", ly=forward)
+        check(any(server.telemetry_data["motor_speeds"]), "forward from the origin was blocked")
+        check(geofence_alerts(server) == 0, "alert raised well inside the fence")
+
+        # Facing out, just inside the edge: forward would cross it within the lookahead.
+        place(server, 0.95, 0.0)
+        send(server, "joystick", ly=forward)
+        check(not any(server.telemetry_data["motor_speeds"]),
+              f"outward drive not stopped: {server.telemetry_data['motor_speeds']}")
+        check(server.telemetry_data["geofence"]["tripped"], "trip not reported in telemetry")
+        check(geofence_alerts(server) == 1, f"expected one geofence alert, got {geofence_alerts(server)}")
+
+        place(server, 0.95, 0.0)
+        send(server, "joystick", ly=forward * 0.9)
+        check(geofence_alerts(server) == 1, "holding the stick outward raised the alert again")
+
+        place(server, 0.95, 0.0)
+        send(server, "joystick", rx=0.8)
+        check(any(server.telemetry_data["motor_speeds"]), "rotating at the edge was blocked")
+
+        place(server, 0.95, 0.0)
+        send(server, "joystick", ly=-forward)
+        check(any(server.telemetry_data["motor_speeds"]), "driving back in was blocked")
+        check(not server.telemetry_data["geofence"]["tripped"], "trip not cleared once back inside")
+
+        # Heading matters: facing +y, forward at the +x edge runs along it, strafing left runs out.
+        check(not robot.geofence_blocks(robot.GEOFENCE_ORIGIN[0] + 0.5, robot.GEOFENCE_ORIGIN[1], 90.0, 1.0, 0.0, RADIUS_M),
+              "tangential drive blocked well inside")
+        check(robot.geofence_blocks(robot.GEOFENCE_ORIGIN[0] + 0.9, robot.GEOFENCE_ORIGIN[1], 90.0, 0.0, -1.0, RADIUS_M),
+              "outward strafe at a 90 deg heading not blocked")
+
+        check(send(server, "geofence", radius_m="off")["radius_m"] is None, "geofence not turned off")
+        place(server, 0.95, 0.0)
+        send(server, "joystick", ly=forward)
+        check(any(server.telemetry_data["motor_speeds"]), "drive blocked with the geofence off")
+    finally:
+        server.cleanup()
+
+    return check.report("geofence stops outward motion and lets the robot back in")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
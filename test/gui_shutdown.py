@@ -0,0 +1,95 @@
+"""Check that background threads stop once the GUI's event loop is gone.
+
+Runs comm.ConnectionManager and comm.TelemetryReceiver threads without sockets:
+
+    python test/gui_shutdown.py
+
+Simulates a torn-down application by making a signal's emit raise the
+RuntimeError PyQt gives for a deleted QObject. Asserts that posting works
+while the GUI is up, that the first failure closes the signals so nothing is
+emitted again, and that both threads notice and exit on their own instead of
+retrying into the dead loop, as they also do when the window closes them
+explicitly. Exits non-zero if any check fails.
+"""
+
+import sys
+import time
+
+from _harness import Checks
+import comm
+
+STOP_TIMEOUT_S = 2.0
+
+
+class DeadSignal:
+    """A signal whose QObject has been deleted, as after the application shuts down."""
+
+    def __init__(self):
+        self.calls = 0
+
+    def emit(self, *args):
+        self.calls += 1
+        raise RuntimeError("wrapped C/C++ object of type WorkerSignals has been deleted")
+
+
+class UnreachableManager(comm.ConnectionManager):
+    """Every attempt reports its first stage, then fails."""
+
+    def __init__(self):
+        super().__init__()
+        self.hooks = []
+        self.attempts = 0
+
+    def _connect(self, address):
+        self.attempts += 1
+        self._report_stage(address, comm.STAGE_RESOLVING)
+        return None
+
+
+def main():
+    check = Checks()
+
+    signals = comm.WorkerSignals()
+    seen = []
+    signals.telemetry_update.connect(seen.append)
+    check(signals.post("telemetry_update", {"battery": 12.0}), "post failed with the GUI up")
+    check(seen == [{"battery": 12.0}], f"GUI received {seen}")
+    dead = DeadSignal()
+    signals.telemetry_update = dead
+    check(not signals.post("telemetry_update", {}), "post to a deleted object reported success")
+    check(signals.closed.is_set(), "deleted object didn't close the signals")
+    check(not signals.post("telemetry_update", {}) and dead.calls == 1, f"emitted {dead.calls} times")
+
+    comm.ROBOT_ADDRESSES = ["tcp://a:5555"]
+    comm.RECONNECT_IDLE_S = 0.01
+    manager = UnreachableManager()
+    manager.start()
+    deadline = time.monotonic() + STOP_TIMEOUT_S
+    while manager.attempts < 3 and time.monotonic() < deadline:
+        time.sleep(0.01)
+    check(manager.attempts >= 3, f"manager made {manager.attempts} attempts while the GUI was up")
+    dead = DeadSignal()
+    manager.signals.connection_progress = dead
+    receiver = comm.TelemetryReceiver(manager)
+    receiver.start()
+    manager.join(STOP_TIMEOUT_S)
+    receiver.join(STOP_TIMEOUT_S)
+    check(not manager.is_alive(), "connection manager kept running into a dead event loop")
+    check(not receiver.is_alive(), "telemetry receiver kept running into a dead event loop")
+    check(dead.calls == 1, f"dead signal emitted {dead.calls} times")
+
+    # The window closing says so directly, before any emit has to fail.
+    manager = UnreachableManager()
+    receiver = comm.TelemetryReceiver(manager)
+    manager.start()
+    receiver.start()
+    manager.signals.close()
+    manager.join(STOP_TIMEOUT_S)
+    receiver.join(STOP_TIMEOUT_S)
+    check(not manager.is_alive() and not receiver.is_alive(), "threads outlived an explicit close")
+
+    return check.report("background threads stop once the GUI's event loop is gone")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
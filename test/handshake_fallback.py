@@ -0,0 +1,77 @@
+"""Check that the driver negotiates the unacked drive channel and falls back without it.
+
+Runs RobotClient against a mock robot instead of sockets:
+
+    python test/handshake_fallback.py
+
+One mock advertises the drive channel in its 'hello' reply, one answers 'hello'
+without it, one predates 'hello' and rejects it, and one answers with a
+malformed drive port. The client should send joystick frames fire-and-forget
+only to the first, and over REQ/REP to the others, without dropping the
+connection. Exits non-zero if any check fails.
+"""
+
+import sys
+
+from _harness import Checks
+import comm
+
+DRIVE_PORT = 5557
+
+MOCK_ROBOTS = {
+    "drive_channel": {"status": "success", "drive_port": DRIVE_PORT, "unacked_commands": ["joystick"]},
+    "no_drive_channel": {"status": "success"},
+    "legacy": {"status": "error", "message": "Unknown command: hello"},
+    "malformed_drive_port": {"status": "success", "drive_port": "fast", "unacked_commands": "joystick"},
+}
+
+
+class MockRobotClient(comm.RobotClient):
+    """RobotClient whose replies come from a canned 'hello' instead of the REQ socket."""
+
+    def __init__(self, hello_reply):
+        super().__init__("127.0.0.1")
+        self.hello_reply = hello_reply
+        self.acked = []
+        self.unacked = []
+
+    def send_raw(self, command):
+        self.acked.append(command["type"])
+        if command["type"] == "hello":
+            return dict(self.hello_reply)
+        if command["type"] == "capabilities":
+            return {"status": "success", "commands": ["capabilities", "joystick", "ping"]}
+        return {"status": "success"}
+
+    def send_unacked(self, command):
+        self.unacked.append(command["type"])
+        return True
+
+
+def main():
+    check = Checks()
+
+    for name, hello_reply in MOCK_ROBOTS.items():
+        client = MockRobotClient(hello_reply)
+        try:
+            client.handshake()
+            client.send_joystick(0.5, 0.0, 0.0, 0.0)
+            client.send_ping()
+
+            if name == "drive_channel":
+                check(client.drive_socket is not None, f"{name}: drive channel not opened")
+                check(client.unacked == ["joystick"], f"{name}: unacked frames {client.unacked}")
+                check("joystick" not in client.acked, f"{name}: joystick also sent over REQ/REP")
+            else:
+                check(client.drive_socket is None, f"{name}: drive channel opened without being offered")
+                check(client.unacked == [], f"{name}: unacked frames {client.unacked}")
+                check("joystick" in client.acked, f"{name}: joystick not sent over REQ/REP")
+            check("ping" in client.acked, f"{name}: ping not sent over REQ/REP")
+        finally:
+            client.cleanup()
+
+    return check.report(f"handshake negotiated or fell back for {', '.join(MOCK_ROBOTS)}")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
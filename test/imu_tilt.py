@@ -0,0 +1,112 @@
+"""Check IMU orientation telemetry and the robot's tilt lockout, using injected readings.
+
+Drives RobotServer and calls the driver's telemetry parser directly:
+
+    python test/imu_tilt.py
+
+Asserts that readings reach telemetry (malformed or failing ones as no
+reading), that leaning past the tilt limit stops the motors with one 'tilt'
+alert and holds joystick frames off, that the lockout only clears below the
+hysteresis band and then waits for centered sticks, that a limit of 0 leaves
+the robot alone, and that the driver parses Euler and quaternion readings and
+rejects broken ones. Exits non-zero if any check fails.
+"""
+
+import logging
+import math
+import sys
+
+from _harness import Checks, send
+import robot
+import driver
+
+LIMIT_DEG = 30.0
+
+
+def tilt_alerts(server):
+    return sum(1 for alert in server.alerts if alert["kind"] == "tilt")
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    check = Checks()
+
+    reading = [None]
+
+    def read():
+        if isinstance(reading[0], Exception):
+            raise reading[0]
+        return reading[0]
+
+    def lean(value):
+        reading[0] = value
+        server._poll_imu()
+
+    forward = robot.JOYSTICK_Y_SIGN
+    robot.TILT_LIMIT_DEG = LIMIT_DEG
+    server = robot.RobotServer(bind=False)
+    server.read_orientation = read
+    try:
+        lean(None)
+        check(server.telemetry_data["imu"] is None, "no IMU reported a reading")
+        lean((2.0, -3.0, 370.0))
+        check(server.telemetry_data["imu"] == {"roll": 2.0, "pitch": -3.0, "yaw": 10.0},
+              f"reading {server.telemetry_data['imu']}")
+        for bad in (("level", 0.0, 0.0), (math.nan, 0.0, 0.0), (1.0, 2.0), OSError("i2c bus error")):
+            lean(bad)
+            check(server.telemetry_data["imu"] is None, f"{bad!r} reported as {server.telemetry_data['imu']}")
+
+        send(server, "mode", mode="TELEOP")
+        lean((0.0, 0.0, 0.0))
+        send(server, "joystick", ly=forward)
+        check(any(server.telemetry_data["motor_speeds"]), "level robot didn't drive")
+
+        lean((5.0, 35.0, 0.0))
+        check(server.tilted and not any(server.telemetry_data["motor_speeds"]), "tilt didn't stop the motors")
+        check(tilt_alerts(server) == 1, f"expected one tilt alert, got {tilt_alerts(server)}")
+        check(server.telemetry_data["tilt"] == {"limit_deg": LIMIT_DEG, "tripped": True},
+              f"telemetry tilt {server.telemetry_data['tilt']}")
+        check(server.control_state()["summary"] == "TELEOP TILTED", f"state {server.control_state()['summary']}")
+        reply = send(server, "joystick", ly=forward)
+        check(reply.get("tilted") and not any(server.telemetry_data["motor_speeds"]), f"drove while tilted: {reply}")
+
+        lean((-40.0, 0.0, 0.0))
+        lean(None)
+        check(server.tilted and tilt_alerts(server) == 1, "lockout cleared or alert repeated without a level reading")
+        lean((0.0, LIMIT_DEG - 2.0, 0.0))
+        check(server.tilted, "cleared inside the hysteresis band")
+        lean((0.0, 3.0, 0.0))
+        check(not server.tilted and not server.telemetry_data["tilt"]["tripped"], "level robot still locked out")
+        reply = send(server, "joystick", ly=forward)
+        check(reply.get("awaiting_center") and not any(server.telemetry_data["motor_speeds"]),
+              f"lurched off with a deflected stick: {reply}")
+        send(server, "joystick", ly=0.0)
+        send(server, "joystick", ly=forward)
+        check(any(server.telemetry_data["motor_speeds"]), "didn't drive again after centering")
+
+        robot.TILT_LIMIT_DEG = 0.0
+        lean((80.0, 80.0, 0.0))
+        check(not server.tilted and any(server.telemetry_data["motor_speeds"]), "tilt reaction ran while disabled")
+    finally:
+        server.cleanup()
+
+    check(driver.parse_orientation({"roll": "1.5", "pitch": -2, "yaw": -90}) == {"roll": 1.5, "pitch": -2.0,
+                                                                                "yaw": 270.0},
+          "Euler reading misparsed")
+    half = math.sqrt(0.5)
+    quarter_turn = driver.parse_orientation({"w": half, "x": 0.0, "y": 0.0, "z": half})
+    check(quarter_turn is not None and abs(quarter_turn["yaw"] - 90.0) < 1e-6
+          and abs(quarter_turn["roll"]) < 1e-6 and abs(quarter_turn["pitch"]) < 1e-6,
+          f"quaternion yaw {quarter_turn}")
+    rolled = driver.parse_orientation({"w": 2 * math.cos(math.radians(10)), "x": 2 * math.sin(math.radians(10)),
+                                       "y": 0.0, "z": 0.0})
+    check(rolled is not None and abs(rolled["roll"] - 20.0) < 1e-6, f"unnormalized quaternion roll {rolled}")
+    for bad in (None, [1.0, 2.0, 3.0], {"roll": 1.0, "pitch": 2.0}, {"roll": "x", "pitch": 0, "yaw": 0},
+                {"roll": math.inf, "pitch": 0, "yaw": 0}, {"w": 0, "x": 0, "y": 0, "z": 0}):
+        check(driver.parse_orientation(bad) is None, f"{bad!r} parsed")
+
+    return check.report("orientation reported, tilt stops the robot until it is level and the sticks centered")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
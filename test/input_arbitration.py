@@ -0,0 +1,57 @@
+"""Check how the driver arbitrates between sticks and the D-pad within one frame.
+
+Exercises driver.arbitrate_drive_inputs and driver.dpad_nudge without a controller:
+
+    python test/input_arbitration.py
+
+Feeds frames where both controls are active at once and asserts that a deflected
+stick always wins over the D-pad as a whole, that the D-pad applies only when the
+sticks are at rest, and that the result never mixes directions from both. Exits
+non-zero if any check fails.
+"""
+
+import sys
+
+from _harness import Checks
+import driver
+
+NUDGE = 0.25
+REST = (0.0, 0.0, 0.0, 0.0)
+
+
+def main():
+    check = Checks()
+
+    def arbitrate(stick, hat):
+        return driver.arbitrate_drive_inputs(stick, driver.dpad_nudge(hat, NUDGE))
+
+    # (stick [lx, ly, rx, ry], hat (x, y), expected, why)
+    frames = [
+        (REST, (0, 0), REST, "nothing pressed"),
+        (REST, (0, 1), (0.0, NUDGE, 0.0, 0.0), "D-pad up alone drives forward"),
+        (REST, (-1, 0), (-NUDGE, 0.0, 0.0, 0.0), "D-pad left alone strafes left"),
+        (REST, (1, -1), (NUDGE, -NUDGE, 0.0, 0.0), "D-pad diagonal alone"),
+        ((0.0, 0.8, 0.0, 0.0), (0, -1), (0.0, 0.8, 0.0, 0.0), "stick forward beats D-pad back"),
+        ((0.0, 0.8, 0.0, 0.0), (1, 0), (0.0, 0.8, 0.0, 0.0), "stick forward + D-pad right doesn't go diagonal"),
+        ((0.0, 0.0, -0.5, 0.0), (0, 1), (0.0, 0.0, -0.5, 0.0), "rotating on the stick ignores D-pad"),
+        ((0.05, 0.0, 0.0, 0.0), (0, 1), (0.05, 0.0, 0.0, 0.0), "any stick deflection past deadzone wins"),
+    ]
+    for stick, hat, expected, why in frames:
+        result = arbitrate(stick, hat)
+        check(result == expected, f"{why}: {stick} + hat {hat} -> {result}, expected {expected}")
+
+    # Whatever wins, the output comes from exactly one control.
+    for stick, hat, _, why in frames:
+        result = arbitrate(stick, hat)
+        from_stick = result == tuple(stick)
+        from_dpad = result == driver.dpad_nudge(hat, NUDGE)
+        check(from_stick or from_dpad, f"{why}: output mixes sources: {result}")
+
+    check(driver.dpad_nudge((0, 0), NUDGE) == REST, "centered hat is not at rest")
+    check(all(v == 0.0 for v in driver.dpad_nudge((1, 1), NUDGE)[2:]), "D-pad must never rotate")
+
+    return check.report(f"{len(frames)} conflicting-input frames arbitrated")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
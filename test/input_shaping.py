@@ -0,0 +1,70 @@
+"""Check the robot-side stick shaping set by the 'shape' command.
+
+Drives RobotServer and calls the driver station's shaping function directly:
+
+    python test/input_shaping.py
+
+Sets the robot's deadzone and expo with 'shape' and asserts they are range
+checked, persisted and reported by 'shape_query', and that joystick frames are
+shaped by them. Frames a driver station marks "shaped": true get the robot's
+curve too, on top of the station's own: the two stages compose, and a robot
+set to 0 / 1.00 leaves the station's shaping as it is. Exits non-zero if any
+check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import driver
+import robot
+
+
+def forward_target(server, ly, **fields):
+    """The forward demand the robot takes from a stick pushed ``ly`` forward."""
+    send(server, "joystick", lx=0.0, ly=ly * robot.JOYSTICK_Y_SIGN, rx=0.0, ry=0.0, **fields)
+    return server.velocity_target[0]
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    settings = {}
+    server = robot.RobotServer(bind=False)
+    server._save_settings = lambda: settings.update(server.settings)
+    check = Checks()
+
+    try:
+        send(server, "mode", mode="TELEOP")
+
+        reply = send(server, "shape", deadzone=0.2, expo=2.0)
+        check(reply.get("status") == "success" and reply.get("shape") == {"deadzone": 0.2, "expo": 2.0},
+              f"shape: {reply}")
+        check(settings.get("input_shaping") == {"deadzone": 0.2, "expo": 2.0}, f"shaping not persisted: {settings}")
+        for fields in ({"deadzone": 0.9}, {"expo": 0.5}, {"expo": "soft"}):
+            reply = send(server, "shape", **fields)
+            check(reply.get("status") == "error", f"shape {fields} accepted: {reply}")
+        check(send(server, "shape_query").get("shape") == {"deadzone": 0.2, "expo": 2.0},
+              "a refused shape changed the setting")
+
+        target = forward_target(server, 0.6)
+        check(abs(target - 0.25) < 1e-9, f"0.6 through deadzone 0.2 / expo 2 gave {target}, expected 0.25")
+        check(forward_target(server, 0.15) == 0.0, "a stick inside the deadzone moved the robot")
+
+        # A shaped frame gets the robot's stage on top of the station's, not instead of it.
+        station = driver.shape_axis(0.8, 0.1, 1.5)
+        target = forward_target(server, station, shaped=True)
+        expected = ((station - 0.2) / 0.8) ** 2.0
+        check(abs(target - expected) < 1e-9, f"shaped frame gave {target}, expected both stages: {expected}")
+        check(abs(forward_target(server, 0.6, shaped=True) - 0.25) < 1e-9, "shaped frame skipped the robot's curve")
+
+        send(server, "shape", deadzone=0.0, expo=1.0)
+        target = forward_target(server, station, shaped=True)
+        check(abs(target - station) < 1e-9, f"a neutral robot curve changed the station's output: {target}")
+    finally:
+        server.cleanup()
+
+    return check.report("robot shaping set, persisted and composed with the driver station's")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,113 @@
+"""Check that the link stats count bytes each way and report them per second.
+
+Runs RobotClient against in-memory sockets:
+
+    python test/link_bandwidth.py
+
+Sends an acked command, pushes a drive frame and receives a telemetry message,
+and asserts the link's BandwidthMeter counted exactly the bytes of each frame
+in the right direction. Then checks the rate is averaged over the window,
+that traffic older than the window drops out of it, and that the connection
+manager starts each new link from zero. Exits non-zero if any check fails.
+"""
+
+import json
+import sys
+import time
+
+import zmq
+
+from _harness import Checks
+import comm
+
+REPLY = json.dumps({"status": "success", "timestamp": 0.0}).encode()
+
+
+class EchoReq:
+    """Stands in for the REQ socket, answering every command with REPLY."""
+
+    def __init__(self):
+        self.sent = []
+
+    def send(self, payload, flags=0):
+        self.sent.append(payload)
+
+    def recv(self, flags=0):
+        return REPLY
+
+    def close(self, linger=None):
+        pass
+
+
+class CapturingPush:
+    """Stands in for the drive channel's PUSH socket."""
+
+    def __init__(self):
+        self.sent = []
+
+    def send(self, payload, flags=0):
+        self.sent.append(payload)
+
+    def close(self, linger=None):
+        pass
+
+
+class ScriptedSub:
+    """Stands in for the driver station's SUB socket, replaying queued messages."""
+
+    def __init__(self, messages):
+        self.messages = list(messages)
+
+    def recv_multipart(self, flags=0):
+        if not self.messages:
+            raise zmq.Again()
+        return self.messages.pop(0)
+
+    def close(self, linger=None):
+        pass
+
+
+def main():
+    check = Checks()
+
+    client = comm.RobotClient("127.0.0.1")
+    client.bandwidth = comm.BandwidthMeter()
+    client.command_socket = EchoReq()
+    client.drive_socket = CapturingPush()
+    telemetry = [json.dumps({"battery": 12.0, "telemetry_seq": 1}).encode()]
+    client.telemetry_socket = ScriptedSub([telemetry])
+    try:
+        check(client.send_command("ping") is not None, "ping got no reply")
+        client.send_unacked(comm.build_command("joystick", lx=0.0, ly=0.5, rx=0.0, ry=0.0))
+        check(client.receive_telemetry() is not None, "telemetry frame not received")
+        stats = client.bandwidth.snapshot()
+        sent = len(client.command_socket.sent[0]) + len(client.drive_socket.sent[0])
+        received = len(REPLY) + len(telemetry[0])
+        check(stats["sent_bytes"] == sent, f"sent {stats['sent_bytes']} bytes counted, expected {sent}")
+        check(stats["received_bytes"] == received, f"received {stats['received_bytes']} counted, expected {received}")
+    finally:
+        client.cleanup()
+
+    window = comm.BANDWIDTH_WINDOW_S
+    meter = comm.BandwidthMeter()
+    meter.started -= 2 * window
+    meter.record(sent=1000, received=4000)
+    stats = meter.snapshot()
+    check(abs(stats["sent_bytes_per_s"] - 1000 / window) < 1e-6
+          and abs(stats["received_bytes_per_s"] - 4000 / window) < 1e-6, f"rates over the window: {stats}")
+    meter.samples[0] = (time.monotonic() - 2 * window, 1000, 4000)
+    stats = meter.snapshot()
+    check(stats["sent_bytes_per_s"] == 0.0 and stats["received_bytes_per_s"] == 0.0,
+          f"traffic older than the window still counted: {stats}")
+    check(stats["sent_bytes"] == 1000 and stats["received_bytes"] == 4000, f"totals lost with the window: {stats}")
+
+    manager = comm.ConnectionManager(["127.0.0.1"])
+    manager.bandwidth.record(sent=10, received=20)
+    manager._reset_stats(comm.CONNECTING, "127.0.0.1")
+    check(manager.bandwidth.snapshot()["received_bytes"] == 0, "a new link inherited the last one's bandwidth")
+
+    return check.report("bytes counted each way, averaged over the window, reset per link")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,110 @@
+"""Check link URL parsing and transport selection.
+
+Exercises comm.parse_link_url and comm.open_link without sockets:
+
+    python test/link_urls.py
+
+Parses one address per supported scheme (plus the bare hosts that
+KSU_ROBOT_ADDRESSES has always accepted), a set of malformed addresses that
+must be rejected with LinkUrlError, and checks that open_link builds a TCP
+client with the right ports, refuses schemes with no transport, and uses a
+factory added with register_link. Exits non-zero if any check fails.
+"""
+
+import sys
+
+from _harness import Checks
+import comm
+
+# address -> (scheme, host, port, path, params that must be present)
+VALID = {
+    "10.42.0.3": ("tcp", "10.42.0.3", 5555, "", {"telemetry": "5556"}),
+    "robot.local:6000": ("tcp", "robot.local", 6000, "", {"telemetry": "6001"}),
+    "fe80::1": ("tcp", "fe80::1", 5555, "", {}),
+    "[fe80::1]:7000": ("tcp", "fe80::1", 7000, "", {}),
+    "tcp://10.42.0.3:5000": ("tcp", "10.42.0.3", 5000, "", {"telemetry": "5001"}),
+    "tcp://10.42.0.3:5000?telemetry=6000": ("tcp", "10.42.0.3", 5000, "", {"telemetry": "6000"}),
+    "TCP://10.42.0.3": ("tcp", "10.42.0.3", 5555, "", {}),
+    "udp://10.42.0.3:5000": ("udp", "10.42.0.3", 5000, "", {}),
+    "tls://robot.local:5443": ("tls", "robot.local", 5443, "", {}),
+    "ws://robot.local:8080/": ("ws", "robot.local", 8080, "", {}),
+    "serial:///dev/ttyUSB0?baud=115200": ("serial", "", 0, "/dev/ttyUSB0", {"baud": "115200"}),
+    "serial:///dev/ttyACM1": ("serial", "", 0, "/dev/ttyACM1", {"baud": str(comm.DEFAULT_SERIAL_BAUD)}),
+}
+
+INVALID = [
+    "",
+    "   ",
+    "http://10.42.0.3:80",
+    "tcp://:5000",
+    "udp://",
+    "tcp://10.42.0.3:abc",
+    "tcp://10.42.0.3:70000",
+    "tcp://10.42.0.3:0",
+    "tcp://10.42.0.3:65535",
+    "tcp://10.42.0.3:5000?telemetry=x",
+    "tcp://10.42.0.3:5000/robot",
+    "serial://",
+    "serial://host/dev/ttyUSB0",
+    "serial:///dev/ttyUSB0?baud=fast",
+    "serial:///dev/ttyUSB0?baud=0",
+]
+
+
+def main():
+    check = Checks()
+
+    for address, (scheme, host, port, path, params) in VALID.items():
+        try:
+            spec = comm.parse_link_url(address)
+        except comm.LinkUrlError as e:
+            check.fail(f"{address!r} rejected: {e}")
+            continue
+        got = (spec.scheme, spec.host, spec.port, spec.path)
+        check(got == (scheme, host, port, path), f"{address!r} parsed as {got}")
+        for key, value in params.items():
+            check(spec.params.get(key) == value, f"{address!r} {key}={spec.params.get(key)!r}, expected {value!r}")
+
+    for address in INVALID:
+        try:
+            spec = comm.parse_link_url(address)
+        except comm.LinkUrlError:
+            continue
+        except Exception as e:
+            check.fail(f"{address!r} raised {type(e).__name__} instead of LinkUrlError: {e}")
+            continue
+        check.fail(f"{address!r} accepted as {spec}")
+
+    check(comm.parse_link_url("10.42.0.3").display() == "10.42.0.3:5555", "tcp display form changed")
+    check(comm.parse_link_url("fe80::1").display() == "[fe80::1]:5555", "IPv6 display isn't bracketed")
+
+    client = comm.open_link(comm.parse_link_url("tcp://127.0.0.1:6000?telemetry=6100"))
+    check(isinstance(client, comm.RobotClient), f"tcp opened {type(client).__name__}")
+    check((client.command_port, client.telemetry_port) == (6000, 6100),
+          f"tcp client ports {(client.command_port, client.telemetry_port)}")
+    client.cleanup()
+
+    try:
+        comm.open_link(comm.parse_link_url("serial:///dev/ttyUSB0"))
+        check.fail("serial opened with no transport registered")
+    except comm.LinkUnsupported:
+        pass
+
+    opened = []
+    comm.register_link("udp", lambda spec: opened.append(spec) or "udp-link")
+    try:
+        link = comm.open_link(comm.parse_link_url("udp://10.42.0.3:5000"))
+        check(link == "udp-link" and opened and opened[0].port == 5000, "registered udp factory wasn't used")
+    finally:
+        comm.LINK_FACTORIES.pop("udp", None)
+    try:
+        comm.register_link("carrier-pigeon", lambda spec: None)
+        check.fail("registered a scheme parse_link_url can't produce")
+    except ValueError:
+        pass
+
+    return check.report(f"{len(VALID)} link URLs parsed, {len(INVALID)} rejected, transports dispatched by scheme")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
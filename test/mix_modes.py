@@ -0,0 +1,98 @@
+"""Check the mecanum desaturation strategies and the command that picks one.
+
+Calls robot.mix_motor_speeds directly and drives RobotServer without sockets:
+
+    python test/mix_modes.py
+
+Asserts that inputs inside the motors' range mix the same in every mode, that
+saturating inputs come out as each strategy promises (scale keeps the ratios,
+clip caps each motor, translation and forward keep their part at full strength
+and hand the rest what is left), that no mode leaves [-1, 1], and that the
+mix_mode command validates, persists in the settings and shows in telemetry and
+config, and that a malformed stored mode falls back to the default. Exits
+non-zero if any check fails.
+"""
+
+import itertools
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+
+def close(a, b):
+    return all(abs(x - y) < 1e-9 for x, y in zip(a, b))
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    check = Checks()
+
+    gentle = (0.3, 0.2, 0.1)
+    for mode in robot.MIX_MODES:
+        speeds = robot.mix_motor_speeds(*gentle, mode)
+        check(close(speeds, robot.mix_motor_speeds(*gentle)), f"{mode} changed an unsaturated mix: {speeds}")
+
+    # Half of each axis saturates the front left motor; every mode resolves it differently.
+    expected = {
+        "scale": [1.0, -1 / 3, 1 / 3, 1 / 3],
+        "clip": [1.0, -0.5, 0.5, 0.5],
+        "translation": [1.0, 0.0, 0.0, 1.0],
+        "forward": [1.0, 0.0, 0.5, 0.5],
+    }
+    for mode, want in expected.items():
+        speeds = robot.mix_motor_speeds(0.5, 0.5, 0.5, mode)
+        check(close(speeds, want), f"{mode} mixed half of each axis to {speeds}, expected {want}")
+
+    # Translation alone over range is scaled, keeping its direction.
+    speeds = robot.mix_motor_speeds(1.0, 1.0, 0.5, "translation")
+    check(close(speeds, [1.0, 0.0, 0.0, 1.0]), f"diagonal with a turn: {speeds}")
+    # Forward keeps full power while strafe and rotation share what's left.
+    speeds = robot.mix_motor_speeds(1.0, 0.2, 0.0, "forward")
+    check(close(speeds, [1.0, 1.0, 1.0, 1.0]), f"full forward gave way to strafe: {speeds}")
+    speeds = robot.mix_motor_speeds(0.6, 0.5, 0.5, "forward")
+    check(close([(speeds[0] + speeds[1]) / 2, (speeds[2] + speeds[3]) / 2], [0.6, 0.6]),
+          f"forward component lost: {speeds}")
+
+    steps = [i / 4 for i in range(-4, 5)]
+    for mode in robot.MIX_MODES:
+        worst = max(max(abs(s) for s in robot.mix_motor_speeds(f, s, r, mode))
+                    for f, s, r in itertools.product(steps, repeat=3))
+        check(worst <= 1.0 + 1e-9, f"{mode} commanded {worst:.3f}")
+
+    settings = {}
+    server = robot.RobotServer(bind=False)
+    server._save_settings = lambda: settings.update(server.settings)
+    try:
+        check(send(server, "mix_mode_query").get("mode") == "scale", "scale is not the default")
+        reply = send(server, "mix_mode", mode="Translation")
+        check(reply.get("status") == "success" and reply.get("mode") == "translation", f"mix_mode: {reply}")
+        check(server.mix_mode == "translation" and settings.get("mix_mode") == "translation",
+              f"mix mode not persisted: {settings}")
+        check(server.telemetry_data.get("mix_mode") == "translation", "telemetry missed the mix mode")
+        reply = send(server, "mix_mode", mode="squash")
+        check(reply.get("status") == "error" and server.mix_mode == "translation", f"unknown mode accepted: {reply}")
+        reply = send(server, "config", key="mix_mode", value="clip")
+        check(reply.get("status") == "success" and server.mix_mode == "clip", f"config mix_mode: {reply}")
+        check(send(server, "config_query").get("config", {}).get("mix_mode") == "clip", "config_query missed mix_mode")
+    finally:
+        server.cleanup()
+
+    # A broken settings file falls back to the default mode instead of stopping the robot from starting.
+    load_settings = robot.load_robot_settings
+    try:
+        for stored in (["clip"], {"mode": "clip"}, 3, "squash"):
+            robot.load_robot_settings = lambda stored=stored: {"mix_mode": stored}
+            server = robot.RobotServer(bind=False)
+            mode = server.mix_mode
+            server.cleanup()
+            check(mode == "scale", f"stored {stored!r} gave mix mode {mode!r}")
+    finally:
+        robot.load_robot_settings = load_settings
+
+    return check.report("each mix mode desaturates as documented and the choice persists")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,64 @@
+"""Check that reversing a motor negates exactly that motor's output.
+
+    python test/motor_reverse.py
+
+Captures what the drive path sends to the motor hardware for a full-forward
+stick, reverses one motor, and asserts that only that motor's output flips
+while telemetry keeps reporting the drivetrain's speeds. Also checks that the
+setting can't change while the motors are turning. Exits non-zero if any
+check fails.
+"""
+
+import logging
+import sys
+
+from _harness import Checks, send
+import robot
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    written = []
+    robot.set_motor_speeds = lambda speeds: written.append(list(speeds))
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    try:
+        send(server, "mode", mode="TELEOP")
+        send(server, "joystick", ly=-1.0, rx=0.3)
+        normal = written[-1]
+        check(all(normal), f"expected every motor driven: {normal}")
+
+        check(send(server, "motor_reverse", motor="FR", reversed=True)["status"] == "error",
+              "direction changed while the motors were turning")
+        send(server, "joystick")
+        check(send(server, "motor_reverse", motor="XX", reversed=True)["status"] == "error", "bad motor accepted")
+        check(send(server, "motor_reverse", motor=4, reversed=True)["status"] == "error", "bad index accepted")
+        check(send(server, "motor_reverse", motor="FR", reversed="on")["status"] == "error", "non-bool accepted")
+        reply = send(server, "motor_reverse", motor=1, reversed=True)
+        check(reply["status"] == "success" and reply["reversed"] == {"FL": False, "FR": True, "RL": False, "RR": False},
+              f"reverse by index: {reply}")
+
+        send(server, "joystick", ly=-1.0, rx=0.3)
+        flipped = written[-1]
+        check(flipped == [normal[0], -normal[1], normal[2], normal[3]],
+              f"reversing FR sent {flipped}, from {normal}")
+        check(server.telemetry_data["motor_speeds"] == normal,
+              f"telemetry should keep the unreversed speeds: {server.telemetry_data['motor_speeds']}")
+        check(server.telemetry_data["motor_reversed"] == [False, True, False, False],
+              f"telemetry motor_reversed {server.telemetry_data['motor_reversed']}")
+        check(server.settings.get("motor_reverse") == [False, True, False, False], "setting not persisted")
+
+        send(server, "joystick")
+        send(server, "motor_reverse", motor="fr", reversed=False)
+        send(server, "joystick", ly=-1.0, rx=0.3)
+        check(written[-1] == normal, f"un-reversing FR sent {written[-1]}")
+        check(send(server, "motor_reverse_query")["reversed"]["FR"] is False, "query still reports FR reversed")
+    finally:
+        server.cleanup()
+
+    return check.report("reversing a motor negates only its output")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
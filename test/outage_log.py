@@ -0,0 +1,114 @@
+"""Check that connection outages are logged with their cause, duration and uptime.
+
+Drives comm.ConnectionManager's lifecycle hooks without sockets:
+
+    python test/outage_log.py
+
+Connects to a fake robot, drops the link with a reason, fails a retry and
+reconnects on another address, then asserts the outage recorded both addresses,
+the reason, the attempts and a duration, that the uptime summary accounts for
+the time down, and that the JSON lines file ends with a session summary once
+closed. Exits non-zero if any check fails.
+"""
+
+import json
+import os
+import sys
+import tempfile
+import time
+
+from _harness import Checks
+import comm
+
+ADDRESSES = ["tcp://a:5555", "tcp://b:5555"]
+OUTAGE_S = 0.2
+
+
+class FakeClient:
+    """Stands in for a handshaken RobotClient."""
+
+    def __init__(self):
+        self.connected = True
+        self.consecutive_timeouts = 0
+        self.disconnect_reason = ""
+        self.signals = None
+        self.on_status = None
+        self.wire_log = None
+
+    def drop(self, reason):
+        self.connected = False
+        self.disconnect_reason = reason
+        self.on_status(False)
+
+    def flush_outbox(self):
+        pass
+
+    def cleanup(self):
+        self.connected = False
+
+
+class ScriptedManager(comm.ConnectionManager):
+    """Only addresses in ``reachable`` accept the connection."""
+
+    def __init__(self, reachable):
+        super().__init__()
+        self.reachable = set(reachable)
+
+    def _connect(self, address):
+        return FakeClient() if address in self.reachable else None
+
+
+def main():
+    check = Checks()
+
+    comm.ROBOT_ADDRESSES = list(ADDRESSES)
+    path = os.path.join(tempfile.mkdtemp(), "outages.jsonl")
+    manager = ScriptedManager(reachable={ADDRESSES[0]})
+    manager.outages.path = path
+
+    # Searching at launch is not an outage.
+    manager._attempt_connection()
+    check(manager.client is not None, "never connected")
+    check(manager.outages.snapshot() == [], "launch counted as an outage")
+
+    manager.client.drop("2 command timeouts in a row")
+    open_outage = manager.outages.snapshot()
+    check(len(open_outage) == 1 and "duration_s" not in open_outage[0], f"open outage not shown: {open_outage}")
+    manager.reachable = {ADDRESSES[1]}
+    time.sleep(OUTAGE_S)
+    for _ in range(3):
+        manager._attempt_connection()
+        if manager.client is not None and manager.client.connected:
+            break
+
+    outages = manager.outages.snapshot()
+    check(len(outages) == 1, f"expected one outage, got {outages}")
+    if outages:
+        outage = outages[0]
+        check(outage["failed_address"] == ADDRESSES[0], f"failed address {outage['failed_address']}")
+        check(outage["reconnected_address"] == ADDRESSES[1], f"reconnected address {outage['reconnected_address']}")
+        check(outage["reason"] == "2 command timeouts in a row", f"reason {outage['reason']!r}")
+        check(outage["attempts"] == 2, f"{outage['attempts']} attempts")
+        check(outage["duration_s"] >= OUTAGE_S, f"duration {outage['duration_s']:.3f} s")
+
+    summary = manager.outages.summary()
+    check(summary["outages"] == 1, f"summary counts {summary['outages']} outages")
+    check(summary["down_s"] >= OUTAGE_S and 0.0 < summary["uptime_pct"] < 100.0, f"summary {summary}")
+
+    # A drop that is still open at shutdown is logged without a reconnect address.
+    manager.client.drop("Connection reset")
+    manager.outages.close()
+    manager.client.drop("after close")
+    with open(path, encoding="utf-8") as f:
+        entries = [json.loads(line) for line in f]
+    kinds = [entry["event"] for entry in entries]
+    check(kinds == ["outage", "outage", "session"], f"log holds {kinds}")
+    if kinds == ["outage", "outage", "session"]:
+        check(entries[1]["reconnected_address"] is None, "open outage logged as reconnected")
+        check(entries[2]["outages"] == 2 and entries[2]["uptime_pct"] < 100.0, f"session line {entries[2]}")
+
+    return check.report(f"outages logged with cause and duration, uptime {summary['uptime_pct']:.1f}%")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
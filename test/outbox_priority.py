@@ -0,0 +1,109 @@
+"""Check that the reliable outbox replays safety commands first and never drops them.
+
+Exercises comm.ReliableOutbox and RobotClient.flush_outbox without sockets:
+
+    python test/outbox_priority.py
+
+Saturates the outbox with mode changes while a link is down, interleaves stops,
+and asserts that the stops come out first and intact, that the bounded tier only
+keeps its newest entries, that a failed replay puts everything back in the
+same order, that a resume replays after its pause but not after a later one,
+and that the replay runs before the other connect hooks. Exits non-zero if any
+check fails.
+"""
+
+import sys
+
+from _harness import Checks
+import comm
+
+MAX_ENTRIES = 4
+
+
+class FlakyLink:
+    """Stands in for RobotClient: the first ``fail_after`` sends succeed, then the link drops."""
+
+    flush_outbox = comm.RobotClient.flush_outbox
+    history = None
+
+    def __init__(self, outbox, fail_after=None):
+        self.outbox = outbox
+        self.fail_after = fail_after
+        self.sent = []
+
+    def send_raw(self, command):
+        if self.fail_after is not None and len(self.sent) >= self.fail_after:
+            return None
+        self.sent.append(command)
+        return {"status": "success"}
+
+
+def mode(name, seq):
+    return {"type": "mode", "mode": name, "seq": seq}
+
+
+def labels(commands):
+    return [f"{c['type']}:{c.get('mode', '')}" if c["type"] == "mode" else c["type"] for c in commands]
+
+
+def main():
+    check = Checks()
+
+    # More queued mode changes than the bounded tier holds, with stops mixed in.
+    outbox = comm.ReliableOutbox(max_entries=MAX_ENTRIES)
+    outbox.add(mode("TELEOP", 1))
+    outbox.add({"type": "pause", "seq": 2})
+    for seq in range(3, 3 + MAX_ENTRIES * 2):
+        outbox.add(mode("AUTO" if seq % 2 else "TELEOP", seq))
+    outbox.add({"type": "reset", "seq": 20})
+    outbox.add({"type": "pause", "seq": 21})
+    for seq in range(22, 22 + MAX_ENTRIES + 2):
+        outbox.add(mode("TELEOP", seq))
+
+    pending = outbox.drain()
+    check(labels(pending[:2]) == ["reset", "pause"], f"stops not first: {labels(pending)}")
+    check(pending[1]["seq"] == 21, "repeated pause did not replace the earlier one")
+    rest = pending[2:]
+    check(len(rest) == MAX_ENTRIES, f"bounded tier kept {len(rest)} entries, expected {MAX_ENTRIES}")
+    check([c["seq"] for c in rest] == list(range(22 + 2, 22 + MAX_ENTRIES + 2)),
+          f"bounded tier did not keep the newest, in order: {[c['seq'] for c in rest]}")
+    check(all(c["seq"] > 21 for c in rest), "commands queued before a stop were replayed after it")
+    check(outbox.drain() == [], "drain left entries behind")
+
+    # A switch to STOPPED is a stop too, and supersedes the TELEOP queued before it.
+    outbox.add(mode("TELEOP", 30))
+    outbox.add(mode("STOPPED", 31))
+    check(labels(outbox.drain()) == ["mode:STOPPED"], "STOPPED did not supersede the earlier TELEOP")
+
+    # A replay that fails part way re-queues the rest with the stops still first.
+    outbox.add({"type": "pause", "seq": 40})
+    outbox.add(mode("TELEOP", 41))
+    outbox.add({"type": "reset", "seq": 42})
+    outbox.add(mode("AUTO", 43))
+    outbox.add(mode("TELEOP", 44))
+    link = FlakyLink(outbox, fail_after=1)
+    link.flush_outbox()
+    check(labels(link.sent) == ["pause"], f"first replay sent {labels(link.sent)}")
+    link.fail_after = None
+    link.flush_outbox()
+    check(labels(link.sent) == ["pause", "reset", "mode:AUTO", "mode:TELEOP"],
+          f"replay order after failure: {labels(link.sent)}")
+
+    # Resume is reliable too: replayed after the pause it follows, dropped by a pause after it.
+    check("resume" in comm.RELIABLE_COMMANDS, "resume is not sent reliably")
+    outbox.add({"type": "pause", "seq": 50})
+    outbox.add({"type": "resume", "seq": 51})
+    check(labels(outbox.drain()) == ["pause", "resume"], "resume not replayed after its pause")
+    outbox.add({"type": "resume", "seq": 52})
+    outbox.add({"type": "pause", "seq": 53})
+    check(labels(outbox.drain()) == ["pause"], "a resume outlived the pause sent after it")
+
+    # The replay goes out before any other connect hook can send something of its own.
+    manager = comm.ConnectionManager()
+    check(manager.hooks[0] == manager._resync_link, f"resync is not the first hook: {manager.hooks}")
+
+    return check.report("safety commands replayed first and never dropped")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,55 @@
+"""Check that reconnect waits are jittered so driver stations don't retry in lockstep.
+
+Samples comm.ReconnectMonitor's delays without sockets:
+
+    python test/reconnect_jitter.py
+
+Asserts every wait stays within the configured band around its base interval
+while actually spreading across it, that two driver stations started together
+don't pick the same waits, that a jitter of 0 gives the plain intervals, and
+that an out-of-range jitter is clamped so a wait never reaches zero. Exits
+non-zero if any check fails.
+"""
+
+import random
+import sys
+
+from _harness import Checks
+import comm
+
+SAMPLES = 2000
+
+
+def main():
+    check = Checks()
+
+    monitor = comm.ReconnectMonitor(jitter=0.25, rng=random.Random(1))
+    for linked, base in ((False, comm.RECONNECT_IDLE_S), (True, comm.RECONNECT_LINKED_S)):
+        delays = [monitor.retry_delay(linked) for _ in range(SAMPLES)]
+        low, high = min(delays), max(delays)
+        check(low >= base * 0.75 and high <= base * 1.25, f"linked={linked}: {low:.3f}-{high:.3f} s outside +/-25%")
+        check(low < base * 0.8 and high > base * 1.2, f"linked={linked}: {low:.3f}-{high:.3f} s barely spread")
+        mean = sum(delays) / len(delays)
+        check(abs(mean - base) < base * 0.02, f"linked={linked}: mean {mean:.3f} s drifted from {base} s")
+
+    first = comm.ReconnectMonitor(jitter=0.25)
+    second = comm.ReconnectMonitor(jitter=0.25)
+    together = sum(first.retry_delay(False) == second.retry_delay(False) for _ in range(100))
+    check(together == 0, f"two driver stations waited the same {together} times out of 100")
+
+    steady = comm.ReconnectMonitor(jitter=0.0)
+    check({steady.retry_delay(False) for _ in range(50)} == {comm.RECONNECT_IDLE_S}, "jitter 0 still varied")
+    check(steady.jittered(comm.FOREIGN_SERVICE_BACKOFF_S) == comm.FOREIGN_SERVICE_BACKOFF_S, "backoff changed")
+
+    wild = comm.ReconnectMonitor(jitter=5.0, rng=random.Random(2))
+    check(wild.jitter == comm.RECONNECT_MAX_JITTER, f"jitter 5.0 clamped to {wild.jitter}")
+    check(min(wild.retry_delay(True) for _ in range(SAMPLES)) > 0.0, "a wait reached zero")
+    check(comm.ReconnectMonitor(jitter=-1.0).jitter == 0.0, "negative jitter accepted")
+
+    check(comm.ConnectionManager().reconnect.jitter == comm.RECONNECT_JITTER, "manager ignores KSU_RECONNECT_JITTER")
+
+    return check.report(f"reconnect waits spread within +/-{comm.RECONNECT_JITTER:.0%}")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,93 @@
+"""Check that the driver reconnects to the last working robot address first.
+
+Exercises comm.ConnectionManager's connection attempts without sockets:
+
+    python test/reconnect_order.py
+
+Fakes a robot that only answers on one of several candidate addresses and
+asserts that once a connection there has succeeded, a lost link is retried on
+that address before cycling through the others, and that a manager told the
+address from a previous run (as the driver does from its saved settings) tries
+it first. Exits non-zero if any check fails.
+"""
+
+import sys
+
+from _harness import Checks
+import comm
+
+ADDRESSES = ["tcp://a:5555", "tcp://b:5555", "tcp://c:5555"]
+
+
+class FakeClient:
+    """Stands in for a handshaken RobotClient."""
+
+    def __init__(self):
+        self.connected = True
+        self.consecutive_timeouts = 0
+        self.signals = None
+        self.on_status = None
+
+    def cleanup(self):
+        self.connected = False
+
+
+class ScriptedManager(comm.ConnectionManager):
+    """Records each address tried; only those in ``reachable`` accept the connection."""
+
+    def __init__(self, reachable):
+        super().__init__()
+        self.hooks = []
+        self.reachable = set(reachable)
+        self.attempts = []
+
+    def _connect(self, address):
+        self.attempts.append(address)
+        return FakeClient() if address in self.reachable else None
+
+
+def main():
+    check = Checks()
+
+    comm.ROBOT_ADDRESSES = list(ADDRESSES)
+
+    # Cold start with nothing remembered: walk the list until the robot answers.
+    manager = ScriptedManager(reachable={ADDRESSES[1]})
+    while manager.client is None and len(manager.attempts) < 10:
+        manager._attempt_connection()
+    check(manager.attempts == ADDRESSES[:2], f"cold start tried {manager.attempts}")
+    check(manager.current_address_idx == 1, f"left on address {manager.current_address_idx}")
+
+    # The link drops while the robot is briefly unreachable: the working address
+    # comes first on the retry, and again first once the others have been tried.
+    manager.client.connected = False
+    manager.reachable.clear()
+    manager.attempts.clear()
+    for _ in range(4):
+        manager._attempt_connection()
+    check(manager.attempts == [ADDRESSES[1], ADDRESSES[2], ADDRESSES[0], ADDRESSES[1]],
+          f"retries after a loss went {manager.attempts}")
+    manager.reachable.add(ADDRESSES[1])
+    manager.attempts.clear()
+    while manager.client is None and len(manager.attempts) < 10:
+        manager._attempt_connection()
+    check(manager.attempts == [ADDRESSES[2], ADDRESSES[0], ADDRESSES[1]],
+          f"reconnect went {manager.attempts}")
+
+    # A new run told the last working address tries it before anything else.
+    manager = ScriptedManager(reachable={ADDRESSES[2]})
+    manager.prefer_address(ADDRESSES[2])
+    manager._attempt_connection()
+    check(manager.attempts == [ADDRESSES[2]], f"preferred address not first: {manager.attempts}")
+
+    # A remembered address that is no longer configured is ignored.
+    manager = ScriptedManager(reachable={ADDRESSES[0]})
+    manager.prefer_address("tcp://gone:5555")
+    manager._attempt_connection()
+    check(manager.attempts == [ADDRESSES[0]], f"stale preference changed the order: {manager.attempts}")
+
+    return check.report("the last working address is tried first")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
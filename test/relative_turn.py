@@ -0,0 +1,102 @@
+"""Check the 'turn' command that rotates the robot by a relative angle.
+
+Drives RobotServer without sockets, stepping the turn controller by hand and
+moving the odometry heading between steps as the robot would:
+
+    python test/relative_turn.py
+
+Asserts that bad angles and turns outside TELEOP or against a held stick are
+refused, that the controller turns the short way across 0/360, slows as it
+nears the target and holds still once within tolerance, reporting progress in
+the 'turn' telemetry field. Then checks that a deflected stick, a stop, an
+odometry reset and the timeout each abort the turn, while centered frames
+don't. Exits non-zero if any check fails.
+"""
+
+import logging
+import sys
+import time
+
+from _harness import Checks, send
+import robot
+
+
+def step(server, heading_deg):
+    """Move the odometry heading to ``heading_deg`` and run one controller step."""
+    server.pose_theta_deg = heading_deg
+    server._step_turn(time.monotonic())
+    return server.velocity_target[2]
+
+
+def main():
+    logging.getLogger("robot").setLevel(logging.CRITICAL)
+    written = []
+    robot.set_motor_speeds = lambda speeds: written.append(list(speeds))
+    server = robot.RobotServer(bind=False)
+    check = Checks()
+
+    try:
+        reply = send(server, "turn", degrees=90)
+        check(reply.get("status") == "error", f"turn while STOPPED accepted: {reply}")
+        send(server, "mode", mode="TELEOP")
+        for degrees in (None, "left", 400, float("nan")):
+            reply = send(server, "turn", degrees=degrees)
+            check(reply.get("status") == "error", f"turn of {degrees!r} accepted: {reply}")
+        send(server, "joystick", rx=0.5)
+        check(send(server, "turn", degrees=90).get("status") == "error", "turn started against a held stick")
+        send(server, "joystick")
+
+        # From 350 degrees, a 20 degree turn crosses 0 and ends near 10.
+        server.pose_theta_deg = 350.0
+        reply = send(server, "turn", degrees=20)
+        check(reply.get("status") == "success" and reply.get("turn") == 20, f"turn: {reply}")
+        check(server.telemetry_data["turn"]["state"] == "running", f"turn telemetry {server.telemetry_data['turn']}")
+        rotate = step(server, 350.0)
+        check(abs(rotate - 20 * robot.TURN_KP) < 1e-9, f"20 degrees out gave rotate {rotate}")
+        rotate = step(server, 5.0)
+        check(abs(rotate - robot.TURN_MIN_ROTATE) < 1e-9, f"5 degrees out gave rotate {rotate}, expected the minimum")
+        check(server.telemetry_data["turn"]["turned_deg"] == 15.0,
+              f"turned across 0: {server.telemetry_data['turn']}")
+        step(server, 9.0)
+        check(server.turn_run is None and server.telemetry_data["turn"]["state"] == "done",
+              f"within tolerance: {server.telemetry_data['turn']}")
+        check(server.velocity_target == (0.0, 0.0, 0.0) and written[-1] == robot.ZERO_MOTOR_SPEEDS,
+              f"finished turn left {server.velocity_target}, motors {written[-1]}")
+
+        send(server, "turn", degrees=-180)
+        rotate = step(server, server.pose_theta_deg)
+        check(rotate == -robot.TURN_MAX_ROTATE, f"a long right turn gave rotate {rotate}, expected the maximum")
+        send(server, "joystick")
+        check(server.turn_run is not None, "a centered frame interrupted the turn")
+
+        aborts = (
+            (lambda: send(server, "joystick", lx=0.5), "manual takeover"),
+            (lambda: send(server, "reset_odometry"), "odometry reset"),
+            (lambda: send(server, "mode", mode="STOPPED"), "drive stopped"),
+        )
+        for abort, reason in aborts:
+            send(server, "mode", mode="TELEOP")
+            send(server, "joystick")
+            send(server, "turn", degrees=45)
+            step(server, server.pose_theta_deg)
+            abort()
+            turn = server.telemetry_data["turn"]
+            check(server.turn_run is None and turn["state"] == "aborted" and turn["reason"] == reason,
+                  f"{reason}: {turn}")
+
+        send(server, "mode", mode="TELEOP")
+        send(server, "joystick")
+        send(server, "turn", degrees=45)
+        server.turn_run.started -= robot.TURN_TIMEOUT_S + 1
+        step(server, server.pose_theta_deg)
+        turn = server.telemetry_data["turn"]
+        check(turn["state"] == "aborted" and turn["reason"] == "timed out", f"timeout: {turn}")
+        check(server.velocity_target == (0.0, 0.0, 0.0), f"timed-out turn left {server.velocity_target}")
+    finally:
+        server.cleanup()
+
+    return check.report("turns run on the odometry heading, finish within tolerance and abort cleanly")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
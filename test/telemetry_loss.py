@@ -0,0 +1,89 @@
+"""Check that telemetry loss is estimated from gaps in the robot's frame numbers.
+
+Feeds numbered telemetry frames through RobotClient.receive_telemetry against
+an in-memory socket:
+
+    python test/telemetry_loss.py
+
+Drops frames across the wrap of the 16-bit counter and asserts exactly those
+are counted lost, that repeated and slightly older frames count as late rather
+than filling a gap, that a robot restarting its count isn't taken for
+thousands of lost frames, and that frames without a number (older robots) are
+ignored. Exits non-zero if any check fails.
+"""
+
+import json
+import sys
+
+import zmq
+
+from _harness import Checks
+import comm
+
+
+class ScriptedSub:
+    """Stands in for the driver station's SUB socket, replaying queued messages."""
+
+    def __init__(self, messages):
+        self.messages = list(messages)
+
+    def recv_multipart(self, flags=0):
+        if not self.messages:
+            raise zmq.Again()
+        return self.messages.pop(0)
+
+    def close(self, linger=None):
+        pass
+
+
+def frame(seq):
+    data = {"battery": 12.0} if seq is None else {"battery": 12.0, "telemetry_seq": seq}
+    return [comm.CHANNEL_TELEMETRY, json.dumps(data).encode()]
+
+
+def receive(seqs):
+    client = comm.RobotClient("127.0.0.1")
+    client.telemetry_loss = comm.TelemetryLossMeter()
+    client.telemetry_socket = ScriptedSub(frame(seq) for seq in seqs)
+    try:
+        while client.receive_telemetry() is not None:
+            pass
+    finally:
+        client.cleanup()
+    return client.telemetry_loss.snapshot()
+
+
+def main():
+    check = Checks()
+
+    top = comm.TELEMETRY_SEQ_MODULUS
+    # 100 frames straddling the wrap, with 5 dropped on either side of it.
+    sent = [(top - 50 + i) % top for i in range(100)]
+    dropped = {sent[10], sent[11], sent[49], sent[50], sent[80]}
+    loss = receive(seq for seq in sent if seq not in dropped)
+    check(loss["received"] == 95 and loss["lost"] == 5, f"across the wrap: {loss}")
+    check(abs(loss["loss_pct"] - 5.0) < 1e-9, f"loss {loss['loss_pct']}%")
+    check(loss["recent_pct"] is not None and abs(loss["recent_pct"] - 5.0) < 1e-9, f"recent {loss['recent_pct']}")
+
+    loss = receive([1, 2, 2, 4, 3, 5])
+    check(loss["lost"] == 1 and loss["late"] == 2 and loss["received"] == 4, f"repeat and reorder: {loss}")
+    check(loss["recent_pct"] is None, "recent loss reported from too few frames")
+
+    loss = receive([5000, 5001, 5002, 0, 1, 2])
+    check(loss["lost"] == 0 and loss["received"] == 6, f"restarted robot counted as loss: {loss}")
+
+    loss = receive([None, None, 7, None, 9])
+    check(loss["received"] == 2 and loss["lost"] == 1, f"unnumbered frames miscounted: {loss}")
+
+    meter = comm.TelemetryLossMeter()
+    for seq in (10, 11):
+        meter.observe(seq)
+    meter.resync()
+    meter.observe(40)
+    check(meter.snapshot()["lost"] == 0, "gap across a link switch counted as loss")
+
+    return check.report("telemetry gaps counted across the wrap, late frames and restarts told apart")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,134 @@
+"""Check that control characters and line breaks can't ride through the protocol.
+
+Runs both ends without sockets:
+
+    python test/text_sanitize.py
+
+Sends the robot wire frames whose command type, robot name and other string
+fields carry newlines, carriage returns, ANSI escapes, NUL and Unicode line
+separators. The check is that nothing the robot stores, logs, echoes back or
+puts in an alert still contains them. It then feeds the driver station's
+RobotClient a hostile reply, telemetry frame and alert, and checks the decoded
+values are clean too. Exits non-zero if any check fails.
+"""
+
+import io
+import json
+import logging
+import sys
+
+import zmq
+
+from _harness import Checks
+import comm
+import robot
+
+HOSTILE = [
+    "line1\nFORGED log line",
+    "carriage\rreturn",
+    "ansi \x1b[2J\x1b[31mred",
+    "nul\x00byte",
+    "c1\x85next-line",
+    "unicode separator paragraph",
+    "tab\tand bell\x07",
+]
+
+
+def dirty(text):
+    return any(ch in text for ch in "\n\r\x1b\x00\x85  \t\x07")
+
+
+def all_strings(value):
+    if isinstance(value, str):
+        yield value
+    elif isinstance(value, dict):
+        for key, item in value.items():
+            yield key
+            yield from all_strings(item)
+    elif isinstance(value, list):
+        for item in value:
+            yield from all_strings(item)
+
+
+class ScriptedSocket:
+    def __init__(self, messages):
+        self.messages = list(messages)
+
+    def send(self, payload, flags=0):
+        pass
+
+    def recv(self):
+        return self.messages.pop(0)
+
+    def recv_multipart(self, flags=0):
+        if not self.messages:
+            raise zmq.Again()
+        return self.messages.pop(0)
+
+    def close(self, linger=None):
+        pass
+
+
+def main():
+    check = Checks()
+
+    for text in HOSTILE:
+        cleaned = robot.clean_text(text)
+        check(not dirty(cleaned), f"robot.clean_text left control characters in {cleaned!r}")
+        check(comm.clean_text(text) == cleaned, f"robot and driver station clean {text!r} differently")
+    check(robot.clean_text("plain name 123") == "plain name 123", "clean_text changed ordinary text")
+
+    # Robot side: capture the log so forged lines would show up as extra records.
+    log_output = io.StringIO()
+    handler = logging.StreamHandler(log_output)
+    robot_logger = logging.getLogger("robot")
+    robot_logger.addHandler(handler)
+    robot_logger.setLevel(logging.INFO)
+    server = robot.RobotServer(bind=False)
+
+    for text in HOSTILE:
+        frame = json.dumps({"type": "name", "name": text, "client_id": text, "seq": 1}).encode()
+        command = robot.decode_command(frame)
+        check(not any(dirty(s) for s in all_strings(command)), f"decoded command still dirty: {command!r}")
+        response = server.handle_command(command)
+        check(not any(dirty(s) for s in all_strings(response)), f"name reply dirty: {response!r}")
+        check(not dirty(server.robot_name), f"stored name dirty: {server.robot_name!r}")
+
+        unknown = server.handle_command(robot.decode_command(json.dumps({"type": f"x{text}"}).encode()))
+        check(unknown["status"] == "error" and not dirty(unknown["message"]),
+              f"unknown-command echo dirty: {unknown!r}")
+
+    server._raise_alert("test\nkind", "motor\r\nFAKE ALERT\x1b[0m")
+    alert = server.telemetry_data["alerts"][-1]
+    check(not dirty(alert["kind"]) and not dirty(alert["message"]), f"stored alert dirty: {alert!r}")
+
+    robot_logger.removeHandler(handler)
+    for line in log_output.getvalue().splitlines():
+        check("FORGED" not in line or "line1" in line, f"log line forged by a newline: {line!r}")
+        check("FAKE ALERT" not in line or "motor" in line, f"alert forged a log line: {line!r}")
+
+    # Driver side: replies, telemetry and alerts from a hostile or broken robot.
+    client = comm.RobotClient("127.0.0.1")
+    client.command_socket = ScriptedSocket([json.dumps({
+        "status": "error", "message": "bad\nFORGED", "name": "robot\x1b[2J", "list": ["a b"],
+    }).encode()])
+    reply = client.request({"type": "name_query"})
+    check(not any(dirty(s) for s in all_strings(reply)), f"reply not cleaned: {reply!r}")
+
+    seen = []
+    client.signals.telemetry_update.connect(seen.append)
+    client.signals.robot_alert.connect(seen.append)
+    client.telemetry_socket = ScriptedSocket([
+        [comm.CHANNEL_ALERT, json.dumps({"id": 1, "kind": "geo\nfence", "message": "x\ry"}).encode()],
+        [comm.CHANNEL_TELEMETRY, json.dumps({"mode": "TELEOP\n", "alerts": [{"message": "\x00"}]}).encode()],
+    ])
+    client.receive_telemetry()
+    check(len(seen) == 2, f"expected an alert and a telemetry frame, got {seen!r}")
+    check(not any(dirty(s) for data in seen for s in all_strings(data)), f"telemetry/alert not cleaned: {seen!r}")
+    client.cleanup()
+
+    return check.report(f"{len(HOSTILE)} hostile strings cleaned in commands, replies, telemetry, alerts and logs")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,59 @@
+"""Measure round-trip time of small command frames against a running robot or sim.py.
+
+    python tools/ping_latency.py [host] [count]
+
+With Nagle enabled, back-to-back small frames stall for tens of milliseconds
+waiting on delayed ACKs. libzmq sets TCP_NODELAY, so on a LAN or loopback every
+ping should come back in a few milliseconds; the max is printed so a stall is
+obvious. This is a probe, not a pass/fail check: it needs a robot or sim.py
+listening.
+"""
+
+import statistics
+import sys
+import time
+
+import zmq
+
+COMMAND_PORT = 5555
+TIMEOUT_MS = 2000
+# Anything slower than this on loopback points at frames being coalesced.
+STALL_MS = 20.0
+
+
+def main():
+    host = sys.argv[1] if len(sys.argv) > 1 else "127.0.0.1"
+    count = int(sys.argv[2]) if len(sys.argv) > 2 else 200
+
+    context = zmq.Context()
+    socket = context.socket(zmq.REQ)
+    socket.setsockopt(zmq.IPV6, 1)
+    socket.setsockopt(zmq.RCVTIMEO, TIMEOUT_MS)
+    socket.setsockopt(zmq.LINGER, 0)
+    host_part = f"[{host}]" if ":" in host else host
+    socket.connect(f"tcp://{host_part}:{COMMAND_PORT}")
+
+    rtts = []
+    try:
+        for seq in range(count):
+            started = time.perf_counter()
+            socket.send_json({"type": "ping", "client_id": "ping_latency", "seq": seq})
+            socket.recv_json()
+            rtts.append((time.perf_counter() - started) * 1000.0)
+    except zmq.Again:
+        print(f"No reply from {host} after {len(rtts)} pings")
+        return 1
+    finally:
+        socket.close()
+        context.term()
+
+    stalls = sum(1 for rtt in rtts if rtt > STALL_MS)
+    print(
+        f"{count} pings to {host}: median {statistics.median(rtts):.2f} ms, "
+        f"max {max(rtts):.2f} ms, {stalls} over {STALL_MS:.0f} ms"
+    )
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())